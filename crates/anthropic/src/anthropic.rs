@@ -24,6 +24,35 @@ pub struct AnthropicModelCacheConfiguration {
     pub max_cache_anchors: usize,
 }
 
+/// Whether a model answers right away or thinks before answering.
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AnthropicModelMode {
+    #[default]
+    Default,
+    /// Use extended thinking, which only some models support.
+    Thinking {
+        /// The maximum number of tokens the model may spend thinking, which is added
+        /// to its maximum output tokens. Must be at least 1024.
+        budget_tokens: Option<u32>,
+    },
+}
+
+impl AnthropicModelMode {
+    pub const DEFAULT_THINKING_BUDGET_TOKENS: u32 = 4_096;
+
+    /// Returns the thinking budget, if thinking is enabled.
+    pub fn thinking_budget_tokens(&self) -> Option<u32> {
+        match self {
+            Self::Default => None,
+            Self::Thinking { budget_tokens } => {
+                Some(budget_tokens.unwrap_or(Self::DEFAULT_THINKING_BUDGET_TOKENS))
+            }
+        }
+    }
+}
+
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, EnumIter)]
 pub enum Model {
@@ -54,6 +83,8 @@ pub enum Model {
         default_temperature: Option<f32>,
         #[serde(default)]
         extra_beta_headers: Vec<String>,
+        #[serde(default)]
+        mode: AnthropicModelMode,
     },
 }
 
@@ -157,6 +188,13 @@ impl Model {
         }
     }
 
+    pub fn mode(&self) -> AnthropicModelMode {
+        match self {
+            Self::Custom { mode, .. } => mode.clone(),
+            _ => AnthropicModelMode::Default,
+        }
+    }
+
     pub const DEFAULT_BETA_HEADERS: &[&str] = &["prompt-caching-2024-07-31"];
 
     pub fn beta_headers(&self) -> String {
@@ -408,7 +446,9 @@ pub async fn extract_tool_args_from_events(
         let result = match event {
             Err(error) => Some(Err(error)),
             Ok(Event::ContentBlockDelta { index, delta }) => match delta {
-                ContentDelta::TextDelta { .. } => None,
+                ContentDelta::TextDelta { .. }
+                | ContentDelta::ThinkingDelta { .. }
+                | ContentDelta::SignatureDelta { .. } => None,
                 ContentDelta::InputJsonDelta { partial_json } => {
                     if index == tool_use_index {
                         Some(Ok(partial_json))
@@ -458,6 +498,10 @@ pub enum RequestContent {
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String, signature: String },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
     #[serde(rename = "image")]
     Image {
         source: ImageSource,
@@ -487,6 +531,14 @@ pub enum RequestContent {
 pub enum ResponseContent {
     #[serde(rename = "text")]
     Text { text: String },
+    #[serde(rename = "thinking")]
+    Thinking {
+        thinking: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+    },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
     #[serde(rename = "tool_use")]
     ToolUse {
         id: String,
@@ -518,11 +570,19 @@ pub enum ToolChoice {
     Tool { name: String },
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Thinking {
+    Enabled { budget_tokens: u32 },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Request {
     pub model: String,
     pub max_tokens: u32,
     pub messages: Vec<Message>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<Thinking>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<Tool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -609,6 +669,10 @@ pub enum Event {
 pub enum ContentDelta {
     #[serde(rename = "text_delta")]
     TextDelta { text: String },
+    #[serde(rename = "thinking_delta")]
+    ThinkingDelta { thinking: String },
+    #[serde(rename = "signature_delta")]
+    SignatureDelta { signature: String },
     #[serde(rename = "input_json_delta")]
     InputJsonDelta { partial_json: String },
 }
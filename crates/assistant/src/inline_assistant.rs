@@ -2947,6 +2947,7 @@ impl CodegenAlternative {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            response_format: None,
         })
     }
 
@@ -296,6 +296,7 @@ impl TerminalInlineAssistant {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            response_format: None,
         })
     }
 
@@ -1,20 +1,24 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use assistant_tool::ToolWorkingSet;
+use anyhow::anyhow;
+use assistant_tool::{Tool, ToolPermission, ToolPermissionsSettings, ToolSource, ToolWorkingSet};
 use collections::HashMap;
 use editor::{Editor, MultiBuffer};
 use gpui::{
     list, AbsoluteLength, AnyElement, App, ClickEvent, DefiniteLength, EdgesRefinement, Empty,
-    Entity, Focusable, Length, ListAlignment, ListOffset, ListState, StyleRefinement, Subscription,
-    Task, TextStyleRefinement, UnderlineStyle, WeakEntity,
+    Entity, Focusable, Length, ListAlignment, ListOffset, ListState, PromptLevel, StyleRefinement,
+    Subscription, Task, TextStyleRefinement, UnderlineStyle, WeakEntity,
 };
 use language::{Buffer, LanguageRegistry};
 use language_model::{LanguageModelRegistry, LanguageModelToolUseId, Role};
+use language_model_usage::CacheStatusIndicator;
 use markdown::{Markdown, MarkdownStyle};
-use settings::Settings as _;
+use settings::{update_settings_file, Settings as _};
 use theme::ThemeSettings;
-use ui::{prelude::*, Disclosure, KeyBinding};
+use ui::{prelude::*, Disclosure, KeyBinding, Tooltip};
 use util::ResultExt as _;
+use workspace::notifications::DetachAndPromptErr as _;
 use workspace::Workspace;
 
 use crate::thread::{MessageId, RequestKind, Thread, ThreadError, ThreadEvent};
@@ -32,8 +36,11 @@ pub struct ActiveThread {
     messages: Vec<MessageId>,
     list_state: ListState,
     rendered_messages_by_id: HashMap<MessageId, Entity<Markdown>>,
+    rendered_thinking_by_id: HashMap<MessageId, Entity<Markdown>>,
+    expanded_thinking: HashMap<MessageId, bool>,
     editing_message: Option<(MessageId, EditMessageState)>,
     expanded_tool_uses: HashMap<LanguageModelToolUseId, bool>,
+    show_compaction_summary: bool,
     last_error: Option<ThreadError>,
     _subscriptions: Vec<Subscription>,
 }
@@ -66,7 +73,10 @@ impl ActiveThread {
             save_thread_task: None,
             messages: Vec::new(),
             rendered_messages_by_id: HashMap::default(),
+            rendered_thinking_by_id: HashMap::default(),
+            expanded_thinking: HashMap::default(),
             expanded_tool_uses: HashMap::default(),
+            show_compaction_summary: false,
             list_state: ListState::new(0, ListAlignment::Bottom, px(1024.), {
                 let this = cx.entity().downgrade();
                 move |ix, window: &mut Window, cx: &mut App| {
@@ -81,6 +91,7 @@ impl ActiveThread {
 
         for message in thread.read(cx).messages().cloned().collect::<Vec<_>>() {
             this.push_message(&message.id, message.text.clone(), window, cx);
+            this.append_thinking(&message.id, &message.thinking_text(), window, cx);
         }
 
         this
@@ -157,6 +168,26 @@ impl ActiveThread {
         self.messages.remove(index);
         self.list_state.splice(index..index + 1, 0);
         self.rendered_messages_by_id.remove(id);
+        self.rendered_thinking_by_id.remove(id);
+    }
+
+    fn append_thinking(
+        &mut self,
+        id: &MessageId,
+        text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(markdown) = self.rendered_thinking_by_id.get(id) {
+            markdown.update(cx, |markdown, cx| markdown.append(text, cx));
+        } else {
+            let markdown = self.render_markdown(text.to_string().into(), window, cx);
+            self.rendered_thinking_by_id.insert(*id, markdown);
+        }
     }
 
     fn render_markdown(
@@ -262,14 +293,18 @@ impl ActiveThread {
                     });
                 }
             }
+            ThreadEvent::StreamedAssistantThinking(message_id, text) => {
+                self.append_thinking(message_id, text, window, cx);
+            }
             ThreadEvent::MessageAdded(message_id) => {
-                if let Some(message_text) = self
+                if let Some((message_text, thinking)) = self
                     .thread
                     .read(cx)
                     .message(*message_id)
-                    .map(|message| message.text.clone())
+                    .map(|message| (message.text.clone(), message.thinking_text()))
                 {
                     self.push_message(message_id, message_text, window, cx);
+                    self.append_thinking(message_id, &thinking, window, cx);
                 }
 
                 self.save_thread(cx);
@@ -288,6 +323,18 @@ impl ActiveThread {
                 self.save_thread(cx);
                 cx.notify();
             }
+            ThreadEvent::MessagePinChanged(message_id) => {
+                if let Some(index) = self.messages.iter().position(|id| id == message_id) {
+                    self.list_state.splice(index..index + 1, 1);
+                }
+                self.save_thread(cx);
+                cx.notify();
+            }
+            ThreadEvent::Compacted => {
+                self.list_state.reset(self.messages.len());
+                self.save_thread(cx);
+                cx.notify();
+            }
             ThreadEvent::MessageDeleted(message_id) => {
                 self.deleted_message(message_id);
                 self.save_thread(cx);
@@ -303,13 +350,39 @@ impl ActiveThread {
                     .cloned()
                     .collect::<Vec<_>>();
 
+                let worktree_roots = self.worktree_roots(cx);
                 for tool_use in pending_tool_uses {
-                    if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), window, cx);
-
+                    let tool = self
+                        .tools
+                        .tool(&tool_use.name, cx)
+                        .filter(|_| self.thread.read(cx).is_tool_enabled(&tool_use.name, cx));
+                    let Some(tool) = tool else {
+                        // Answer the tool use so the thread doesn't wait for it forever.
+                        let error = anyhow!("The tool {} is not available.", tool_use.name);
                         self.thread.update(cx, |thread, cx| {
-                            thread.insert_tool_output(tool_use.id.clone(), task, cx);
+                            thread.insert_tool_output(tool_use.id, Task::ready(Err(error)), cx);
                         });
+                        continue;
+                    };
+                    let permission = ToolPermissionsSettings::get_global(cx).permission(
+                        tool.as_ref(),
+                        &tool_use.input,
+                        &worktree_roots,
+                    );
+                    match permission {
+                        ToolPermission::Allow => {
+                            self.run_tool(tool, tool_use.id, tool_use.input, window, cx);
+                        }
+                        ToolPermission::Ask => {
+                            self.thread.update(cx, |thread, cx| {
+                                thread.request_tool_use_confirmation(&tool_use.id, cx);
+                            });
+                        }
+                        ToolPermission::Deny => {
+                            self.thread.update(cx, |thread, cx| {
+                                thread.deny_tool_use(tool_use.id, cx);
+                            });
+                        }
                     }
                 }
             }
@@ -321,8 +394,7 @@ impl ActiveThread {
                     .into_iter()
                     .all(|tool_use| tool_use.status.is_error());
                 if all_tools_finished {
-                    let model_registry = LanguageModelRegistry::read_global(cx);
-                    if let Some(model) = model_registry.active_model() {
+                    if let Some(model) = self.thread.read(cx).model(cx) {
                         self.thread.update(cx, |thread, cx| {
                             // Insert a user message to contain the tool results.
                             thread.insert_user_message(
@@ -422,8 +494,7 @@ impl ActiveThread {
             cx.notify();
             return;
         }
-        let model_registry = LanguageModelRegistry::read_global(cx);
-        let Some(model) = model_registry.active_model() else {
+        let Some(model) = self.thread.read(cx).model(cx) else {
             return;
         };
 
@@ -433,6 +504,38 @@ impl ActiveThread {
         cx.notify();
     }
 
+    fn restore_checkpoint(
+        &mut self,
+        message_id: MessageId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let answer = window.prompt(
+            PromptLevel::Warning,
+            "Restore the project's files to their state before this message?",
+            Some("Every change made to files since this message was sent, including the changes of later messages, will be reverted. Files created since then will be moved to the trash."),
+            &["Restore", "Cancel"],
+            cx,
+        );
+        let thread = self.thread.clone();
+        cx.spawn_in(window, |_, mut cx| async move {
+            if answer.await? != 0 {
+                return Ok(());
+            }
+            thread
+                .update(&mut cx, |thread, cx| {
+                    thread.restore_checkpoint(message_id, cx)
+                })?
+                .await
+        })
+        .detach_and_prompt_err(
+            "Failed to restore checkpoint",
+            window,
+            cx,
+            |error, _, _| Some(error.to_string()),
+        );
+    }
+
     fn last_user_message(&self, cx: &Context<Self>) -> Option<MessageId> {
         self.messages
             .iter()
@@ -479,15 +582,32 @@ impl ActiveThread {
 
         let context = self.thread.read(cx).context_for_message(message_id);
         let tool_uses = self.thread.read(cx).tool_uses_for_message(message_id);
+        let cache_status = self
+            .thread
+            .read(cx)
+            .token_usage_for_message(message_id)
+            .and_then(|usage| CacheStatusIndicator::new(("cache-status", ix), *usage));
         let colors = cx.theme().colors();
+        let is_compacted = self.thread.read(cx).is_message_compacted(message);
+        let is_compaction_boundary = self
+            .thread
+            .read(cx)
+            .compaction()
+            .map_or(false, |compaction| compaction.through == message_id);
 
         // Don't render user messages that are just there for returning tool results.
         if message.role == Role::User && self.thread.read(cx).message_has_tool_results(message_id) {
-            return Empty.into_any();
+            return if is_compaction_boundary {
+                self.render_compaction_marker(cx).into_any_element()
+            } else {
+                Empty.into_any()
+            };
         }
 
         let allow_editing_message =
             message.role == Role::User && self.last_user_message(cx) == Some(message_id);
+        let has_checkpoint =
+            message.role == Role::User && self.thread.read(cx).has_checkpoint(message_id);
 
         let edit_message_editor = self
             .editing_message
@@ -603,32 +723,77 @@ impl ActiveThread {
                                         )
                                     },
                                 )
-                                .when(
-                                    edit_message_editor.is_none() && allow_editing_message,
-                                    |this| {
-                                        this.child(
-                                            Button::new("edit-message", "Edit")
-                                                .label_size(LabelSize::Small)
-                                                .on_click(cx.listener({
-                                                    let message_text = message.text.clone();
-                                                    move |this, _, window, cx| {
-                                                        this.start_editing_message(
-                                                            message_id,
-                                                            message_text.clone(),
-                                                            window,
-                                                            cx,
-                                                        );
-                                                    }
-                                                })),
-                                        )
-                                    },
-                                ),
+                                .when(edit_message_editor.is_none(), |this| {
+                                    this.child(
+                                        h_flex()
+                                            .gap_1()
+                                            .child(self.render_pin_button(
+                                                message_id,
+                                                message.pinned,
+                                                cx,
+                                            ))
+                                            .when(has_checkpoint, |this| {
+                                                this.child(
+                                                    Button::new(
+                                                        "restore-checkpoint",
+                                                        "Restore Checkpoint",
+                                                    )
+                                                    .label_size(LabelSize::Small)
+                                                    .icon(IconName::RotateCcw)
+                                                    .icon_size(IconSize::XSmall)
+                                                    .icon_color(Color::Muted)
+                                                    .icon_position(IconPosition::Start)
+                                                    .on_click(cx.listener(
+                                                        move |this, _, window, cx| {
+                                                            this.restore_checkpoint(
+                                                                message_id, window, cx,
+                                                            );
+                                                        },
+                                                    )),
+                                                )
+                                            })
+                                            .when(allow_editing_message, |this| {
+                                                this.child(
+                                                    Button::new("edit-message", "Edit")
+                                                        .label_size(LabelSize::Small)
+                                                        .on_click(cx.listener({
+                                                            let message_text = message.text.clone();
+                                                            move |this, _, window, cx| {
+                                                                this.start_editing_message(
+                                                                    message_id,
+                                                                    message_text.clone(),
+                                                                    window,
+                                                                    cx,
+                                                                );
+                                                            }
+                                                        })),
+                                                )
+                                            }),
+                                    )
+                                }),
                         )
                         .child(message_content),
                 ),
             Role::Assistant => div()
                 .id(("message-container", ix))
+                .group("assistant-message")
+                .relative()
+                .children(self.render_thinking(message_id, message.text.is_empty(), cx))
                 .child(message_content)
+                .child(
+                    h_flex()
+                        .absolute()
+                        .top_1()
+                        .right_2()
+                        .gap_1()
+                        .children(cache_status)
+                        .child(
+                            self.render_pin_button(message_id, message.pinned, cx)
+                                .when(!message.pinned, |button| {
+                                    button.visible_on_hover("assistant-message")
+                                }),
+                        ),
+                )
                 .map(|parent| {
                     if tool_uses.is_empty() {
                         return parent;
@@ -650,10 +815,329 @@ impl ActiveThread {
             ),
         };
 
-        styled_message.into_any()
+        let styled_message = styled_message.when(is_compacted, |message| message.opacity(0.6));
+        if is_compaction_boundary {
+            v_flex()
+                .child(styled_message)
+                .child(self.render_compaction_marker(cx))
+                .into_any()
+        } else {
+            styled_message.into_any()
+        }
+    }
+
+    /// Renders the model's reasoning as a block that is collapsed unless the
+    /// user expands it.
+    fn render_thinking(
+        &self,
+        message_id: MessageId,
+        is_thinking: bool,
+        cx: &mut Context<Self>,
+    ) -> Option<impl IntoElement> {
+        let markdown = self.rendered_thinking_by_id.get(&message_id)?;
+        let is_thinking = is_thinking && self.thread.read(cx).is_streaming();
+        let is_open = self
+            .expanded_thinking
+            .get(&message_id)
+            .copied()
+            .unwrap_or_default();
+
+        Some(
+            div().px_2p5().pt_2().child(
+                v_flex()
+                    .rounded_lg()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .py_0p5()
+                            .pl_1()
+                            .pr_2()
+                            .bg(cx.theme().colors().editor_foreground.opacity(0.02))
+                            .when(is_open, |element| element.border_b_1().rounded_t(px(6.)))
+                            .when(!is_open, |element| element.rounded(px(6.)))
+                            .border_color(cx.theme().colors().border)
+                            .child(
+                                Disclosure::new(("thinking-disclosure", message_id.0), is_open)
+                                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                                        let is_open = this
+                                            .expanded_thinking
+                                            .entry(message_id)
+                                            .or_insert(false);
+                                        *is_open = !*is_open;
+                                        if let Some(ix) =
+                                            this.messages.iter().position(|id| *id == message_id)
+                                        {
+                                            this.list_state.splice(ix..ix + 1, 1);
+                                        }
+                                        cx.notify();
+                                    })),
+                            )
+                            .child(
+                                Label::new(if is_thinking {
+                                    "Thinking…"
+                                } else {
+                                    "Thought Process"
+                                })
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                            ),
+                    )
+                    .when(is_open, |parent| {
+                        parent.child(div().p_2p5().text_ui(cx).child(markdown.clone()))
+                    }),
+            ),
+        )
+    }
+
+    fn render_pin_button(
+        &self,
+        message_id: MessageId,
+        pinned: bool,
+        cx: &mut Context<Self>,
+    ) -> IconButton {
+        IconButton::new(("pin-message", message_id.0), IconName::Pin)
+            .icon_size(IconSize::XSmall)
+            .icon_color(if pinned { Color::Accent } else { Color::Muted })
+            .toggle_state(pinned)
+            .tooltip(Tooltip::text(if pinned {
+                "Unpin Message"
+            } else {
+                "Pin Message to Keep It When the Thread Is Summarized"
+            }))
+            .on_click(cx.listener(move |this, _, _window, cx| {
+                this.thread.update(cx, |thread, cx| {
+                    thread.toggle_message_pinned(message_id, cx);
+                });
+            }))
+    }
+
+    /// Renders the divider below the last message that was summarized to keep the
+    /// thread within the model's context window.
+    fn render_compaction_marker(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let summary = self
+            .thread
+            .read(cx)
+            .compaction()
+            .map(|compaction| compaction.summary.clone())
+            .unwrap_or_default();
+
+        v_flex()
+            .mx_2p5()
+            .my_2()
+            .py_1()
+            .border_y_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Disclosure::new("compaction-summary", self.show_compaction_summary)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.show_compaction_summary = !this.show_compaction_summary;
+                                this.list_state.reset(this.messages.len());
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Label::new(
+                            "Messages above were summarized to fit the context window. Pinned messages are kept as is.",
+                        )
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    ),
+            )
+            .when(self.show_compaction_summary, |this| {
+                this.child(
+                    div()
+                        .px_2()
+                        .pt_1()
+                        .child(Label::new(summary).size(LabelSize::Small)),
+                )
+            })
+    }
+
+    /// Returns the absolute paths of the project's visible worktrees, which relative paths in
+    /// tool inputs are resolved against.
+    fn worktree_roots(&self, cx: &App) -> Vec<PathBuf> {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return Vec::new();
+        };
+        workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .collect()
+    }
+
+    fn run_tool(
+        &mut self,
+        tool: Arc<dyn Tool>,
+        tool_use_id: LanguageModelToolUseId,
+        input: serde_json::Value,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let task = tool.run(input, self.workspace.clone(), window, cx);
+
+        self.thread.update(cx, |thread, cx| {
+            thread.insert_tool_output(tool_use_id, task, cx);
+        });
+    }
+
+    fn confirm_tool_use(
+        &mut self,
+        tool_use_id: LanguageModelToolUseId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(tool_use) = self
+            .thread
+            .read(cx)
+            .pending_tool_uses()
+            .into_iter()
+            .find(|tool_use| tool_use.id == tool_use_id && tool_use.status.needs_confirmation())
+            .cloned()
+        else {
+            return;
+        };
+
+        if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
+            self.run_tool(tool, tool_use.id, tool_use.input, window, cx);
+        }
+    }
+
+    /// Runs the tool use and allows all future uses of the tool in the settings.
+    fn always_allow_tool_use(
+        &mut self,
+        tool_use_id: LanguageModelToolUseId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(tool_use) = self
+            .thread
+            .read(cx)
+            .pending_tool_uses()
+            .into_iter()
+            .find(|tool_use| tool_use.id == tool_use_id)
+            .cloned()
+        else {
+            return;
+        };
+        let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+            return;
+        };
+        let Some(fs) = self
+            .workspace
+            .read_with(cx, |workspace, _cx| workspace.app_state().fs.clone())
+            .log_err()
+        else {
+            return;
+        };
+
+        let tool_name = tool.name();
+        let source = tool.source();
+        update_settings_file::<ToolPermissionsSettings>(fs, cx, move |settings, _cx| {
+            settings.set_tool_permission(&tool_name, &source, ToolPermission::Allow);
+        });
+
+        self.confirm_tool_use(tool_use_id, window, cx);
+    }
+
+    fn deny_tool_use(&mut self, tool_use_id: LanguageModelToolUseId, cx: &mut Context<Self>) {
+        self.thread.update(cx, |thread, cx| {
+            thread.deny_tool_use(tool_use_id, cx);
+        });
+    }
+
+    fn render_tool_use_confirmation(
+        &self,
+        tool_use: &ToolUse,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let tool_use_id = tool_use.id.clone();
+        let source = self
+            .tools
+            .tool(&tool_use.name, cx)
+            .map_or(ToolSource::Native, |tool| tool.source());
+        let prompt = match source {
+            ToolSource::Native => format!("Allow {} to run?", tool_use.name),
+            ToolSource::ContextServer { id } => {
+                format!(
+                    "Allow {} from the {id} context server to run?",
+                    tool_use.name
+                )
+            }
+        };
+
+        v_flex()
+            .gap_1()
+            .py_1()
+            .px_2p5()
+            .border_t_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                Label::new(prompt)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Label::new(serde_json::to_string_pretty(&tool_use.input).unwrap_or_default())
+                    .size(LabelSize::Small)
+                    .buffer_font(cx),
+            )
+            .child(
+                h_flex()
+                    .justify_end()
+                    .gap_1()
+                    .child(
+                        Button::new(
+                            SharedString::from(format!("deny-tool-use-{tool_use_id}")),
+                            "Deny",
+                        )
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener({
+                            let tool_use_id = tool_use_id.clone();
+                            move |this, _, _window, cx| {
+                                this.deny_tool_use(tool_use_id.clone(), cx);
+                            }
+                        })),
+                    )
+                    .child(
+                        Button::new(
+                            SharedString::from(format!("always-allow-tool-use-{tool_use_id}")),
+                            "Always Allow",
+                        )
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener({
+                            let tool_use_id = tool_use_id.clone();
+                            move |this, _, window, cx| {
+                                this.always_allow_tool_use(tool_use_id.clone(), window, cx);
+                            }
+                        })),
+                    )
+                    .child(
+                        Button::new(
+                            SharedString::from(format!("allow-tool-use-{tool_use_id}")),
+                            "Allow",
+                        )
+                        .label_size(LabelSize::Small)
+                        .style(ButtonStyle::Filled)
+                        .on_click(cx.listener(
+                            move |this, _, window, cx| {
+                                this.confirm_tool_use(tool_use_id.clone(), window, cx);
+                            },
+                        )),
+                    ),
+            )
     }
 
     fn render_tool_use(&self, tool_use: ToolUse, cx: &mut Context<Self>) -> impl IntoElement {
+        let confirmation = matches!(tool_use.status, ToolUseStatus::NeedsConfirmation)
+            .then(|| self.render_tool_use_confirmation(&tool_use, cx));
         let is_open = self
             .expanded_tool_uses
             .get(&tool_use.id)
@@ -696,6 +1180,7 @@ impl ActiveThread {
                         )
                         .child(
                             Label::new(match tool_use.status {
+                                ToolUseStatus::NeedsConfirmation => "Awaiting Confirmation",
                                 ToolUseStatus::Pending => "Pending",
                                 ToolUseStatus::Running => "Running",
                                 ToolUseStatus::Finished(_) => "Finished",
@@ -742,10 +1227,13 @@ impl ActiveThread {
                                         .child(Label::new("Error:"))
                                         .child(Label::new(err)),
                                 ),
-                                ToolUseStatus::Pending | ToolUseStatus::Running => parent,
+                                ToolUseStatus::NeedsConfirmation
+                                | ToolUseStatus::Pending
+                                | ToolUseStatus::Running => parent,
                             }),
                     )
-                }),
+                })
+                .children(confirmation),
         )
     }
 }
@@ -3,6 +3,7 @@ mod assistant_configuration;
 mod assistant_model_selector;
 mod assistant_panel;
 mod buffer_codegen;
+mod checkpoint;
 mod context;
 mod context_picker;
 mod context_store;
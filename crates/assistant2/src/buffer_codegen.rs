@@ -423,6 +423,7 @@ impl CodegenAlternative {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            response_format: None,
             messages: vec![request_message],
         })
     }
@@ -0,0 +1,535 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use collections::{HashMap, HashSet};
+use fs::MTime;
+use futures::future::{join_all, Shared};
+use futures::FutureExt as _;
+use gpui::{App, Entity, Task, WeakEntity};
+use language::{Buffer, DiskState};
+use project::{Project, ProjectPath};
+use text::BufferId;
+use worktree::Snapshot;
+
+use crate::thread::MessageId;
+
+/// The state of the project at the start of a turn, from which the changes made
+/// to files during that turn and all later turns can be reverted.
+pub struct Checkpoint {
+    pub message_id: MessageId,
+    buffers: HashMap<BufferId, BufferCheckpoint>,
+    worktrees: Vec<Snapshot>,
+    /// The contents of the worktrees' files, which are read in the background.
+    files: Shared<Task<Arc<CapturedFiles>>>,
+}
+
+/// Files larger than this aren't captured, to bound the memory used by checkpoints.
+const MAX_CAPTURED_FILE_SIZE: u64 = 1024 * 1024;
+
+type CapturedFiles = HashMap<ProjectPath, CapturedFile>;
+
+#[derive(Clone)]
+struct CapturedFile {
+    mtime: Option<MTime>,
+    text: Arc<str>,
+}
+
+#[derive(Clone)]
+struct BufferCheckpoint {
+    buffer: WeakEntity<Buffer>,
+    version: clock::Global,
+    was_dirty: bool,
+    /// Whether the buffer was already open when the checkpoint was created.
+    was_open: bool,
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint of the project's current state. The contents of files
+    /// that haven't changed since the `previous` checkpoint are shared with it.
+    pub fn new(
+        message_id: MessageId,
+        project: &Project,
+        previous: Option<&Checkpoint>,
+        cx: &App,
+    ) -> Self {
+        // Worktrees that are still being scanned are left out, so that files
+        // the scan hasn't found yet aren't mistaken for files created later.
+        let worktrees = project
+            .worktrees(cx)
+            .map(|worktree| worktree.read(cx))
+            .filter(|worktree| worktree.completed_scan_id() >= worktree.scan_id())
+            .map(|worktree| worktree.snapshot())
+            .collect::<Vec<_>>();
+        let files = capture_files(
+            project,
+            &worktrees,
+            previous.map(|previous| previous.files.clone()),
+            cx,
+        );
+        let mut this = Self {
+            message_id,
+            buffers: HashMap::default(),
+            worktrees,
+            files,
+        };
+        for buffer in project.buffer_store().read(cx).buffers() {
+            this.insert_buffer(&buffer, true, cx);
+        }
+        this
+    }
+
+    /// Records the current state of a buffer that was opened after the checkpoint
+    /// was created, which is the state it had at the start of the turn.
+    pub fn track_buffer(&mut self, buffer: &Entity<Buffer>, cx: &App) {
+        self.insert_buffer(buffer, false, cx);
+    }
+
+    fn insert_buffer(&mut self, buffer: &Entity<Buffer>, was_open: bool, cx: &App) {
+        let buffer_ref = buffer.read(cx);
+        self.buffers
+            .entry(buffer_ref.remote_id())
+            .or_insert_with(|| BufferCheckpoint {
+                buffer: buffer.downgrade(),
+                version: buffer_ref.version(),
+                was_dirty: buffer_ref.is_dirty(),
+                was_open,
+            });
+    }
+}
+
+/// Reads the contents of the worktrees' files in the background, reusing the
+/// contents from the previous checkpoint for files that haven't changed since.
+fn capture_files(
+    project: &Project,
+    worktrees: &[Snapshot],
+    previous: Option<Shared<Task<Arc<CapturedFiles>>>>,
+    cx: &App,
+) -> Shared<Task<Arc<CapturedFiles>>> {
+    // The files of remote projects can't be read from here.
+    if !project.is_local() {
+        return Task::ready(Arc::default()).shared();
+    }
+
+    let fs = project.fs().clone();
+    let files = worktrees
+        .iter()
+        .flat_map(|worktree| {
+            worktree
+                .files(false, 0)
+                .filter(|entry| entry.size <= MAX_CAPTURED_FILE_SIZE)
+                .filter_map(move |entry| {
+                    let abs_path = worktree.absolutize(&entry.path).ok()?;
+                    let project_path = ProjectPath {
+                        worktree_id: worktree.id(),
+                        path: entry.path.clone(),
+                    };
+                    Some((project_path, abs_path, entry.mtime))
+                })
+        })
+        .collect::<Vec<_>>();
+
+    cx.background_executor()
+        .spawn(async move {
+            let previous = match previous {
+                Some(previous) => previous.await,
+                None => Arc::default(),
+            };
+
+            let mut captured = CapturedFiles::default();
+            for (project_path, abs_path, mtime) in files {
+                if let Some(file) = previous.get(&project_path) {
+                    if file.mtime == mtime {
+                        captured.insert(project_path, file.clone());
+                        continue;
+                    }
+                }
+
+                // A file that changed since the worktree was scanned no longer has
+                // the contents it had at the checkpoint.
+                let metadata = fs.metadata(&abs_path).await.ok().flatten();
+                if metadata.map(|metadata| metadata.mtime) != mtime {
+                    continue;
+                }
+                if let Ok(text) = fs.load(&abs_path).await {
+                    captured.insert(
+                        project_path,
+                        CapturedFile {
+                            mtime,
+                            text: text.into(),
+                        },
+                    );
+                }
+            }
+            Arc::new(captured)
+        })
+        .shared()
+}
+
+/// Reverts the project to its state at the first of the given checkpoints, undoing
+/// the changes made since then.
+///
+/// Buffers are restored through a single transaction each, so the restore can itself
+/// be undone. Files that were changed or deleted outside of the buffers open at the
+/// checkpoint are rewritten from the contents captured at the checkpoint, and reloaded
+/// in any buffers opened since. Files created since the checkpoint are moved to the
+/// trash, unless they have unsaved edits.
+pub fn restore_checkpoints(
+    checkpoints: &[Checkpoint],
+    project: Entity<Project>,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let Some(first_checkpoint) = checkpoints.first() else {
+        return Task::ready(Ok(()));
+    };
+
+    // When a buffer was tracked by several checkpoints, the earliest state wins.
+    let mut buffers = HashMap::<BufferId, BufferCheckpoint>::default();
+    for checkpoint in checkpoints {
+        for (buffer_id, buffer_checkpoint) in &checkpoint.buffers {
+            buffers
+                .entry(*buffer_id)
+                .or_insert_with(|| buffer_checkpoint.clone());
+        }
+    }
+    // Only buffers open at the first checkpoint hold the contents files had back then.
+    for (buffer_id, buffer_checkpoint) in &mut buffers {
+        buffer_checkpoint.was_open = first_checkpoint
+            .buffers
+            .get(buffer_id)
+            .map_or(false, |buffer_checkpoint| buffer_checkpoint.was_open);
+    }
+
+    let old_worktrees = first_checkpoint.worktrees.clone();
+    let captured_files = first_checkpoint.files.clone();
+    let fs = project.read(cx).fs().clone();
+    cx.spawn(|mut cx| async move {
+        let captured_files = captured_files.await;
+        let plan = cx.update(|cx| {
+            plan_restore(buffers, &old_worktrees, &captured_files, &project, cx)
+        })?;
+
+        for (abs_path, text) in plan.files_to_write {
+            if let Some(parent) = abs_path.parent() {
+                fs.create_dir(parent).await?;
+            }
+            fs.atomic_write(abs_path, text.to_string()).await?;
+        }
+
+        let reloads = cx.update(|cx| {
+            plan.buffers_to_reload
+                .into_iter()
+                .map(|buffer| buffer.update(cx, |buffer, cx| buffer.reload(cx)))
+                .collect::<Vec<_>>()
+        })?;
+        let tasks = project.update(&mut cx, |project, cx| {
+            let mut tasks = plan
+                .created_paths
+                .into_iter()
+                .filter_map(|project_path| project.delete_file(project_path, true, cx))
+                .collect::<Vec<_>>();
+            tasks.extend(
+                plan.buffers_to_save
+                    .into_iter()
+                    .map(|buffer| project.save_buffer(buffer, cx)),
+            );
+            tasks
+        })?;
+        for result in join_all(tasks).await {
+            result?;
+        }
+        join_all(reloads).await;
+
+        if plan.lost_paths.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "The following files could not be restored because their contents weren't captured: {}",
+                display_paths(&plan.lost_paths)
+            ))
+        }
+    })
+}
+
+#[derive(Default)]
+struct RestorePlan {
+    buffers_to_save: Vec<Entity<Buffer>>,
+    buffers_to_reload: Vec<Entity<Buffer>>,
+    files_to_write: Vec<(PathBuf, Arc<str>)>,
+    created_paths: Vec<ProjectPath>,
+    lost_paths: Vec<Arc<Path>>,
+}
+
+fn plan_restore(
+    buffers: HashMap<BufferId, BufferCheckpoint>,
+    old_worktrees: &[Snapshot],
+    captured_files: &CapturedFiles,
+    project: &Entity<Project>,
+    cx: &mut App,
+) -> RestorePlan {
+    let existed = |project_path: &ProjectPath| {
+        old_worktrees
+            .iter()
+            .find(|worktree| worktree.id() == project_path.worktree_id)
+            .map_or(false, |worktree| {
+                worktree.entry_for_path(&project_path.path).is_some()
+            })
+    };
+
+    let mut plan = RestorePlan::default();
+    let mut restored_paths = HashSet::default();
+    let mut kept_paths = HashSet::default();
+    let mut buffers_opened_later = HashMap::default();
+    for buffer_checkpoint in buffers.into_values() {
+        let Some(buffer) = buffer_checkpoint.buffer.upgrade() else {
+            continue;
+        };
+        buffer.update(cx, |buffer, cx| {
+            let project_path = buffer.file().map(|file| ProjectPath {
+                worktree_id: file.worktree_id(cx),
+                path: file.path().clone(),
+            });
+            if let Some(project_path) = &project_path {
+                // Keep files created since the checkpoint that have unsaved edits.
+                if !existed(project_path) && buffer.is_dirty() {
+                    kept_paths.insert(project_path.clone());
+                    return;
+                }
+            }
+
+            let snapshot = buffer.snapshot();
+            if snapshot.has_edits_since(&buffer_checkpoint.version) {
+                let old_text = snapshot.rope_for_version(&buffer_checkpoint.version);
+                let edits = snapshot
+                    .edits_since::<usize>(&buffer_checkpoint.version)
+                    .map(|edit| {
+                        let text = old_text.chunks_in_range(edit.old).collect::<String>();
+                        (edit.new, text)
+                    })
+                    .collect::<Vec<_>>();
+
+                buffer.finalize_last_transaction();
+                buffer.start_transaction();
+                buffer.edit(edits, None, cx);
+                buffer.end_transaction(cx);
+            }
+
+            let Some(project_path) = project_path.filter(|path| existed(path)) else {
+                return;
+            };
+            if !buffer_checkpoint.was_open && captured_files.contains_key(&project_path) {
+                buffers_opened_later.insert(project_path, cx.entity());
+                return;
+            }
+
+            // Write the contents back to disk when the turn saved or deleted the file,
+            // but leave changes the user hadn't saved before the turn alone.
+            let needs_save = !buffer_checkpoint.was_dirty
+                && (buffer.is_dirty()
+                    || buffer
+                        .file()
+                        .map_or(false, |file| file.disk_state() == DiskState::Deleted));
+            if needs_save {
+                plan.buffers_to_save.push(cx.entity());
+            }
+            restored_paths.insert(project_path);
+        });
+    }
+
+    for worktree in project.read(cx).worktrees(cx) {
+        let worktree = worktree.read(cx);
+        let Some(old_worktree) = old_worktrees
+            .iter()
+            .find(|old_worktree| old_worktree.id() == worktree.id())
+        else {
+            continue;
+        };
+
+        // Remove the topmost entries that didn't exist, which also removes their contents.
+        for entry in worktree.entries(false, 0) {
+            let is_new = old_worktree.entry_for_path(&entry.path).is_none();
+            let parent_existed = entry
+                .path
+                .parent()
+                .map_or(true, |parent| old_worktree.entry_for_path(parent).is_some());
+            let is_kept = kept_paths.iter().any(|kept_path: &ProjectPath| {
+                kept_path.worktree_id == worktree.id() && kept_path.path.starts_with(&entry.path)
+            });
+            if is_new && parent_existed && !is_kept {
+                plan.created_paths.push(ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: entry.path.clone(),
+                });
+            }
+        }
+
+        for entry in old_worktree.files(false, 0) {
+            let project_path = ProjectPath {
+                worktree_id: worktree.id(),
+                path: entry.path.clone(),
+            };
+            let changed = worktree
+                .entry_for_path(&entry.path)
+                .map_or(true, |current_entry| current_entry.mtime != entry.mtime);
+            if !changed || restored_paths.contains(&project_path) {
+                continue;
+            }
+
+            match captured_files.get(&project_path) {
+                Some(file) => {
+                    if let Ok(abs_path) = worktree.absolutize(&entry.path) {
+                        plan.files_to_write.push((abs_path, file.text.clone()));
+                    }
+                    if let Some(buffer) = buffers_opened_later.remove(&project_path) {
+                        plan.buffers_to_reload.push(buffer);
+                    }
+                }
+                None => plan.lost_paths.push(entry.path.clone()),
+            }
+        }
+    }
+
+    plan
+}
+
+fn display_paths(paths: &[Arc<Path>]) -> String {
+    paths
+        .iter()
+        .map(|path| path.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::{FakeFs, Fs, RemoveOptions};
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_restore_checkpoint(cx: &mut TestAppContext) {
+        let (fs, project) = init_test(cx).await;
+        let edited = open_buffer("/root/edited.txt", &project, cx).await;
+        let checkpoint = project.read_with(cx, |project, cx| {
+            Checkpoint::new(MessageId(0), project, None, cx)
+        });
+        cx.run_until_parked();
+
+        // Edit an open buffer and save it, then change files that aren't open.
+        edited.update(cx, |buffer, cx| buffer.edit([(0..0, "changed ")], None, cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(edited.clone(), cx))
+            .await
+            .unwrap();
+        fs.remove_file(Path::new("/root/deleted.txt"), RemoveOptions::default())
+            .await
+            .unwrap();
+        fs.insert_file("/root/modified.txt", b"changed modified".to_vec())
+            .await;
+        fs.insert_file("/root/created.txt", b"created".to_vec())
+            .await;
+        cx.run_until_parked();
+
+        cx.update(|cx| restore_checkpoints(&[checkpoint], project.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        assert_eq!(
+            fs.load(Path::new("/root/edited.txt")).await.unwrap(),
+            "edited"
+        );
+        assert_eq!(edited.read_with(cx, |buffer, _| buffer.text()), "edited");
+        assert_eq!(
+            fs.load(Path::new("/root/deleted.txt")).await.unwrap(),
+            "deleted"
+        );
+        assert_eq!(
+            fs.load(Path::new("/root/modified.txt")).await.unwrap(),
+            "modified"
+        );
+        assert!(!fs.is_file(Path::new("/root/created.txt")).await);
+    }
+
+    #[gpui::test]
+    async fn test_restore_checkpoint_with_buffers_opened_later(cx: &mut TestAppContext) {
+        let (fs, project) = init_test(cx).await;
+        let mut checkpoint = project.read_with(cx, |project, cx| {
+            Checkpoint::new(MessageId(0), project, None, cx)
+        });
+        cx.run_until_parked();
+
+        // The agent edits and saves a file, and creates another that the user keeps editing.
+        let modified = open_buffer("/root/modified.txt", &project, cx).await;
+        cx.update(|cx| checkpoint.track_buffer(&modified, cx));
+        modified.update(cx, |buffer, cx| buffer.edit([(0..0, "changed ")], None, cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(modified.clone(), cx))
+            .await
+            .unwrap();
+        fs.insert_file("/root/created.txt", b"created".to_vec())
+            .await;
+        cx.run_until_parked();
+        let created = open_buffer("/root/created.txt", &project, cx).await;
+        cx.update(|cx| checkpoint.track_buffer(&created, cx));
+        created.update(cx, |buffer, cx| buffer.edit([(0..0, "unsaved ")], None, cx));
+
+        cx.update(|cx| restore_checkpoints(&[checkpoint], project.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        assert_eq!(
+            fs.load(Path::new("/root/modified.txt")).await.unwrap(),
+            "modified"
+        );
+        assert_eq!(
+            modified.read_with(cx, |buffer, _| buffer.text()),
+            "modified"
+        );
+        assert_eq!(
+            fs.load(Path::new("/root/created.txt")).await.unwrap(),
+            "created"
+        );
+        assert_eq!(
+            created.read_with(cx, |buffer, _| buffer.text()),
+            "unsaved created"
+        );
+    }
+
+    async fn init_test(cx: &mut TestAppContext) -> (Arc<FakeFs>, Entity<Project>) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "edited.txt": "edited",
+                "deleted.txt": "deleted",
+                "modified.txt": "modified",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [Path::new("/root")], cx).await;
+        cx.run_until_parked();
+        (fs, project)
+    }
+
+    async fn open_buffer(
+        path: &str,
+        project: &Entity<Project>,
+        cx: &mut TestAppContext,
+    ) -> Entity<Buffer> {
+        project
+            .update(cx, |project, cx| project.open_local_buffer(path, cx))
+            .await
+            .unwrap()
+    }
+}
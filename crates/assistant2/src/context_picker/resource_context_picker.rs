@@ -0,0 +1,452 @@
+use std::sync::Arc;
+
+use collections::HashMap;
+use context_server::manager::ContextServerManager;
+use context_server::protocol::ServerCapability;
+use context_server::uri_template::{expand_uri_template, uri_template_variables};
+use fuzzy::StringMatchCandidate;
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Task, WeakEntity};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, ListItem, Tooltip};
+use url::Url;
+use util::ResultExt as _;
+use workspace::notifications::NotifyResultExt as _;
+
+use crate::context_picker::{ConfirmBehavior, ContextPicker};
+use crate::context_store::ContextStore;
+
+pub struct ResourceContextPicker {
+    picker: Entity<Picker<ResourceContextPickerDelegate>>,
+    _load_entries: Task<()>,
+}
+
+impl ResourceContextPicker {
+    pub fn new(
+        context_server_manager: Entity<ContextServerManager>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let load_entries = load_resource_entries(&context_server_manager, cx);
+        let delegate = ResourceContextPickerDelegate::new(
+            context_server_manager,
+            context_picker,
+            context_store,
+            confirm_behavior,
+        );
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+
+        let _load_entries = cx.spawn_in(window, {
+            let picker = picker.downgrade();
+            |_, mut cx| async move {
+                let entries = load_entries.await;
+                picker
+                    .update_in(&mut cx, |picker, window, cx| {
+                        picker.delegate.entries = entries;
+                        picker.refresh(window, cx);
+                    })
+                    .ok();
+            }
+        });
+
+        ResourceContextPicker {
+            picker,
+            _load_entries,
+        }
+    }
+}
+
+impl Focusable for ResourceContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for ResourceContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceContextEntry {
+    pub server_id: Arc<str>,
+    pub name: SharedString,
+    pub description: Option<SharedString>,
+    pub kind: ResourceContextEntryKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ResourceContextEntryKind {
+    Resource(Url),
+    Template(String),
+}
+
+/// The values entered so far for the variables of a resource template.
+struct TemplateArguments {
+    entry: ResourceContextEntry,
+    uri_template: String,
+    variables: Vec<String>,
+    values: HashMap<String, String>,
+    current_value: String,
+}
+
+impl TemplateArguments {
+    fn current_variable(&self) -> &str {
+        &self.variables[self.values.len()]
+    }
+}
+
+/// Lists the resources and resource templates of every running context server.
+fn load_resource_entries(
+    context_server_manager: &Entity<ContextServerManager>,
+    cx: &App,
+) -> Task<Vec<ResourceContextEntry>> {
+    let servers = context_server_manager.read(cx).servers();
+    cx.spawn(|_| async move {
+        let mut entries = Vec::new();
+        for server in servers {
+            let Some(protocol) = server.client() else {
+                continue;
+            };
+            if !protocol.capable(ServerCapability::Resources) {
+                continue;
+            }
+
+            if let Some(response) = protocol.list_resources().await.log_err() {
+                entries.extend(response.resources.into_iter().map(|resource| {
+                    ResourceContextEntry {
+                        server_id: server.id(),
+                        name: resource.name.into(),
+                        description: resource.description.map(Into::into),
+                        kind: ResourceContextEntryKind::Resource(resource.uri),
+                    }
+                }));
+            }
+
+            // Not every server that exposes resources implements templates.
+            if let Ok(response) = protocol.list_resource_templates().await {
+                entries.extend(response.resource_templates.into_iter().map(|template| {
+                    ResourceContextEntry {
+                        server_id: server.id(),
+                        name: template.name.into(),
+                        description: template.description.map(Into::into),
+                        kind: ResourceContextEntryKind::Template(template.uri_template),
+                    }
+                }));
+            }
+        }
+        entries
+    })
+}
+
+pub struct ResourceContextPickerDelegate {
+    context_server_manager: Entity<ContextServerManager>,
+    context_picker: WeakEntity<ContextPicker>,
+    context_store: WeakEntity<ContextStore>,
+    confirm_behavior: ConfirmBehavior,
+    entries: Vec<ResourceContextEntry>,
+    matches: Vec<ResourceContextEntry>,
+    selected_index: usize,
+    template_arguments: Option<TemplateArguments>,
+}
+
+impl ResourceContextPickerDelegate {
+    pub fn new(
+        context_server_manager: Entity<ContextServerManager>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+    ) -> Self {
+        ResourceContextPickerDelegate {
+            context_server_manager,
+            context_picker,
+            context_store,
+            confirm_behavior,
+            entries: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+            template_arguments: None,
+        }
+    }
+
+    fn add_resource(
+        &mut self,
+        server_id: Arc<str>,
+        name: SharedString,
+        uri: Url,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(task) = self
+            .context_store
+            .update(cx, |context_store, cx| {
+                context_store.add_resource(
+                    self.context_server_manager.clone(),
+                    server_id,
+                    name,
+                    uri,
+                    cx,
+                )
+            })
+            .ok()
+        else {
+            return;
+        };
+
+        let confirm_behavior = self.confirm_behavior;
+        cx.spawn_in(window, |this, mut cx| async move {
+            if task.await.notify_async_err(&mut cx).is_none() {
+                return;
+            }
+
+            this.update_in(&mut cx, |this, window, cx| match confirm_behavior {
+                ConfirmBehavior::KeepOpen => cx.notify(),
+                ConfirmBehavior::Close => this.delegate.dismissed(window, cx),
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Clears the query so that it can be used to enter the next template argument.
+    fn reset_query(window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.defer_in(window, |picker, window, cx| {
+            picker.set_query("", window, cx);
+            picker.refresh_placeholder(window, cx);
+        });
+    }
+}
+
+impl PickerDelegate for ResourceContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        if self.template_arguments.is_some() {
+            1
+        } else {
+            self.matches.len()
+        }
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> SharedString {
+        "No resources found".into()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match &self.template_arguments {
+            Some(arguments) => {
+                format!("Enter a value for `{}`…", arguments.current_variable()).into()
+            }
+            None => "Search resources…".into(),
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        if let Some(arguments) = self.template_arguments.as_mut() {
+            arguments.current_value = query;
+            self.selected_index = 0;
+            return Task::ready(());
+        }
+
+        let entries = self.entries.clone();
+        let executor = cx.background_executor().clone();
+        let search_task = cx.background_spawn(async move {
+            if query.is_empty() {
+                entries
+            } else {
+                let candidates = entries
+                    .iter()
+                    .enumerate()
+                    .map(|(id, entry)| StringMatchCandidate::new(id, &entry.name))
+                    .collect::<Vec<_>>();
+                let matches = fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await;
+
+                matches
+                    .into_iter()
+                    .map(|mat| entries[mat.candidate_id].clone())
+                    .collect()
+            }
+        });
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = search_task.await;
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(mut arguments) = self.template_arguments.take() {
+            let variable = arguments.current_variable().to_string();
+            let value = std::mem::take(&mut arguments.current_value);
+            arguments.values.insert(variable, value);
+
+            if arguments.values.len() < arguments.variables.len() {
+                self.template_arguments = Some(arguments);
+                Self::reset_query(window, cx);
+                return;
+            }
+
+            let uri = expand_uri_template(&arguments.uri_template, &arguments.values);
+            Self::reset_query(window, cx);
+            match Url::parse(&uri) {
+                Ok(uri) => self.add_resource(
+                    arguments.entry.server_id,
+                    arguments.entry.name,
+                    uri,
+                    window,
+                    cx,
+                ),
+                Err(error) => log::error!("invalid resource URI {uri:?}: {error}"),
+            }
+            return;
+        }
+
+        let Some(entry) = self.matches.get(self.selected_index).cloned() else {
+            return;
+        };
+
+        match &entry.kind {
+            ResourceContextEntryKind::Resource(uri) => {
+                let uri = uri.clone();
+                self.add_resource(entry.server_id, entry.name, uri, window, cx);
+            }
+            ResourceContextEntryKind::Template(uri_template) => {
+                let variables = uri_template_variables(uri_template);
+                if variables.is_empty() {
+                    match Url::parse(uri_template) {
+                        Ok(uri) => self.add_resource(entry.server_id, entry.name, uri, window, cx),
+                        Err(error) => log::error!("invalid resource URI {uri_template:?}: {error}"),
+                    }
+                    return;
+                }
+
+                self.template_arguments = Some(TemplateArguments {
+                    uri_template: uri_template.clone(),
+                    entry,
+                    variables,
+                    values: HashMap::default(),
+                    current_value: String::new(),
+                });
+                Self::reset_query(window, cx);
+            }
+        }
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        if let Some(arguments) = &self.template_arguments {
+            let mut values = arguments.values.clone();
+            values.insert(
+                arguments.current_variable().to_string(),
+                arguments.current_value.clone(),
+            );
+            let uri = expand_uri_template(&arguments.uri_template, &values);
+
+            return Some(
+                ListItem::new(ix).inset(true).toggle_state(selected).child(
+                    h_flex()
+                        .gap_1p5()
+                        .child(Label::new(arguments.entry.name.clone()))
+                        .child(Label::new(uri).size(LabelSize::Small).color(Color::Muted)),
+                ),
+            );
+        }
+
+        let entry = &self.matches[ix];
+        let added = match &entry.kind {
+            ResourceContextEntryKind::Resource(uri) => {
+                self.context_store.upgrade().map_or(false, |context_store| {
+                    context_store
+                        .read(cx)
+                        .includes_resource(&entry.server_id, uri)
+                        .is_some()
+                })
+            }
+            ResourceContextEntryKind::Template(_) => false,
+        };
+        let icon = match entry.kind {
+            ResourceContextEntryKind::Resource(_) => IconName::DatabaseZap,
+            ResourceContextEntryKind::Template(_) => IconName::Code,
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .gap_1p5()
+                        .w_full()
+                        .child(Icon::new(icon).size(IconSize::XSmall).color(Color::Muted))
+                        .child(Label::new(entry.name.clone()))
+                        .child(
+                            Label::new(entry.server_id.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+                .when_some(entry.description.clone(), |item, description| {
+                    item.tooltip(move |_window, cx| Tooltip::simple(description.clone(), cx))
+                })
+                .when(added, |item| {
+                    item.disabled(true).end_slot(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Icon::new(IconName::Check)
+                                    .size(IconSize::Small)
+                                    .color(Color::Success),
+                            )
+                            .child(Label::new("Added").size(LabelSize::Small)),
+                    )
+                }),
+        )
+    }
+}
@@ -3,17 +3,24 @@ use std::sync::Arc;
 
 use anyhow::{anyhow, bail, Result};
 use collections::{BTreeMap, HashMap, HashSet};
+use context_server::manager::{self as context_server_manager, ContextServerManager};
+use context_server::types::{ResourceContentsType, ResourcesReadResponse};
 use futures::{self, future, Future, FutureExt};
-use gpui::{App, AppContext as _, AsyncApp, Context, Entity, SharedString, Task, WeakEntity};
+use gpui::{
+    App, AppContext as _, AsyncApp, Context, Entity, SharedString, Subscription, Task, WeakEntity,
+};
 use language::Buffer;
 use project::{ProjectPath, Worktree};
 use rope::Rope;
 use text::BufferId;
+use url::Url;
+use util::paths::PathMatcher;
+use util::ResultExt as _;
 use workspace::Workspace;
 
 use crate::context::{
     AssistantContext, ContextBuffer, ContextId, ContextSnapshot, DirectoryContext,
-    FetchedUrlContext, FileContext, ThreadContext,
+    FetchedUrlContext, FileContext, ResourceContext, ThreadContext,
 };
 use crate::context_strip::SuggestedContext;
 use crate::thread::{Thread, ThreadId};
@@ -27,6 +34,8 @@ pub struct ContextStore {
     directories: HashMap<PathBuf, ContextId>,
     threads: HashMap<ThreadId, ContextId>,
     fetched_urls: HashMap<String, ContextId>,
+    resources: HashMap<(Arc<str>, Url), ContextId>,
+    context_server_manager: Option<(Entity<ContextServerManager>, Subscription)>,
 }
 
 impl ContextStore {
@@ -39,6 +48,8 @@ impl ContextStore {
             directories: HashMap::default(),
             threads: HashMap::default(),
             fetched_urls: HashMap::default(),
+            resources: HashMap::default(),
+            context_server_manager: None,
         }
     }
 
@@ -52,12 +63,15 @@ impl ContextStore {
         &self.context
     }
 
-    pub fn clear(&mut self) {
+    pub fn clear(&mut self, cx: &mut Context<Self>) {
         self.context.clear();
         self.files.clear();
         self.directories.clear();
         self.threads.clear();
         self.fetched_urls.clear();
+        for (server_id, uri) in std::mem::take(&mut self.resources).into_keys() {
+            self.unsubscribe_from_resource(&server_id, uri, cx);
+        }
     }
 
     pub fn add_file_from_path(
@@ -82,10 +96,10 @@ impl ContextStore {
             let buffer_entity = open_buffer_task.await?;
             let buffer_id = this.update(&mut cx, |_, cx| buffer_entity.read(cx).remote_id())?;
 
-            let already_included = this.update(&mut cx, |this, _cx| {
+            let already_included = this.update(&mut cx, |this, cx| {
                 match this.will_include_buffer(buffer_id, &project_path.path) {
                     Some(FileInclusion::Direct(context_id)) => {
-                        this.remove_context(context_id);
+                        this.remove_context(context_id, cx);
                         true
                     }
                     Some(FileInclusion::InDirectory(_)) => true,
@@ -153,6 +167,58 @@ impl ContextStore {
             .push(AssistantContext::File(FileContext { id, context_buffer }));
     }
 
+    /// Adds the files of the project matching any of the given globs, leaving the
+    /// files that are already included untouched.
+    pub fn add_files_matching(
+        &mut self,
+        globs: &[String],
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(project) = self
+            .workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("failed to read project")));
+        };
+        let matcher = match PathMatcher::new(globs) {
+            Ok(matcher) => matcher,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+
+        let project_paths = project
+            .read(cx)
+            .worktrees(cx)
+            .flat_map(|worktree| {
+                let worktree = worktree.read(cx);
+                let worktree_id = worktree.id();
+                worktree
+                    .files(false, 0)
+                    .filter(|entry| matcher.is_match(&entry.path))
+                    .map(|entry| ProjectPath {
+                        worktree_id,
+                        path: entry.path.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|project_path| {
+                self.will_include_file_path(&project_path.path, cx)
+                    .is_none()
+            })
+            .collect::<Vec<_>>();
+
+        let tasks = project_paths
+            .into_iter()
+            .map(|project_path| self.add_file_from_path(project_path, cx))
+            .collect::<Vec<_>>();
+        cx.background_spawn(async move {
+            for result in future::join_all(tasks).await {
+                result?;
+            }
+            anyhow::Ok(())
+        })
+    }
+
     pub fn add_directory(
         &mut self,
         project_path: ProjectPath,
@@ -168,7 +234,7 @@ impl ContextStore {
 
         let already_included = if let Some(context_id) = self.includes_directory(&project_path.path)
         {
-            self.remove_context(context_id);
+            self.remove_context(context_id, cx);
             true
         } else {
             false
@@ -257,7 +323,7 @@ impl ContextStore {
 
     pub fn add_thread(&mut self, thread: Entity<Thread>, cx: &mut Context<Self>) {
         if let Some(context_id) = self.includes_thread(&thread.read(cx).id()) {
-            self.remove_context(context_id);
+            self.remove_context(context_id, cx);
         } else {
             self.insert_thread(thread, cx);
         }
@@ -290,6 +356,116 @@ impl ContextStore {
             }));
     }
 
+    /// Reads the resource from the context server and adds it to the context, keeping it up to
+    /// date for as long as it remains attached.
+    pub fn add_resource(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        server_id: Arc<str>,
+        name: SharedString,
+        uri: Url,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if self.includes_resource(&server_id, &uri).is_some() {
+            return Task::ready(Ok(()));
+        }
+
+        let read_task = read_resource(&context_server_manager, &server_id, uri.clone(), cx);
+        cx.spawn(|this, mut cx| async move {
+            let text = read_task.await?;
+            this.update(&mut cx, |this, cx| {
+                if this.includes_resource(&server_id, &uri).is_none() {
+                    this.insert_resource(context_server_manager, server_id, name, uri, text, cx);
+                }
+            })
+        })
+    }
+
+    fn insert_resource(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        server_id: Arc<str>,
+        name: SharedString,
+        uri: Url,
+        text: SharedString,
+        cx: &mut Context<Self>,
+    ) {
+        if self
+            .context_server_manager
+            .as_ref()
+            .map_or(true, |(manager, _)| *manager != context_server_manager)
+        {
+            let subscription =
+                cx.subscribe(&context_server_manager, Self::handle_context_server_event);
+            self.context_server_manager = Some((context_server_manager.clone(), subscription));
+        }
+        context_server_manager.update(cx, |manager, cx| {
+            manager
+                .subscribe_to_resource(&server_id, uri.clone(), cx)
+                .detach_and_log_err(cx)
+        });
+
+        let id = self.next_context_id.post_inc();
+        self.resources.insert((server_id.clone(), uri.clone()), id);
+        self.context
+            .push(AssistantContext::Resource(ResourceContext {
+                id,
+                server_id,
+                uri,
+                name,
+                text,
+            }));
+    }
+
+    fn unsubscribe_from_resource(&self, server_id: &Arc<str>, uri: Url, cx: &mut Context<Self>) {
+        if let Some((context_server_manager, _)) = self.context_server_manager.as_ref() {
+            context_server_manager.update(cx, |manager, cx| {
+                manager
+                    .unsubscribe_from_resource(server_id, uri, cx)
+                    .detach_and_log_err(cx)
+            });
+        }
+    }
+
+    fn handle_context_server_event(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        event: &context_server_manager::Event,
+        cx: &mut Context<Self>,
+    ) {
+        let context_server_manager::Event::ResourceUpdated { server_id, uri } = event else {
+            return;
+        };
+        let Some(id) = self.includes_resource(server_id, uri) else {
+            return;
+        };
+
+        let read_task = read_resource(&context_server_manager, server_id, uri.clone(), cx);
+        cx.spawn(|this, mut cx| async move {
+            let Some(text) = read_task.await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                let Some(AssistantContext::Resource(resource)) =
+                    this.context.iter().find(|context| context.id() == id)
+                else {
+                    return;
+                };
+                let new_context = AssistantContext::Resource(ResourceContext {
+                    id,
+                    server_id: resource.server_id.clone(),
+                    uri: resource.uri.clone(),
+                    name: resource.name.clone(),
+                    text,
+                });
+                this.replace_context(new_context);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
     pub fn accept_suggested_context(
         &mut self,
         suggested: &SuggestedContext,
@@ -314,7 +490,7 @@ impl ContextStore {
         Task::ready(Ok(()))
     }
 
-    pub fn remove_context(&mut self, id: ContextId) {
+    pub fn remove_context(&mut self, id: ContextId, cx: &mut Context<Self>) {
         let Some(ix) = self.context.iter().position(|context| context.id() == id) else {
             return;
         };
@@ -332,6 +508,10 @@ impl ContextStore {
             AssistantContext::Thread(_) => {
                 self.threads.retain(|_, context_id| *context_id != id);
             }
+            AssistantContext::Resource(resource) => {
+                self.resources.retain(|_, context_id| *context_id != id);
+                self.unsubscribe_from_resource(&resource.server_id, resource.uri, cx);
+            }
         }
     }
 
@@ -397,6 +577,12 @@ impl ContextStore {
         self.fetched_urls.get(url).copied()
     }
 
+    pub fn includes_resource(&self, server_id: &Arc<str>, uri: &Url) -> Option<ContextId> {
+        self.resources
+            .get(&(server_id.clone(), uri.clone()))
+            .copied()
+    }
+
     /// Replaces the context that matches the ID of the new context, if any match.
     fn replace_context(&mut self, new_context: AssistantContext) {
         let id = new_context.id();
@@ -418,7 +604,8 @@ impl ContextStore {
                 }
                 AssistantContext::Directory(_)
                 | AssistantContext::FetchedUrl(_)
-                | AssistantContext::Thread(_) => None,
+                | AssistantContext::Thread(_)
+                | AssistantContext::Resource(_) => None,
             })
             .collect()
     }
@@ -515,6 +702,56 @@ fn to_fenced_codeblock(path: &Path, content: Rope) -> SharedString {
     buffer.into()
 }
 
+fn read_resource(
+    context_server_manager: &Entity<ContextServerManager>,
+    server_id: &Arc<str>,
+    uri: Url,
+    cx: &App,
+) -> Task<Result<SharedString>> {
+    let Some(protocol) = context_server_manager
+        .read(cx)
+        .get_server(server_id)
+        .and_then(|server| server.client())
+    else {
+        return Task::ready(Err(anyhow!("context server {server_id} is not running")));
+    };
+
+    cx.spawn(|_| async move {
+        let response = protocol.read_resource(uri).await?;
+        Ok(resource_contents_to_text(response))
+    })
+}
+
+fn resource_contents_to_text(response: ResourcesReadResponse) -> SharedString {
+    let mut text = String::new();
+    for contents in response.contents {
+        match contents {
+            ResourceContentsType::Text(contents) => {
+                text.push_str("```");
+                text.push_str(contents.uri.as_str());
+                text.push('\n');
+                text.push_str(&contents.text);
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str("```\n");
+            }
+            ResourceContentsType::Blob(contents) => {
+                // Binary resources can't be represented in a text message.
+                text.push_str(&format!(
+                    "{} ({}, binary content omitted)\n",
+                    contents.uri,
+                    contents
+                        .mime_type
+                        .as_deref()
+                        .unwrap_or("application/octet-stream")
+                ));
+            }
+        }
+    }
+    text.into()
+}
+
 fn collect_files_in_path(worktree: &Worktree, path: &Path) -> Vec<Arc<Path>> {
     let mut files = Vec::new();
 
@@ -556,6 +793,8 @@ pub fn refresh_context_store_text(
             // and doing the caching properly could be tricky (unless it's already handled by
             // the HttpClient?).
             AssistantContext::FetchedUrl(_) => {}
+            // Resources are refreshed when the context server reports that they changed.
+            AssistantContext::Resource(_) => {}
         }
     }
 
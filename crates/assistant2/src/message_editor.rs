@@ -1,10 +1,11 @@
 use std::sync::Arc;
 
+use assistant_settings::AssistantSettings;
 use editor::actions::MoveUp;
 use editor::{Editor, EditorElement, EditorEvent, EditorStyle};
 use fs::Fs;
 use gpui::{
-    Animation, AnimationExt, App, DismissEvent, Entity, Focusable, Subscription, TextStyle,
+    Animation, AnimationExt, App, Corner, DismissEvent, Entity, Focusable, Subscription, TextStyle,
     WeakEntity,
 };
 use language_model::LanguageModelRegistry;
@@ -15,9 +16,10 @@ use std::time::Duration;
 use text::Bias;
 use theme::ThemeSettings;
 use ui::{
-    prelude::*, ButtonLike, KeyBinding, PlatformStyle, PopoverMenu, PopoverMenuHandle, Switch,
-    Tooltip,
+    prelude::*, ButtonLike, ContextMenu, KeyBinding, PlatformStyle, PopoverMenu, PopoverMenuHandle,
+    Switch, Tooltip,
 };
+use util::ResultExt as _;
 use vim_mode_setting::VimModeSetting;
 use workspace::Workspace;
 
@@ -99,7 +101,13 @@ impl MessageEditor {
             cx.subscribe_in(&context_strip, window, Self::handle_context_strip_event),
         ];
 
-        Self {
+        let use_tools = thread
+            .read(cx)
+            .profile_settings(cx)
+            .and_then(|profile| profile.tools.as_ref())
+            .map_or(false, |tools| !tools.is_empty());
+
+        let this = Self {
             thread,
             editor: editor.clone(),
             context_store,
@@ -116,9 +124,42 @@ impl MessageEditor {
                     cx,
                 )
             }),
-            use_tools: false,
+            use_tools,
             _subscriptions: subscriptions,
+        };
+        this.attach_profile_context(cx);
+        this
+    }
+
+    fn set_profile(&mut self, profile: Option<SharedString>, cx: &mut Context<Self>) {
+        self.thread
+            .update(cx, |thread, cx| thread.set_profile(profile, cx));
+        if let Some(tools) = self
+            .thread
+            .read(cx)
+            .profile_settings(cx)
+            .and_then(|profile| profile.tools.as_ref())
+        {
+            self.use_tools = !tools.is_empty();
         }
+        self.attach_profile_context(cx);
+        cx.notify();
+    }
+
+    fn attach_profile_context(&self, cx: &mut Context<Self>) {
+        let Some(globs) = self
+            .thread
+            .read(cx)
+            .profile_settings(cx)
+            .map(|profile| profile.context.clone())
+            .filter(|globs| !globs.is_empty())
+        else {
+            return;
+        };
+
+        self.context_store
+            .update(cx, |store, cx| store.add_files_matching(&globs, cx))
+            .detach_and_log_err(cx);
     }
 
     fn toggle_chat_mode(&mut self, _: &ChatMode, _window: &mut Window, cx: &mut Context<Self>) {
@@ -141,7 +182,7 @@ impl MessageEditor {
         _window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.context_store.update(cx, |store, _cx| store.clear());
+        self.context_store.update(cx, |store, cx| store.clear(cx));
         cx.notify();
     }
 
@@ -154,9 +195,7 @@ impl MessageEditor {
     }
 
     fn is_model_selected(&self, cx: &App) -> bool {
-        LanguageModelRegistry::read_global(cx)
-            .active_model()
-            .is_some()
+        self.thread.read(cx).model(cx).is_some()
     }
 
     fn send_to_model(
@@ -165,7 +204,11 @@ impl MessageEditor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let provider = LanguageModelRegistry::read_global(cx).active_provider();
+        let Some(model) = self.thread.read(cx).model(cx) else {
+            return;
+        };
+
+        let provider = LanguageModelRegistry::read_global(cx).provider(&model.provider_id());
         if provider
             .as_ref()
             .map_or(false, |provider| provider.must_accept_terms(cx))
@@ -174,11 +217,6 @@ impl MessageEditor {
             return;
         }
 
-        let model_registry = LanguageModelRegistry::read_global(cx);
-        let Some(model) = model_registry.active_model() else {
-            return;
-        };
-
         let user_message = self.editor.update(cx, |editor, cx| {
             let text = editor.text(cx);
             editor.clear(window, cx);
@@ -186,16 +224,21 @@ impl MessageEditor {
         });
 
         let refresh_task = refresh_context_store_text(self.context_store.clone(), cx);
+        let rules_task = self
+            .thread
+            .update(cx, |thread, cx| thread.load_profile_rules(cx));
 
         let thread = self.thread.clone();
         let context_store = self.context_store.clone();
         let use_tools = self.use_tools;
         cx.spawn(move |_, mut cx| async move {
             refresh_task.await;
+            rules_task.await;
             thread
                 .update(&mut cx, |thread, cx| {
                     let context = context_store.read(cx).snapshot(cx).collect::<Vec<_>>();
-                    thread.insert_user_message(user_message, context, cx);
+                    let message_id = thread.insert_user_message(user_message, context, cx);
+                    thread.create_checkpoint(message_id, cx);
                     thread.send_to_model(model, request_kind, use_tools, cx);
                 })
                 .ok();
@@ -260,6 +303,94 @@ impl MessageEditor {
         }
     }
 
+    fn render_profile_selector(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let profiles = AssistantSettings::get_global(cx)
+            .profiles
+            .iter()
+            .map(|(name, profile)| (SharedString::from(name.clone()), profile.clone()))
+            .collect::<Vec<_>>();
+        if profiles.is_empty() {
+            return None;
+        }
+
+        let active_profile = self.thread.read(cx).profile().cloned();
+        let this = cx.entity().downgrade();
+        Some(
+            PopoverMenu::new("profile-selector")
+                .trigger_with_tooltip(
+                    ButtonLike::new("active-profile")
+                        .style(ButtonStyle::Subtle)
+                        .child(
+                            h_flex()
+                                .gap_0p5()
+                                .child(
+                                    Label::new(
+                                        active_profile
+                                            .clone()
+                                            .unwrap_or_else(|| "No Profile".into()),
+                                    )
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                )
+                                .child(
+                                    Icon::new(IconName::ChevronDown)
+                                        .color(Color::Muted)
+                                        .size(IconSize::XSmall),
+                                ),
+                        ),
+                    Tooltip::text("Change Profile"),
+                )
+                .anchor(Corner::BottomRight)
+                .menu(move |window, cx| {
+                    let this = this.clone();
+                    let profiles = profiles.clone();
+                    let active_profile = active_profile.clone();
+                    Some(ContextMenu::build(
+                        window,
+                        cx,
+                        move |mut menu, _window, _cx| {
+                            menu = menu.toggleable_entry(
+                                "No Profile",
+                                active_profile.is_none(),
+                                IconPosition::Start,
+                                None,
+                                {
+                                    let this = this.clone();
+                                    move |_window, cx| {
+                                        this.update(cx, |this, cx| this.set_profile(None, cx))
+                                            .log_err();
+                                    }
+                                },
+                            );
+                            for (name, profile) in &profiles {
+                                let label = match &profile.model {
+                                    Some(model) => format!("{name} ({})", model.model),
+                                    None => name.to_string(),
+                                };
+                                menu = menu.toggleable_entry(
+                                    label,
+                                    active_profile.as_ref() == Some(name),
+                                    IconPosition::Start,
+                                    None,
+                                    {
+                                        let this = this.clone();
+                                        let name = name.clone();
+                                        move |_window, cx| {
+                                            this.update(cx, |this, cx| {
+                                                this.set_profile(Some(name.clone()), cx)
+                                            })
+                                            .log_err();
+                                        }
+                                    },
+                                );
+                            }
+                            menu
+                        },
+                    ))
+                }),
+        )
+    }
+
     fn move_up(&mut self, _: &MoveUp, window: &mut Window, cx: &mut Context<Self>) {
         if self.context_picker_menu_handle.is_deployed()
             || self.inline_context_picker_menu_handle.is_deployed()
@@ -447,59 +578,71 @@ impl Render for MessageEditor {
                                             )),
                                     )
                                     .child(
-                                        h_flex().gap_1().child(self.model_selector.clone()).child(
-                                            ButtonLike::new("submit-message")
-                                                .width(button_width.into())
-                                                .style(ButtonStyle::Filled)
-                                                .disabled(
-                                                    is_editor_empty
-                                                        || !is_model_selected
-                                                        || is_streaming_completion,
-                                                )
-                                                .child(
-                                                    h_flex()
-                                                        .w_full()
-                                                        .justify_between()
-                                                        .child(
-                                                            Label::new("Submit")
-                                                                .size(LabelSize::Small)
-                                                                .color(submit_label_color),
-                                                        )
-                                                        .children(
-                                                            KeyBinding::for_action_in(
-                                                                &Chat,
-                                                                &focus_handle,
-                                                                window,
-                                                                cx,
+                                        h_flex()
+                                            .gap_1()
+                                            .children(self.render_profile_selector(cx))
+                                            .child(self.model_selector.clone())
+                                            .child(
+                                                ButtonLike::new("submit-message")
+                                                    .width(button_width.into())
+                                                    .style(ButtonStyle::Filled)
+                                                    .disabled(
+                                                        is_editor_empty
+                                                            || !is_model_selected
+                                                            || is_streaming_completion,
+                                                    )
+                                                    .child(
+                                                        h_flex()
+                                                            .w_full()
+                                                            .justify_between()
+                                                            .child(
+                                                                Label::new("Submit")
+                                                                    .size(LabelSize::Small)
+                                                                    .color(submit_label_color),
                                                             )
-                                                            .map(|binding| {
-                                                                binding
-                                                                    .when(vim_mode_enabled, |kb| {
-                                                                        kb.size(rems_from_px(12.))
-                                                                    })
-                                                                    .into_any_element()
-                                                            }),
-                                                        ),
-                                                )
-                                                .on_click(move |_event, window, cx| {
-                                                    focus_handle.dispatch_action(&Chat, window, cx);
-                                                })
-                                                .when(is_editor_empty, |button| {
-                                                    button.tooltip(Tooltip::text(
-                                                        "Type a message to submit",
-                                                    ))
-                                                })
-                                                .when(is_streaming_completion, |button| {
-                                                    button.tooltip(Tooltip::text(
-                                                        "Cancel to submit a new message",
-                                                    ))
-                                                })
-                                                .when(!is_model_selected, |button| {
-                                                    button.tooltip(Tooltip::text(
-                                                        "Select a model to continue",
-                                                    ))
-                                                }),
-                                        ),
+                                                            .children(
+                                                                KeyBinding::for_action_in(
+                                                                    &Chat,
+                                                                    &focus_handle,
+                                                                    window,
+                                                                    cx,
+                                                                )
+                                                                .map(|binding| {
+                                                                    binding
+                                                                        .when(
+                                                                            vim_mode_enabled,
+                                                                            |kb| {
+                                                                                kb.size(
+                                                                                    rems_from_px(
+                                                                                        12.,
+                                                                                    ),
+                                                                                )
+                                                                            },
+                                                                        )
+                                                                        .into_any_element()
+                                                                }),
+                                                            ),
+                                                    )
+                                                    .on_click(move |_event, window, cx| {
+                                                        focus_handle
+                                                            .dispatch_action(&Chat, window, cx);
+                                                    })
+                                                    .when(is_editor_empty, |button| {
+                                                        button.tooltip(Tooltip::text(
+                                                            "Type a message to submit",
+                                                        ))
+                                                    })
+                                                    .when(is_streaming_completion, |button| {
+                                                        button.tooltip(Tooltip::text(
+                                                            "Cancel to submit a new message",
+                                                        ))
+                                                    })
+                                                    .when(!is_model_selected, |button| {
+                                                        button.tooltip(Tooltip::text(
+                                                            "Select a model to continue",
+                                                        ))
+                                                    }),
+                                            ),
                                     ),
                             ),
                     ),
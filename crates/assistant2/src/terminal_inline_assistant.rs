@@ -262,6 +262,7 @@ impl TerminalInlineAssistant {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            response_format: None,
         })
     }
 
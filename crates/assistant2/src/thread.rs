@@ -1,21 +1,28 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use assistant_settings::{AssistantProfile, AssistantSettings};
 use assistant_tool::ToolWorkingSet;
 use chrono::{DateTime, Utc};
-use collections::{BTreeMap, HashMap, HashSet};
+use collections::{BTreeMap, BTreeSet, HashMap};
 use futures::StreamExt as _;
-use gpui::{App, Context, EventEmitter, SharedString, Task};
+use gpui::{App, Context, Entity, EventEmitter, SharedString, Subscription, Task};
 use language_model::{
-    LanguageModel, LanguageModelCompletionEvent, LanguageModelRegistry, LanguageModelRequest,
-    LanguageModelRequestMessage, LanguageModelRequestTool, LanguageModelToolResult,
-    LanguageModelToolUseId, MaxMonthlySpendReachedError, MessageContent, PaymentRequiredError,
-    Role, StopReason,
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelId, LanguageModelProviderId,
+    LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
+    LanguageModelRequestTool, LanguageModelToolResult, LanguageModelToolUseId,
+    MaxMonthlySpendReachedError, MessageContent, PaymentRequiredError, Role, StopReason,
+    TokenUsage,
 };
+use project::buffer_store::BufferStoreEvent;
+use project::Project;
+use prompt_store::{compaction_message, PromptStore, COMPACTION_PROMPT};
 use serde::{Deserialize, Serialize};
-use util::{post_inc, TryFutureExt as _};
+use settings::Settings as _;
+use util::{post_inc, ResultExt as _, TryFutureExt as _};
 use uuid::Uuid;
 
+use crate::checkpoint::{restore_checkpoints, Checkpoint};
 use crate::context::{attach_context_to_message, ContextId, ContextSnapshot};
 use crate::thread_store::SavedThread;
 use crate::tool_use::{PendingToolUse, ToolUse, ToolUseState};
@@ -57,6 +64,44 @@ pub struct Message {
     pub id: MessageId,
     pub role: Role,
     pub text: String,
+    /// The reasoning the model streamed before the message's text.
+    pub thinking: Vec<ThinkingBlock>,
+    /// Whether the message is kept verbatim when the thread is compacted.
+    pub pinned: bool,
+}
+
+impl Message {
+    /// Returns the reasoning that can be shown, across all of its blocks.
+    pub fn thinking_text(&self) -> String {
+        self.thinking
+            .iter()
+            .filter_map(|block| match block {
+                ThinkingBlock::Thinking { text, .. } => Some(text.as_str()),
+                ThinkingBlock::Redacted { .. } => None,
+            })
+            .collect()
+    }
+}
+
+/// A block of a model's reasoning, which is sent back as it was streamed, each
+/// block with its own signature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ThinkingBlock {
+    Thinking {
+        text: String,
+        signature: Option<String>,
+    },
+    /// Reasoning the provider encrypted, which can't be shown.
+    Redacted { data: String },
+}
+
+/// A summary that stands in for the older messages of a [`Thread`] in requests
+/// to the model, once the thread nears the model's context window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Compaction {
+    /// The last message covered by the summary.
+    pub through: MessageId,
+    pub summary: String,
 }
 
 /// A thread of conversation with the LLM.
@@ -69,14 +114,29 @@ pub struct Thread {
     next_message_id: MessageId,
     context: BTreeMap<ContextId, ContextSnapshot>,
     context_by_message: HashMap<MessageId, Vec<ContextId>>,
+    compaction: Option<Compaction>,
     completion_count: usize,
     pending_completions: Vec<PendingCompletion>,
+    /// The tokens used by the completions that produced each assistant message.
+    token_usage_by_message: HashMap<MessageId, TokenUsage>,
+    project: Entity<Project>,
+    checkpoints: Vec<Checkpoint>,
     tools: Arc<ToolWorkingSet>,
     tool_use: ToolUseState,
+    /// The name of the profile, from the assistant settings, used for the thread.
+    profile: Option<SharedString>,
+    /// The rules of the profile, as loaded from the prompt library.
+    profile_rules: Vec<String>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl Thread {
-    pub fn new(tools: Arc<ToolWorkingSet>, _cx: &mut Context<Self>) -> Self {
+    pub fn new(
+        project: Entity<Project>,
+        tools: Arc<ToolWorkingSet>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscriptions = Self::subscribe_to_project(&project, cx);
         Self {
             id: ThreadId::new(),
             updated_at: Utc::now(),
@@ -86,19 +146,31 @@ impl Thread {
             next_message_id: MessageId(0),
             context: BTreeMap::default(),
             context_by_message: HashMap::default(),
+            compaction: None,
             completion_count: 0,
             pending_completions: Vec::new(),
+            token_usage_by_message: HashMap::default(),
+            project,
+            checkpoints: Vec::new(),
             tools,
             tool_use: ToolUseState::new(),
+            profile: AssistantSettings::get_global(cx)
+                .default_profile
+                .clone()
+                .map(SharedString::from),
+            profile_rules: Vec::new(),
+            _subscriptions: subscriptions,
         }
     }
 
     pub fn from_saved(
         id: ThreadId,
         saved: SavedThread,
+        project: Entity<Project>,
         tools: Arc<ToolWorkingSet>,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Self {
+        let subscriptions = Self::subscribe_to_project(&project, cx);
         let next_message_id = MessageId(
             saved
                 .messages
@@ -120,18 +192,41 @@ impl Thread {
                     id: message.id,
                     role: message.role,
                     text: message.text,
+                    thinking: message.thinking,
+                    pinned: message.pinned,
                 })
                 .collect(),
             next_message_id,
             context: BTreeMap::default(),
             context_by_message: HashMap::default(),
+            compaction: saved.compaction,
             completion_count: 0,
             pending_completions: Vec::new(),
+            token_usage_by_message: HashMap::default(),
+            project,
+            checkpoints: Vec::new(),
             tools,
             tool_use,
+            profile: saved.profile,
+            profile_rules: Vec::new(),
+            _subscriptions: subscriptions,
         }
     }
 
+    fn subscribe_to_project(
+        project: &Entity<Project>,
+        cx: &mut Context<Self>,
+    ) -> Vec<Subscription> {
+        let buffer_store = project.read(cx).buffer_store().clone();
+        vec![cx.subscribe(&buffer_store, |this, _, event, cx| {
+            if let BufferStoreEvent::BufferAdded(buffer) = event {
+                if let Some(checkpoint) = this.checkpoints.last_mut() {
+                    checkpoint.track_buffer(buffer, cx);
+                }
+            }
+        })]
+    }
+
     pub fn id(&self) -> &ThreadId {
         &self.id
     }
@@ -166,10 +261,18 @@ impl Thread {
         self.messages.iter().find(|message| message.id == id)
     }
 
+    fn message_mut(&mut self, id: MessageId) -> Option<&mut Message> {
+        self.messages.iter_mut().find(|message| message.id == id)
+    }
+
     pub fn messages(&self) -> impl Iterator<Item = &Message> {
         self.messages.iter()
     }
 
+    pub fn token_usage_for_message(&self, id: MessageId) -> Option<&TokenUsage> {
+        self.token_usage_by_message.get(&id)
+    }
+
     pub fn is_streaming(&self) -> bool {
         !self.pending_completions.is_empty()
     }
@@ -210,12 +313,62 @@ impl Thread {
         text: impl Into<String>,
         context: Vec<ContextSnapshot>,
         cx: &mut Context<Self>,
-    ) {
+    ) -> MessageId {
         let message_id = self.insert_message(Role::User, text, cx);
         let context_ids = context.iter().map(|context| context.id).collect::<Vec<_>>();
         self.context
             .extend(context.into_iter().map(|context| (context.id, context)));
         self.context_by_message.insert(message_id, context_ids);
+        message_id
+    }
+
+    /// Records the state of the project's files before the turn started by the given
+    /// user message, so that the changes made during the turn can be reverted.
+    pub fn create_checkpoint(&mut self, message_id: MessageId, cx: &mut Context<Self>) {
+        let checkpoint = Checkpoint::new(
+            message_id,
+            self.project.read(cx),
+            self.checkpoints.last(),
+            cx,
+        );
+        self.checkpoints.push(checkpoint);
+    }
+
+    pub fn has_checkpoint(&self, message_id: MessageId) -> bool {
+        self.checkpoints
+            .iter()
+            .any(|checkpoint| checkpoint.message_id == message_id)
+    }
+
+    /// Reverts the changes made to the project's files since the turn started by the
+    /// given user message, including the changes of every later turn.
+    pub fn restore_checkpoint(
+        &mut self,
+        message_id: MessageId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(ix) = self
+            .checkpoints
+            .iter()
+            .position(|checkpoint| checkpoint.message_id == message_id)
+        else {
+            return Task::ready(Err(anyhow!("no checkpoint found for message")));
+        };
+
+        let task = restore_checkpoints(&self.checkpoints[ix..], self.project.clone(), cx);
+        // The files are back to their state at this checkpoint, so the later ones no longer apply.
+        self.checkpoints.truncate(ix + 1);
+        cx.notify();
+        task
+    }
+
+    /// Returns the assistant message being streamed, inserting one when the
+    /// response starts with reasoning.
+    fn streamed_assistant_message_id(&mut self, cx: &mut Context<Self>) -> MessageId {
+        match self.messages.last() {
+            Some(message) if message.role == Role::Assistant => message.id,
+            _ => self.insert_message(Role::Assistant, "", cx),
+        }
     }
 
     pub fn insert_message(
@@ -229,6 +382,8 @@ impl Thread {
             id,
             role,
             text: text.into(),
+            thinking: Vec::new(),
+            pinned: false,
         });
         self.touch_updated_at();
         cx.emit(ThreadEvent::MessageAdded(id));
@@ -252,6 +407,118 @@ impl Thread {
         true
     }
 
+    pub fn toggle_message_pinned(&mut self, id: MessageId, cx: &mut Context<Self>) {
+        let Some(message) = self.messages.iter_mut().find(|message| message.id == id) else {
+            return;
+        };
+        message.pinned = !message.pinned;
+        self.touch_updated_at();
+        cx.emit(ThreadEvent::MessagePinChanged(id));
+    }
+
+    pub fn profile(&self) -> Option<&SharedString> {
+        self.profile.as_ref()
+    }
+
+    pub fn set_profile(&mut self, profile: Option<SharedString>, cx: &mut Context<Self>) {
+        self.profile = profile;
+        self.profile_rules.clear();
+        cx.notify();
+    }
+
+    /// Returns the settings of the thread's profile, if it still exists.
+    pub fn profile_settings<'a>(&self, cx: &'a App) -> Option<&'a AssistantProfile> {
+        let profile = self.profile.as_ref()?;
+        AssistantSettings::get_global(cx).profiles.get(&**profile)
+    }
+
+    /// Returns whether the thread's profile lets the model use the tool with the given name.
+    pub fn is_tool_enabled(&self, tool_name: &str, cx: &App) -> bool {
+        self.profile_settings(cx)
+            .and_then(|profile| profile.tools.as_ref())
+            .map_or(true, |tools| tools.iter().any(|tool| tool == tool_name))
+    }
+
+    /// Returns the model pinned by the thread's profile, falling back to the
+    /// active model.
+    pub fn model(&self, cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        let registry = LanguageModelRegistry::read_global(cx);
+        if let Some(selection) = self
+            .profile_settings(cx)
+            .and_then(|profile| profile.model.as_ref())
+        {
+            let provider_id = LanguageModelProviderId::from(selection.provider.clone());
+            let model_id = LanguageModelId::from(selection.model.clone());
+            let model = registry.provider(&provider_id).and_then(|provider| {
+                provider
+                    .provided_models(cx)
+                    .into_iter()
+                    .find(|model| model.id() == model_id)
+            });
+            if model.is_some() {
+                return model;
+            }
+            log::warn!(
+                "model {:?} of profile {:?} is not available",
+                selection.model,
+                self.profile
+            );
+        }
+
+        registry.active_model()
+    }
+
+    /// Loads the rules of the thread's profile from the prompt library, so that
+    /// edits to them apply to the next request.
+    pub fn load_profile_rules(&mut self, cx: &mut Context<Self>) -> Task<()> {
+        let rules = self
+            .profile_settings(cx)
+            .map(|profile| profile.rules.clone())
+            .unwrap_or_default();
+        if rules.is_empty() {
+            self.profile_rules.clear();
+            return Task::ready(());
+        }
+
+        let prompt_store = PromptStore::global(cx);
+        cx.spawn(|this, mut cx| async move {
+            let profile_rules = async {
+                let prompt_store = prompt_store.await?;
+                let mut profile_rules = Vec::new();
+                for title in rules {
+                    let Some(id) = prompt_store.id_for_title(&title) else {
+                        log::warn!("rule {title:?} was not found in the prompt library");
+                        continue;
+                    };
+                    profile_rules.push(prompt_store.load(id).await?);
+                }
+                anyhow::Ok(profile_rules)
+            }
+            .await
+            .log_err()
+            .unwrap_or_default();
+
+            this.update(&mut cx, |this, _cx| this.profile_rules = profile_rules)
+                .ok();
+        })
+    }
+
+    pub fn compaction(&self) -> Option<&Compaction> {
+        self.compaction.as_ref()
+    }
+
+    /// Returns whether the message is replaced by the compaction summary, rather than
+    /// sent to the model verbatim.
+    pub fn is_message_compacted(&self, message: &Message) -> bool {
+        !message.pinned && self.is_in_compacted_region(message.id)
+    }
+
+    fn is_in_compacted_region(&self, id: MessageId) -> bool {
+        self.compaction
+            .as_ref()
+            .map_or(false, |compaction| id <= compaction.through)
+    }
+
     pub fn delete_message(&mut self, id: MessageId, cx: &mut Context<Self>) -> bool {
         let Some(index) = self.messages.iter().position(|message| message.id == id) else {
             return false;
@@ -291,6 +558,26 @@ impl Thread {
         use_tools: bool,
         cx: &mut Context<Self>,
     ) {
+        let request = self.to_completion_request_with_tools(request_kind, use_tools, cx);
+
+        let settings = AssistantSettings::get_global(cx);
+        if matches!(request_kind, RequestKind::Chat)
+            && settings.auto_compact
+            && self.next_compaction_boundary().is_some()
+        {
+            let threshold = settings.auto_compact_threshold;
+            self.compact_and_stream_completion(request, model, threshold, use_tools, cx);
+        } else {
+            self.stream_completion(request, model, cx);
+        }
+    }
+
+    fn to_completion_request_with_tools(
+        &self,
+        request_kind: RequestKind,
+        use_tools: bool,
+        cx: &App,
+    ) -> LanguageModelRequest {
         let mut request = self.to_completion_request(request_kind, cx);
 
         if use_tools {
@@ -298,6 +585,7 @@ impl Thread {
                 .tools()
                 .tools(cx)
                 .into_iter()
+                .filter(|tool| self.is_tool_enabled(&tool.name(), cx))
                 .map(|tool| LanguageModelRequestTool {
                     name: tool.name(),
                     description: tool.description(),
@@ -306,43 +594,306 @@ impl Thread {
                 .collect();
         }
 
-        self.stream_completion(request, model, cx);
+        request
+    }
+
+    /// Streams the completion, first compacting the thread if the request takes up
+    /// more than `threshold` of the model's context window.
+    fn compact_and_stream_completion(
+        &mut self,
+        request: LanguageModelRequest,
+        model: Arc<dyn LanguageModel>,
+        threshold: f32,
+        use_tools: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let pending_completion_id = post_inc(&mut self.completion_count);
+
+        let task = cx.spawn(|thread, mut cx| async move {
+            let request = async {
+                let Some(token_count) = cx
+                    .update(|cx| model.count_tokens(request.clone(), cx))?
+                    .await
+                    .log_err()
+                else {
+                    return anyhow::Ok(request);
+                };
+                if (token_count as f32) < model.max_token_count() as f32 * threshold {
+                    return Ok(request);
+                }
+
+                let compacted_request = async {
+                    thread
+                        .update(&mut cx, |thread, cx| thread.compact(model.clone(), cx))?
+                        .await?;
+                    thread.update(&mut cx, |thread, cx| {
+                        thread.to_completion_request_with_tools(RequestKind::Chat, use_tools, cx)
+                    })
+                }
+                .await;
+                match compacted_request {
+                    Ok(request) => Ok(request),
+                    // A request that still fits in the context window is sent as is.
+                    Err(error) if token_count <= model.max_token_count() => {
+                        log::error!("failed to compact thread: {error:#}");
+                        Ok(request)
+                    }
+                    Err(error) => Err(error.context(
+                        "The thread is too long for the model's context window and couldn't be compacted",
+                    )),
+                }
+            }
+            .await;
+
+            thread
+                .update(&mut cx, |thread, cx| {
+                    thread
+                        .pending_completions
+                        .retain(|completion| completion.id != pending_completion_id);
+
+                    match request {
+                        Ok(request) => thread.stream_completion(request, model, cx),
+                        Err(error) => cx.emit(ThreadEvent::ShowError(ThreadError::Message(
+                            format!("{error:#}").into(),
+                        ))),
+                    }
+                })
+                .ok();
+        });
+
+        self.pending_completions.push(PendingCompletion {
+            id: pending_completion_id,
+            _task: task,
+        });
+    }
+
+    /// Returns the last message that compacting the thread now would summarize.
+    ///
+    /// The messages of the current turn, starting with the last message the user sent,
+    /// are never summarized, so that the model sees its ongoing tool uses verbatim.
+    fn next_compaction_boundary(&self) -> Option<MessageId> {
+        let current_turn_ix = self.messages.iter().rposition(|message| {
+            message.role == Role::User && !self.tool_use.message_has_tool_results(message.id)
+        })?;
+        let boundary = self.messages[..current_turn_ix].last()?.id;
+        (!self.is_in_compacted_region(boundary)).then_some(boundary)
+    }
+
+    /// Summarizes the messages before the current turn with the given model, replacing
+    /// them in future requests. Pinned messages and attached context are kept verbatim.
+    ///
+    /// When the messages don't fit in a single summary request, the oldest ones are
+    /// summarized first, and each later summary builds on the one before it.
+    pub fn compact(
+        &mut self,
+        model: Arc<dyn LanguageModel>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(target) = self.next_compaction_boundary() else {
+            return Task::ready(Ok(()));
+        };
+        let max_request_tokens = (model.max_token_count() as f32
+            * AssistantSettings::get_global(cx).auto_compact_threshold)
+            as usize;
+
+        cx.spawn(|this, mut cx| async move {
+            loop {
+                let message_ids = this.read_with(&cx, |this, _| {
+                    this.messages
+                        .iter()
+                        .map(|message| message.id)
+                        .filter(|id| *id <= target && !this.is_in_compacted_region(*id))
+                        .collect::<Vec<_>>()
+                })?;
+                if message_ids.is_empty() {
+                    return Ok(());
+                }
+
+                // Find the most messages whose summary request fits, trying all of them
+                // first, as they usually do.
+                let request_fits = |count: usize| {
+                    let this = this.clone();
+                    let model = model.clone();
+                    let through = message_ids[count - 1];
+                    let cx = cx.clone();
+                    async move {
+                        let request =
+                            this.read_with(&cx, |this, _| this.compaction_request(through))?;
+                        let token_count = cx.update(|cx| model.count_tokens(request, cx))?.await?;
+                        anyhow::Ok(token_count <= max_request_tokens)
+                    }
+                };
+                let mut count = message_ids.len();
+                if !request_fits(count).await? {
+                    let (mut fitting, mut too_many) = (0, count);
+                    while too_many - fitting > 1 {
+                        let mid = (fitting + too_many) / 2;
+                        if request_fits(mid).await? {
+                            fitting = mid;
+                        } else {
+                            too_many = mid;
+                        }
+                    }
+                    if fitting == 0 {
+                        return Err(anyhow!("a message is too long to summarize"));
+                    }
+                    count = fitting;
+                }
+
+                let through = message_ids[count - 1];
+                let request = this.read_with(&cx, |this, _| this.compaction_request(through))?;
+                let stream = model.stream_completion_text(request, &cx);
+                let mut chunks = stream.await?.stream;
+                let mut summary = String::new();
+                while let Some(chunk) = chunks.next().await {
+                    summary.push_str(&chunk?);
+                }
+
+                if summary.trim().is_empty() {
+                    return Err(anyhow!("the model returned an empty summary"));
+                }
+
+                this.update(&mut cx, |this, cx| {
+                    this.compaction = Some(Compaction { through, summary });
+                    this.touch_updated_at();
+                    cx.emit(ThreadEvent::Compacted);
+                    cx.notify();
+                })?;
+            }
+        })
+    }
+
+    /// Returns the request that summarizes the messages through the given one, along
+    /// with the summary of those compacted before.
+    fn compaction_request(&self, through: MessageId) -> LanguageModelRequest {
+        let mut request = LanguageModelRequest {
+            messages: Vec::new(),
+            tools: Vec::new(),
+            stop: Vec::new(),
+            temperature: None,
+            response_format: None,
+        };
+        if let Some(compaction) = &self.compaction {
+            request.messages.push(LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![compaction_message(&compaction.summary).into()],
+                cache: false,
+            });
+        }
+        for message in &self.messages {
+            if message.id > through {
+                break;
+            }
+            // The messages summarized before are covered by the previous summary.
+            if self.is_in_compacted_region(message.id) {
+                continue;
+            }
+
+            let mut content = Vec::new();
+            if !message.text.is_empty() {
+                content.push(MessageContent::Text(message.text.clone()));
+            }
+            // Tool uses and results are described as text, since the summary request
+            // doesn't offer any tools.
+            for tool_use in self.tool_use.tool_uses_for_message(message.id) {
+                content.push(MessageContent::Text(format!(
+                    "[Used the `{}` tool with input: {}]",
+                    tool_use.name, tool_use.input
+                )));
+            }
+            for tool_result in self.tool_use.tool_results_for_message(message.id) {
+                content.push(MessageContent::Text(format!(
+                    "[Tool result: {}]",
+                    tool_result.content
+                )));
+            }
+            if !content.is_empty() {
+                request.messages.push(LanguageModelRequestMessage {
+                    role: message.role,
+                    content,
+                    cache: false,
+                });
+            }
+        }
+        request.messages.push(LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![COMPACTION_PROMPT.into()],
+            cache: false,
+        });
+
+        request
     }
 
     pub fn to_completion_request(
         &self,
         request_kind: RequestKind,
-        _cx: &App,
+        cx: &App,
     ) -> LanguageModelRequest {
         let mut request = LanguageModelRequest {
             messages: vec![],
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            response_format: None,
         };
 
-        let mut referenced_context_ids = HashSet::default();
+        // Context is collected in a stable order so that the prompt prefix, and with it
+        // the provider's prompt cache, stays the same across requests.
+        let mut referenced_context_ids = BTreeSet::default();
+
+        if let Some(compaction) = &self.compaction {
+            request.messages.push(LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![compaction_message(&compaction.summary).into()],
+                cache: false,
+            });
+        }
 
         for message in &self.messages {
+            // The context attached to summarized messages is still sent verbatim.
             if let Some(context_ids) = self.context_by_message.get(&message.id) {
                 referenced_context_ids.extend(context_ids);
             }
 
+            if self.is_message_compacted(message) {
+                continue;
+            }
+            // Pinned messages from the summarized region are sent without their tool
+            // uses and results, since their counterparts may have been summarized.
+            let include_tool_use = !self.is_in_compacted_region(message.id);
+
             let mut request_message = LanguageModelRequestMessage {
                 role: message.role,
                 content: Vec::new(),
                 cache: false,
             };
             match request_kind {
-                RequestKind::Chat => {
+                RequestKind::Chat if include_tool_use => {
                     self.tool_use
                         .attach_tool_results(message.id, &mut request_message);
                 }
+                RequestKind::Chat => {}
                 RequestKind::Summarize => {
                     // We don't care about tool use during summarization.
                 }
             }
 
+            // Providers that need the reasoning to continue a turn get it back, but it
+            // isn't part of what gets summarized.
+            if matches!(request_kind, RequestKind::Chat) {
+                request_message
+                    .content
+                    .extend(message.thinking.iter().map(|block| match block {
+                        ThinkingBlock::Thinking { text, signature } => MessageContent::Thinking {
+                            text: text.clone(),
+                            signature: signature.clone(),
+                        },
+                        ThinkingBlock::Redacted { data } => {
+                            MessageContent::RedactedThinking { data: data.clone() }
+                        }
+                    }));
+            }
+
             if !message.text.is_empty() {
                 request_message
                     .content
@@ -350,23 +901,30 @@ impl Thread {
             }
 
             match request_kind {
-                RequestKind::Chat => {
+                RequestKind::Chat if include_tool_use => {
                     self.tool_use
                         .attach_tool_uses(message.id, &mut request_message);
                 }
+                RequestKind::Chat => {}
                 RequestKind::Summarize => {
                     // We don't care about tool use during summarization.
                 }
             }
 
+            if !include_tool_use && request_message.content.is_empty() {
+                continue;
+            }
+
             request.messages.push(request_message);
         }
 
+        // The attached context changes far less often than the conversation, so it goes
+        // first and is marked as a cache anchor along with the latest message.
         if !referenced_context_ids.is_empty() {
             let mut context_message = LanguageModelRequestMessage {
                 role: Role::User,
                 content: Vec::new(),
-                cache: false,
+                cache: true,
             };
 
             let referenced_context = referenced_context_ids
@@ -375,7 +933,27 @@ impl Thread {
                 .cloned();
             attach_context_to_message(&mut context_message, referenced_context);
 
-            request.messages.push(context_message);
+            request.messages.insert(0, context_message);
+        }
+
+        if matches!(request_kind, RequestKind::Chat) {
+            if !self.profile_rules.is_empty() {
+                request.messages.insert(
+                    0,
+                    LanguageModelRequestMessage {
+                        role: Role::System,
+                        content: vec![self.profile_rules.join("\n\n").into()],
+                        cache: false,
+                    },
+                );
+            }
+            request.temperature = self
+                .profile_settings(cx)
+                .and_then(|profile| profile.temperature);
+
+            if let Some(last_message) = request.messages.last_mut() {
+                last_message.cache = true;
+            }
         }
 
         request
@@ -391,6 +969,7 @@ impl Thread {
 
         let task = cx.spawn(|thread, mut cx| async move {
             let stream = model.stream_completion(request, &cx);
+            let mut token_usage = None;
             let stream_completion = async {
                 let mut events = stream.await?;
                 let mut stop_reason = StopReason::EndTurn;
@@ -406,6 +985,9 @@ impl Thread {
                             LanguageModelCompletionEvent::Stop(reason) => {
                                 stop_reason = reason;
                             }
+                            LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                                token_usage = Some(usage);
+                            }
                             LanguageModelCompletionEvent::Text(chunk) => {
                                 if let Some(last_message) = thread.messages.last_mut() {
                                     if last_message.role == Role::Assistant {
@@ -424,6 +1006,37 @@ impl Thread {
                                     }
                                 }
                             }
+                            LanguageModelCompletionEvent::Thinking { text, signature } => {
+                                let message_id = thread.streamed_assistant_message_id(cx);
+                                if let Some(message) = thread.message_mut(message_id) {
+                                    // A signature ends its block, so any reasoning after it
+                                    // starts a new one.
+                                    match message.thinking.last_mut() {
+                                        Some(ThinkingBlock::Thinking {
+                                            text: block_text,
+                                            signature: block_signature @ None,
+                                        }) => {
+                                            block_text.push_str(&text);
+                                            *block_signature = signature;
+                                        }
+                                        _ => message.thinking.push(ThinkingBlock::Thinking {
+                                            text: text.clone(),
+                                            signature,
+                                        }),
+                                    }
+                                    if !text.is_empty() {
+                                        cx.emit(ThreadEvent::StreamedAssistantThinking(
+                                            message_id, text,
+                                        ));
+                                    }
+                                }
+                            }
+                            LanguageModelCompletionEvent::RedactedThinking { data } => {
+                                let message_id = thread.streamed_assistant_message_id(cx);
+                                if let Some(message) = thread.message_mut(message_id) {
+                                    message.thinking.push(ThinkingBlock::Redacted { data });
+                                }
+                            }
                             LanguageModelCompletionEvent::ToolUse(tool_use) => {
                                 if let Some(last_assistant_message) = thread
                                     .messages
@@ -460,6 +1073,29 @@ impl Thread {
 
             let result = stream_completion.await;
 
+            // Requests that fail partway are recorded too, as their tokens are still billed.
+            if let Some(usage) = token_usage {
+                thread
+                    .update(&mut cx, |thread, cx| {
+                        language_model_usage::record_usage(
+                            model.as_ref(),
+                            usage,
+                            Some(&thread.project),
+                            cx,
+                        );
+                        if let Some(last_assistant_message) = thread
+                            .messages
+                            .iter()
+                            .rfind(|message| message.role == Role::Assistant)
+                        {
+                            thread
+                                .token_usage_by_message
+                                .insert(last_assistant_message.id, usage);
+                        }
+                    })
+                    .ok();
+            }
+
             thread
                 .update(&mut cx, |thread, cx| match result.as_ref() {
                     Ok(stop_reason) => match stop_reason {
@@ -576,6 +1212,25 @@ impl Thread {
             .run_pending_tool(tool_use_id, insert_output_task);
     }
 
+    /// Marks the pending tool use as awaiting the user's approval before it can run.
+    pub fn request_tool_use_confirmation(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        cx: &mut Context<Self>,
+    ) {
+        self.tool_use.request_confirmation(tool_use_id);
+        cx.notify();
+    }
+
+    /// Rejects a tool use that was awaiting the user's approval, reporting the denial to the model.
+    pub fn deny_tool_use(&mut self, tool_use_id: LanguageModelToolUseId, cx: &mut Context<Self>) {
+        self.insert_tool_output(
+            tool_use_id,
+            Task::ready(Err(anyhow!("The user denied permission to run this tool."))),
+            cx,
+        );
+    }
+
     /// Cancels the last pending completion, if there are any pending.
     ///
     /// Returns whether a completion was canceled.
@@ -600,10 +1255,13 @@ pub enum ThreadEvent {
     ShowError(ThreadError),
     StreamedCompletion,
     StreamedAssistantText(MessageId, String),
+    StreamedAssistantThinking(MessageId, String),
     MessageAdded(MessageId),
     MessageEdited(MessageId),
     MessageDeleted(MessageId),
     SummaryChanged,
+    MessagePinChanged(MessageId),
+    Compacted,
     UsePendingTools,
     ToolFinished {
         #[allow(unused)]
@@ -617,3 +1275,193 @@ struct PendingCompletion {
     id: usize,
     _task: Task<()>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use language_model::fake_provider::FakeLanguageModel;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::cell::RefCell;
+    use std::path::Path;
+    use std::rc::Rc;
+
+    #[gpui::test]
+    async fn test_auto_compact(cx: &mut TestAppContext) {
+        // The fake model counts a token per byte, so threads compact past 1000 bytes.
+        let (thread, model) = init_test(0.001, cx).await;
+        let pinned_id = thread.update(cx, |thread, cx| {
+            let pinned_id = thread.insert_user_message("Always answer in French.", vec![], cx);
+            thread.toggle_message_pinned(pinned_id, cx);
+            thread.insert_message(Role::Assistant, "D'accord.", cx);
+            thread.insert_user_message("old question ".repeat(20), vec![], cx);
+            thread.insert_message(Role::Assistant, "old answer ".repeat(20), cx);
+            thread.insert_user_message("new question ".repeat(60), vec![], cx);
+            thread.send_to_model(model.clone(), RequestKind::Chat, false, cx);
+            pinned_id
+        });
+        cx.run_until_parked();
+
+        let summary_request = model.pending_completions().pop().unwrap();
+        assert_eq!(
+            request_contents(&summary_request),
+            vec![
+                "Always answer in French.".to_string(),
+                "D'accord.".to_string(),
+                "old question ".repeat(20),
+                "old answer ".repeat(20),
+                COMPACTION_PROMPT.to_string(),
+            ]
+        );
+        model.stream_last_completion_response("The user asked an old question.".into());
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+
+        let request = model.pending_completions().pop().unwrap();
+        assert_eq!(
+            request_contents(&request),
+            vec![
+                compaction_message("The user asked an old question."),
+                "Always answer in French.".to_string(),
+                "new question ".repeat(60),
+            ]
+        );
+        thread.read_with(cx, |thread, _| {
+            let pinned = thread.message(pinned_id).unwrap();
+            assert!(!thread.is_message_compacted(pinned));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_compaction_in_passes_when_messages_overflow(cx: &mut TestAppContext) {
+        let (thread, model) = init_test(0.001, cx).await;
+        thread.update(cx, |thread, cx| {
+            thread.insert_user_message("a".repeat(400), vec![], cx);
+            thread.insert_message(Role::Assistant, "b".repeat(400), cx);
+            thread.insert_user_message("c".repeat(400), vec![], cx);
+            thread.insert_message(Role::Assistant, "d".repeat(100), cx);
+            thread.insert_user_message("e".repeat(100), vec![], cx);
+            thread.send_to_model(model.clone(), RequestKind::Chat, false, cx);
+        });
+        cx.run_until_parked();
+
+        // Only one of the older messages fits in a summary request at a time, so each
+        // pass summarizes the next one along with the summary of the previous pass.
+        let summary_request = model.pending_completions().pop().unwrap();
+        assert_eq!(
+            request_contents(&summary_request),
+            vec!["a".repeat(400), COMPACTION_PROMPT.to_string()]
+        );
+        model.stream_last_completion_response("summary 1".into());
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+
+        let summary_request = model.pending_completions().pop().unwrap();
+        assert_eq!(
+            request_contents(&summary_request),
+            vec![
+                compaction_message("summary 1"),
+                "b".repeat(400),
+                COMPACTION_PROMPT.to_string()
+            ]
+        );
+        model.stream_last_completion_response("summary 2".into());
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+
+        let summary_request = model.pending_completions().pop().unwrap();
+        assert_eq!(
+            request_contents(&summary_request),
+            vec![
+                compaction_message("summary 2"),
+                "c".repeat(400),
+                "d".repeat(100),
+                COMPACTION_PROMPT.to_string()
+            ]
+        );
+        model.stream_last_completion_response("summary 3".into());
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+
+        let request = model.pending_completions().pop().unwrap();
+        assert_eq!(
+            request_contents(&request),
+            vec![compaction_message("summary 3"), "e".repeat(100)]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_request_too_long_to_compact(cx: &mut TestAppContext) {
+        let (thread, model) = init_test(0.001, cx).await;
+        let events = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let events = events.clone();
+            cx.subscribe(&thread, move |_, event: &ThreadEvent, _| {
+                events.borrow_mut().push(event.clone());
+            })
+            .detach();
+        });
+
+        // The older message alone exceeds both the summary request's budget and the
+        // model's context window, so nothing is sent.
+        thread.update(cx, |thread, cx| {
+            thread.insert_user_message("a".repeat(model.max_token_count()), vec![], cx);
+            thread.insert_message(Role::Assistant, "b", cx);
+            thread.insert_user_message("c", vec![], cx);
+            thread.send_to_model(model.clone(), RequestKind::Chat, false, cx);
+        });
+        cx.run_until_parked();
+
+        assert!(model.pending_completions().is_empty());
+        assert!(events
+            .borrow()
+            .iter()
+            .any(|event| matches!(event, ThreadEvent::ShowError(ThreadError::Message(_)))));
+        thread.read_with(cx, |thread, _| {
+            assert!(thread.pending_completions.is_empty());
+            assert!(thread.compaction.is_none());
+        });
+    }
+
+    async fn init_test(
+        auto_compact_threshold: f32,
+        cx: &mut TestAppContext,
+    ) -> (Entity<Thread>, Arc<FakeLanguageModel>) {
+        cx.update(|cx| {
+            let mut settings_store = SettingsStore::test(cx);
+            settings_store
+                .set_user_settings(
+                    &json!({
+                        "assistant": {
+                            "version": "2",
+                            "auto_compact_threshold": auto_compact_threshold,
+                        }
+                    })
+                    .to_string(),
+                    cx,
+                )
+                .unwrap();
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            AssistantSettings::register(cx);
+            LanguageModelRegistry::test(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({})).await;
+        let project = Project::test(fs, [Path::new("/root")], cx).await;
+        let thread = cx.new(|cx| Thread::new(project, Arc::new(ToolWorkingSet::default()), cx));
+        (thread, Arc::new(FakeLanguageModel::default()))
+    }
+
+    fn request_contents(request: &LanguageModelRequest) -> Vec<String> {
+        request
+            .messages
+            .iter()
+            .map(|message| message.string_contents())
+            .collect()
+    }
+}
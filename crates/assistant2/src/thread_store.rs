@@ -19,7 +19,7 @@ use project::Project;
 use serde::{Deserialize, Serialize};
 use util::ResultExt as _;
 
-use crate::thread::{MessageId, Thread, ThreadId};
+use crate::thread::{Compaction, MessageId, ThinkingBlock, Thread, ThreadId};
 
 pub fn init(cx: &mut App) {
     ThreadsDatabase::init(cx);
@@ -62,6 +62,10 @@ impl ThreadStore {
         Ok(this)
     }
 
+    pub fn context_server_manager(&self) -> Entity<ContextServerManager> {
+        self.context_server_manager.clone()
+    }
+
     /// Returns the number of threads.
     pub fn thread_count(&self) -> usize {
         self.threads.len()
@@ -78,7 +82,7 @@ impl ThreadStore {
     }
 
     pub fn create_thread(&mut self, cx: &mut Context<Self>) -> Entity<Thread> {
-        cx.new(|cx| Thread::new(self.tools.clone(), cx))
+        cx.new(|cx| Thread::new(self.project.clone(), self.tools.clone(), cx))
     }
 
     pub fn open_thread(
@@ -96,7 +100,15 @@ impl ThreadStore {
                 .ok_or_else(|| anyhow!("no thread found with ID: {id:?}"))?;
 
             this.update(&mut cx, |this, cx| {
-                cx.new(|cx| Thread::from_saved(id.clone(), thread, this.tools.clone(), cx))
+                cx.new(|cx| {
+                    Thread::from_saved(
+                        id.clone(),
+                        thread,
+                        this.project.clone(),
+                        this.tools.clone(),
+                        cx,
+                    )
+                })
             })
         })
     }
@@ -113,6 +125,8 @@ impl ThreadStore {
                         id: message.id,
                         role: message.role,
                         text: message.text.clone(),
+                        thinking: message.thinking.clone(),
+                        pinned: message.pinned,
                         tool_uses: thread
                             .tool_uses_for_message(message.id)
                             .into_iter()
@@ -133,6 +147,8 @@ impl ThreadStore {
                             .collect(),
                     })
                     .collect(),
+                compaction: thread.compaction().cloned(),
+                profile: thread.profile().cloned(),
             };
 
             (id, thread)
@@ -239,6 +255,7 @@ impl ThreadStore {
                     tool_working_set.remove(&tool_ids);
                 }
             }
+            context_server::manager::Event::ResourceUpdated { .. } => {}
         }
     }
 }
@@ -255,6 +272,10 @@ pub struct SavedThread {
     pub summary: SharedString,
     pub updated_at: DateTime<Utc>,
     pub messages: Vec<SavedMessage>,
+    #[serde(default)]
+    pub compaction: Option<Compaction>,
+    #[serde(default)]
+    pub profile: Option<SharedString>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -263,6 +284,10 @@ pub struct SavedMessage {
     pub role: Role,
     pub text: String,
     #[serde(default)]
+    pub thinking: Vec<ThinkingBlock>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
     pub tool_uses: Vec<SavedToolUse>,
     #[serde(default)]
     pub tool_results: Vec<SavedToolResult>,
@@ -23,6 +23,7 @@ pub struct ToolUse {
 
 #[derive(Debug, Clone)]
 pub enum ToolUseStatus {
+    NeedsConfirmation,
     Pending,
     Running,
     Finished(SharedString),
@@ -122,6 +123,7 @@ impl ToolUseState {
                 if let Some(pending_tool_use) = self.pending_tool_uses_by_id.get(&tool_use.id) {
                     return match pending_tool_use.status {
                         PendingToolUseStatus::Idle => ToolUseStatus::Pending,
+                        PendingToolUseStatus::NeedsConfirmation => ToolUseStatus::NeedsConfirmation,
                         PendingToolUseStatus::Running { .. } => ToolUseStatus::Running,
                         PendingToolUseStatus::Error(ref err) => {
                             ToolUseStatus::Error(err.clone().into())
@@ -190,6 +192,12 @@ impl ToolUseState {
         );
     }
 
+    pub fn request_confirmation(&mut self, tool_use_id: &LanguageModelToolUseId) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(tool_use_id) {
+            tool_use.status = PendingToolUseStatus::NeedsConfirmation;
+        }
+    }
+
     pub fn run_pending_tool(&mut self, tool_use_id: LanguageModelToolUseId, task: Task<()>) {
         if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(&tool_use_id) {
             tool_use.status = PendingToolUseStatus::Running {
@@ -276,6 +284,7 @@ pub struct PendingToolUse {
 #[derive(Debug, Clone)]
 pub enum PendingToolUseStatus {
     Idle,
+    NeedsConfirmation,
     Running { _task: Shared<Task<()>> },
     Error(#[allow(unused)] Arc<str>),
 }
@@ -285,6 +294,10 @@ impl PendingToolUseStatus {
         matches!(self, PendingToolUseStatus::Idle)
     }
 
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(self, PendingToolUseStatus::NeedsConfirmation)
+    }
+
     pub fn is_error(&self) -> bool {
         matches!(self, PendingToolUseStatus::Error(_))
     }
@@ -2,6 +2,7 @@ mod context;
 mod context_editor;
 mod context_history;
 mod context_store;
+mod model_comparison;
 mod patch;
 mod slash_command;
 mod slash_command_picker;
@@ -15,6 +16,7 @@ pub use crate::context::*;
 pub use crate::context_editor::*;
 pub use crate::context_history::*;
 pub use crate::context_store::*;
+pub use crate::model_comparison::*;
 pub use crate::patch::*;
 pub use crate::slash_command::*;
 
@@ -3,6 +3,7 @@ mod context_tests;
 
 use crate::patch::{AssistantEdit, AssistantPatch, AssistantPatchStatus};
 use anyhow::{anyhow, Context as _, Result};
+use assistant_settings::AssistantSettings;
 use assistant_slash_command::{
     SlashCommandContent, SlashCommandEvent, SlashCommandLine, SlashCommandOutputSection,
     SlashCommandResult, SlashCommandWorkingSet,
@@ -14,21 +15,22 @@ use collections::{HashMap, HashSet};
 use fs::{Fs, RemoveOptions};
 use futures::{future::Shared, FutureExt, StreamExt};
 use gpui::{
-    App, AppContext as _, Context, Entity, EventEmitter, RenderImage, SharedString, Subscription,
-    Task,
+    App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, RenderImage, SharedString,
+    Subscription, Task, WeakEntity,
 };
 use language::{AnchorRangeExt, Bias, Buffer, LanguageRegistry, OffsetRangeExt, Point, ToOffset};
 use language_model::{
     report_assistant_event, LanguageModel, LanguageModelCacheConfiguration,
     LanguageModelCompletionEvent, LanguageModelImage, LanguageModelRegistry, LanguageModelRequest,
     LanguageModelRequestMessage, LanguageModelToolUseId, MaxMonthlySpendReachedError,
-    MessageContent, PaymentRequiredError, Role, StopReason,
+    MessageContent, PaymentRequiredError, Role, StopReason, TokenUsage,
 };
 use open_ai::Model as OpenAiModel;
 use paths::contexts_dir;
 use project::Project;
-use prompt_store::PromptBuilder;
+use prompt_store::{compaction_message, PromptBuilder, COMPACTION_PROMPT};
 use serde::{Deserialize, Serialize};
+use settings::Settings as _;
 use smallvec::SmallVec;
 use std::{
     cmp::{max, Ordering},
@@ -418,6 +420,7 @@ pub enum ContextEvent {
     MessagesEdited,
     SummaryChanged,
     StreamedCompletion,
+    Compacted,
     PatchesUpdated {
         removed: Vec<Range<language::Anchor>>,
         updated: Vec<Range<language::Anchor>>,
@@ -432,6 +435,10 @@ pub enum ContextEvent {
     SlashCommandOutputSectionAdded {
         section: SlashCommandOutputSection<language::Anchor>,
     },
+    ThoughtProcessSectionsUpdated {
+        removed: Vec<Range<language::Anchor>>,
+        updated: Vec<Range<language::Anchor>>,
+    },
     Operation(ContextOperation),
 }
 
@@ -442,6 +449,15 @@ pub struct ContextSummary {
     timestamp: clock::Lamport,
 }
 
+/// A summary that stands in for the older messages of an [`AssistantContext`] in
+/// requests to the model, once the conversation nears the model's context window.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContextCompaction {
+    /// The last message covered by the summary.
+    pub through: MessageId,
+    pub summary: String,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct MessageAnchor {
     pub id: MessageId,
@@ -566,6 +582,7 @@ pub enum XmlTagKind {
     OldText,
     NewText,
     Operation,
+    Think,
 }
 
 pub struct AssistantContext {
@@ -585,10 +602,14 @@ pub struct AssistantContext {
     messages_metadata: HashMap<MessageId, MessageMetadata>,
     summary: Option<ContextSummary>,
     pending_summary: Task<Option<()>>,
+    /// The summary of the older messages, which isn't shared with collaborators.
+    compaction: Option<ContextCompaction>,
     completion_count: usize,
     pending_completions: Vec<PendingCompletion>,
     token_count: Option<usize>,
     pending_token_count: Task<Option<()>>,
+    /// The tokens used by the completions that produced each assistant message.
+    token_usage_by_message: HashMap<MessageId, TokenUsage>,
     pending_save: Task<Result<()>>,
     pending_cache_warming_task: Task<Option<()>>,
     path: Option<PathBuf>,
@@ -597,6 +618,8 @@ pub struct AssistantContext {
     language_registry: Arc<LanguageRegistry>,
     patches: Vec<AssistantPatch>,
     xml_tags: Vec<XmlTag>,
+    /// The ranges of the `<think>` blocks holding the reasoning of assistant messages.
+    thought_process_sections: Vec<Range<language::Anchor>>,
     project: Option<Entity<Project>>,
     prompt_builder: Arc<PromptBuilder>,
 }
@@ -686,10 +709,12 @@ impl AssistantContext {
             edits_since_last_parse: edits_since_last_slash_command_parse,
             summary: None,
             pending_summary: Task::ready(None),
+            compaction: None,
             completion_count: Default::default(),
             pending_completions: Default::default(),
             token_count: None,
             pending_token_count: Task::ready(None),
+            token_usage_by_message: HashMap::default(),
             pending_cache_warming_task: Task::ready(None),
             _subscriptions: vec![cx.subscribe(&buffer, Self::handle_buffer_event)],
             pending_save: Task::ready(Ok(())),
@@ -701,6 +726,7 @@ impl AssistantContext {
             slash_commands,
             patches: Vec::new(),
             xml_tags: Vec::new(),
+            thought_process_sections: Vec::new(),
             prompt_builder,
         };
 
@@ -748,6 +774,7 @@ impl AssistantContext {
                 .as_ref()
                 .map(|summary| summary.text.clone())
                 .unwrap_or_default(),
+            compaction: self.compaction.clone(),
             slash_command_output_sections: self
                 .slash_command_output_sections
                 .iter()
@@ -792,6 +819,7 @@ impl AssistantContext {
             cx,
         );
         this.path = Some(path);
+        this.compaction = saved_context.compaction.clone();
         this.buffer.update(cx, |buffer, cx| {
             buffer.set_text(saved_context.text.as_str(), cx)
         });
@@ -1182,6 +1210,10 @@ impl AssistantContext {
         self.token_count
     }
 
+    pub fn token_usage_for_message(&self, message_id: MessageId) -> Option<&TokenUsage> {
+        self.token_usage_by_message.get(&message_id)
+    }
+
     pub(crate) fn count_remaining_tokens(&mut self, cx: &mut Context<Self>) {
         // Assume it will be a Chat request, even though that takes fewer tokens (and risks going over the limit),
         // because otherwise you see in the UI that your empty message has a bunch of tokens already used.
@@ -1455,6 +1487,46 @@ impl AssistantContext {
                 updated: updated_patches,
             });
         }
+
+        self.reparse_thought_process_sections(cx);
+    }
+
+    /// Rebuilds the thought process sections from the parsed tags. Sections are only
+    /// reported once their closing tag has been streamed.
+    fn reparse_thought_process_sections(&mut self, cx: &mut Context<Self>) {
+        let mut sections = Vec::new();
+        let mut section_start = None;
+        for tag in &self.xml_tags {
+            if tag.kind != XmlTagKind::Think {
+                continue;
+            }
+            if tag.is_open_tag {
+                section_start.get_or_insert(tag.range.start);
+            } else if let Some(start) = section_start.take() {
+                sections.push(start..tag.range.end);
+            }
+        }
+
+        let removed = self
+            .thought_process_sections
+            .iter()
+            .filter(|range| !sections.contains(range))
+            .cloned()
+            .collect::<Vec<_>>();
+        let updated = sections
+            .iter()
+            .filter(|range| !self.thought_process_sections.contains(range))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.thought_process_sections = sections;
+
+        if !removed.is_empty() || !updated.is_empty() {
+            cx.emit(ContextEvent::ThoughtProcessSectionsUpdated { removed, updated });
+        }
+    }
+
+    pub fn thought_process_sections(&self) -> &[Range<language::Anchor>] {
+        &self.thought_process_sections
     }
 
     fn reparse_slash_commands_in_range(
@@ -1662,6 +1734,16 @@ impl AssistantContext {
                 break;
             }
 
+            // Patches the model drafts while thinking are never applied.
+            if tag.kind == XmlTagKind::Think && tag.is_open_tag && patch_tag_depth == 0 {
+                for tag in tags.by_ref() {
+                    if tag.kind == XmlTagKind::Think && !tag.is_open_tag {
+                        break;
+                    }
+                }
+                continue;
+            }
+
             if tag.kind == XmlTagKind::Patch && tag.is_open_tag {
                 patch_tag_depth += 1;
                 let patch_start = tag.range.start;
@@ -2209,17 +2291,39 @@ impl AssistantContext {
             .insert_message_after(assistant_message.id, Role::User, MessageStatus::Done, cx)
             .unwrap();
 
+        let settings = AssistantSettings::get_global(cx);
+        let compaction_threshold = (settings.auto_compact
+            && self.compaction_boundary(last_message_id, cx).is_some())
+        .then_some(settings.auto_compact_threshold);
+
         let pending_completion_id = post_inc(&mut self.completion_count);
 
         let task = cx.spawn({
             |this, mut cx| async move {
-                let stream = model.stream_completion(request, &cx);
                 let assistant_message_id = assistant_message.id;
                 let mut response_latency = None;
+                let mut token_usage = None;
                 let stream_completion = async {
+                    let request = match compaction_threshold {
+                        Some(threshold) => {
+                            Self::compact_request_if_needed(
+                                &this,
+                                request,
+                                &model,
+                                threshold,
+                                request_type,
+                                last_message_id,
+                                &mut cx,
+                            )
+                            .await?
+                        }
+                        None => request,
+                    };
+                    let stream = model.stream_completion(request, &cx);
                     let request_start = Instant::now();
                     let mut events = stream.await?;
                     let mut stop_reason = StopReason::EndTurn;
+                    let mut is_thinking = false;
 
                     while let Some(event) = events.next().await {
                         if response_latency.is_none() {
@@ -2227,45 +2331,56 @@ impl AssistantContext {
                         }
                         let event = event?;
 
-                        this.update(&mut cx, |this, cx| {
-                            let message_ix = this
-                                .message_anchors
-                                .iter()
-                                .position(|message| message.id == assistant_message_id)?;
-                            this.buffer.update(cx, |buffer, cx| {
-                                let message_old_end_offset = this.message_anchors[message_ix + 1..]
-                                    .iter()
-                                    .find(|message| message.start.is_valid(buffer))
-                                    .map_or(buffer.len(), |message| {
-                                        message.start.to_offset(buffer).saturating_sub(1)
-                                    });
-
-                                match event {
-                                    LanguageModelCompletionEvent::StartMessage { .. } => {}
-                                    LanguageModelCompletionEvent::Stop(reason) => {
-                                        stop_reason = reason;
-                                    }
-                                    LanguageModelCompletionEvent::Text(chunk) => {
-                                        buffer.edit(
-                                            [(
-                                                message_old_end_offset..message_old_end_offset,
-                                                chunk,
-                                            )],
-                                            None,
-                                            cx,
-                                        );
-                                    }
-                                    LanguageModelCompletionEvent::ToolUse(_) => {}
+                        // The reasoning is wrapped in `<think>` tags, so that it can be folded
+                        // and kept out of the patches and of later requests.
+                        let text = match event {
+                            LanguageModelCompletionEvent::StartMessage { .. }
+                            | LanguageModelCompletionEvent::RedactedThinking { .. } => None,
+                            LanguageModelCompletionEvent::Stop(reason) => {
+                                stop_reason = reason;
+                                None
+                            }
+                            LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                                token_usage = Some(usage);
+                                None
+                            }
+                            LanguageModelCompletionEvent::Thinking { text, .. } => {
+                                if text.is_empty() {
+                                    None
+                                } else if is_thinking {
+                                    Some(text)
+                                } else {
+                                    is_thinking = true;
+                                    Some(format!("<think>\n{text}"))
                                 }
-                            });
-
-                            cx.emit(ContextEvent::StreamedCompletion);
+                            }
+                            LanguageModelCompletionEvent::Text(chunk) => {
+                                if is_thinking {
+                                    is_thinking = false;
+                                    Some(format!("\n</think>\n\n{}", chunk.trim_start()))
+                                } else {
+                                    Some(chunk)
+                                }
+                            }
+                            LanguageModelCompletionEvent::ToolUse(_) => None,
+                        };
 
-                            Some(())
-                        })?;
+                        if let Some(text) = text {
+                            this.update(&mut cx, |this, cx| {
+                                this.append_to_message(assistant_message_id, text, cx);
+                                cx.emit(ContextEvent::StreamedCompletion);
+                            })?;
+                        }
                         smol::future::yield_now().await;
                     }
                     this.update(&mut cx, |this, cx| {
+                        if is_thinking {
+                            this.append_to_message(
+                                assistant_message_id,
+                                "\n</think>".to_string(),
+                                cx,
+                            );
+                        }
                         this.pending_completions
                             .retain(|completion| completion.id != pending_completion_id);
                         this.summarize(false, cx);
@@ -2278,6 +2393,17 @@ impl AssistantContext {
                 let result = stream_completion.await;
 
                 this.update(&mut cx, |this, cx| {
+                    if let Some(usage) = token_usage {
+                        language_model_usage::record_usage(
+                            model.as_ref(),
+                            usage,
+                            this.project.as_ref(),
+                            cx,
+                        );
+                        this.token_usage_by_message
+                            .insert(assistant_message_id, usage);
+                    }
+
                     let error_message = if let Some(error) = result.as_ref().err() {
                         if error.is::<PaymentRequiredError>() {
                             cx.emit(ContextEvent::ShowPaymentRequiredError);
@@ -2357,34 +2483,130 @@ impl AssistantContext {
         Some(user_message)
     }
 
+    /// Appends a response that was completed outside of this context, such as one picked
+    /// from a comparison of several models, and queues up the user's next reply.
+    pub fn insert_assistant_response(
+        &mut self,
+        text: String,
+        usage: Option<TokenUsage>,
+        cx: &mut Context<Self>,
+    ) -> Option<MessageAnchor> {
+        let last_message_id = self.get_last_valid_message_id(cx)?;
+        let assistant_message =
+            self.insert_message_after(last_message_id, Role::Assistant, MessageStatus::Done, cx)?;
+        self.append_to_message(assistant_message.id, text, cx);
+        if let Some(usage) = usage {
+            self.token_usage_by_message
+                .insert(assistant_message.id, usage);
+        }
+
+        let user_message =
+            self.insert_message_after(assistant_message.id, Role::User, MessageStatus::Done, cx)?;
+        self.summarize(false, cx);
+        Some(user_message)
+    }
+
+    fn append_to_message(&mut self, message_id: MessageId, text: String, cx: &mut Context<Self>) {
+        let Some(message_ix) = self
+            .message_anchors
+            .iter()
+            .position(|message| message.id == message_id)
+        else {
+            return;
+        };
+        self.buffer.update(cx, |buffer, cx| {
+            let message_old_end_offset = self.message_anchors[message_ix + 1..]
+                .iter()
+                .find(|message| message.start.is_valid(buffer))
+                .map_or(buffer.len(), |message| {
+                    message.start.to_offset(buffer).saturating_sub(1)
+                });
+            buffer.edit(
+                [(message_old_end_offset..message_old_end_offset, text)],
+                None,
+                cx,
+            );
+        });
+    }
+
     pub fn to_completion_request(
         &self,
         request_type: RequestType,
         cx: &App,
+    ) -> LanguageModelRequest {
+        self.completion_request_through(request_type, None, cx)
+    }
+
+    /// Returns the request for the messages up to and including the given one, or for
+    /// all of them.
+    fn completion_request_through(
+        &self,
+        request_type: RequestType,
+        through: Option<MessageId>,
+        cx: &App,
     ) -> LanguageModelRequest {
         let buffer = self.buffer.read(cx);
 
         let mut contents = self.contents(cx).peekable();
 
-        fn collect_text_content(buffer: &Buffer, range: Range<usize>) -> Option<String> {
-            let text: String = buffer.text_for_range(range.clone()).collect();
+        // The model's reasoning is left out of the conversation it is sent.
+        let thought_process_ranges = self
+            .thought_process_sections
+            .iter()
+            .map(|range| range.to_offset(buffer))
+            .collect::<Vec<_>>();
+        let collect_text_content = |buffer: &Buffer, range: Range<usize>| -> Option<String> {
+            let mut text = String::new();
+            let mut offset = range.start;
+            for section in &thought_process_ranges {
+                if section.end <= offset || section.start >= range.end {
+                    continue;
+                }
+                text.extend(buffer.text_for_range(offset..section.start.max(offset)));
+                offset = section.end.min(range.end);
+            }
+            text.extend(buffer.text_for_range(offset..range.end));
             if text.trim().is_empty() {
                 None
             } else {
                 Some(text)
             }
-        }
+        };
 
         let mut completion_request = LanguageModelRequest {
             messages: Vec::new(),
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            response_format: None,
         };
+        let compacted_through_ix = self.compacted_through_ix();
+        if let Some(compaction) = &self.compaction {
+            if compacted_through_ix.is_some() {
+                completion_request
+                    .messages
+                    .push(LanguageModelRequestMessage {
+                        role: Role::User,
+                        content: vec![compaction_message(&compaction.summary).into()],
+                        cache: false,
+                    });
+            }
+        }
+        let mut reached_last_message = false;
         for message in self.messages(cx) {
+            if reached_last_message {
+                break;
+            }
+            reached_last_message = through == Some(message.id);
             if message.status != MessageStatus::Done {
                 continue;
             }
+            // Messages covered by the summary are left out, unless they have attached context.
+            let is_compacted =
+                compacted_through_ix.map_or(false, |ix| message.index_range.start <= ix);
+            if is_compacted && !self.has_attached_context(&message, cx) {
+                continue;
+            }
 
             let mut offset = message.offset_range.start;
             let mut request_message = LanguageModelRequestMessage {
@@ -2449,12 +2671,228 @@ impl AssistantContext {
                 completion_request
                     .messages
                     .swap(last_elem_index, last_elem_index.saturating_sub(1));
+
+                // The other messages are sent in the order they're written, which puts the
+                // stable ones first. The preamble isn't part of later requests though, so
+                // a cached prefix that includes it would never be read.
+                if let Some(last_message) = completion_request.messages.last_mut() {
+                    last_message.cache = false;
+                }
             }
         }
 
         completion_request
     }
 
+    pub fn compaction(&self) -> Option<&ContextCompaction> {
+        self.compaction.as_ref()
+    }
+
+    /// Returns whether the message is replaced by the compaction summary in requests,
+    /// rather than sent verbatim. Messages with attached context, such as the output
+    /// of slash commands or images, are always sent verbatim.
+    pub fn is_message_compacted(&self, message_id: MessageId, cx: &App) -> bool {
+        let Some(through_ix) = self.compacted_through_ix() else {
+            return false;
+        };
+        self.messages(cx)
+            .take_while(|message| message.index_range.start <= through_ix)
+            .find(|message| message.id == message_id)
+            .map_or(false, |message| !self.has_attached_context(&message, cx))
+    }
+
+    /// Returns the index of the last message covered by the compaction summary.
+    fn compacted_through_ix(&self) -> Option<usize> {
+        let compaction = self.compaction.as_ref()?;
+        self.message_anchors
+            .iter()
+            .position(|message| message.id == compaction.through)
+    }
+
+    fn has_attached_context(&self, message: &Message, cx: &App) -> bool {
+        let buffer = self.buffer.read(cx);
+        let starts_in_message = |range: &Range<language::Anchor>| {
+            message
+                .offset_range
+                .contains(&range.start.to_offset(buffer))
+        };
+        self.slash_command_output_sections
+            .iter()
+            .any(|section| section.is_valid(buffer) && starts_in_message(&section.range))
+            || self
+                .contents
+                .iter()
+                .any(|content| starts_in_message(&content.range()))
+    }
+
+    /// Returns the last message that compacting the context would summarize, which is
+    /// the one before the given message, unless it's already summarized.
+    fn compaction_boundary(&self, before: MessageId, cx: &App) -> Option<MessageId> {
+        let mut previous_message = None;
+        for message in self.messages(cx) {
+            if message.id == before {
+                let boundary: Message = previous_message?;
+                let is_compacted = self
+                    .compacted_through_ix()
+                    .map_or(false, |ix| boundary.index_range.start <= ix);
+                return (!is_compacted).then_some(boundary.id);
+            }
+            previous_message = Some(message);
+        }
+        None
+    }
+
+    /// Returns the request to send, first compacting the context if the request takes
+    /// up more than `threshold` of the model's context window.
+    async fn compact_request_if_needed(
+        this: &WeakEntity<Self>,
+        request: LanguageModelRequest,
+        model: &Arc<dyn LanguageModel>,
+        threshold: f32,
+        request_type: RequestType,
+        last_message_id: MessageId,
+        cx: &mut AsyncApp,
+    ) -> Result<LanguageModelRequest> {
+        let Some(token_count) = cx
+            .update(|cx| model.count_tokens(request.clone(), cx))?
+            .await
+            .log_err()
+        else {
+            return Ok(request);
+        };
+        if (token_count as f32) < model.max_token_count() as f32 * threshold {
+            return Ok(request);
+        }
+
+        let compacted_request = async {
+            this.update(cx, |this, cx| {
+                this.compact(model.clone(), last_message_id, cx)
+            })?
+            .await?;
+            this.read_with(cx, |this, cx| {
+                this.completion_request_through(request_type, Some(last_message_id), cx)
+            })
+        }
+        .await;
+        match compacted_request {
+            Ok(request) => Ok(request),
+            // A request that still fits in the context window is sent as is.
+            Err(error) if token_count <= model.max_token_count() => {
+                log::error!("failed to compact context: {error:#}");
+                Ok(request)
+            }
+            Err(error) => Err(error.context(
+                "The conversation is too long for the model's context window and couldn't be compacted",
+            )),
+        }
+    }
+
+    /// Summarizes the messages before the given one with the model, replacing them in
+    /// future requests. Messages with attached context are kept verbatim.
+    ///
+    /// When the messages don't fit in a single summary request, the oldest ones are
+    /// summarized first, and each later summary builds on the one before it.
+    pub fn compact(
+        &mut self,
+        model: Arc<dyn LanguageModel>,
+        before: MessageId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(target) = self.compaction_boundary(before, cx) else {
+            return Task::ready(Ok(()));
+        };
+        let max_request_tokens = (model.max_token_count() as f32
+            * AssistantSettings::get_global(cx).auto_compact_threshold)
+            as usize;
+
+        cx.spawn(|this, mut cx| async move {
+            loop {
+                let message_ids = this.read_with(&cx, |this, cx| {
+                    let compacted_through_ix = this.compacted_through_ix();
+                    let mut message_ids = Vec::new();
+                    for message in this.messages(cx) {
+                        if compacted_through_ix.map_or(true, |ix| message.index_range.start > ix) {
+                            message_ids.push(message.id);
+                        }
+                        if message.id == target {
+                            return message_ids;
+                        }
+                    }
+                    // The messages to summarize were deleted.
+                    Vec::new()
+                })?;
+                if message_ids.is_empty() {
+                    return Ok(());
+                }
+
+                // Find the most messages whose summary request fits, trying all of them
+                // first, as they usually do.
+                let request_fits = |count: usize| {
+                    let this = this.clone();
+                    let model = model.clone();
+                    let through = message_ids[count - 1];
+                    let cx = cx.clone();
+                    async move {
+                        let request =
+                            this.read_with(&cx, |this, cx| this.compaction_request(through, cx))?;
+                        let token_count = cx.update(|cx| model.count_tokens(request, cx))?.await?;
+                        anyhow::Ok(token_count <= max_request_tokens)
+                    }
+                };
+                let mut count = message_ids.len();
+                if !request_fits(count).await? {
+                    let (mut fitting, mut too_many) = (0, count);
+                    while too_many - fitting > 1 {
+                        let mid = (fitting + too_many) / 2;
+                        if request_fits(mid).await? {
+                            fitting = mid;
+                        } else {
+                            too_many = mid;
+                        }
+                    }
+                    if fitting == 0 {
+                        return Err(anyhow!("a message is too long to summarize"));
+                    }
+                    count = fitting;
+                }
+
+                let through = message_ids[count - 1];
+                let request =
+                    this.read_with(&cx, |this, cx| this.compaction_request(through, cx))?;
+                let stream = model.stream_completion_text(request, &cx);
+                let mut chunks = stream.await?.stream;
+                let mut summary = String::new();
+                while let Some(chunk) = chunks.next().await {
+                    summary.push_str(&chunk?);
+                }
+
+                if summary.trim().is_empty() {
+                    return Err(anyhow!("the model returned an empty summary"));
+                }
+
+                this.update(&mut cx, |this, cx| {
+                    this.compaction = Some(ContextCompaction { through, summary });
+                    cx.emit(ContextEvent::Compacted);
+                })?;
+            }
+        })
+    }
+
+    /// Returns the request that summarizes the messages through the given one, along
+    /// with the summary of those compacted before.
+    fn compaction_request(&self, through: MessageId, cx: &App) -> LanguageModelRequest {
+        let mut request = self.completion_request_through(RequestType::Chat, Some(through), cx);
+        for message in &mut request.messages {
+            message.cache = false;
+        }
+        request.messages.push(LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![COMPACTION_PROMPT.into()],
+            cache: false,
+        });
+        request
+    }
+
     pub fn cancel_last_assist(&mut self, cx: &mut Context<Self>) -> bool {
         if let Some(pending_completion) = self.pending_completions.pop() {
             self.update_metadata(pending_completion.assistant_message_id, cx, |metadata| {
@@ -3123,6 +3561,8 @@ pub struct SavedContext {
     pub text: String,
     pub messages: Vec<SavedMessage>,
     pub summary: String,
+    #[serde(default)]
+    pub compaction: Option<ContextCompaction>,
     pub slash_command_output_sections:
         Vec<assistant_slash_command::SlashCommandOutputSection<usize>>,
 }
@@ -3299,6 +3739,7 @@ impl SavedContextV0_3_0 {
                 })
                 .collect(),
             summary: self.summary,
+            compaction: None,
             slash_command_output_sections: self.slash_command_output_sections,
         }
     }
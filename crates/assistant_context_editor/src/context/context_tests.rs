@@ -1,8 +1,10 @@
 use crate::{
     AssistantContext, AssistantEdit, AssistantEditKind, CacheStatus, ContextEvent, ContextId,
     ContextOperation, InvokedSlashCommandId, MessageCacheMetadata, MessageId, MessageStatus,
+    RequestType,
 };
 use anyhow::Result;
+use assistant_settings::AssistantSettings;
 use assistant_slash_command::{
     ArgumentCompletion, SlashCommand, SlashCommandContent, SlashCommandEvent, SlashCommandOutput,
     SlashCommandOutputSection, SlashCommandRegistry, SlashCommandResult, SlashCommandWorkingSet,
@@ -20,7 +22,7 @@ use language_model::{LanguageModelCacheConfiguration, LanguageModelRegistry, Rol
 use parking_lot::Mutex;
 use pretty_assertions::assert_eq;
 use project::Project;
-use prompt_store::PromptBuilder;
+use prompt_store::{compaction_message, PromptBuilder, COMPACTION_PROMPT};
 use rand::prelude::*;
 use serde_json::json;
 use settings::SettingsStore;
@@ -1587,6 +1589,115 @@ fn test_mark_cache_anchors(cx: &mut App) {
     );
 }
 
+#[gpui::test]
+async fn test_auto_compact(cx: &mut TestAppContext) {
+    cx.update(|cx| {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        AssistantSettings::register(cx);
+        SettingsStore::update_global(cx, |store, cx| {
+            store
+                .set_user_settings(
+                    &json!({
+                        "assistant": {
+                            "version": "2",
+                            "auto_compact_threshold": 0.001,
+                        }
+                    })
+                    .to_string(),
+                    cx,
+                )
+                .unwrap();
+        });
+        LanguageModelRegistry::test(cx);
+    });
+    let model = cx.update(|cx| {
+        LanguageModelRegistry::read_global(cx)
+            .active_model()
+            .unwrap()
+    });
+    let registry = Arc::new(LanguageRegistry::test(cx.executor()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context = cx.new(|cx| {
+        AssistantContext::local(
+            registry,
+            None,
+            None,
+            prompt_builder.clone(),
+            Arc::new(SlashCommandWorkingSet::default()),
+            cx,
+        )
+    });
+    let buffer = context.read_with(cx, |context, _| context.buffer.clone());
+    let append = |text: String, cx: &mut TestAppContext| {
+        buffer.update(cx, |buffer, cx| {
+            let len = buffer.len();
+            buffer.edit([(len..len, text)], None, cx)
+        });
+    };
+
+    // The fake model counts a token per byte and has a window of a million tokens,
+    // so the last request goes over the threshold of a thousand tokens.
+    let message_1 = context.read_with(cx, |context, _| context.message_anchors[0].clone());
+    append("old question ".repeat(20), cx);
+    let message_2 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_1.id, Role::Assistant, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    append("old answer ".repeat(20), cx);
+    let message_3 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_2.id, Role::User, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    append("new question ".repeat(60), cx);
+
+    context.update(cx, |context, cx| context.assist(RequestType::Chat, cx));
+    cx.run_until_parked();
+
+    let fake_model = model.as_fake();
+    let summary_request = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        summary_request
+            .messages
+            .iter()
+            .map(|message| message.string_contents())
+            .collect::<Vec<_>>(),
+        vec![
+            format!("{}\n", "old question ".repeat(20)),
+            format!("{}\n", "old answer ".repeat(20)),
+            COMPACTION_PROMPT.to_string(),
+        ]
+    );
+
+    fake_model.stream_last_completion_response("The user asked an old question.".into());
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let request = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        request
+            .messages
+            .iter()
+            .map(|message| message.string_contents())
+            .collect::<Vec<_>>(),
+        vec![
+            compaction_message("The user asked an old question."),
+            format!("{}\n", "new question ".repeat(60)),
+        ]
+    );
+    context.read_with(cx, |context, cx| {
+        assert!(context.is_message_compacted(message_1.id, cx));
+        assert!(context.is_message_compacted(message_2.id, cx));
+        assert!(!context.is_message_compacted(message_3.id, cx));
+        assert_eq!(
+            context.serialize(cx).compaction.unwrap().through,
+            message_2.id
+        );
+    });
+}
+
 fn messages(context: &Entity<AssistantContext>, cx: &App) -> Vec<(MessageId, Role, Range<usize>)> {
     context
         .read(cx)
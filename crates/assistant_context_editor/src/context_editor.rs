@@ -40,6 +40,7 @@ use language_model::{
 use language_model_selector::{
     LanguageModelSelector, LanguageModelSelectorPopoverMenu, ToggleModelSelector,
 };
+use language_model_usage::CacheStatusIndicator;
 use multi_buffer::MultiBufferRow;
 use picker::Picker;
 use project::lsp_store::LocalLspAdapterDelegate;
@@ -68,13 +69,15 @@ use crate::{slash_command::SlashCommandCompletionProvider, slash_command_picker}
 use crate::{
     AssistantContext, AssistantPatch, AssistantPatchStatus, CacheStatus, Content, ContextEvent,
     ContextId, InvokedSlashCommandId, InvokedSlashCommandStatus, Message, MessageId,
-    MessageMetadata, MessageStatus, ParsedSlashCommand, PendingSlashCommandStatus, RequestType,
+    MessageMetadata, MessageStatus, ModelComparison, ParsedSlashCommand, PendingSlashCommandStatus,
+    RequestType,
 };
 
 actions!(
     assistant,
     [
         Assist,
+        CompareModels,
         ConfirmCommand,
         CopyCode,
         CycleMessageRole,
@@ -184,6 +187,7 @@ pub struct ContextEditor {
     remote_id: Option<workspace::ViewId>,
     pending_slash_command_creases: HashMap<Range<language::Anchor>, CreaseId>,
     invoked_slash_command_creases: HashMap<InvokedSlashCommandId, CreaseId>,
+    thought_process_creases: HashMap<Range<language::Anchor>, CreaseId>,
     _subscriptions: Vec<Subscription>,
     patches: HashMap<Range<language::Anchor>, PatchViewState>,
     active_patch: Option<Range<language::Anchor>>,
@@ -254,6 +258,7 @@ impl ContextEditor {
 
         let sections = context.read(cx).slash_command_output_sections().to_vec();
         let patch_ranges = context.read(cx).patch_ranges().collect::<Vec<_>>();
+        let thought_process_sections = context.read(cx).thought_process_sections().to_vec();
         let slash_commands = context.read(cx).slash_commands().clone();
         let mut this = Self {
             context,
@@ -269,6 +274,7 @@ impl ContextEditor {
             project,
             pending_slash_command_creases: HashMap::default(),
             invoked_slash_command_creases: HashMap::default(),
+            thought_process_creases: HashMap::default(),
             _subscriptions,
             patches: HashMap::default(),
             active_patch: None,
@@ -295,6 +301,7 @@ impl ContextEditor {
         this.update_image_blocks(cx);
         this.insert_slash_command_output_sections(sections, false, window, cx);
         this.patches_updated(&Vec::new(), &patch_ranges, window, cx);
+        this.thought_process_sections_updated(&[], &thought_process_sections, window, cx);
         this
     }
 
@@ -344,6 +351,15 @@ impl ContextEditor {
         self.send_to_model(RequestType::SuggestEdits, window, cx);
     }
 
+    fn compare_models(&mut self, _: &CompareModels, window: &mut Window, cx: &mut Context<Self>) {
+        let context = self.context.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ModelComparison::toggle(workspace, context, window, cx)
+            })
+            .ok();
+    }
+
     fn focus_active_patch(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
         if let Some((_range, patch)) = self.active_patch() {
             if let Some(editor) = patch
@@ -593,6 +609,13 @@ impl ContextEditor {
                     context.save(Some(Duration::from_millis(500)), self.fs.clone(), cx);
                 });
             }
+            ContextEvent::Compacted => {
+                // The headers show which messages were summarized.
+                self.editor.update(cx, |_, cx| cx.notify());
+                self.context.update(cx, |context, cx| {
+                    context.save(Some(Duration::from_millis(500)), self.fs.clone(), cx);
+                });
+            }
             ContextEvent::SummaryChanged => {
                 cx.emit(EditorEvent::TitleChanged);
                 self.context.update(cx, |context, cx| {
@@ -709,6 +732,9 @@ impl ContextEditor {
             ContextEvent::SlashCommandOutputSectionAdded { section } => {
                 self.insert_slash_command_output_sections([section.clone()], false, window, cx);
             }
+            ContextEvent::ThoughtProcessSectionsUpdated { removed, updated } => {
+                self.thought_process_sections_updated(removed, updated, window, cx);
+            }
             ContextEvent::Operation(_) => {}
             ContextEvent::ShowAssistError(error_message) => {
                 self.last_error = Some(AssistError::Message(error_message.clone()));
@@ -946,6 +972,54 @@ impl ContextEditor {
         self.update_active_patch(window, cx);
     }
 
+    /// Folds the model's reasoning into a button that expands it, once it has
+    /// finished streaming.
+    fn thought_process_sections_updated(
+        &mut self,
+        removed: &[Range<language::Anchor>],
+        updated: &[Range<language::Anchor>],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let excerpt_id = *buffer.as_singleton().unwrap().0;
+
+            editor.remove_creases(
+                removed
+                    .iter()
+                    .filter_map(|range| self.thought_process_creases.remove(range)),
+                cx,
+            );
+
+            let creases = updated
+                .iter()
+                .map(|range| {
+                    let start = buffer.anchor_in_excerpt(excerpt_id, range.start).unwrap();
+                    let end = buffer.anchor_in_excerpt(excerpt_id, range.end).unwrap();
+                    Crease::inline(
+                        start..end,
+                        FoldPlaceholder {
+                            render: render_fold_icon_button(
+                                cx.entity().downgrade(),
+                                IconName::Ai,
+                                "Thought Process".into(),
+                            ),
+                            merge_adjacent: false,
+                            ..Default::default()
+                        },
+                        fold_toggle("thought-process"),
+                        |_, _, _, _| Empty.into_any_element(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let crease_ids = editor.insert_creases(creases.clone(), cx);
+            editor.fold_creases(creases, false, window, cx);
+            self.thought_process_creases
+                .extend(updated.iter().cloned().zip(crease_ids));
+        });
+    }
+
     fn insert_slash_command_output_sections(
         &mut self,
         sections: impl IntoIterator<Item = SlashCommandOutputSection<language::Anchor>>,
@@ -1402,6 +1476,17 @@ impl ContextEditor {
                                 },
                                 _ => None,
                             })
+                            .children(
+                                context
+                                    .read(cx)
+                                    .token_usage_for_message(message_id)
+                                    .and_then(|usage| {
+                                        CacheStatusIndicator::new(
+                                            ("cache_status", message_id.as_u64()),
+                                            *usage,
+                                        )
+                                    }),
+                            )
                             .children(match &message.status {
                                 MessageStatus::Error(error) => Some(
                                     Button::new("show-error", "Error")
@@ -1444,6 +1529,36 @@ impl ContextEditor {
                                 ),
                                 _ => None,
                             })
+                            .when(
+                                context.read(cx).is_message_compacted(message_id, cx),
+                                |header| {
+                                    header.child(
+                                        h_flex()
+                                            .id(("summarized", message_id.as_u64()))
+                                            .gap_1()
+                                            .items_center()
+                                            .child(
+                                                Icon::new(IconName::ListTree)
+                                                    .color(Color::Muted)
+                                                    .size(IconSize::XSmall),
+                                            )
+                                            .child(
+                                                Label::new("Summarized")
+                                                    .size(LabelSize::Small)
+                                                    .color(Color::Muted),
+                                            )
+                                            .tooltip(|window, cx| {
+                                                Tooltip::with_meta(
+                                                    "Summarized",
+                                                    None,
+                                                    "Replaced by a summary in requests to fit the model's context window",
+                                                    window,
+                                                    cx,
+                                                )
+                                            }),
+                                    )
+                                },
+                            )
                             .into_any_element()
                     }
                 })
@@ -1864,7 +1979,7 @@ impl ContextEditor {
     ) {
         cx.stop_propagation();
 
-        let images = if let Some(item) = cx.read_from_clipboard() {
+        let mut images = if let Some(item) = cx.read_from_clipboard() {
             item.into_entries()
                 .filter_map(|entry| {
                     if let ClipboardEntry::Image(image) = entry {
@@ -1878,6 +1993,23 @@ impl ContextEditor {
             Vec::new()
         };
 
+        // Only paste images for models that can see them, and fall back to pasting the text.
+        if !images.is_empty() {
+            if let Some(model) = LanguageModelRegistry::read_global(cx).active_model() {
+                if !model.supports_images() {
+                    images.clear();
+                    self.last_error = Some(AssistError::Message(
+                        format!(
+                            "{} can't see images, so only text was pasted.",
+                            model.name().0
+                        )
+                        .into(),
+                    ));
+                    cx.notify();
+                }
+            }
+        }
+
         let metadata = if let Some(item) = cx.read_from_clipboard() {
             item.entries().first().and_then(|entry| {
                 if let ClipboardEntry::String(text) = entry {
@@ -2885,6 +3017,7 @@ impl Render for ContextEditor {
             .capture_action(cx.listener(ContextEditor::confirm_command))
             .on_action(cx.listener(ContextEditor::edit))
             .on_action(cx.listener(ContextEditor::assist))
+            .on_action(cx.listener(ContextEditor::compare_models))
             .on_action(cx.listener(ContextEditor::split))
             .on_action(move |_: &ToggleModelSelector, window, cx| {
                 language_model_selector.toggle(window, cx);
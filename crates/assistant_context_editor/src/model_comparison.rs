@@ -0,0 +1,461 @@
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use collections::HashSet;
+use futures::StreamExt as _;
+use gpui::{
+    App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, HighlightStyle,
+    StrikethroughStyle, StyledText, Task,
+};
+use language_model::{
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelRegistry, TokenUsage,
+};
+use ui::{prelude::*, Checkbox, ToggleState, Tooltip};
+use workspace::{ModalView, Workspace};
+
+use crate::{AssistantContext, RequestType};
+
+/// Sends the conversation of a context to several models at once and streams their
+/// responses side by side, so that one of them can be kept in the conversation.
+pub struct ModelComparison {
+    context: Entity<AssistantContext>,
+    models: Vec<Arc<dyn LanguageModel>>,
+    selected_models: HashSet<usize>,
+    responses: Vec<ComparedResponse>,
+    show_diff: bool,
+    focus_handle: FocusHandle,
+    _completion_tasks: Vec<Task<()>>,
+}
+
+struct ComparedResponse {
+    model: Arc<dyn LanguageModel>,
+    text: String,
+    status: ResponseStatus,
+    started_at: Instant,
+    first_token_latency: Option<Duration>,
+    duration: Option<Duration>,
+    usage: Option<TokenUsage>,
+}
+
+enum ResponseStatus {
+    Pending,
+    Done,
+    Error(SharedString),
+}
+
+impl ModelComparison {
+    pub fn toggle(
+        workspace: &mut Workspace,
+        context: Entity<AssistantContext>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        workspace.toggle_modal(window, cx, |_window, cx| Self::new(context, cx));
+    }
+
+    fn new(context: Entity<AssistantContext>, cx: &mut Context<Self>) -> Self {
+        let registry = LanguageModelRegistry::read_global(cx);
+        let models = registry
+            .providers()
+            .into_iter()
+            .filter(|provider| provider.is_authenticated(cx))
+            .flat_map(|provider| provider.provided_models(cx))
+            .collect::<Vec<_>>();
+
+        let mut selected_models = HashSet::default();
+        if let Some(active_model) = registry.active_model() {
+            if let Some(ix) = models.iter().position(|model| {
+                model.provider_id() == active_model.provider_id() && model.id() == active_model.id()
+            }) {
+                selected_models.insert(ix);
+            }
+        }
+
+        Self {
+            context,
+            models,
+            selected_models,
+            responses: Vec::new(),
+            show_diff: false,
+            focus_handle: cx.focus_handle(),
+            _completion_tasks: Vec::new(),
+        }
+    }
+
+    fn dismiss(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn toggle_model(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if !self.selected_models.remove(&ix) {
+            self.selected_models.insert(ix);
+        }
+        cx.notify();
+    }
+
+    fn compare(&mut self, cx: &mut Context<Self>) {
+        let request = self
+            .context
+            .read(cx)
+            .to_completion_request(RequestType::Chat, cx);
+
+        let mut selected_models = self.selected_models.iter().copied().collect::<Vec<_>>();
+        selected_models.sort_unstable();
+        for (response_ix, model_ix) in selected_models.into_iter().enumerate() {
+            let model = self.models[model_ix].clone();
+            self.responses.push(ComparedResponse {
+                model: model.clone(),
+                text: String::new(),
+                status: ResponseStatus::Pending,
+                started_at: Instant::now(),
+                first_token_latency: None,
+                duration: None,
+                usage: None,
+            });
+
+            let request = request.clone();
+            let task = cx.spawn(|this, mut cx| async move {
+                let stream = model.stream_completion(request, &cx);
+                let result = async {
+                    let mut events = stream.await?;
+                    while let Some(event) = events.next().await {
+                        let event = event?;
+                        this.update(&mut cx, |this, cx| {
+                            let response = &mut this.responses[response_ix];
+                            match event {
+                                LanguageModelCompletionEvent::Text(chunk) => {
+                                    let started_at = response.started_at;
+                                    response
+                                        .first_token_latency
+                                        .get_or_insert_with(|| started_at.elapsed());
+                                    response.text.push_str(&chunk);
+                                }
+                                LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                                    response.usage = Some(usage);
+                                }
+                                // The reasoning of the models isn't compared, only their answers.
+                                LanguageModelCompletionEvent::Thinking { .. }
+                                | LanguageModelCompletionEvent::RedactedThinking { .. }
+                                | LanguageModelCompletionEvent::StartMessage { .. }
+                                | LanguageModelCompletionEvent::Stop(_)
+                                | LanguageModelCompletionEvent::ToolUse(_) => {}
+                            }
+                            cx.notify();
+                        })?;
+                    }
+                    anyhow::Ok(())
+                }
+                .await;
+
+                this.update(&mut cx, |this, cx| {
+                    let project = this.context.read(cx).project();
+                    let response = &mut this.responses[response_ix];
+                    response.duration = Some(response.started_at.elapsed());
+                    response.status = match result {
+                        Ok(()) => ResponseStatus::Done,
+                        Err(error) => ResponseStatus::Error(error.to_string().into()),
+                    };
+                    if let Some(usage) = response.usage {
+                        language_model_usage::record_usage(
+                            response.model.as_ref(),
+                            usage,
+                            project.as_ref(),
+                            cx,
+                        );
+                    }
+                    cx.notify();
+                })
+                .ok();
+            });
+            self._completion_tasks.push(task);
+        }
+        cx.notify();
+    }
+
+    fn keep(&mut self, response_ix: usize, cx: &mut Context<Self>) {
+        let Some(response) = self.responses.get(response_ix) else {
+            return;
+        };
+        let text = response.text.clone();
+        let usage = response.usage;
+        self.context.update(cx, |context, cx| {
+            context.insert_assistant_response(text, usage, cx);
+        });
+        cx.emit(DismissEvent);
+    }
+
+    fn render_model_selection(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let can_compare = self.selected_models.len() >= 2;
+
+        v_flex()
+            .gap_2()
+            .child(
+                Label::new("Choose the models that should answer the conversation so far.")
+                    .color(Color::Muted),
+            )
+            .child(
+                v_flex()
+                    .id("compared-models")
+                    .max_h_96()
+                    .overflow_y_scroll()
+                    .gap_1()
+                    .children(self.models.iter().enumerate().map(|(ix, model)| {
+                        let state = if self.selected_models.contains(&ix) {
+                            ToggleState::Selected
+                        } else {
+                            ToggleState::Unselected
+                        };
+                        Checkbox::new(("compared-model", ix), state)
+                            .label(format!("{} ({})", model.name().0, model.provider_name().0))
+                            .on_click(cx.listener(move |this, _, _, cx| this.toggle_model(ix, cx)))
+                    })),
+            )
+            .child(
+                h_flex().justify_end().child(
+                    Button::new("compare", "Compare")
+                        .style(ButtonStyle::Filled)
+                        .disabled(!can_compare)
+                        .on_click(cx.listener(|this, _, _, cx| this.compare(cx))),
+                ),
+            )
+    }
+
+    fn render_response(
+        &self,
+        response_ix: usize,
+        response: &ComparedResponse,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let mut stats = Vec::new();
+        if let Some(latency) = response.first_token_latency {
+            stats.push(format!("first token {}", format_duration(latency)));
+        }
+        if let Some(duration) = response.duration {
+            stats.push(format!("total {}", format_duration(duration)));
+        }
+        if let Some(usage) = response.usage {
+            stats.push(format!(
+                "{} in, {} out",
+                usage.input_tokens
+                    + usage.cache_creation_input_tokens
+                    + usage.cache_read_input_tokens,
+                usage.output_tokens
+            ));
+        }
+
+        let base_text = self
+            .responses
+            .first()
+            .map_or("", |response| response.text.as_str());
+        let body = if self.show_diff && response_ix > 0 {
+            let (text, hunks) = diff_against(base_text, &response.text);
+            let highlights = hunks.into_iter().map(|(range, kind)| {
+                let style = match kind {
+                    DiffHunkKind::Deleted => HighlightStyle {
+                        background_color: Some(cx.theme().status().deleted_background),
+                        strikethrough: Some(StrikethroughStyle {
+                            thickness: px(1.),
+                            color: Some(cx.theme().colors().text_muted),
+                        }),
+                        ..Default::default()
+                    },
+                    DiffHunkKind::Inserted => HighlightStyle {
+                        background_color: Some(cx.theme().status().created_background),
+                        ..Default::default()
+                    },
+                };
+                (range, style)
+            });
+            StyledText::new(text).with_highlights(highlights)
+        } else {
+            StyledText::new(response.text.clone())
+        };
+
+        let status = match &response.status {
+            ResponseStatus::Pending => Label::new("Streaming…")
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+                .into_any_element(),
+            ResponseStatus::Done => Button::new(("keep-response", response_ix), "Keep")
+                .label_size(LabelSize::Small)
+                .tooltip(Tooltip::text("Add this response to the conversation"))
+                .on_click(cx.listener(move |this, _, _, cx| this.keep(response_ix, cx)))
+                .into_any_element(),
+            ResponseStatus::Error(error) => Label::new(error.clone())
+                .size(LabelSize::Small)
+                .color(Color::Error)
+                .into_any_element(),
+        };
+
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .rounded_md()
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        v_flex()
+                            .min_w_0()
+                            .child(Label::new(response.model.name().0).truncate())
+                            .child(
+                                Label::new(stats.join(", "))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .child(status),
+            )
+            .child(
+                div()
+                    .id(("compared-response", response_ix))
+                    .p_2()
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .text_sm()
+                    .child(body),
+            )
+    }
+}
+
+impl Render for ModelComparison {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let border_color = cx.theme().colors().border;
+
+        v_flex()
+            .key_context("ModelComparison")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::dismiss))
+            .w(window.viewport_size().width - px(320.))
+            .max_h(window.viewport_size().height - px(160.))
+            .bg(cx.theme().colors().elevated_surface_background)
+            .border_1()
+            .border_color(border_color)
+            .rounded_lg()
+            .shadow_lg()
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(border_color)
+                    .child(Headline::new("Compare Models").size(HeadlineSize::XSmall))
+                    .when(self.responses.len() > 1, |this| {
+                        this.child(
+                            Checkbox::new(
+                                "show-diff",
+                                if self.show_diff {
+                                    ToggleState::Selected
+                                } else {
+                                    ToggleState::Unselected
+                                },
+                            )
+                            .label("Diff Against First Response")
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.show_diff = !this.show_diff;
+                                cx.notify();
+                            })),
+                        )
+                    }),
+            )
+            .child(div().p_2().flex_1().min_h_0().map(|this| {
+                if self.responses.is_empty() {
+                    this.child(self.render_model_selection(cx))
+                } else {
+                    this.child(
+                        h_flex().size_full().items_start().gap_2().children(
+                            self.responses
+                                .iter()
+                                .enumerate()
+                                .map(|(ix, response)| self.render_response(ix, response, cx)),
+                        ),
+                    )
+                }
+            }))
+    }
+}
+
+impl EventEmitter<DismissEvent> for ModelComparison {}
+
+impl Focusable for ModelComparison {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl ModalView for ModelComparison {}
+
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffHunkKind {
+    Deleted,
+    Inserted,
+}
+
+/// Interleaves the text that was removed from `base` with the text that replaced it,
+/// returning the combined text along with the ranges of the removed and inserted parts.
+fn diff_against(base: &str, text: &str) -> (String, Vec<(Range<usize>, DiffHunkKind)>) {
+    let mut diff = String::with_capacity(base.len() + text.len());
+    let mut hunks = Vec::new();
+    let mut base_offset = 0;
+    for (old_range, new_text) in language::text_diff(base, text) {
+        diff.push_str(&base[base_offset..old_range.start]);
+        if !old_range.is_empty() {
+            let start = diff.len();
+            diff.push_str(&base[old_range.clone()]);
+            hunks.push((start..diff.len(), DiffHunkKind::Deleted));
+        }
+        if !new_text.is_empty() {
+            let start = diff.len();
+            diff.push_str(&new_text);
+            hunks.push((start..diff.len(), DiffHunkKind::Inserted));
+        }
+        base_offset = old_range.end;
+    }
+    diff.push_str(&base[base_offset..]);
+    (diff, hunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_against() {
+        let (diff, hunks) = diff_against(
+            "The quick brown fox\njumps over the dog.\n",
+            "The slow brown fox\njumps over the dog.\n",
+        );
+        assert_eq!(
+            diff,
+            "The quickslow brown fox\njumps over the dog.\n".to_string()
+        );
+        assert_eq!(
+            hunks,
+            vec![
+                (4..9, DiffHunkKind::Deleted),
+                (9..13, DiffHunkKind::Inserted)
+            ]
+        );
+
+        let (diff, hunks) = diff_against("Same answer.", "Same answer.");
+        assert_eq!(diff, "Same answer.".to_string());
+        assert!(hunks.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use ::open_ai::Model as OpenAiModel;
@@ -65,6 +66,10 @@ pub struct AssistantSettings {
     pub inline_alternatives: Vec<LanguageModelSelection>,
     pub using_outdated_settings_version: bool,
     pub enable_experimental_live_diffs: bool,
+    pub auto_compact: bool,
+    pub auto_compact_threshold: f32,
+    pub profiles: BTreeMap<String, AssistantProfile>,
+    pub default_profile: Option<String>,
 }
 
 impl AssistantSettings {
@@ -164,6 +169,10 @@ impl AssistantSettingsContent {
                         }),
                     inline_alternatives: None,
                     enable_experimental_live_diffs: None,
+                    auto_compact: None,
+                    auto_compact_threshold: None,
+                    profiles: None,
+                    default_profile: None,
                 },
                 VersionedAssistantSettingsContent::V2(settings) => settings.clone(),
             },
@@ -184,6 +193,10 @@ impl AssistantSettingsContent {
                 }),
                 inline_alternatives: None,
                 enable_experimental_live_diffs: None,
+                auto_compact: None,
+                auto_compact_threshold: None,
+                profiles: None,
+                default_profile: None,
             },
         }
     }
@@ -234,7 +247,7 @@ impl AssistantSettingsContent {
                             _ => None,
                         };
                         settings.provider = Some(AssistantProviderContentV1::Ollama {
-                            default_model: Some(ollama::Model::new(&model, None, None)),
+                            default_model: Some(ollama::Model::new(&model, None, None, false)),
                             api_url,
                         });
                     }
@@ -246,7 +259,7 @@ impl AssistantSettingsContent {
                             _ => None,
                         };
                         settings.provider = Some(AssistantProviderContentV1::LmStudio {
-                            default_model: Some(lmstudio::Model::new(&model, None, None)),
+                            default_model: Some(lmstudio::Model::new(&model, None, None, false)),
                             api_url,
                         });
                     }
@@ -312,6 +325,10 @@ impl Default for VersionedAssistantSettingsContent {
             default_model: None,
             inline_alternatives: None,
             enable_experimental_live_diffs: None,
+            auto_compact: None,
+            auto_compact_threshold: None,
+            profiles: None,
+            default_profile: None,
         })
     }
 }
@@ -346,6 +363,26 @@ pub struct AssistantSettingsContentV2 {
     ///
     /// Default: false
     enable_experimental_live_diffs: Option<bool>,
+    /// Whether to summarize the older messages of a thread in the assistant
+    /// panel, or of a conversation in the prompt editor, when it nears the
+    /// context window of the model.
+    ///
+    /// Default: true
+    auto_compact: Option<bool>,
+    /// The fraction of the model's context window a conversation may use before
+    /// it is compacted.
+    ///
+    /// Default: 0.8
+    auto_compact_threshold: Option<f32>,
+    /// Named profiles that can be selected for a thread, each bundling a model,
+    /// a set of tools, rules and context.
+    ///
+    /// Default: {}
+    profiles: Option<BTreeMap<String, AssistantProfile>>,
+    /// The profile to use for new threads.
+    ///
+    /// Default: null
+    default_profile: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -373,6 +410,29 @@ fn providers_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema:
     .into()
 }
 
+/// A named bundle of a model, tools, rules and context for the assistant.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct AssistantProfile {
+    /// The model to use for threads with this profile, in place of the
+    /// selected model.
+    #[serde(default)]
+    pub model: Option<LanguageModelSelection>,
+    /// The temperature to sample the model with.
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// The names of the tools the model may use, including tools provided by
+    /// context servers. All tools are available when omitted.
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+    /// The titles of prompts from the prompt library to follow as rules.
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// Paths or globs, relative to a worktree root, of files to attach as
+    /// context when the profile is selected.
+    #[serde(default)]
+    pub context: Vec<String>,
+}
+
 impl Default for LanguageModelSelection {
     fn default() -> Self {
         Self {
@@ -475,6 +535,20 @@ impl Settings for AssistantSettings {
                 &mut settings.enable_experimental_live_diffs,
                 value.enable_experimental_live_diffs,
             );
+            merge(&mut settings.auto_compact, value.auto_compact);
+            merge(
+                &mut settings.auto_compact_threshold,
+                value
+                    .auto_compact_threshold
+                    .map(|threshold| threshold.clamp(0.1, 1.0)),
+            );
+            if let Some(profiles) = value.profiles {
+                settings.profiles.extend(profiles);
+            }
+            merge(
+                &mut settings.default_profile,
+                value.default_profile.map(Some),
+            );
         }
 
         Ok(settings)
@@ -535,6 +609,10 @@ mod tests {
                             default_width: None,
                             default_height: None,
                             enable_experimental_live_diffs: None,
+                            auto_compact: None,
+                            auto_compact_threshold: None,
+                            profiles: None,
+                            default_profile: None,
                         }),
                     )
                 },
@@ -227,6 +227,7 @@ async fn commands_for_summaries(
         tools: Vec::new(),
         stop: Vec::new(),
         temperature: None,
+        response_format: None,
     };
 
     while let Some(current_summaries) = stack.pop() {
@@ -106,7 +106,7 @@ impl SlashCommand for ProjectSlashCommand {
                 prompt_builder.generate_project_slash_command_prompt(context_buffer.text())?;
 
             let search_queries = current_model
-                .use_tool::<SearchQueries>(
+                .complete_structured::<SearchQueries>(
                     language_model::LanguageModelRequest {
                         messages: vec![language_model::LanguageModelRequestMessage {
                             role: language_model::Role::User,
@@ -116,6 +116,7 @@ impl SlashCommand for ProjectSlashCommand {
                         tools: vec![],
                         stop: vec![],
                         temperature: None,
+                        response_format: None,
                     },
                     cx.deref_mut(),
                 )
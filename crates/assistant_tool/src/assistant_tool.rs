@@ -1,3 +1,4 @@
+mod tool_permissions;
 mod tool_registry;
 mod tool_working_set;
 
@@ -5,13 +6,25 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use gpui::{App, Task, WeakEntity, Window};
+use settings::Settings;
 use workspace::Workspace;
 
+pub use crate::tool_permissions::*;
 pub use crate::tool_registry::*;
 pub use crate::tool_working_set::*;
 
 pub fn init(cx: &mut App) {
     ToolRegistry::default_global(cx);
+    ToolPermissionsSettings::register(cx);
+}
+
+/// Where a tool comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolSource {
+    /// A tool built into Zed or provided by an extension.
+    Native,
+    /// A tool provided by a context server.
+    ContextServer { id: Arc<str> },
 }
 
 /// A tool that can be used by a language model.
@@ -27,6 +40,16 @@ pub trait Tool: 'static + Send + Sync {
         serde_json::Value::Object(serde_json::Map::default())
     }
 
+    /// Returns where the tool comes from.
+    fn source(&self) -> ToolSource {
+        ToolSource::Native
+    }
+
+    /// Returns whether the user must approve each use of the tool before it runs.
+    fn needs_confirmation(&self) -> bool {
+        false
+    }
+
     /// Runs the tool with the provided input.
     fn run(
         self: Arc<Self>,
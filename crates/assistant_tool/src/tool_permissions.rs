@@ -0,0 +1,505 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use globset::Glob;
+use gpui::App;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+use crate::{Tool, ToolSource};
+
+/// Whether a tool may run when the model asks to use it.
+///
+/// Permissions are ordered from the least to the most restrictive.
+#[derive(
+    Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, JsonSchema, Debug,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPermission {
+    /// Run the tool without asking.
+    Allow,
+    /// Ask the user for approval every time the tool is used.
+    Ask,
+    /// Never run the tool, and tell the model it was denied.
+    Deny,
+}
+
+/// The policies deciding which tool uses need the user's approval.
+///
+/// The most specific matching policy wins: an argument rule of the tool,
+/// then the tool's default, then the context server's default, then the
+/// global default. Built-in tools without any policy run without asking,
+/// unless the tool itself requires confirmation, while the tools of context
+/// servers ask. The server and global defaults can't let a tool that requires
+/// confirmation run without asking, only a policy of the tool itself can.
+#[derive(Deserialize, Serialize, Default, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ToolPermissionsSettings {
+    /// The permission of tools that have no policy of their own.
+    pub default: Option<ToolPermission>,
+    /// Policies for the built-in tools, keyed by tool name.
+    #[serde(default)]
+    pub tools: HashMap<String, ToolPolicy>,
+    /// Policies for the tools of context servers, keyed by server ID.
+    #[serde(default)]
+    pub context_servers: HashMap<String, ContextServerPolicy>,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ContextServerPolicy {
+    /// The permission of this server's tools that have no policy of their own.
+    pub default: Option<ToolPermission>,
+    /// Policies for this server's tools, keyed by tool name.
+    #[serde(default)]
+    pub tools: HashMap<String, ToolPolicy>,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ToolPolicy {
+    /// The permission used when none of the rules match.
+    pub default: Option<ToolPermission>,
+    /// Rules matched against the tool's input, in order. The first matching rule wins.
+    #[serde(default)]
+    pub rules: Vec<ToolPermissionRule>,
+}
+
+/// A rule that applies when an argument of the tool matches a glob.
+///
+/// For example, `{ "argument": "path", "glob": "/home/me/project/**", "permission": "allow" }`
+/// allows the tool to run when its `path` argument is inside the project.
+///
+/// When the glob is an absolute path, the argument is resolved against the
+/// worktree roots before matching. Relative globs match the argument's path
+/// within its worktree, such as `src/main.rs` for `./src/main.rs` or
+/// `project/src/main.rs`, as well as the argument itself. Arguments that go up
+/// a directory with `..` never match; they make the tool ask for approval
+/// instead.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ToolPermissionRule {
+    /// The name of a top-level argument, or a JSON pointer (such as `/options/path`)
+    /// for nested ones. Rules on arguments that aren't strings never match.
+    pub argument: String,
+    /// The glob the argument must match.
+    pub glob: String,
+    /// The permission to use when the rule matches.
+    pub permission: ToolPermission,
+}
+
+impl ToolPermissionsSettings {
+    /// Returns the permission for using the tool with the given input, resolving relative
+    /// paths in the input against the given worktree roots.
+    pub fn permission(
+        &self,
+        tool: &dyn Tool,
+        input: &serde_json::Value,
+        worktree_roots: &[PathBuf],
+    ) -> ToolPermission {
+        self.permission_for(
+            &tool.name(),
+            &tool.source(),
+            tool.needs_confirmation(),
+            input,
+            worktree_roots,
+        )
+    }
+
+    fn permission_for(
+        &self,
+        tool_name: &str,
+        source: &ToolSource,
+        needs_confirmation: bool,
+        input: &serde_json::Value,
+        worktree_roots: &[PathBuf],
+    ) -> ToolPermission {
+        let (policy, server_default) = match source {
+            ToolSource::Native => (self.tools.get(tool_name), None),
+            ToolSource::ContextServer { id } => {
+                let server = self.context_servers.get(id.as_ref());
+                (
+                    server.and_then(|server| server.tools.get(tool_name)),
+                    server.and_then(|server| server.default),
+                )
+            }
+        };
+
+        if let Some(permission) = policy.and_then(|policy| policy.permission(input, worktree_roots))
+        {
+            return permission;
+        }
+
+        // Third-party tools aren't trusted to run without asking unless the user says so.
+        let tool_default =
+            if needs_confirmation || matches!(source, ToolSource::ContextServer { .. }) {
+                ToolPermission::Ask
+            } else {
+                ToolPermission::Allow
+            };
+        match server_default.or(self.default) {
+            Some(default) if needs_confirmation => default.max(tool_default),
+            Some(default) => default,
+            None => tool_default,
+        }
+    }
+
+    /// Sets the default permission of a single tool, keeping its argument rules.
+    pub fn set_tool_permission(
+        &mut self,
+        tool_name: &str,
+        source: &ToolSource,
+        permission: ToolPermission,
+    ) {
+        let tools = match source {
+            ToolSource::Native => &mut self.tools,
+            ToolSource::ContextServer { id } => {
+                &mut self
+                    .context_servers
+                    .entry(id.to_string())
+                    .or_default()
+                    .tools
+            }
+        };
+        tools.entry(tool_name.to_string()).or_default().default = Some(permission);
+    }
+
+    fn validate(&self) -> Result<()> {
+        let policies = self.tools.values().chain(
+            self.context_servers
+                .values()
+                .flat_map(|server| server.tools.values()),
+        );
+        for rule in policies.flat_map(|policy| &policy.rules) {
+            Glob::new(&rule.glob)
+                .with_context(|| format!("invalid glob in tool permission rule: {}", rule.glob))?;
+        }
+        Ok(())
+    }
+}
+
+impl ToolPolicy {
+    fn permission(
+        &self,
+        input: &serde_json::Value,
+        worktree_roots: &[PathBuf],
+    ) -> Option<ToolPermission> {
+        for rule in &self.rules {
+            match rule.matches(input, worktree_roots) {
+                Some(true) => return Some(rule.permission),
+                Some(false) => {}
+                // The argument can't be checked safely, so it's up to the user to approve it.
+                None => {
+                    return [
+                        Some(ToolPermission::Ask),
+                        Some(rule.permission),
+                        self.default,
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .max();
+                }
+            }
+        }
+        self.default
+    }
+}
+
+impl ToolPermissionRule {
+    /// Returns whether the rule's argument matches its glob, or `None` if the argument
+    /// goes up a directory or is a path that can't be normalized.
+    fn matches(&self, input: &serde_json::Value, worktree_roots: &[PathBuf]) -> Option<bool> {
+        let argument = if self.argument.starts_with('/') {
+            input.pointer(&self.argument)
+        } else {
+            input.get(&self.argument)
+        };
+        let Some(argument) = argument.and_then(|argument| argument.as_str()) else {
+            return Some(false);
+        };
+        if argument
+            .split(|c: char| c == '/' || c == '\\' || c.is_whitespace())
+            .any(|part| part == "..")
+        {
+            return None;
+        }
+        let Ok(glob) = Glob::new(&self.glob) else {
+            return Some(false);
+        };
+        let matcher = glob.compile_matcher();
+        if self.glob.starts_with('/') || Path::new(&self.glob).is_absolute() {
+            let path = normalize_path(argument, worktree_roots)?;
+            return Some(matcher.is_match(path));
+        }
+
+        // Paths are matched relative to their worktree however they're spelled, while the
+        // argument itself is also matched for arguments that aren't paths.
+        let worktree_path = normalize_path(argument, worktree_roots).and_then(|path| {
+            worktree_roots
+                .iter()
+                .find_map(|root| Some(path.strip_prefix(root).ok()?.to_path_buf()))
+        });
+        Some(
+            matcher.is_match(argument)
+                || worktree_path.map_or(false, |path| matcher.is_match(path)),
+        )
+    }
+}
+
+/// Resolves a path argument to an absolute path without `.` components. Relative paths are
+/// resolved against the worktree whose root directory they start with, or else the first
+/// worktree.
+fn normalize_path(argument: &str, worktree_roots: &[PathBuf]) -> Option<PathBuf> {
+    let path = Path::new(argument);
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        let first_component = path.components().next()?;
+        let worktree_root = worktree_roots
+            .iter()
+            .find(|root| {
+                root.file_name()
+                    .map_or(false, |name| name == first_component.as_os_str())
+            })
+            .and_then(|root| Some(root.parent()?.join(path)));
+        match worktree_root {
+            Some(path) => path,
+            None => worktree_roots.first()?.join(path),
+        }
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => return None,
+            component => normalized.push(component),
+        }
+    }
+    Some(normalized)
+}
+
+impl Settings for ToolPermissionsSettings {
+    const KEY: Option<&'static str> = Some("tool_permissions");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        let settings: Self = sources.json_merge()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_tool_permissions() {
+        let settings: ToolPermissionsSettings = serde_json::from_value(json!({
+            "tools": {
+                "run-command": { "default": "deny" }
+            },
+            "context_servers": {
+                "filesystem": {
+                    "default": "ask",
+                    "tools": {
+                        "read_file": {
+                            "rules": [
+                                { "argument": "path", "glob": "/project/**", "permission": "allow" }
+                            ]
+                        },
+                        "write_file": { "default": "deny" }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        let filesystem = ToolSource::ContextServer {
+            id: "filesystem".into(),
+        };
+
+        assert_eq!(
+            settings.permission_for("run-command", &ToolSource::Native, true, &json!({}), &[]),
+            ToolPermission::Deny
+        );
+        assert_eq!(
+            settings.permission_for("diagnostics", &ToolSource::Native, false, &json!({}), &[]),
+            ToolPermission::Allow
+        );
+        assert_eq!(
+            settings.permission_for(
+                "read_file",
+                &filesystem,
+                false,
+                &json!({ "path": "/project/src/main.rs" }),
+                &[]
+            ),
+            ToolPermission::Allow
+        );
+        assert_eq!(
+            settings.permission_for(
+                "read_file",
+                &filesystem,
+                false,
+                &json!({ "path": "/etc/passwd" }),
+                &[]
+            ),
+            ToolPermission::Ask
+        );
+        assert_eq!(
+            settings.permission_for(
+                "write_file",
+                &filesystem,
+                false,
+                &json!({ "path": "/project/src/main.rs" }),
+                &[]
+            ),
+            ToolPermission::Deny
+        );
+        assert_eq!(
+            settings.permission_for(
+                "read_file",
+                &ToolSource::ContextServer { id: "other".into() },
+                false,
+                &json!({ "path": "/project/src/main.rs" }),
+                &[]
+            ),
+            ToolPermission::Ask
+        );
+    }
+
+    #[test]
+    fn test_tool_permission_paths() {
+        let settings: ToolPermissionsSettings = serde_json::from_value(json!({
+            "tools": {
+                "read-file": {
+                    "rules": [
+                        { "argument": "path", "glob": "/project/**", "permission": "allow" }
+                    ]
+                },
+                "delete-path": {
+                    "default": "deny",
+                    "rules": [
+                        { "argument": "path", "glob": "/project/tmp/**", "permission": "allow" }
+                    ]
+                }
+            }
+        }))
+        .unwrap();
+        let roots = [PathBuf::from("/project")];
+        let permission = |tool: &str, path: &str| {
+            settings.permission_for(
+                tool,
+                &ToolSource::Native,
+                false,
+                &json!({ "path": path }),
+                &roots,
+            )
+        };
+
+        assert_eq!(
+            permission("read-file", "project/src/main.rs"),
+            ToolPermission::Allow
+        );
+        assert_eq!(
+            permission("read-file", "src/main.rs"),
+            ToolPermission::Allow
+        );
+        assert_eq!(
+            permission("read-file", "./src/main.rs"),
+            ToolPermission::Allow
+        );
+        assert_eq!(
+            permission("read-file", "/other/main.rs"),
+            ToolPermission::Allow
+        );
+        assert_eq!(
+            permission("read-file", "/project/../etc/passwd"),
+            ToolPermission::Ask
+        );
+        assert_eq!(
+            permission("read-file", "project/../../etc/passwd"),
+            ToolPermission::Ask
+        );
+        assert_eq!(
+            permission("delete-path", "/project/tmp/../src"),
+            ToolPermission::Deny
+        );
+        assert_eq!(
+            permission("delete-path", "/project/tmp/a.txt"),
+            ToolPermission::Allow
+        );
+    }
+
+    #[test]
+    fn test_tool_permission_relative_globs() {
+        let settings: ToolPermissionsSettings = serde_json::from_value(json!({
+            "tools": {
+                "read-file": {
+                    "rules": [
+                        { "argument": "path", "glob": "secrets/**", "permission": "deny" }
+                    ]
+                }
+            }
+        }))
+        .unwrap();
+        let roots = [PathBuf::from("/work/project"), PathBuf::from("/work/docs")];
+        let permission = |path: &str| {
+            settings.permission_for(
+                "read-file",
+                &ToolSource::Native,
+                false,
+                &json!({ "path": path }),
+                &roots,
+            )
+        };
+
+        assert_eq!(permission("secrets/key"), ToolPermission::Deny);
+        assert_eq!(permission("./secrets/key"), ToolPermission::Deny);
+        assert_eq!(permission("project/secrets/key"), ToolPermission::Deny);
+        assert_eq!(permission("docs/secrets/key"), ToolPermission::Deny);
+        assert_eq!(
+            permission("/work/project/secrets/key"),
+            ToolPermission::Deny
+        );
+        assert_eq!(permission("./project/./secrets/key"), ToolPermission::Deny);
+        assert_eq!(permission("src/secrets.rs"), ToolPermission::Allow);
+        assert_eq!(permission("/work/other/secrets/key"), ToolPermission::Allow);
+    }
+
+    #[test]
+    fn test_defaults_dont_skip_confirmation() {
+        let settings: ToolPermissionsSettings = serde_json::from_value(json!({
+            "default": "allow",
+            "tools": {
+                "edit-files": { "default": "allow" }
+            },
+            "context_servers": {
+                "github": { "default": "allow" }
+            }
+        }))
+        .unwrap();
+        let github = ToolSource::ContextServer {
+            id: "github".into(),
+        };
+
+        assert_eq!(
+            settings.permission_for("run-command", &ToolSource::Native, true, &json!({}), &[]),
+            ToolPermission::Ask
+        );
+        assert_eq!(
+            settings.permission_for("edit-files", &ToolSource::Native, true, &json!({}), &[]),
+            ToolPermission::Allow
+        );
+        assert_eq!(
+            settings.permission_for("create_issue", &github, true, &json!({}), &[]),
+            ToolPermission::Ask
+        );
+        assert_eq!(
+            settings.permission_for("diagnostics", &ToolSource::Native, false, &json!({}), &[]),
+            ToolPermission::Allow
+        );
+    }
+}
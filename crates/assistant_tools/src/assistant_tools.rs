@@ -1,13 +1,24 @@
+mod code_snippets;
+mod diagnostics_tool;
+mod edit_file_tool;
 mod list_worktrees_tool;
 mod now_tool;
 mod read_file_tool;
+mod run_command_tool;
+mod search_project_tool;
+mod workspace_symbols_tool;
 
 use assistant_tool::ToolRegistry;
 use gpui::App;
 
+use crate::diagnostics_tool::DiagnosticsTool;
+use crate::edit_file_tool::EditFileTool;
 use crate::list_worktrees_tool::ListWorktreesTool;
 use crate::now_tool::NowTool;
 use crate::read_file_tool::ReadFileTool;
+use crate::run_command_tool::RunCommandTool;
+use crate::search_project_tool::SearchProjectTool;
+use crate::workspace_symbols_tool::WorkspaceSymbolsTool;
 
 pub fn init(cx: &mut App) {
     assistant_tool::init(cx);
@@ -16,4 +27,9 @@ pub fn init(cx: &mut App) {
     registry.register_tool(NowTool);
     registry.register_tool(ListWorktreesTool);
     registry.register_tool(ReadFileTool);
+    registry.register_tool(EditFileTool);
+    registry.register_tool(SearchProjectTool);
+    registry.register_tool(WorkspaceSymbolsTool);
+    registry.register_tool(DiagnosticsTool);
+    registry.register_tool(RunCommandTool);
 }
@@ -0,0 +1,139 @@
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use language::{BufferSnapshot, Point};
+
+/// A rough approximation of how many bytes make up a single token.
+const BYTES_PER_TOKEN_GUESS: usize = 3;
+
+/// The number of tokens a tool's output is allowed to use when the model does not specify one.
+pub(crate) const DEFAULT_TOKEN_BUDGET: usize = 4096;
+
+/// Accumulates tool output, stopping once the token budget has been exhausted.
+pub(crate) struct BudgetedOutput {
+    text: String,
+    max_bytes: usize,
+    omitted_sections: usize,
+}
+
+impl BudgetedOutput {
+    pub fn new(max_tokens: usize) -> Self {
+        Self {
+            text: String::new(),
+            max_bytes: max_tokens.saturating_mul(BYTES_PER_TOKEN_GUESS),
+            omitted_sections: 0,
+        }
+    }
+
+    /// Appends the section if it fits in the remaining budget.
+    ///
+    /// Returns `false` when the section was omitted.
+    pub fn push(&mut self, section: &str) -> bool {
+        if self.omitted_sections > 0 || self.text.len() + section.len() > self.max_bytes {
+            self.omitted_sections += 1;
+            return false;
+        }
+
+        self.text.push_str(section);
+        true
+    }
+
+    /// Records sections that were skipped without being passed to [`Self::push`].
+    pub fn omit(&mut self, count: usize) {
+        self.omitted_sections += count;
+    }
+
+    /// Returns whether no further section can be pushed.
+    pub fn is_exhausted(&self) -> bool {
+        self.omitted_sections > 0 || self.text.len() >= self.max_bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn finish(mut self) -> String {
+        if self.omitted_sections > 0 {
+            write!(
+                self.text,
+                "\n{} more result(s) were omitted to stay within the token budget. Narrow down the query to see them.",
+                self.omitted_sections
+            )
+            .ok();
+        }
+        self.text
+    }
+}
+
+/// Combines overlapping or adjacent row ranges into a sorted list of disjoint ranges.
+pub(crate) fn merge_row_ranges(mut ranges: Vec<Range<u32>>) -> Vec<Range<u32>> {
+    ranges.sort_unstable_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<u32>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last) = merged.last_mut() {
+            if range.start <= last.end {
+                last.end = last.end.max(range.end);
+                continue;
+            }
+        }
+        merged.push(range);
+    }
+    merged
+}
+
+/// Expands the (zero-based, end-inclusive) row range by `context_lines` in each direction,
+/// clamped to the bounds of the buffer.
+pub(crate) fn rows_with_context(
+    rows: Range<u32>,
+    context_lines: u32,
+    snapshot: &BufferSnapshot,
+) -> Range<u32> {
+    let max_row = snapshot.max_point().row;
+    rows.start.saturating_sub(context_lines)..(rows.end + context_lines).min(max_row)
+}
+
+/// Renders the (zero-based, end-inclusive) rows as a fenced code block, prefixed with a header
+/// that uses one-based line numbers.
+pub(crate) fn render_snippet(path: &str, rows: Range<u32>, snapshot: &BufferSnapshot) -> String {
+    let start = Point::new(rows.start, 0);
+    let end = Point::new(rows.end, snapshot.line_len(rows.end));
+    let text = snapshot.text_for_range(start..end).collect::<String>();
+
+    let mut snippet = format!("{path}:{}-{}\n```\n", rows.start + 1, rows.end + 1);
+    snippet.push_str(&text);
+    if !text.ends_with('\n') {
+        snippet.push('\n');
+    }
+    snippet.push_str("```\n\n");
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_row_ranges() {
+        assert_eq!(
+            merge_row_ranges(vec![10..12, 0..3, 2..5, 12..14, 20..20]),
+            vec![0..5, 10..14, 20..20]
+        );
+        assert_eq!(merge_row_ranges(Vec::new()), Vec::<Range<u32>>::new());
+    }
+
+    #[test]
+    fn test_budgeted_output() {
+        let mut output = BudgetedOutput::new(2);
+        assert!(output.push("abc"));
+        assert!(!output.is_exhausted());
+        assert!(!output.push("defg"));
+        assert!(output.is_exhausted());
+        // Once a section was omitted, later sections are omitted too to keep results in order.
+        assert!(!output.push("h"));
+        assert_eq!(
+            output.finish(),
+            "abc\n2 more result(s) were omitted to stay within the token budget. Narrow down the query to see them."
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use collections::{HashMap, HashSet};
+use gpui::{App, Task, WeakEntity, Window};
+use language::{DiagnosticSeverity, ToPoint as _};
+use project::ProjectPath;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+use crate::code_snippets::{render_snippet, BudgetedOutput, DEFAULT_TOKEN_BUDGET};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiagnosticsToolInput {
+    /// The path of the file to get diagnostics for, starting with the name of the worktree root,
+    /// e.g. `my-project/src/main.rs`.
+    ///
+    /// When omitted, diagnostics for every file in the project are returned.
+    pub path: Option<PathBuf>,
+    /// Whether to include warnings in addition to errors.
+    #[serde(default)]
+    pub include_warnings: bool,
+    /// The maximum number of tokens the results may use.
+    pub max_tokens: Option<usize>,
+}
+
+pub struct DiagnosticsTool;
+
+impl Tool for DiagnosticsTool {
+    fn name(&self) -> String {
+        "diagnostics".into()
+    }
+
+    fn description(&self) -> String {
+        "Returns the errors (and optionally warnings) reported by the language servers for a single file or for the whole project, along with the lines of code they refer to. Use this tool after editing files to check whether your changes compile.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(DiagnosticsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let input = match serde_json::from_value::<DiagnosticsToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+
+        let project = workspace.read(cx).project().clone();
+        // Used to count the diagnostics in files that don't fit in the budget without
+        // opening them.
+        let mut diagnostic_counts = HashMap::<ProjectPath, (usize, usize)>::default();
+        for (project_path, _, summary) in project.read(cx).diagnostic_summaries(false, cx) {
+            let counts = diagnostic_counts.entry(project_path).or_default();
+            counts.0 += summary.error_count;
+            counts.1 += summary.warning_count;
+        }
+        let project_paths = if let Some(path) = input.path.as_deref() {
+            let Some(project_path) = project.read(cx).find_project_path(path, cx) else {
+                return Task::ready(Err(anyhow!("no such path in the project: {path:?}")));
+            };
+            vec![project_path]
+        } else {
+            let mut seen_paths = HashSet::default();
+            project
+                .read(cx)
+                .diagnostic_summaries(false, cx)
+                .filter(|(_, _, summary)| {
+                    summary.error_count > 0 || (input.include_warnings && summary.warning_count > 0)
+                })
+                .filter_map(|(project_path, _, _)| {
+                    seen_paths
+                        .insert(project_path.clone())
+                        .then_some(project_path)
+                })
+                .collect()
+        };
+
+        cx.spawn(|mut cx| async move {
+            let mut output = BudgetedOutput::new(input.max_tokens.unwrap_or(DEFAULT_TOKEN_BUDGET));
+            let mut error_count = 0;
+            let mut warning_count = 0;
+
+            for (ix, project_path) in project_paths.iter().enumerate() {
+                if output.is_exhausted() {
+                    for project_path in &project_paths[ix..] {
+                        let (errors, warnings) = diagnostic_counts
+                            .get(project_path)
+                            .copied()
+                            .unwrap_or_default();
+                        error_count += errors;
+                        output.omit(errors);
+                        if input.include_warnings {
+                            warning_count += warnings;
+                            output.omit(warnings);
+                        }
+                    }
+                    break;
+                }
+
+                let Some(buffer) = project
+                    .update(&mut cx, |project, cx| {
+                        project.open_buffer(project_path.clone(), cx)
+                    })?
+                    .await
+                    .log_err()
+                else {
+                    continue;
+                };
+
+                let sections = buffer.read_with(&cx, |buffer, cx| {
+                    let snapshot = buffer.snapshot();
+                    let path = buffer
+                        .file()
+                        .map(|file| file.full_path(cx))
+                        .unwrap_or_else(|| Path::new("untitled").into());
+                    let path = path.to_string_lossy();
+
+                    let mut sections = Vec::new();
+                    for (_, group) in snapshot.diagnostic_groups(None) {
+                        let entry = &group.entries[group.primary_ix];
+                        let severity = match entry.diagnostic.severity {
+                            DiagnosticSeverity::ERROR => {
+                                error_count += 1;
+                                "error"
+                            }
+                            DiagnosticSeverity::WARNING if input.include_warnings => {
+                                warning_count += 1;
+                                "warning"
+                            }
+                            _ => continue,
+                        };
+
+                        let start = entry.range.start.to_point(&snapshot);
+                        let end = entry.range.end.to_point(&snapshot);
+                        let mut section = String::new();
+                        write!(
+                            section,
+                            "{severity}: {}\n{}",
+                            entry.diagnostic.message,
+                            render_snippet(&path, start.row..end.row, &snapshot)
+                        )
+                        .ok();
+                        sections.push(section);
+                    }
+                    sections
+                })?;
+
+                for section in sections {
+                    output.push(&section);
+                }
+            }
+
+            if error_count + warning_count == 0 {
+                return Ok(if input.include_warnings {
+                    "No errors or warnings found.".to_string()
+                } else {
+                    "No errors found.".to_string()
+                });
+            }
+
+            let mut summary = format!("Found {error_count} error(s)");
+            if input.include_warnings {
+                write!(summary, " and {warning_count} warning(s)").ok();
+            }
+            summary.push_str(":\n\n");
+            summary.push_str(&output.finish());
+            Ok(summary)
+        })
+    }
+}
@@ -0,0 +1,255 @@
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{App, Task, WeakEntity, Window};
+use project::{ProjectPath, WorktreeId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EditFileToolInput {
+    /// The ID of the worktree in which the file resides.
+    pub worktree_id: usize,
+    /// The path to the file to edit.
+    ///
+    /// This path is relative to the worktree root, it must not be an absolute path.
+    pub path: Arc<Path>,
+    /// The replacements to apply to the file.
+    ///
+    /// Every `old_text` must appear exactly once in the file, and the
+    /// replacements must not overlap. Either all replacements are applied or
+    /// none of them are.
+    pub edits: Vec<EditFileToolReplacement>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EditFileToolReplacement {
+    /// The exact text to replace, including whitespace and indentation.
+    ///
+    /// Include enough surrounding lines to make the text unique within the file.
+    pub old_text: String,
+    /// The text to insert in place of `old_text`.
+    pub new_text: String,
+}
+
+/// The reason an [`EditFileToolReplacement`] could not be applied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EditFileError {
+    /// The `old_text` of the replacement was empty.
+    EmptyOldText { edit_index: usize },
+    /// The `old_text` of the replacement does not appear in the file.
+    NoMatch { edit_index: usize },
+    /// The `old_text` of the replacement appears more than once in the file.
+    AmbiguousMatch {
+        edit_index: usize,
+        match_count: usize,
+    },
+    /// The replacement overlaps with another replacement.
+    OverlappingEdits {
+        edit_index: usize,
+        other_edit_index: usize,
+    },
+}
+
+impl std::fmt::Display for EditFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            EditFileError::EmptyOldText { edit_index } => {
+                format!("edit {edit_index} has an empty `old_text`")
+            }
+            EditFileError::NoMatch { edit_index } => {
+                format!("the `old_text` of edit {edit_index} was not found in the file")
+            }
+            EditFileError::AmbiguousMatch {
+                edit_index,
+                match_count,
+            } => format!(
+                "the `old_text` of edit {edit_index} matches {match_count} locations in the file; include more surrounding context to make it unique"
+            ),
+            EditFileError::OverlappingEdits {
+                edit_index,
+                other_edit_index,
+            } => format!("edit {edit_index} overlaps with edit {other_edit_index}"),
+        };
+
+        // Include the structured form so the model can act on it programmatically.
+        let json = serde_json::to_string(self).map_err(|_| std::fmt::Error)?;
+        write!(f, "{description}. No edits were applied. Details: {json}")
+    }
+}
+
+impl std::error::Error for EditFileError {}
+
+pub struct EditFileTool;
+
+impl Tool for EditFileTool {
+    fn name(&self) -> String {
+        "edit-file".into()
+    }
+
+    fn description(&self) -> String {
+        "Edits a file specified by a worktree ID and path by replacing exact occurrences of `old_text` with `new_text`. Each `old_text` must match exactly one location in the file. The edits are applied to the open buffer without saving it, and a unified diff of the changes is returned so the user can review them before saving. Use the `read-file` tool first to see the current contents of the file.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(EditFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let input = match serde_json::from_value::<EditFileToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+
+        let buffer_store = workspace.read(cx).project().read(cx).buffer_store().clone();
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_usize(input.worktree_id),
+            path: input.path.clone(),
+        };
+        cx.spawn(|mut cx| async move {
+            let buffer = buffer_store
+                .update(&mut cx, |buffer_store, cx| {
+                    buffer_store.open_buffer(project_path, cx)
+                })?
+                .await?;
+
+            let old_text = buffer.read_with(&cx, |buffer, _cx| buffer.text())?;
+            let edits = resolve_edits(&old_text, &input.edits)?;
+
+            let new_text = buffer.update(&mut cx, |buffer, cx| {
+                buffer.finalize_last_transaction();
+                buffer.start_transaction();
+                buffer.edit(edits, None, cx);
+                buffer.end_transaction(cx);
+                buffer.text()
+            })?;
+
+            let diff = language::unified_diff(&old_text, &new_text);
+            Ok(format!(
+                "Applied {} edit(s) to {}. The file has not been saved.\n\n```diff\n{diff}```",
+                input.edits.len(),
+                input.path.display()
+            ))
+        })
+    }
+}
+
+/// Resolves each replacement to the byte range it applies to in `text`.
+///
+/// Fails without resolving any edits when a replacement is missing, ambiguous,
+/// or overlaps with another replacement.
+fn resolve_edits(
+    text: &str,
+    replacements: &[EditFileToolReplacement],
+) -> Result<Vec<(Range<usize>, String)>, EditFileError> {
+    let mut edits = Vec::with_capacity(replacements.len());
+    for (edit_index, replacement) in replacements.iter().enumerate() {
+        if replacement.old_text.is_empty() {
+            return Err(EditFileError::EmptyOldText { edit_index });
+        }
+
+        let mut matches = text.match_indices(replacement.old_text.as_str());
+        let Some((start, _)) = matches.next() else {
+            return Err(EditFileError::NoMatch { edit_index });
+        };
+        let remaining_matches = matches.count();
+        if remaining_matches > 0 {
+            return Err(EditFileError::AmbiguousMatch {
+                edit_index,
+                match_count: remaining_matches + 1,
+            });
+        }
+
+        let range = start..start + replacement.old_text.len();
+        edits.push((edit_index, range, replacement.new_text.clone()));
+    }
+
+    edits.sort_unstable_by_key(|(_, range, _)| range.start);
+    for pair in edits.windows(2) {
+        let (prev_index, prev_range, _) = &pair[0];
+        let (next_index, next_range, _) = &pair[1];
+        if prev_range.end > next_range.start {
+            return Err(EditFileError::OverlappingEdits {
+                edit_index: *prev_index.max(next_index),
+                other_edit_index: *prev_index.min(next_index),
+            });
+        }
+    }
+
+    Ok(edits
+        .into_iter()
+        .map(|(_, range, new_text)| (range, new_text))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replacement(old_text: &str, new_text: &str) -> EditFileToolReplacement {
+        EditFileToolReplacement {
+            old_text: old_text.into(),
+            new_text: new_text.into(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_edits() {
+        let text = "fn one() {}\nfn two() {}\nfn three() {}\n";
+
+        assert_eq!(
+            resolve_edits(
+                text,
+                &[
+                    replacement("fn three", "fn 3"),
+                    replacement("fn one", "fn 1")
+                ]
+            ),
+            Ok(vec![(0..6, "fn 1".into()), (24..32, "fn 3".into())])
+        );
+        assert_eq!(
+            resolve_edits(text, &[replacement("fn four", "fn 4")]),
+            Err(EditFileError::NoMatch { edit_index: 0 })
+        );
+        assert_eq!(
+            resolve_edits(
+                text,
+                &[replacement("fn one", "fn 1"), replacement("() {}", "")]
+            ),
+            Err(EditFileError::AmbiguousMatch {
+                edit_index: 1,
+                match_count: 3
+            })
+        );
+        assert_eq!(
+            resolve_edits(text, &[replacement("", "fn zero() {}")]),
+            Err(EditFileError::EmptyOldText { edit_index: 0 })
+        );
+        assert_eq!(
+            resolve_edits(
+                text,
+                &[replacement("one() {}\nfn", ""), replacement("fn two", "")]
+            ),
+            Err(EditFileError::OverlappingEdits {
+                edit_index: 1,
+                other_edit_index: 0
+            })
+        );
+    }
+}
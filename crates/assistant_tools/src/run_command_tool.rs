@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tool::Tool;
+use gpui::{App, Entity, Task, WeakEntity, Window};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use task::{HideStrategy, RevealStrategy, TaskContext, TaskTemplate};
+use terminal::TaskStatus;
+use terminal_view::terminal_panel::TerminalPanel;
+use workspace::Workspace;
+
+/// The number of trailing output lines returned when the model does not specify one.
+const DEFAULT_OUTPUT_LINES: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunCommandToolInput {
+    /// The shell command to run, e.g. `cargo check --workspace`.
+    pub command: String,
+    /// The directory to run the command in, starting with the name of the worktree root,
+    /// e.g. `my-project/crates/foo`.
+    ///
+    /// Defaults to the root of the first worktree in the project.
+    pub cwd: Option<PathBuf>,
+    /// The maximum number of trailing output lines to return.
+    pub max_output_lines: Option<usize>,
+}
+
+pub struct RunCommandTool;
+
+impl Tool for RunCommandTool {
+    fn name(&self) -> String {
+        "run-command".into()
+    }
+
+    fn description(&self) -> String {
+        "Runs a shell command in a new terminal inside the project and returns whether it succeeded along with the last lines of its output. The user must approve every command before it runs. Use this tool to build the project or run its tests, e.g. after editing files.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(RunCommandToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn needs_confirmation(&self) -> bool {
+        true
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let input = match serde_json::from_value::<RunCommandToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+
+        let cwd = match resolve_cwd(&workspace, input.cwd.as_deref(), cx) {
+            Ok(cwd) => cwd,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let Some(terminal_panel) = workspace.read(cx).panel::<TerminalPanel>(cx) else {
+            return Task::ready(Err(anyhow!("no terminal panel")));
+        };
+
+        let template = TaskTemplate {
+            label: format!("Assistant: {}", input.command),
+            command: input.command.clone(),
+            cwd: Some(cwd.to_string_lossy().to_string()),
+            use_new_terminal: true,
+            allow_concurrent_runs: true,
+            reveal: RevealStrategy::NoFocus,
+            hide: HideStrategy::Never,
+            show_summary: true,
+            show_command: true,
+            ..TaskTemplate::default()
+        };
+        let Some(spawn_in_terminal) = template
+            .resolve_task("assistant", &TaskContext::default())
+            .and_then(|task| task.resolved)
+        else {
+            return Task::ready(Err(anyhow!("invalid command: {:?}", input.command)));
+        };
+
+        let terminal = terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.spawn_in_new_terminal(spawn_in_terminal, window, cx)
+        });
+        let max_output_lines = input.max_output_lines.unwrap_or(DEFAULT_OUTPUT_LINES);
+
+        cx.spawn(|cx| async move {
+            let terminal = terminal.await?;
+            cx.update(|cx| terminal.read(cx).wait_for_completed_task(cx))?
+                .await;
+
+            terminal.read_with(&cx, |terminal, _cx| {
+                let status = match terminal.task().map(|task| task.status) {
+                    Some(TaskStatus::Completed { success: true, .. }) => "succeeded".to_string(),
+                    Some(TaskStatus::Completed {
+                        success: false,
+                        exit_code,
+                    }) => format!("failed with exit code {exit_code}"),
+                    Some(TaskStatus::Running) | Some(TaskStatus::Unknown) | None => {
+                        "was interrupted".to_string()
+                    }
+                };
+                let lines = terminal.last_n_non_empty_lines(max_output_lines);
+
+                let mut output = format!("The command `{}` {status}.", input.command);
+                if lines.is_empty() {
+                    output.push_str(" It produced no output.");
+                } else {
+                    if terminal.total_lines() > lines.len() {
+                        output.push_str(&format!(
+                            " Only the last {} lines of output are shown.",
+                            lines.len()
+                        ));
+                    }
+                    output.push_str("\n\n```\n");
+                    output.push_str(&lines.join("\n"));
+                    output.push_str("\n```");
+                }
+                output
+            })
+        })
+    }
+}
+
+/// Resolves the directory to run the command in, ensuring it lies within one of the project's
+/// worktrees.
+fn resolve_cwd(workspace: &Entity<Workspace>, cwd: Option<&Path>, cx: &App) -> Result<PathBuf> {
+    let project = workspace.read(cx).project().read(cx);
+    match cwd {
+        Some(cwd) => {
+            let project_path = project
+                .find_project_path(cwd, cx)
+                .with_context(|| format!("no such directory in the project: {cwd:?}"))?;
+            project
+                .absolute_path(&project_path, cx)
+                .with_context(|| format!("cannot resolve the absolute path of {cwd:?}"))
+        }
+        None => project
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .context("no worktrees in the project"),
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{App, Task, WeakEntity, Window};
+use language::ToPoint as _;
+use project::search::{SearchQuery, SearchResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+use workspace::Workspace;
+
+use crate::code_snippets::{
+    merge_row_ranges, render_snippet, rows_with_context, BudgetedOutput, DEFAULT_TOKEN_BUDGET,
+};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchProjectToolInput {
+    /// The text or regular expression to search for.
+    pub query: String,
+    /// Whether `query` is a regular expression (using Rust regex syntax) rather than literal text.
+    #[serde(default)]
+    pub regex: bool,
+    /// Whether the search is case sensitive.
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Whether to only match whole words.
+    #[serde(default)]
+    pub whole_word: bool,
+    /// Glob patterns of the files to search, e.g. `["src/**/*.rs"]`. Searches all files when empty.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Glob patterns of the files to skip, e.g. `["**/tests/**"]`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// The number of lines of context to show around each match.
+    #[serde(default = "default_context_lines")]
+    pub context_lines: u32,
+    /// The maximum number of tokens the results may use.
+    pub max_tokens: Option<usize>,
+}
+
+fn default_context_lines() -> u32 {
+    2
+}
+
+pub struct SearchProjectTool;
+
+impl Tool for SearchProjectTool {
+    fn name(&self) -> String {
+        "search-project".into()
+    }
+
+    fn description(&self) -> String {
+        "Searches the contents of all files in the project for literal text or a regular expression, skipping gitignored files. Returns the path, line range and a snippet of surrounding lines for every match. Use this tool to find where something is defined or used.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SearchProjectToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let input = match serde_json::from_value::<SearchProjectToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+
+        let query = match build_query(&input) {
+            Ok(query) => query,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let results = project.update(cx, |project, cx| project.search(query, cx));
+
+        cx.spawn(|cx| async move {
+            let mut output =
+                BudgetedOutput::new(input.max_tokens.unwrap_or(DEFAULT_TOKEN_BUDGET));
+            let mut limit_reached = false;
+            let mut budget_exhausted = false;
+
+            while let Ok(result) = results.recv().await {
+                let (buffer, ranges) = match result {
+                    SearchResult::Buffer { buffer, ranges } => (buffer, ranges),
+                    SearchResult::LimitReached => {
+                        limit_reached = true;
+                        break;
+                    }
+                };
+
+                let sections = cx.update(|cx| {
+                    let buffer = buffer.read(cx);
+                    let snapshot = buffer.snapshot();
+                    let path = buffer
+                        .file()
+                        .map(|file| file.full_path(cx).to_string_lossy().to_string())
+                        .unwrap_or_else(|| "untitled".to_string());
+
+                    let rows = ranges
+                        .iter()
+                        .map(|range| {
+                            let start = range.start.to_point(&snapshot).row;
+                            let end = range.end.to_point(&snapshot).row;
+                            rows_with_context(start..end, input.context_lines, &snapshot)
+                        })
+                        .collect();
+
+                    merge_row_ranges(rows)
+                        .into_iter()
+                        .map(|rows| render_snippet(&path, rows, &snapshot))
+                        .collect::<Vec<_>>()
+                })?;
+
+                let section_count = sections.len();
+                for (index, section) in sections.into_iter().enumerate() {
+                    if !output.push(&section) {
+                        output.omit(section_count - index - 1);
+                        budget_exhausted = true;
+                        break;
+                    }
+                }
+                if budget_exhausted {
+                    break;
+                }
+            }
+            // Dropping the results cancels the rest of the search, since nothing more fits.
+            drop(results);
+
+            if output.is_empty() && !budget_exhausted {
+                return Ok(format!("No matches found for `{}`.", input.query));
+            }
+
+            let mut output = output.finish();
+            if limit_reached {
+                output.push_str(
+                    "\nThe search stopped early because it matched too many files. Narrow down the query to see the remaining matches.",
+                );
+            } else if budget_exhausted {
+                output.push_str("\nOther files may match as well, since the search stopped once the token budget was used up.");
+            }
+            Ok(output)
+        })
+    }
+}
+
+fn build_query(input: &SearchProjectToolInput) -> Result<SearchQuery> {
+    let files_to_include = PathMatcher::new(&input.include)?;
+    let files_to_exclude = PathMatcher::new(&input.exclude)?;
+
+    if input.regex {
+        SearchQuery::regex(
+            &input.query,
+            input.whole_word,
+            input.case_sensitive,
+            false,
+            files_to_include,
+            files_to_exclude,
+            None,
+        )
+    } else {
+        SearchQuery::text(
+            &input.query,
+            input.whole_word,
+            input.case_sensitive,
+            false,
+            files_to_include,
+            files_to_exclude,
+            None,
+        )
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{App, Task, WeakEntity, Window};
+use language::Bias;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+use crate::code_snippets::{render_snippet, BudgetedOutput, DEFAULT_TOKEN_BUDGET};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSymbolsToolInput {
+    /// The name, or part of the name, of the symbol to look up.
+    pub query: String,
+    /// The maximum number of tokens the results may use.
+    pub max_tokens: Option<usize>,
+}
+
+pub struct WorkspaceSymbolsTool;
+
+impl Tool for WorkspaceSymbolsTool {
+    fn name(&self) -> String {
+        "workspace-symbols".into()
+    }
+
+    fn description(&self) -> String {
+        "Looks up symbols (functions, types, constants, etc.) whose name matches the query using the project's language servers. Returns the kind, path, line range and source of every matching symbol. Use this tool to find where a symbol is defined.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(WorkspaceSymbolsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let input = match serde_json::from_value::<WorkspaceSymbolsToolInput>(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let symbols = project.update(cx, |project, cx| project.symbols(&input.query, cx));
+
+        cx.spawn(|mut cx| async move {
+            let symbols = symbols.await?;
+            if symbols.is_empty() {
+                return Ok(format!("No symbols found matching `{}`.", input.query));
+            }
+
+            let symbol_count = symbols.len();
+            let mut output = BudgetedOutput::new(input.max_tokens.unwrap_or(DEFAULT_TOKEN_BUDGET));
+            for (index, symbol) in symbols.into_iter().enumerate() {
+                let buffer = project
+                    .update(&mut cx, |project, cx| {
+                        project.open_buffer_for_symbol(&symbol, cx)
+                    })?
+                    .await?;
+
+                let section = buffer.read_with(&cx, |buffer, cx| {
+                    let snapshot = buffer.snapshot();
+                    let start = snapshot.clip_point_utf16(symbol.range.start, Bias::Left);
+                    let end = snapshot.clip_point_utf16(symbol.range.end, Bias::Right);
+                    let path = buffer
+                        .file()
+                        .map(|file| file.full_path(cx).to_string_lossy().to_string())
+                        .unwrap_or_else(|| symbol.path.path.to_string_lossy().to_string());
+
+                    format!(
+                        "{} ({:?})\n{}",
+                        symbol.name,
+                        symbol.kind,
+                        render_snippet(&path, start.row..end.row, &snapshot)
+                    )
+                })?;
+
+                if !output.push(&section) {
+                    // Avoid opening buffers for symbols that won't fit in the output.
+                    output.omit(symbol_count - index - 1);
+                    break;
+                }
+            }
+
+            Ok(output.finish())
+        })
+    }
+}
@@ -6,9 +6,10 @@ use anyhow::{anyhow, Context, Error, Result};
 use aws_sdk_bedrockruntime as bedrock;
 pub use aws_sdk_bedrockruntime as bedrock_client;
 pub use aws_sdk_bedrockruntime::types::{
+    CachePointBlock as BedrockCachePoint, CachePointType as BedrockCachePointType,
     ContentBlock as BedrockInnerContent, SpecificToolChoice as BedrockSpecificTool,
-    ToolChoice as BedrockToolChoice, ToolInputSchema as BedrockToolInputSchema,
-    ToolSpecification as BedrockTool,
+    SystemContentBlock as BedrockSystemContent, ToolChoice as BedrockToolChoice,
+    ToolInputSchema as BedrockToolInputSchema, ToolSpecification as BedrockTool,
 };
 use aws_smithy_types::{Document, Number as AwsNumber};
 pub use bedrock::operation::converse_stream::ConverseStreamInput as BedrockStreamingRequest;
@@ -31,6 +32,7 @@ pub async fn complete(
     let response = bedrock::Client::converse(client)
         .model_id(request.model.clone())
         .set_messages(request.messages.into())
+        .set_system(request_system(request.system))
         .send()
         .await
         .context("failed to send request to Bedrock");
@@ -53,6 +55,7 @@ pub async fn stream_completion(
             let response = bedrock::Client::converse_stream(&client)
                 .model_id(request.model.clone())
                 .set_messages(request.messages.into())
+                .set_system(request_system(request.system))
                 .send()
                 .await;
 
@@ -91,6 +94,18 @@ pub async fn stream_completion(
         .map_err(|err| anyhow!("failed to spawn task: {err:?}"))?
 }
 
+fn request_system(system: Vec<BedrockSystemContent>) -> Option<Vec<BedrockSystemContent>> {
+    (!system.is_empty()).then_some(system)
+}
+
+/// Returns a block that marks everything before it as a prefix to cache.
+pub fn cache_point() -> BedrockCachePoint {
+    BedrockCachePoint::builder()
+        .r#type(BedrockCachePointType::Default)
+        .build()
+        .expect("cache point has a type")
+}
+
 pub fn aws_document_to_value(document: &Document) -> Value {
     match document {
         Document::Null => Value::Null,
@@ -142,7 +157,7 @@ pub struct Request {
     pub messages: Vec<BedrockMessage>,
     pub tools: Vec<BedrockTool>,
     pub tool_choice: Option<BedrockToolChoice>,
-    pub system: Option<String>,
+    pub system: Vec<BedrockSystemContent>,
     pub metadata: Option<Metadata>,
     pub stop_sequences: Vec<String>,
     pub temperature: Option<f32>,
@@ -2,6 +2,14 @@ use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use strum::EnumIter;
 
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct BedrockModelCacheConfiguration {
+    pub min_total_token: usize,
+    pub should_speculate: bool,
+    pub max_cache_anchors: usize,
+}
+
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, EnumIter)]
 pub enum Model {
@@ -61,6 +69,8 @@ pub enum Model {
         display_name: Option<String>,
         max_output_tokens: Option<u32>,
         default_temperature: Option<f32>,
+        /// Indicates whether this custom model supports prompt caching.
+        cache_configuration: Option<BedrockModelCacheConfiguration>,
     },
 }
 
@@ -190,6 +200,34 @@ impl Model {
         }
     }
 
+    /// Returns how the model caches prompts, for the models that support prompt caching.
+    pub fn cache_configuration(&self) -> Option<BedrockModelCacheConfiguration> {
+        match self {
+            Self::Claude3_7Sonnet => Some(BedrockModelCacheConfiguration {
+                min_total_token: 1_024,
+                should_speculate: true,
+                max_cache_anchors: 4,
+            }),
+            Self::Claude3_5Haiku => Some(BedrockModelCacheConfiguration {
+                min_total_token: 2_048,
+                should_speculate: true,
+                max_cache_anchors: 4,
+            }),
+            Self::AmazonNovaLite | Self::AmazonNovaMicro | Self::AmazonNovaPro => {
+                Some(BedrockModelCacheConfiguration {
+                    min_total_token: 1_000,
+                    should_speculate: true,
+                    max_cache_anchors: 4,
+                })
+            }
+            Self::Custom {
+                cache_configuration,
+                ..
+            } => cache_configuration.clone(),
+            _ => None,
+        }
+    }
+
     pub fn default_temperature(&self) -> f32 {
         match self {
             Self::Claude3_5Sonnet
@@ -405,6 +405,18 @@ impl Server {
             .add_request_handler(forward_mutating_project_request::<proto::GitCreateBranch>)
             .add_request_handler(forward_mutating_project_request::<proto::GitChangeBranch>)
             .add_request_handler(forward_mutating_project_request::<proto::CheckForPushedCommits>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitStashPush>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitStashList>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitStashShow>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitStashApply>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitStashDrop>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitLog>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitCommitFiles>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitRebaseCommits>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitRebase>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitRebaseContinue>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitRebaseAbort>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitGetRebaseStatus>)
             .add_message_handler(broadcast_project_message_from_host::<proto::AdvertiseContexts>)
             .add_message_handler(update_context)
             .add_request_handler({
@@ -2,6 +2,7 @@ use anyhow::{anyhow, Context, Result};
 use collections::HashMap;
 use futures::{channel::oneshot, select, FutureExt, StreamExt};
 use gpui::{AppContext as _, AsyncApp, BackgroundExecutor, Task};
+use http_client::HttpClient;
 use parking_lot::Mutex;
 use postage::barrier;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -16,9 +17,11 @@ use std::{
     },
     time::{Duration, Instant},
 };
+use url::Url;
 use util::TryFutureExt;
 
-use crate::transport::{StdioTransport, Transport};
+use crate::transport::{HttpTransport, StdioTransport, Transport};
+use crate::ServerEndpoint;
 
 const JSON_RPC_VERSION: &str = "2.0";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
@@ -32,6 +35,7 @@ pub const INTERNAL_ERROR: i32 = -32603;
 
 type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncApp)>;
+type RequestHandler = Box<dyn Send + FnMut(Value, AsyncApp) -> Task<Result<Value>>>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -46,6 +50,7 @@ pub struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
@@ -78,6 +83,27 @@ struct Request<'a, T> {
     params: T,
 }
 
+/// A request sent by the server to the client.
+#[derive(Debug, Clone, Deserialize)]
+struct AnyRequest<'a> {
+    #[allow(dead_code)]
+    jsonrpc: &'a str,
+    id: RequestId,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct OutgoingResponse {
+    jsonrpc: &'static str,
+    id: RequestId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct AnyResponse<'a> {
     jsonrpc: &'a str,
@@ -124,6 +150,8 @@ struct AnyNotification<'a> {
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Error {
+    #[serde(default)]
+    code: i32,
     message: String,
 }
 
@@ -158,22 +186,77 @@ impl Client {
             .unwrap_or_else(String::new);
 
         let transport = Arc::new(StdioTransport::new(binary, &cx)?);
+        Ok(Self::with_transport(
+            server_id,
+            server_name.into(),
+            transport,
+            cx,
+        ))
+    }
+
+    /// Creates a new Client instance for a context server that is already running and reachable
+    /// over HTTP.
+    pub fn new_http(
+        server_id: ContextServerId,
+        endpoint: ServerEndpoint,
+        http_client: Arc<dyn HttpClient>,
+        cx: AsyncApp,
+    ) -> Result<Self> {
+        log::info!("connecting to context server (url={:?})", endpoint.url);
+
+        let server_name = Url::parse(&endpoint.url)
+            .with_context(|| format!("invalid context server URL: {:?}", endpoint.url))?
+            .host_str()
+            .map(|host| host.to_string())
+            .unwrap_or_default();
+
+        let transport = Arc::new(HttpTransport::new(
+            http_client,
+            endpoint.url,
+            endpoint.headers,
+            endpoint.auth_token,
+            endpoint.transport,
+            cx.background_executor().clone(),
+        ));
+        Ok(Self::with_transport(
+            server_id,
+            server_name.into(),
+            transport,
+            cx,
+        ))
+    }
 
+    fn with_transport(
+        server_id: ContextServerId,
+        name: Arc<str>,
+        transport: Arc<dyn Transport>,
+        cx: AsyncApp,
+    ) -> Self {
         let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
         let (output_done_tx, output_done_rx) = barrier::channel();
 
         let notification_handlers =
             Arc::new(Mutex::new(HashMap::<_, NotificationHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
         let response_handlers =
             Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
 
         let stdout_input_task = cx.spawn({
             let notification_handlers = notification_handlers.clone();
+            let request_handlers = request_handlers.clone();
             let response_handlers = response_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
             let transport = transport.clone();
             move |cx| {
-                Self::handle_input(transport, notification_handlers, response_handlers, cx)
-                    .log_err()
+                Self::handle_input(
+                    transport,
+                    notification_handlers,
+                    request_handlers,
+                    response_handlers,
+                    outbound_tx,
+                    cx,
+                )
+                .log_err()
             }
         });
         let stderr_input_task = cx.spawn(|_| Self::handle_stderr(transport.clone()).log_err());
@@ -193,36 +276,42 @@ impl Client {
             .log_err()
         });
 
-        Ok(Self {
+        Self {
             server_id,
             notification_handlers,
+            request_handlers,
             response_handlers,
-            name: server_name.into(),
+            name,
             next_id: Default::default(),
             outbound_tx,
             executor: cx.background_executor().clone(),
             io_tasks: Mutex::new(Some((input_task, output_task))),
             output_done_rx: Mutex::new(Some(output_done_rx)),
             transport,
-        })
+        }
     }
 
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches
+    /// them to the appropriate handlers. Responses are matched to pending requests,
+    /// while requests and notifications from the server trigger registered handlers.
     async fn handle_input(
         transport: Arc<dyn Transport>,
         notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+        request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: AsyncApp,
     ) -> anyhow::Result<()> {
         let mut receiver = transport.receive();
 
         while let Some(message) = receiver.next().await {
-            if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
+            // Requests must be checked first, since they would also parse as responses.
+            if let Ok(request) = serde_json::from_str::<AnyRequest>(&message) {
+                Self::handle_request(request, &request_handlers, outbound_tx.clone(), &cx);
+            } else if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
                 if let Some(handlers) = response_handlers.lock().as_mut() {
                     if let Some(handler) = handlers.remove(&response.id) {
                         handler(Ok(message.to_string()));
@@ -241,6 +330,62 @@ impl Client {
         Ok(())
     }
 
+    /// Runs the handler registered for a request sent by the server and writes its
+    /// result back to the server once it completes.
+    fn handle_request(
+        request: AnyRequest<'_>,
+        request_handlers: &Mutex<HashMap<&'static str, RequestHandler>>,
+        outbound_tx: channel::Sender<String>,
+        cx: &AsyncApp,
+    ) {
+        let task = request_handlers
+            .lock()
+            .get_mut(request.method.as_str())
+            .map(|handler| handler(request.params.unwrap_or(Value::Null), cx.clone()));
+
+        let id = request.id;
+        let method = request.method;
+        cx.spawn(|_| {
+            async move {
+                let (result, error) = match task {
+                    Some(task) => match task.await {
+                        Ok(result) => (Some(result), None),
+                        Err(error) => {
+                            log::warn!(
+                                "failed to handle context server request {method:?}: {error:#}"
+                            );
+                            (
+                                None,
+                                Some(Error {
+                                    code: INTERNAL_ERROR,
+                                    message: format!("{error:#}"),
+                                }),
+                            )
+                        }
+                    },
+                    None => (
+                        None,
+                        Some(Error {
+                            code: METHOD_NOT_FOUND,
+                            message: format!("method not found: {method}"),
+                        }),
+                    ),
+                };
+
+                let response = serde_json::to_string(&OutgoingResponse {
+                    jsonrpc: JSON_RPC_VERSION,
+                    id,
+                    result,
+                    error,
+                })?;
+                outbound_tx.send(response).await?;
+                anyhow::Ok(())
+            }
+            .log_err()
+        })
+        .detach();
+    }
+
     /// Handles the stderr output from the context server.
     /// Continuously reads and logs any error messages from the server.
     async fn handle_stderr(transport: Arc<dyn Transport>) -> anyhow::Result<()> {
@@ -363,6 +508,16 @@ impl Client {
             .insert(method, Box::new(f));
     }
 
+    /// Registers a handler for requests with the given method sent by the server.
+    ///
+    /// The value the handler's task resolves to is sent back as the result of the request.
+    pub fn on_request<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(Value, AsyncApp) -> Task<Result<Value>>,
+    {
+        self.request_handlers.lock().insert(method, Box::new(f));
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
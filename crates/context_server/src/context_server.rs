@@ -4,11 +4,16 @@ mod extension_context_server;
 pub mod manager;
 pub mod protocol;
 mod registry;
+mod roots;
+mod sampling;
 mod transport;
 pub mod types;
+pub mod uri_template;
 
 use command_palette_hooks::CommandPaletteFilter;
-pub use context_server_settings::{ContextServerSettings, ServerCommand, ServerConfig};
+pub use context_server_settings::{
+    ContextServerSettings, SamplingSettings, ServerCommand, ServerConfig, ServerEndpoint,
+};
 use gpui::{actions, App};
 
 pub use crate::context_server_tool::ContextServerTool;
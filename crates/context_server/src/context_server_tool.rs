@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use anyhow::{anyhow, bail};
-use assistant_tool::Tool;
+use assistant_tool::{Tool, ToolSource};
 use gpui::{App, Entity, Task, Window};
 
 use crate::manager::ContextServerManager;
@@ -48,6 +48,12 @@ impl Tool for ContextServerTool {
         }
     }
 
+    fn source(&self) -> ToolSource {
+        ToolSource::ContextServer {
+            id: self.server_id.clone(),
+        }
+    }
+
     fn run(
         self: std::sync::Arc<Self>,
         input: serde_json::Value,
@@ -17,20 +17,23 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use collections::HashMap;
 use command_palette_hooks::CommandPaletteFilter;
-use gpui::{AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
+use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
 use log;
 use parking_lot::RwLock;
+use project::worktree_store::{WorktreeStore, WorktreeStoreEvent};
 use project::Project;
 use settings::{Settings, SettingsStore};
+use url::Url;
 use util::ResultExt as _;
 
 use crate::{ContextServerSettings, ServerConfig};
 
 use crate::{
     client::{self, Client},
+    protocol::ServerCapability,
     types, ContextServerFactoryRegistry, CONTEXT_SERVERS_NAMESPACE,
 };
 
@@ -61,20 +64,40 @@ impl ContextServer {
         self.client.read().clone()
     }
 
-    pub async fn start(self: Arc<Self>, cx: &AsyncApp) -> Result<()> {
+    pub async fn start(
+        self: Arc<Self>,
+        worktree_store: WeakEntity<WorktreeStore>,
+        cx: &AsyncApp,
+    ) -> Result<()> {
         log::info!("starting context server {}", self.id);
-        let Some(command) = &self.config.command else {
-            bail!("no command specified for server {}", self.id);
+        let server_id = client::ContextServerId(self.id.clone());
+        let client = if let Some(endpoint) = &self.config.endpoint {
+            let http_client = cx.update(|cx| cx.http_client())?;
+            Client::new_http(server_id, endpoint.clone(), http_client, cx.clone())?
+        } else if let Some(command) = &self.config.command {
+            Client::new(
+                server_id,
+                client::ModelContextServerBinary {
+                    executable: Path::new(&command.path).to_path_buf(),
+                    args: command.args.clone(),
+                    env: command.env.clone(),
+                },
+                cx.clone(),
+            )?
+        } else {
+            bail!("no command or endpoint specified for server {}", self.id);
         };
-        let client = Client::new(
-            client::ContextServerId(self.id.clone()),
-            client::ModelContextServerBinary {
-                executable: Path::new(&command.path).to_path_buf(),
-                args: command.args.clone(),
-                env: command.env.clone(),
-            },
-            cx.clone(),
-        )?;
+        client.on_request(
+            types::RequestType::CreateMessage.as_str(),
+            crate::sampling::create_message_handler(
+                self.id.clone(),
+                self.config.sampling.clone().unwrap_or_default(),
+            ),
+        );
+        client.on_request(
+            types::RequestType::ListRoots.as_str(),
+            crate::roots::list_roots_handler(worktree_store),
+        );
 
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
@@ -108,12 +131,17 @@ pub struct ContextServerManager {
     registry: Entity<ContextServerFactoryRegistry>,
     update_servers_task: Option<Task<Result<()>>>,
     needs_server_update: bool,
+    /// The number of subscribers to each resource, keyed by server ID and resource URI.
+    resource_subscriptions: HashMap<(Arc<str>, Url), usize>,
+    /// The roots servers were last told about.
+    roots: Vec<Url>,
     _subscriptions: Vec<Subscription>,
 }
 
 pub enum Event {
     ServerStarted { server_id: Arc<str> },
     ServerStopped { server_id: Arc<str> },
+    ResourceUpdated { server_id: Arc<str>, uri: Url },
 }
 
 impl EventEmitter<Event> for ContextServerManager {}
@@ -124,6 +152,8 @@ impl ContextServerManager {
         project: Entity<Project>,
         cx: &mut Context<Self>,
     ) -> Self {
+        let worktree_store = project.read(cx).worktree_store();
+        let roots = Self::root_uris(&worktree_store, cx);
         let mut this = Self {
             _subscriptions: vec![
                 cx.observe(&registry, |this, _registry, cx| {
@@ -132,12 +162,24 @@ impl ContextServerManager {
                 cx.observe_global::<SettingsStore>(|this, cx| {
                     this.available_context_servers_changed(cx);
                 }),
+                cx.subscribe(&worktree_store, |this, worktree_store, event, cx| {
+                    if matches!(
+                        event,
+                        WorktreeStoreEvent::WorktreeAdded(_)
+                            | WorktreeStoreEvent::WorktreeRemoved(..)
+                            | WorktreeStoreEvent::WorktreeOrderChanged
+                    ) {
+                        this.worktrees_changed(worktree_store, cx);
+                    }
+                }),
             ],
             project,
             registry,
             needs_server_update: false,
             servers: HashMap::default(),
             update_servers_task: None,
+            resource_subscriptions: HashMap::default(),
+            roots,
         };
         this.available_context_servers_changed(cx);
         this
@@ -191,8 +233,11 @@ impl ContextServerManager {
                 server.stop()?;
                 let config = server.config();
                 let new_server = Arc::new(ContextServer::new(id.clone(), config));
-                new_server.clone().start(&cx).await?;
+                let worktree_store =
+                    this.update(&mut cx, |this, cx| this.worktree_store(cx).downgrade())?;
+                new_server.clone().start(worktree_store, &cx).await?;
                 this.update(&mut cx, |this, cx| {
+                    this.server_started(&new_server, cx);
                     this.servers.insert(id.clone(), new_server);
                     cx.emit(Event::ServerStopped {
                         server_id: id.clone(),
@@ -206,6 +251,143 @@ impl ContextServerManager {
         })
     }
 
+    /// Subscribes to updates of the given resource, which are reported via
+    /// [`Event::ResourceUpdated`].
+    ///
+    /// Subscriptions are reference-counted, so every call must be balanced by a call to
+    /// [`Self::unsubscribe_from_resource`].
+    pub fn subscribe_to_resource(
+        &mut self,
+        server_id: &Arc<str>,
+        uri: Url,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let subscriber_count = self
+            .resource_subscriptions
+            .entry((server_id.clone(), uri.clone()))
+            .or_insert(0);
+        *subscriber_count += 1;
+        if *subscriber_count > 1 {
+            return Task::ready(Ok(()));
+        }
+
+        let Some(protocol) = self
+            .get_server(server_id)
+            .and_then(|server| server.client())
+        else {
+            return Task::ready(Err(anyhow!("context server {server_id} is not running")));
+        };
+        if !protocol.supports_resource_subscriptions() {
+            return Task::ready(Ok(()));
+        }
+
+        cx.spawn(|_, _| async move { protocol.subscribe_to_resource(uri).await })
+    }
+
+    pub fn unsubscribe_from_resource(
+        &mut self,
+        server_id: &Arc<str>,
+        uri: Url,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let key = (server_id.clone(), uri.clone());
+        match self.resource_subscriptions.get_mut(&key) {
+            Some(subscriber_count) if *subscriber_count > 1 => {
+                *subscriber_count -= 1;
+                return Task::ready(Ok(()));
+            }
+            Some(_) => {
+                self.resource_subscriptions.remove(&key);
+            }
+            None => return Task::ready(Ok(())),
+        }
+
+        let Some(protocol) = self
+            .get_server(server_id)
+            .and_then(|server| server.client())
+        else {
+            return Task::ready(Ok(()));
+        };
+        if !protocol.supports_resource_subscriptions() {
+            return Task::ready(Ok(()));
+        }
+
+        cx.spawn(|_, _| async move { protocol.unsubscribe_from_resource(uri).await })
+    }
+
+    /// Registers notification handlers on a newly started server and restores the resource
+    /// subscriptions that were made with its previous instance.
+    fn server_started(&mut self, server: &Arc<ContextServer>, cx: &mut Context<Self>) {
+        let Some(protocol) = server.client() else {
+            return;
+        };
+        if !protocol.capable(ServerCapability::Resources) {
+            return;
+        }
+
+        let this = cx.entity().downgrade();
+        let server_id = server.id();
+        protocol.on_notification(
+            types::NotificationType::ResourcesUpdated.as_str(),
+            move |params, mut cx| {
+                let Some(params) =
+                    serde_json::from_value::<types::ResourceUpdatedParams>(params).log_err()
+                else {
+                    return;
+                };
+                this.update(&mut cx, |_, cx| {
+                    cx.emit(Event::ResourceUpdated {
+                        server_id: server_id.clone(),
+                        uri: params.uri,
+                    })
+                })
+                .ok();
+            },
+        );
+
+        if protocol.supports_resource_subscriptions() {
+            for (server_id, uri) in self.resource_subscriptions.keys() {
+                if *server_id == server.id() {
+                    let protocol = protocol.clone();
+                    let uri = uri.clone();
+                    cx.spawn(|_, _| async move { protocol.subscribe_to_resource(uri).await })
+                        .detach_and_log_err(cx);
+                }
+            }
+        }
+    }
+
+    fn worktree_store(&self, cx: &App) -> Entity<WorktreeStore> {
+        self.project.read(cx).worktree_store()
+    }
+
+    fn root_uris(worktree_store: &Entity<WorktreeStore>, cx: &App) -> Vec<Url> {
+        crate::roots::visible_roots(worktree_store.read(cx), cx)
+            .into_iter()
+            .map(|root| root.uri)
+            .collect()
+    }
+
+    /// Notifies the running servers when the project's visible worktrees change.
+    fn worktrees_changed(&mut self, worktree_store: Entity<WorktreeStore>, cx: &mut Context<Self>) {
+        let roots = Self::root_uris(&worktree_store, cx);
+        if roots == self.roots {
+            return;
+        }
+        self.roots = roots;
+
+        for server in self.servers() {
+            if let Some(protocol) = server.client() {
+                protocol
+                    .notify(
+                        types::NotificationType::RootsListChanged.as_str(),
+                        serde_json::json!({}),
+                    )
+                    .log_err();
+            }
+        }
+    }
+
     pub fn servers(&self) -> Vec<Arc<ContextServer>> {
         self.servers
             .values()
@@ -217,7 +399,7 @@ impl ContextServerManager {
     async fn maintain_servers(this: WeakEntity<Self>, mut cx: AsyncApp) -> Result<()> {
         let mut desired_servers = HashMap::default();
 
-        let (registry, project) = this.update(&mut cx, |this, cx| {
+        let (registry, project, worktree_store) = this.update(&mut cx, |this, cx| {
             let location = this.project.read(cx).worktrees(cx).next().map(|worktree| {
                 settings::SettingsLocation {
                     worktree_id: worktree.read(cx).id(),
@@ -227,14 +409,18 @@ impl ContextServerManager {
             let settings = ContextServerSettings::get(location, cx);
             desired_servers = settings.context_servers.clone();
 
-            (this.registry.clone(), this.project.clone())
+            (
+                this.registry.clone(),
+                this.project.clone(),
+                this.worktree_store(cx).downgrade(),
+            )
         })?;
 
         for (id, factory) in
             registry.read_with(&cx, |registry, _| registry.context_server_factories())?
         {
             let config = desired_servers.entry(id).or_default();
-            if config.command.is_none() {
+            if config.command.is_none() && config.endpoint.is_none() {
                 if let Some(extension_command) = factory(project.clone(), &cx).await.log_err() {
                     config.command = Some(extension_command);
                 }
@@ -276,8 +462,15 @@ impl ContextServerManager {
         }
 
         for (id, server) in servers_to_start {
-            if server.start(&cx).await.log_err().is_some() {
-                this.update(&mut cx, |_, cx| {
+            if server
+                .clone()
+                .start(worktree_store.clone(), &cx)
+                .await
+                .log_err()
+                .is_some()
+            {
+                this.update(&mut cx, |this, cx| {
+                    this.server_started(&server, cx);
                     cx.emit(Event::ServerStarted { server_id: id })
                 })?;
             }
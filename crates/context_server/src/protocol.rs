@@ -7,6 +7,8 @@
 
 use anyhow::Result;
 use collections::HashMap;
+use gpui::AsyncApp;
+use url::Url;
 
 use crate::client::Client;
 use crate::types;
@@ -34,8 +36,11 @@ impl ModelContextProtocol {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
             capabilities: types::ClientCapabilities {
                 experimental: None,
-                sampling: None,
-                roots: None,
+                // Sampling requests are answered by `crate::sampling`.
+                sampling: Some(serde_json::json!({})),
+                roots: Some(types::RootsCapabilities {
+                    list_changed: Some(true),
+                }),
             },
             meta: None,
             client_info,
@@ -136,6 +141,76 @@ impl InitializedContextServerProtocol {
         Ok(response)
     }
 
+    /// List the MCP resource templates.
+    pub async fn list_resource_templates(&self) -> Result<types::ListResourceTemplatesResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let response: types::ListResourceTemplatesResponse = self
+            .inner
+            .request(
+                types::RequestType::ListResourceTemplates.as_str(),
+                serde_json::json!({}),
+            )
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Reads the contents of the resource with the given URI.
+    pub async fn read_resource(&self, uri: Url) -> Result<types::ResourcesReadResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesReadParams { uri, meta: None };
+        let response: types::ResourcesReadResponse = self
+            .inner
+            .request(types::RequestType::ResourcesRead.as_str(), params)
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Whether the server sends `notifications/resources/updated` for subscribed resources.
+    pub fn supports_resource_subscriptions(&self) -> bool {
+        self.initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
+
+    /// Asks the server to notify us when the resource with the given URI changes.
+    pub async fn subscribe_to_resource(&self, uri: Url) -> Result<()> {
+        if !self.supports_resource_subscriptions() {
+            return Err(anyhow::anyhow!(
+                "Server does not support resource subscriptions"
+            ));
+        }
+
+        let params = types::ResourcesSubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesSubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stops notifications about changes to the resource with the given URI.
+    pub async fn unsubscribe_from_resource(&self, uri: Url) -> Result<()> {
+        if !self.supports_resource_subscriptions() {
+            return Err(anyhow::anyhow!(
+                "Server does not support resource subscriptions"
+            ));
+        }
+
+        let params = types::ResourcesUnsubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesUnsubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
     /// Executes a prompt with the given arguments and returns the result.
     pub async fn run_prompt<P: AsRef<str>>(
         &self,
@@ -224,6 +299,19 @@ impl InitializedContextServerProtocol {
 }
 
 impl InitializedContextServerProtocol {
+    /// Registers a handler for notifications with the given method sent by the server.
+    pub fn on_notification<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(serde_json::Value, AsyncApp),
+    {
+        self.inner.on_notification(method, f);
+    }
+
+    /// Sends a notification to the server.
+    pub fn notify(&self, method: &str, params: impl serde::Serialize) -> Result<()> {
+        self.inner.notify(method, params)
+    }
+
     pub async fn request<R: serde::de::DeserializeOwned>(
         &self,
         method: &str,
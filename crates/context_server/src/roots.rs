@@ -0,0 +1,42 @@
+//! Exposes the project's visible worktrees to context servers as MCP roots, so that
+//! file-oriented servers can scope themselves to the open project.
+
+use anyhow::{Context as _, Result};
+use gpui::{App, AsyncApp, Task, WeakEntity};
+use project::worktree_store::WorktreeStore;
+use serde_json::Value;
+use url::Url;
+
+use crate::types;
+
+/// Returns the handler for the `roots/list` requests of a server.
+pub(crate) fn list_roots_handler(
+    worktree_store: WeakEntity<WorktreeStore>,
+) -> impl 'static + Send + FnMut(Value, AsyncApp) -> Task<Result<Value>> {
+    move |_params, cx| {
+        let roots = worktree_store
+            .read_with(&cx, |worktree_store, cx| visible_roots(worktree_store, cx))
+            .context("project was dropped");
+        Task::ready(roots.and_then(|roots| {
+            Ok(serde_json::to_value(types::ListRootsResponse {
+                roots,
+                meta: None,
+            })?)
+        }))
+    }
+}
+
+/// Returns a root for each visible worktree, in the order they appear in the project panel.
+pub(crate) fn visible_roots(worktree_store: &WorktreeStore, cx: &App) -> Vec<types::Root> {
+    worktree_store
+        .visible_worktrees(cx)
+        .filter_map(|worktree| {
+            let worktree = worktree.read(cx);
+            let uri = Url::from_file_path(worktree.abs_path()).ok()?;
+            Some(types::Root {
+                uri,
+                name: Some(worktree.root_name().to_string()),
+            })
+        })
+        .collect()
+}
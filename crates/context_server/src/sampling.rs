@@ -0,0 +1,262 @@
+//! Answers the `sampling/createMessage` requests context servers send to have the
+//! user's language model generate a completion.
+//!
+//! Every request is shown to the user for approval before it is sent to the model,
+//! and the token limits from the server's [`SamplingSettings`] are enforced.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use futures::StreamExt as _;
+use gpui::{App, AsyncApp, PromptLevel, Task};
+use language_model::{
+    LanguageModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
+    MessageContent, Role,
+};
+use serde_json::Value;
+
+use crate::types;
+use crate::SamplingSettings;
+
+/// A rough approximation of how many bytes make up a single token, used to enforce
+/// the token limits while the completion is streaming.
+const BYTES_PER_TOKEN_GUESS: usize = 4;
+
+/// The number of characters of the latest message shown in the approval prompt.
+const PREVIEW_LENGTH: usize = 500;
+
+/// Returns the handler for the `sampling/createMessage` requests of a single server.
+pub(crate) fn create_message_handler(
+    server_id: Arc<str>,
+    settings: SamplingSettings,
+) -> impl 'static + Send + FnMut(Value, AsyncApp) -> Task<Result<Value>> {
+    let used_tokens = Arc::new(AtomicU64::new(0));
+    move |params, cx| {
+        let server_id = server_id.clone();
+        let settings = settings.clone();
+        let used_tokens = used_tokens.clone();
+        cx.spawn(|cx| async move {
+            let request = serde_json::from_value::<types::CreateMessageRequest>(params)?;
+            let result = create_message(&server_id, &settings, &used_tokens, request, cx).await?;
+            Ok(serde_json::to_value(result)?)
+        })
+    }
+}
+
+async fn create_message(
+    server_id: &str,
+    settings: &SamplingSettings,
+    used_tokens: &AtomicU64,
+    request: types::CreateMessageRequest,
+    mut cx: AsyncApp,
+) -> Result<types::CreateMessageResult> {
+    let model = cx
+        .update(|cx| select_model(request.model_preferences.as_ref(), cx))?
+        .context("no language model is configured")?;
+
+    let max_tokens = settings
+        .max_tokens_per_request
+        .map_or(request.max_tokens, |limit| limit.min(request.max_tokens));
+    let completion_request = to_completion_request(&request)?;
+
+    let prompt_tokens = cx
+        .update(|cx| model.count_tokens(completion_request.clone(), cx))?
+        .await? as u64;
+    if let Some(max_total_tokens) = settings.max_total_tokens {
+        let used = used_tokens.load(Ordering::SeqCst);
+        if used + prompt_tokens + max_tokens as u64 > max_total_tokens {
+            bail!(
+                "context server {server_id} has used {used} of its {max_total_tokens} sampling tokens, \
+                which is not enough for this request"
+            );
+        }
+    }
+
+    let (message, detail) = approval_prompt(server_id, model.as_ref(), &request, max_tokens);
+    let window = cx
+        .update(|cx| cx.active_window())?
+        .context("no window to ask for approval in")?;
+    let answer = window.update(&mut cx, |_, window, cx| {
+        window.prompt(
+            PromptLevel::Info,
+            &message,
+            Some(&detail),
+            &["Allow", "Deny"],
+            cx,
+        )
+    })?;
+    if answer.await? != 0 {
+        bail!("the user denied the sampling request");
+    }
+
+    let mut stream = model
+        .stream_completion_text(completion_request, &cx)
+        .await?
+        .stream;
+    let max_bytes = max_tokens as usize * BYTES_PER_TOKEN_GUESS;
+    let mut text = String::new();
+    let mut stop_reason = "endTurn";
+    while let Some(chunk) = stream.next().await {
+        text.push_str(&chunk?);
+        if text.len() >= max_bytes {
+            stop_reason = "maxTokens";
+            break;
+        }
+    }
+
+    let completion_tokens = text.len().div_ceil(BYTES_PER_TOKEN_GUESS) as u64;
+    used_tokens.fetch_add(prompt_tokens + completion_tokens, Ordering::SeqCst);
+
+    Ok(types::CreateMessageResult {
+        role: types::Role::Assistant,
+        content: types::MessageContent::Text {
+            text,
+            annotations: None,
+        },
+        model: model.id().0.to_string(),
+        stop_reason: Some(stop_reason.to_string()),
+    })
+}
+
+/// Picks the model to run a sampling request with.
+///
+/// The server's model hints are tried in order of preference. Like the MCP specification
+/// suggests, a hint matches any model whose ID or name contains it. Models of the active
+/// provider are preferred over equally good matches from other providers. When no hint
+/// matches, the active model is used.
+fn select_model(
+    preferences: Option<&types::ModelPreferences>,
+    cx: &App,
+) -> Option<Arc<dyn LanguageModel>> {
+    let registry = LanguageModelRegistry::read_global(cx);
+    let active_provider = registry.active_provider().map(|provider| provider.id());
+    let hints = preferences
+        .and_then(|preferences| preferences.hints.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|hint| hint.name.as_deref());
+
+    for hint in hints {
+        let mut matches = registry
+            .available_models(cx)
+            .filter(|model| {
+                model_matches_hint(&model.id().0, &model.name().0, hint)
+                    && registry
+                        .provider(&model.provider_id())
+                        .map_or(false, |provider| provider.is_authenticated(cx))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|model| Some(model.provider_id()) != active_provider);
+        if let Some(model) = matches.into_iter().next() {
+            return Some(model);
+        }
+    }
+
+    registry.active_model()
+}
+
+fn model_matches_hint(model_id: &str, model_name: &str, hint: &str) -> bool {
+    let hint = hint.to_lowercase();
+    !hint.is_empty()
+        && (model_id.to_lowercase().contains(&hint) || model_name.to_lowercase().contains(&hint))
+}
+
+fn to_completion_request(request: &types::CreateMessageRequest) -> Result<LanguageModelRequest> {
+    let mut messages = Vec::with_capacity(request.messages.len() + 1);
+    if let Some(system_prompt) = &request.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![MessageContent::Text(system_prompt.clone())],
+            cache: false,
+        });
+    }
+
+    for message in &request.messages {
+        let role = match message.role {
+            types::Role::User => Role::User,
+            types::Role::Assistant => Role::Assistant,
+        };
+        let text = match &message.content {
+            types::MessageContent::Text { text, .. } => text.clone(),
+            types::MessageContent::Image { .. } => {
+                return Err(anyhow!("images are not supported in sampling requests"));
+            }
+            types::MessageContent::Resource { resource, .. } => {
+                return Err(anyhow!(
+                    "embedded resources are not supported in sampling requests: {}",
+                    resource.uri
+                ));
+            }
+        };
+        messages.push(LanguageModelRequestMessage {
+            role,
+            content: vec![MessageContent::Text(text)],
+            cache: false,
+        });
+    }
+
+    Ok(LanguageModelRequest {
+        messages,
+        tools: Vec::new(),
+        stop: request.stop_sequences.clone().unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+        response_format: None,
+    })
+}
+
+fn approval_prompt(
+    server_id: &str,
+    model: &dyn LanguageModel,
+    request: &types::CreateMessageRequest,
+    max_tokens: u32,
+) -> (String, String) {
+    let message = format!(
+        "Allow {server_id} to use {} from {}?",
+        model.name().0,
+        model.provider_name().0
+    );
+
+    let mut detail = format!(
+        "The context server wants to generate up to {max_tokens} tokens from a conversation of {} message(s).",
+        request.messages.len()
+    );
+    let last_text = request
+        .messages
+        .iter()
+        .rev()
+        .find_map(|message| match &message.content {
+            types::MessageContent::Text { text, .. } => Some(text.as_str()),
+            _ => None,
+        });
+    if let Some(last_text) = last_text {
+        detail.push_str("\n\nLatest message:\n");
+        detail.extend(last_text.chars().take(PREVIEW_LENGTH));
+        if last_text.chars().count() > PREVIEW_LENGTH {
+            detail.push('…');
+        }
+    }
+
+    (message, detail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_matches_hint() {
+        assert!(model_matches_hint(
+            "claude-3-5-sonnet-latest",
+            "Claude 3.5 Sonnet",
+            "sonnet"
+        ));
+        assert!(model_matches_hint(
+            "claude-3-5-sonnet-latest",
+            "Claude 3.5 Sonnet",
+            "Claude 3.5"
+        ));
+        assert!(!model_matches_hint("gpt-4o", "GPT 4o", "claude"));
+        assert!(!model_matches_hint("gpt-4o", "GPT 4o", ""));
+    }
+}
@@ -0,0 +1,635 @@
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use context_server_settings::HttpTransportKind;
+use futures::channel::oneshot;
+use futures::io::BufReader;
+use futures::{AsyncBufReadExt as _, AsyncRead, AsyncReadExt as _, FutureExt as _, Stream};
+use gpui::{BackgroundExecutor, Task};
+use http_client::{http, AsyncBody, HttpClient, Method, Request, Response, StatusCode};
+use parking_lot::Mutex;
+use smol::channel;
+use url::Url;
+use util::{ResultExt as _, TryFutureExt as _};
+
+use crate::transport::Transport;
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+
+/// A transport that talks to an already-running context server over HTTP, using the
+/// MCP Streamable HTTP transport.
+///
+/// Every outgoing message is posted to the server's endpoint. The server replies either with a
+/// single JSON message or with a stream of Server-Sent Events, whose messages are forwarded to
+/// [`Transport::receive`]. Once a session has been established, a long-lived `GET` request is
+/// used to receive messages the server sends on its own initiative.
+///
+/// Servers that only support the legacy HTTP+SSE transport are also supported. There, a `GET`
+/// request to the server's URL opens an event stream, whose first event announces the endpoint
+/// that messages are posted to, and all of the server's messages arrive on that stream.
+pub struct HttpTransport {
+    http_client: Arc<dyn HttpClient>,
+    url: String,
+    headers: HashMap<String, String>,
+    kind: HttpTransportKind,
+    protocol: Mutex<Protocol>,
+    session_id: Mutex<Option<String>>,
+    server_messages_task: Mutex<Option<Task<()>>>,
+    executor: BackgroundExecutor,
+    inbound_sender: channel::Sender<String>,
+    inbound_receiver: channel::Receiver<String>,
+    error_sender: channel::Sender<String>,
+    error_receiver: channel::Receiver<String>,
+}
+
+/// The transport the server was found to support.
+enum Protocol {
+    Unknown,
+    StreamableHttp,
+    /// The legacy HTTP+SSE transport, posting messages to the given endpoint.
+    Sse {
+        endpoint: String,
+    },
+}
+
+impl HttpTransport {
+    pub fn new(
+        http_client: Arc<dyn HttpClient>,
+        url: String,
+        mut headers: HashMap<String, String>,
+        auth_token: Option<String>,
+        kind: HttpTransportKind,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        if let Some(auth_token) = auth_token {
+            headers.insert("Authorization".into(), format!("Bearer {auth_token}"));
+        }
+
+        let (inbound_sender, inbound_receiver) = channel::unbounded::<String>();
+        let (error_sender, error_receiver) = channel::unbounded::<String>();
+
+        Self {
+            http_client,
+            url,
+            headers,
+            kind,
+            protocol: Mutex::new(Protocol::Unknown),
+            session_id: Mutex::new(None),
+            server_messages_task: Mutex::new(None),
+            executor,
+            inbound_sender,
+            inbound_receiver,
+            error_sender,
+            error_receiver,
+        }
+    }
+
+    fn build_request(
+        &self,
+        method: Method,
+        url: &str,
+        body: AsyncBody,
+    ) -> Result<Request<AsyncBody>> {
+        let mut builder = http::Request::builder()
+            .method(method)
+            .uri(url)
+            .header("Accept", "application/json, text/event-stream");
+        if let Some(session_id) = self.session_id.lock().as_ref() {
+            builder = builder.header(SESSION_ID_HEADER, session_id);
+        }
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        Ok(builder.body(body)?)
+    }
+
+    fn update_session_id(&self, response: &Response<AsyncBody>) {
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            *self.session_id.lock() = Some(session_id.to_string());
+        }
+    }
+
+    async fn post(&self, message: String) -> Result<()> {
+        let sse_endpoint = match &*self.protocol.lock() {
+            Protocol::Sse { endpoint } => Some(endpoint.clone()),
+            _ => None,
+        };
+        let endpoint = match sse_endpoint {
+            Some(endpoint) => endpoint,
+            None if self.kind == HttpTransportKind::Sse => self.connect_sse().await?,
+            None => return self.post_streamable(message).await,
+        };
+        self.post_to_sse_endpoint(&endpoint, message).await
+    }
+
+    fn build_post_request(&self, url: &str, message: String) -> Result<Request<AsyncBody>> {
+        let mut request = self.build_request(Method::POST, url, AsyncBody::from(message))?;
+        request
+            .headers_mut()
+            .insert("Content-Type", "application/json".parse()?);
+        Ok(request)
+    }
+
+    async fn post_streamable(&self, message: String) -> Result<()> {
+        let request = self.build_post_request(&self.url, message.clone())?;
+        let mut response = self.http_client.send(request).await?;
+        self.update_session_id(&response);
+
+        let status = response.status();
+        // Servers that predate the Streamable HTTP transport reject the first message, in which
+        // case the legacy HTTP+SSE transport is used instead.
+        if status.is_client_error()
+            && self.kind == HttpTransportKind::Auto
+            && matches!(*self.protocol.lock(), Protocol::Unknown)
+        {
+            log::info!(
+                "context server at {} responded with {status}, falling back to the HTTP+SSE transport",
+                self.url
+            );
+            let endpoint = self.connect_sse().await?;
+            return self.post_to_sse_endpoint(&endpoint, message).await;
+        }
+        if !status.is_success() {
+            let mut body = String::new();
+            response.body_mut().read_to_string(&mut body).await.ok();
+            return Err(anyhow!(
+                "context server responded with {status}: {}",
+                body.trim()
+            ));
+        }
+
+        *self.protocol.lock() = Protocol::StreamableHttp;
+        self.listen_for_server_messages()?;
+
+        if status == StatusCode::ACCEPTED {
+            return Ok(());
+        }
+
+        if is_event_stream(&response) {
+            // Don't block subsequent messages while the server streams its response.
+            let inbound_sender = self.inbound_sender.clone();
+            let error_sender = self.error_sender.clone();
+            self.executor
+                .spawn(async move {
+                    if let Err(error) =
+                        forward_events(response.into_body(), &inbound_sender, None).await
+                    {
+                        error_sender.send(error.to_string()).await.ok();
+                    }
+                })
+                .detach();
+        } else {
+            let mut body = String::new();
+            response.body_mut().read_to_string(&mut body).await?;
+            for message in split_json_messages(&body)? {
+                self.inbound_sender.send(message).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Opens a long-lived stream for messages the server sends without being asked, once a
+    /// session has been established.
+    fn listen_for_server_messages(&self) -> Result<()> {
+        let mut server_messages_task = self.server_messages_task.lock();
+        if server_messages_task.is_some() || self.session_id.lock().is_none() {
+            return Ok(());
+        }
+
+        let request = self.build_request(Method::GET, &self.url, AsyncBody::empty())?;
+        let http_client = self.http_client.clone();
+        let inbound_sender = self.inbound_sender.clone();
+        *server_messages_task = Some(
+            self.executor.spawn(
+                async move {
+                    let response = http_client.send(request).await?;
+                    // Servers are not required to support server-initiated messages.
+                    if response.status() == StatusCode::METHOD_NOT_ALLOWED {
+                        return Ok(());
+                    }
+                    if !response.status().is_success() || !is_event_stream(&response) {
+                        return Err(anyhow!(
+                            "failed to listen for context server messages: {}",
+                            response.status()
+                        ));
+                    }
+                    forward_events(response.into_body(), &inbound_sender, None).await
+                }
+                .log_err()
+                .map(|_| ()),
+            ),
+        );
+        Ok(())
+    }
+
+    /// Opens the event stream of the legacy HTTP+SSE transport, returning the endpoint that the
+    /// server announces for posting messages.
+    async fn connect_sse(&self) -> Result<String> {
+        let request = self.build_request(Method::GET, &self.url, AsyncBody::empty())?;
+        let response = self.http_client.send(request).await?;
+        if !response.status().is_success() || !is_event_stream(&response) {
+            return Err(anyhow!(
+                "failed to open the context server's event stream: {}",
+                response.status()
+            ));
+        }
+
+        let (endpoint_tx, endpoint_rx) = oneshot::channel();
+        let inbound_sender = self.inbound_sender.clone();
+        let error_sender = self.error_sender.clone();
+        // All of the server's messages arrive on this stream, so it closing is an error.
+        *self.server_messages_task.lock() =
+            Some(self.executor.spawn(async move {
+                let error =
+                    match forward_events(response.into_body(), &inbound_sender, Some(endpoint_tx))
+                        .await
+                    {
+                        Ok(()) => "the context server closed its event stream".to_string(),
+                        Err(error) => error.to_string(),
+                    };
+                error_sender.send(error).await.ok();
+            }));
+
+        let endpoint = endpoint_rx
+            .await
+            .map_err(|_| anyhow!("the context server's event stream ended without an endpoint"))?;
+        // The endpoint is usually relative to the URL of the event stream.
+        let endpoint = Url::parse(&self.url)?.join(&endpoint)?.to_string();
+        *self.protocol.lock() = Protocol::Sse {
+            endpoint: endpoint.clone(),
+        };
+        Ok(endpoint)
+    }
+
+    async fn post_to_sse_endpoint(&self, endpoint: &str, message: String) -> Result<()> {
+        let request = self.build_post_request(endpoint, message)?;
+        let mut response = self.http_client.send(request).await?;
+        // The server responds on the event stream rather than to the request itself.
+        let status = response.status();
+        if !status.is_success() {
+            let mut body = String::new();
+            response.body_mut().read_to_string(&mut body).await.ok();
+            return Err(anyhow!(
+                "context server responded with {status}: {}",
+                body.trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        self.post(message)
+            .await
+            .with_context(|| format!("failed to send message to {}", self.url))
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.inbound_receiver.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.error_receiver.clone())
+    }
+}
+
+fn is_event_stream(response: &Response<AsyncBody>) -> bool {
+    response
+        .headers()
+        .get("Content-Type")
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |content_type| {
+            content_type.starts_with("text/event-stream")
+        })
+}
+
+/// Splits a JSON response body, which may contain a single message or a batch of messages, into
+/// individual messages.
+fn split_json_messages(body: &str) -> Result<Vec<String>> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if body.starts_with('[') {
+        let messages: Vec<serde_json::Value> = serde_json::from_str(body)?;
+        Ok(messages
+            .into_iter()
+            .map(|message| message.to_string())
+            .collect())
+    } else {
+        Ok(vec![body.to_string()])
+    }
+}
+
+/// Reads Server-Sent Events from `body` and forwards the data of every message event.
+///
+/// When `endpoint_tx` is given, the first `endpoint` event of the legacy HTTP+SSE transport is
+/// reported through it.
+async fn forward_events(
+    body: impl AsyncRead + Unpin,
+    sender: &channel::Sender<String>,
+    mut endpoint_tx: Option<oneshot::Sender<String>>,
+) -> Result<()> {
+    let mut reader = BufReader::new(body);
+    let mut parser = SseParser::default();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let bytes_read = reader.read_line(&mut line).await?;
+        let event = if bytes_read == 0 {
+            parser.finish()
+        } else {
+            parser.push_line(&line)
+        };
+
+        match event {
+            Some(event) if event.is_endpoint() => {
+                if let Some(endpoint_tx) = endpoint_tx.take() {
+                    endpoint_tx.send(event.data).ok();
+                }
+            }
+            Some(event) if event.is_message() => {
+                let messages = split_json_messages(&event.data).log_err();
+                for message in messages.into_iter().flatten() {
+                    sender.send(message).await?;
+                }
+            }
+            _ => {}
+        }
+
+        if bytes_read == 0 {
+            return Ok(());
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SseEvent {
+    event: Option<String>,
+    data: String,
+}
+
+impl SseEvent {
+    /// Returns whether this event carries a JSON-RPC message, which is the default event type.
+    fn is_message(&self) -> bool {
+        self.event
+            .as_deref()
+            .map_or(true, |event| event == "message")
+    }
+
+    /// Returns whether this event announces the endpoint of the legacy HTTP+SSE transport.
+    fn is_endpoint(&self) -> bool {
+        self.event.as_deref() == Some("endpoint")
+    }
+}
+
+/// An incremental parser for the `text/event-stream` format.
+#[derive(Default)]
+struct SseParser {
+    event: Option<String>,
+    data: Option<String>,
+}
+
+impl SseParser {
+    /// Processes a single line, returning an event when the line completes one.
+    fn push_line(&mut self, line: &str) -> Option<SseEvent> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            return self.finish();
+        }
+
+        // Lines starting with a colon are comments, often used as keep-alives.
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => {
+                let data = self.data.get_or_insert_with(String::new);
+                if !data.is_empty() {
+                    data.push('\n');
+                }
+                data.push_str(value);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Dispatches the pending event, if any.
+    fn finish(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        let data = self.data.take()?;
+        Some(SseEvent { event, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+    use http_client::FakeHttpClient;
+
+    #[test]
+    fn test_sse_parser() {
+        let mut parser = SseParser::default();
+        assert_eq!(parser.push_line(": keep-alive\n"), None);
+        assert_eq!(parser.push_line("\n"), None);
+        assert_eq!(parser.push_line("event: message\n"), None);
+        assert_eq!(parser.push_line("data: {\"a\":\n"), None);
+        assert_eq!(parser.push_line("data:1}\r\n"), None);
+        assert_eq!(
+            parser.push_line("\r\n"),
+            Some(SseEvent {
+                event: Some("message".into()),
+                data: "{\"a\":\n1}".into(),
+            })
+        );
+        assert_eq!(parser.push_line("data: trailing"), None);
+        assert_eq!(
+            parser.finish(),
+            Some(SseEvent {
+                event: None,
+                data: "trailing".into(),
+            })
+        );
+    }
+
+    #[gpui::test]
+    async fn test_http_transport(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let session_id = request
+                        .headers()
+                        .get(SESSION_ID_HEADER)
+                        .map(|value| value.to_str().unwrap().to_string());
+                    let authorization = request
+                        .headers()
+                        .get("Authorization")
+                        .map(|value| value.to_str().unwrap().to_string());
+                    requests.lock().push((
+                        request.method().clone(),
+                        session_id.clone(),
+                        authorization,
+                    ));
+
+                    let response = match (request.method().clone(), session_id) {
+                        (Method::POST, None) => Response::builder()
+                            .status(200)
+                            .header("Content-Type", "text/event-stream")
+                            .header(SESSION_ID_HEADER, "session-1")
+                            .body(AsyncBody::from(
+                                "event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{}}\n\n",
+                            ))?,
+                        (Method::POST, Some(_)) => Response::builder()
+                            .status(200)
+                            .header("Content-Type", "application/json")
+                            .body(AsyncBody::from(
+                                "[{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}},{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{}}]",
+                            ))?,
+                        (Method::GET, Some(_)) => Response::builder()
+                            .status(405)
+                            .body(AsyncBody::empty())?,
+                        _ => Response::builder().status(400).body(AsyncBody::empty())?,
+                    };
+                    Ok(response)
+                }
+            }
+        });
+
+        let transport = HttpTransport::new(
+            http_client,
+            "http://localhost:8080/mcp".into(),
+            HashMap::default(),
+            Some("secret".into()),
+            HttpTransportKind::Auto,
+            cx.executor(),
+        );
+        let mut messages = transport.receive();
+
+        transport
+            .send("{\"jsonrpc\":\"2.0\",\"id\":0,\"method\":\"initialize\"}".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            messages.next().await.unwrap(),
+            "{\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{}}"
+        );
+
+        transport
+            .send("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            messages.next().await.unwrap(),
+            "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}"
+        );
+        assert_eq!(
+            messages.next().await.unwrap(),
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{}}"
+        );
+
+        cx.run_until_parked();
+        let bearer = Some("Bearer secret".to_string());
+        let session_id = Some("session-1".to_string());
+        let requests = requests.lock();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0], (Method::POST, None, bearer.clone()));
+        // Once the session is established, the transport also listens for server messages.
+        assert!(requests.contains(&(Method::GET, session_id.clone(), bearer.clone())));
+        assert!(requests.contains(&(Method::POST, session_id, bearer)));
+    }
+
+    #[gpui::test]
+    async fn test_http_transport_falls_back_to_sse(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    requests
+                        .lock()
+                        .push((request.method().clone(), uri.clone()));
+
+                    let response = match (request.method().clone(), uri.as_str()) {
+                        (Method::POST, "http://localhost:8080/sse") => {
+                            Response::builder().status(405).body(AsyncBody::empty())?
+                        }
+                        (Method::GET, "http://localhost:8080/sse") => Response::builder()
+                            .status(200)
+                            .header("Content-Type", "text/event-stream")
+                            .body(AsyncBody::from(
+                                "event: endpoint\ndata: /messages?session_id=1\n\nevent: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{}}\n\n",
+                            ))?,
+                        (Method::POST, "http://localhost:8080/messages?session_id=1") => {
+                            Response::builder().status(202).body(AsyncBody::empty())?
+                        }
+                        _ => Response::builder().status(400).body(AsyncBody::empty())?,
+                    };
+                    Ok(response)
+                }
+            }
+        });
+
+        let transport = HttpTransport::new(
+            http_client,
+            "http://localhost:8080/sse".into(),
+            HashMap::default(),
+            None,
+            HttpTransportKind::Auto,
+            cx.executor(),
+        );
+        let mut messages = transport.receive();
+        let mut errors = transport.receive_err();
+
+        transport
+            .send("{\"jsonrpc\":\"2.0\",\"id\":0,\"method\":\"initialize\"}".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            messages.next().await.unwrap(),
+            "{\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{}}"
+        );
+        // Once the endpoint is known, messages are posted to it directly.
+        transport
+            .send("{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            errors.next().await.unwrap(),
+            "the context server closed its event stream"
+        );
+
+        let messages_url = "http://localhost:8080/messages?session_id=1".to_string();
+        assert_eq!(
+            *requests.lock(),
+            vec![
+                (Method::POST, "http://localhost:8080/sse".to_string()),
+                (Method::GET, "http://localhost:8080/sse".to_string()),
+                (Method::POST, messages_url.clone()),
+                (Method::POST, messages_url),
+            ]
+        );
+    }
+}
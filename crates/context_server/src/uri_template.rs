@@ -0,0 +1,193 @@
+//! Expansion of the URI templates (RFC 6570) used by MCP resource templates.
+//!
+//! Only string values are supported, which covers the templates servers advertise in practice.
+//! Variables without a value are omitted from the expansion, as the RFC prescribes.
+
+use std::fmt::Write as _;
+
+use collections::HashMap;
+
+/// Returns the names of the variables in the template, in the order they first appear.
+pub fn uri_template_variables(template: &str) -> Vec<String> {
+    let mut variables = Vec::new();
+    for expression in expressions(template) {
+        let (_, variable_list) = split_operator(expression);
+        for variable in variable_list.split(',') {
+            let (name, _) = parse_variable(variable);
+            if !name.is_empty() && !variables.iter().any(|existing| existing == name) {
+                variables.push(name.to_string());
+            }
+        }
+    }
+    variables
+}
+
+/// Expands the template, substituting the given values for its variables.
+pub fn expand_uri_template(template: &str, values: &HashMap<String, String>) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        expand_expression(&rest[start + 1..end], values, &mut expanded);
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+fn expressions(template: &str) -> impl Iterator<Item = &str> {
+    template.split('{').skip(1).filter_map(|part| {
+        let end = part.find('}')?;
+        Some(&part[..end])
+    })
+}
+
+struct Operator {
+    first: &'static str,
+    separator: &'static str,
+    named: bool,
+    if_empty: &'static str,
+    allow_reserved: bool,
+}
+
+fn split_operator(expression: &str) -> (Operator, &str) {
+    let operator = |first, separator, named, if_empty, allow_reserved| Operator {
+        first,
+        separator,
+        named,
+        if_empty,
+        allow_reserved,
+    };
+    let mut chars = expression.chars();
+    let parsed = match chars.next() {
+        Some('+') => operator("", ",", false, "", true),
+        Some('#') => operator("#", ",", false, "", true),
+        Some('.') => operator(".", ".", false, "", false),
+        Some('/') => operator("/", "/", false, "", false),
+        Some(';') => operator(";", ";", true, "", false),
+        Some('?') => operator("?", "&", true, "=", false),
+        Some('&') => operator("&", "&", true, "=", false),
+        _ => return (operator("", ",", false, "", false), expression),
+    };
+    (parsed, chars.as_str())
+}
+
+/// Splits a variable specification into its name and optional prefix length, ignoring the
+/// explode modifier since only string values are supported.
+fn parse_variable(variable: &str) -> (&str, Option<usize>) {
+    let variable = variable.trim().trim_end_matches('*');
+    match variable.split_once(':') {
+        Some((name, max_length)) => (name, max_length.parse().ok()),
+        None => (variable, None),
+    }
+}
+
+fn expand_expression(expression: &str, values: &HashMap<String, String>, output: &mut String) {
+    let (operator, variable_list) = split_operator(expression);
+    let mut first = true;
+    for variable in variable_list.split(',') {
+        let (name, max_length) = parse_variable(variable);
+        let Some(value) = values.get(name) else {
+            continue;
+        };
+
+        output.push_str(if first {
+            operator.first
+        } else {
+            operator.separator
+        });
+        first = false;
+
+        if operator.named {
+            output.push_str(name);
+            if value.is_empty() {
+                output.push_str(operator.if_empty);
+                continue;
+            }
+            output.push('=');
+        }
+
+        let value = match max_length {
+            Some(max_length) => value.chars().take(max_length).collect::<String>(),
+            None => value.clone(),
+        };
+        percent_encode(&value, operator.allow_reserved, output);
+    }
+}
+
+fn percent_encode(value: &str, allow_reserved: bool, output: &mut String) {
+    let bytes = value.as_bytes();
+    for (ix, &byte) in bytes.iter().enumerate() {
+        let is_unreserved = byte.is_ascii_alphanumeric() || b"-._~".contains(&byte);
+        let is_reserved = b":/?#[]@!$&'()*+,;=".contains(&byte);
+        // Reserved expansion leaves existing percent-encoded triplets alone.
+        let is_pct_encoded = byte == b'%'
+            && bytes
+                .get(ix + 1..ix + 3)
+                .map_or(false, |hex| hex.iter().all(u8::is_ascii_hexdigit));
+
+        if is_unreserved || (allow_reserved && (is_reserved || is_pct_encoded)) {
+            output.push(byte as char);
+        } else {
+            write!(output, "%{byte:02X}").ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_uri_template_variables() {
+        assert_eq!(
+            uri_template_variables("repo://{owner}/{repo}/blob{/path*}{?ref,owner}"),
+            vec!["owner", "repo", "path", "ref"]
+        );
+        assert_eq!(
+            uri_template_variables("file:///README.md"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_expand_uri_template() {
+        let values = values(&[
+            ("owner", "zed-industries"),
+            ("path", "src/main.rs"),
+            ("query", "hello world"),
+            ("empty", ""),
+        ]);
+
+        assert_eq!(
+            expand_uri_template("repo://{owner}/{path}", &values),
+            "repo://zed-industries/src%2Fmain.rs"
+        );
+        assert_eq!(
+            expand_uri_template("repo://{owner}/{+path}", &values),
+            "repo://zed-industries/src/main.rs"
+        );
+        assert_eq!(
+            expand_uri_template("search://{owner}{?query,missing,empty}", &values),
+            "search://zed-industries?query=hello%20world&empty="
+        );
+        assert_eq!(
+            expand_uri_template("repo://x{/owner,path}{#path}", &values),
+            "repo://x/zed-industries/src%2Fmain.rs#src/main.rs"
+        );
+        assert_eq!(
+            expand_uri_template("repo://{owner:3}{.missing}", &values),
+            "repo://zed"
+        );
+    }
+}
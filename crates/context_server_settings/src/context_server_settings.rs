@@ -18,6 +18,15 @@ pub struct ServerConfig {
     ///
     /// This will override the command set by an extension.
     pub command: Option<ServerCommand>,
+    /// The HTTP endpoint of an already-running context server.
+    ///
+    /// When set, Zed connects to this server instead of launching `command`.
+    pub endpoint: Option<ServerEndpoint>,
+    /// Limits on the language model usage of this context server.
+    ///
+    /// Context servers can ask Zed to run a completion with the active
+    /// language model. Each request must be approved by the user.
+    pub sampling: Option<SamplingSettings>,
     /// The settings for this context server.
     ///
     /// Consult the documentation for the context server to see what settings
@@ -40,6 +49,51 @@ pub struct ServerCommand {
     pub env: Option<HashMap<String, String>>,
 }
 
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ServerEndpoint {
+    /// The URL of the server. JSON-RPC messages are posted to it when using
+    /// the Streamable HTTP transport, while the HTTP+SSE transport opens its
+    /// event stream there.
+    pub url: String,
+    /// Additional headers to send with every request to the server.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// A token sent to the server as an `Authorization: Bearer` header.
+    pub auth_token: Option<String>,
+    /// The MCP transport the server uses.
+    ///
+    /// Default: auto
+    #[serde(default)]
+    pub transport: HttpTransportKind,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpTransportKind {
+    /// Use the Streamable HTTP transport, falling back to the HTTP+SSE
+    /// transport when the server rejects the first message.
+    #[default]
+    Auto,
+    /// Use the Streamable HTTP transport.
+    StreamableHttp,
+    /// Use the legacy HTTP+SSE transport, which receives messages over an
+    /// event stream and posts to the endpoint the server announces on it.
+    Sse,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct SamplingSettings {
+    /// The maximum number of tokens a single completion may generate.
+    ///
+    /// Requests asking for more tokens are capped to this value.
+    pub max_tokens_per_request: Option<u32>,
+    /// The maximum number of tokens, prompt and completion combined, that the
+    /// context server may use until it is restarted.
+    ///
+    /// Requests that would exceed this limit are rejected.
+    pub max_total_tokens: Option<u64>,
+}
+
 #[derive(Deserialize, Serialize, Default, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct ContextServerSettings {
     /// Settings for context servers used in the Assistant.
@@ -79,6 +79,7 @@ use code_context_menus::{
     CompletionsMenu, ContextMenuOrigin,
 };
 use git::blame::GitBlame;
+use git::conflicts::ConflictState;
 use gpui::{
     div, impl_actions, point, prelude::*, pulsating_between, px, relative, size, Action, Animation,
     AnimationExt, AnyElement, App, AsyncWindowContext, AvailableSpace, Background, Bounds,
@@ -669,6 +670,7 @@ pub struct Editor {
     gutter_highlights: TreeMap<TypeId, GutterHighlight>,
     scrollbar_marker_state: ScrollbarMarkerState,
     active_indent_guides_state: ActiveIndentGuidesState,
+    conflict_state: ConflictState,
     nav_history: Option<ItemNavHistory>,
     context_menu: RefCell<Option<CodeContextMenu>>,
     mouse_context_menu: Option<MouseContextMenu>,
@@ -1296,6 +1298,17 @@ impl Editor {
                         },
                     ));
                 }
+                // Conflicts are only looked for in the files git reports as conflicted.
+                let git_store = project.read(cx).git_store().clone();
+                project_subscriptions.push(cx.subscribe_in(
+                    &git_store,
+                    window,
+                    |editor, _, event, window, cx| {
+                        if let project::git::GitEvent::GitStateUpdated = event {
+                            editor.refresh_conflicts(window, cx);
+                        }
+                    },
+                ));
             }
         }
 
@@ -1380,6 +1393,7 @@ impl Editor {
             gutter_highlights: TreeMap::default(),
             scrollbar_marker_state: ScrollbarMarkerState::default(),
             active_indent_guides_state: ActiveIndentGuidesState::default(),
+            conflict_state: ConflictState::default(),
             nav_history: None,
             context_menu: RefCell::new(None),
             mouse_context_menu: None,
@@ -1503,6 +1517,8 @@ impl Editor {
                 this.start_git_blame_inline(false, window, cx);
             }
 
+            this.refresh_conflicts(window, cx);
+
             if let Some(buffer) = buffer.read(cx).as_singleton() {
                 if let Some(project) = this.project.as_ref() {
                     let handle = project.update(cx, |project, cx| {
@@ -14523,28 +14539,35 @@ impl Editor {
         snapshot.line_len(buffer_row) == 0
     }
 
-    fn get_permalink_to_line(&self, cx: &mut Context<Self>) -> Task<Result<url::Url>> {
-        let buffer_and_selection = maybe!({
-            let selection = self.selections.newest::<Point>(cx);
-            let selection_range = selection.range();
+    /// Returns the buffer under the newest selection, along with the (zero-based) rows the
+    /// selection spans within that buffer.
+    pub fn newest_selection_buffer_rows(
+        &self,
+        cx: &mut App,
+    ) -> Option<(Entity<Buffer>, Range<u32>)> {
+        let selection = self.selections.newest::<Point>(cx);
+        let selection_range = selection.range();
 
-            let multi_buffer = self.buffer().read(cx);
-            let multi_buffer_snapshot = multi_buffer.snapshot(cx);
-            let buffer_ranges = multi_buffer_snapshot.range_to_buffer_ranges(selection_range);
+        let multi_buffer = self.buffer().read(cx);
+        let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+        let buffer_ranges = multi_buffer_snapshot.range_to_buffer_ranges(selection_range);
 
-            let (buffer, range, _) = if selection.reversed {
-                buffer_ranges.first()
-            } else {
-                buffer_ranges.last()
-            }?;
+        let (buffer, range, _) = if selection.reversed {
+            buffer_ranges.first()
+        } else {
+            buffer_ranges.last()
+        }?;
+
+        let selection = text::ToPoint::to_point(&range.start, &buffer).row
+            ..text::ToPoint::to_point(&range.end, &buffer).row;
+        Some((
+            multi_buffer.buffer(buffer.remote_id()).unwrap().clone(),
+            selection,
+        ))
+    }
 
-            let selection = text::ToPoint::to_point(&range.start, &buffer).row
-                ..text::ToPoint::to_point(&range.end, &buffer).row;
-            Some((
-                multi_buffer.buffer(buffer.remote_id()).unwrap().clone(),
-                selection,
-            ))
-        });
+    fn get_permalink_to_line(&self, cx: &mut Context<Self>) -> Task<Result<url::Url>> {
+        let buffer_and_selection = self.newest_selection_buffer_rows(cx);
 
         let Some((buffer, selection)) = buffer_and_selection else {
             return Task::ready(Err(anyhow!("failed to determine buffer and selection")));
@@ -15307,6 +15330,7 @@ impl Editor {
                 self.active_indent_guides_state.dirty = true;
                 self.refresh_active_diagnostics(cx);
                 self.refresh_code_actions(window, cx);
+                self.refresh_conflicts(window, cx);
                 if self.has_active_inline_completion() {
                     self.update_visible_inline_completion(window, cx);
                 }
@@ -15369,6 +15393,7 @@ impl Editor {
                 excerpts,
             } => {
                 self.tasks_update_task = Some(self.refresh_runnables(window, cx));
+                self.refresh_conflicts(window, cx);
                 let buffer_id = buffer.read(cx).remote_id();
                 if self.buffer.read(cx).diff_for(buffer_id).is_none() {
                     if let Some(project) = &self.project {
@@ -15389,6 +15414,7 @@ impl Editor {
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
+                self.refresh_conflicts(window, cx);
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
                 let buffer = self.buffer.read(cx);
                 self.registered_buffers
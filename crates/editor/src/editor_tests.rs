@@ -11372,6 +11372,48 @@ async fn test_diagnostics_with_links(cx: &mut TestAppContext) {
     cx.update_editor(|editor, _, _| assert!(editor.hover_state.diagnostic_popover.is_some()))
 }
 
+#[gpui::test]
+async fn test_resolve_conflicts(executor: BackgroundExecutor, cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    // Only files that git reports as conflicted are scanned for conflicts.
+    cx.set_conflicted();
+    cx.set_state(
+        &r#"
+        ˇone
+        <<<<<<< HEAD
+        two
+        =======
+        three
+        >>>>>>> feature
+        four
+        <<<<<<< HEAD
+        five
+        ||||||| base
+        =======
+        six
+        >>>>>>> feature
+        "#
+        .unindent(),
+    );
+    executor.advance_clock(Duration::from_millis(100));
+    executor.run_until_parked();
+
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_next_conflict(&::git::GoToNextConflict, window, cx);
+        editor.accept_theirs(&::git::AcceptTheirs, window, cx);
+    });
+    executor.advance_clock(Duration::from_millis(100));
+    executor.run_until_parked();
+
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_next_conflict(&::git::GoToNextConflict, window, cx);
+        editor.accept_both(&::git::AcceptBoth, window, cx);
+        assert_eq!(editor.text(cx), "one\nthree\nfour\nfive\nsix\n");
+    });
+}
+
 #[gpui::test]
 async fn test_go_to_hunk(executor: BackgroundExecutor, cx: &mut TestAppContext) {
     init_test(cx, |_| {});
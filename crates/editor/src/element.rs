@@ -415,6 +415,12 @@ impl EditorElement {
         register_action(editor, window, Editor::toggle_staged_selected_diff_hunks);
         register_action(editor, window, Editor::stage_and_next);
         register_action(editor, window, Editor::unstage_and_next);
+        register_action(editor, window, Editor::accept_ours);
+        register_action(editor, window, Editor::accept_theirs);
+        register_action(editor, window, Editor::accept_both);
+        register_action(editor, window, Editor::accept_base);
+        register_action(editor, window, Editor::go_to_next_conflict);
+        register_action(editor, window, Editor::go_to_previous_conflict);
         register_action(editor, window, Editor::expand_all_diff_hunks);
 
         register_action(editor, window, |editor, action, window, cx| {
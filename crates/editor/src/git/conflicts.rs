@@ -0,0 +1,625 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use collections::{HashMap, HashSet};
+use git::{
+    repository::RepoPath, AcceptBase, AcceptBoth, AcceptOurs, AcceptTheirs, GoToNextConflict,
+    GoToPreviousConflict,
+};
+use gpui::{App, AppContext as _, Context, Entity, SharedString, Task, Window};
+use language::ToOffset as _;
+use multi_buffer::{Anchor, MultiBufferSnapshot, ToOffset as _};
+use project::git::Repository;
+use text::BufferId;
+use ui::{prelude::*, Tooltip};
+use util::post_inc;
+
+use crate::display_map::{
+    BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId,
+};
+use crate::scroll::Autoscroll;
+use crate::{Editor, EditorMode};
+
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// A region of a file in which a merge left conflicting changes, delimited by
+/// markers such as:
+///
+/// ```text
+/// <<<<<<< HEAD
+/// our changes
+/// ||||||| base
+/// the common ancestor, with the `diff3` conflict style
+/// =======
+/// their changes
+/// >>>>>>> feature
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConflictRegion<T> {
+    /// The whole region, from the start of the first marker to the end of the last.
+    pub range: Range<T>,
+    pub ours: Range<T>,
+    pub base: Option<Range<T>>,
+    pub theirs: Range<T>,
+    /// The text after the `<<<<<<<` marker, usually the branch being merged into.
+    pub ours_label: SharedString,
+    /// The text after the `>>>>>>>` marker, usually the branch or commit being merged.
+    pub theirs_label: SharedString,
+}
+
+impl<T> ConflictRegion<T> {
+    fn map<U>(self, mut f: impl FnMut(T) -> U) -> ConflictRegion<U> {
+        ConflictRegion {
+            range: f(self.range.start)..f(self.range.end),
+            ours: f(self.ours.start)..f(self.ours.end),
+            base: self.base.map(|base| f(base.start)..f(base.end)),
+            theirs: f(self.theirs.start)..f(self.theirs.end),
+            ours_label: self.ours_label,
+            theirs_label: self.theirs_label,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+    Base,
+}
+
+struct PendingConflict {
+    start: usize,
+    ours_label: SharedString,
+    ours_start: usize,
+    /// The start of the `|||||||` marker and of the base text after it.
+    base: Option<(usize, usize)>,
+    /// The start of the `=======` marker and of their text after it.
+    separator: Option<(usize, usize)>,
+}
+
+/// Returns the conflict regions in the given text, with their byte ranges.
+/// Regions with missing or misplaced markers are skipped.
+pub fn parse_conflicts(text: &str) -> Vec<ConflictRegion<usize>> {
+    let mut conflicts = Vec::new();
+    let mut pending: Option<PendingConflict> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if let Some(label) = marker_label(line, b'<') {
+            pending = Some(PendingConflict {
+                start: line_start,
+                ours_label: label.to_string().into(),
+                ours_start: offset,
+                base: None,
+                separator: None,
+            });
+            continue;
+        }
+
+        let Some(conflict) = pending.as_mut() else {
+            continue;
+        };
+        match conflict.separator {
+            None if conflict.base.is_none() && marker_label(line, b'|').is_some() => {
+                conflict.base = Some((line_start, offset));
+            }
+            None if line == "=======" => {
+                conflict.separator = Some((line_start, offset));
+            }
+            Some((separator_start, theirs_start)) => {
+                if let Some(label) = marker_label(line, b'>') {
+                    let ours_end = conflict
+                        .base
+                        .map_or(separator_start, |(base_marker_start, _)| base_marker_start);
+                    conflicts.push(ConflictRegion {
+                        range: conflict.start..offset,
+                        ours: conflict.ours_start..ours_end,
+                        base: conflict
+                            .base
+                            .map(|(_, base_start)| base_start..separator_start),
+                        theirs: theirs_start..line_start,
+                        ours_label: conflict.ours_label.clone(),
+                        theirs_label: label.to_string().into(),
+                    });
+                    pending = None;
+                }
+            }
+            None => {}
+        }
+    }
+
+    conflicts
+}
+
+/// Returns whether the text still has conflict markers, even ones that aren't part
+/// of a complete conflict region.
+///
+/// Only the `<<<<<<<` and `>>>>>>>` markers are looked for. A `=======` or `|||||||`
+/// line is only a marker between those two, and on its own is ordinary text, such
+/// as the underline of a Markdown heading.
+fn has_conflict_markers(text: &str) -> bool {
+    text.lines().any(|line| {
+        [b'<', b'>'].iter().any(|marker| {
+            line.as_bytes()
+                .get(..7)
+                .is_some_and(|prefix| prefix.iter().all(|byte| byte == marker))
+        })
+    })
+}
+
+/// Returns the label of a line made of a seven character conflict marker,
+/// optionally followed by a space and a label.
+fn marker_label(line: &str, marker: u8) -> Option<&str> {
+    if !line.as_bytes().get(..7)?.iter().all(|byte| *byte == marker) {
+        return None;
+    }
+
+    let rest = &line[7..];
+    if rest.is_empty() {
+        Some(rest)
+    } else {
+        rest.strip_prefix(' ').map(str::trim)
+    }
+}
+
+enum ConflictOursHighlight {}
+enum ConflictBaseHighlight {}
+enum ConflictTheirsHighlight {}
+
+struct Conflict {
+    buffer_id: BufferId,
+    region: ConflictRegion<Anchor>,
+    /// The block showing the conflict's actions, once it's been inserted.
+    block_id: Option<CustomBlockId>,
+}
+
+#[derive(Default)]
+pub struct ConflictState {
+    conflicts: Vec<Conflict>,
+    /// Distinguishes the buttons of the blocks, which outlive the refreshes.
+    next_block_ix: usize,
+    /// The buffers that had conflicts on the last refresh, so that they can be
+    /// staged once their last conflict is resolved.
+    conflicted_buffers: HashSet<BufferId>,
+    refresh_task: Option<Task<()>>,
+}
+
+impl Editor {
+    pub(crate) fn refresh_conflicts(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.mode != EditorMode::Full {
+            return;
+        }
+
+        // Only the files git reports as conflicted are scanned, so that editing
+        // other files doesn't copy their text on every keystroke.
+        let excerpts = self
+            .buffer
+            .read(cx)
+            .snapshot(cx)
+            .excerpts()
+            .filter(|(_, buffer, _)| self.conflicted_repository(buffer.remote_id(), cx).is_some())
+            .map(|(excerpt_id, buffer, range)| (excerpt_id, buffer.clone(), range.context))
+            .collect::<Vec<_>>();
+        self.conflict_state.refresh_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+            cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            let conflicts = cx
+                .background_spawn(async move {
+                    let mut conflicts = Vec::new();
+                    for (excerpt_id, buffer, range) in excerpts {
+                        let range = range.start.to_offset(&buffer)..range.end.to_offset(&buffer);
+                        let text = buffer.text_for_range(range.clone()).collect::<String>();
+                        for region in parse_conflicts(&text) {
+                            let buffer_id = buffer.remote_id();
+                            conflicts.push(Conflict {
+                                buffer_id,
+                                region: region.map(|offset| {
+                                    Anchor::in_buffer(
+                                        excerpt_id,
+                                        buffer_id,
+                                        buffer.anchor_after(range.start + offset),
+                                    )
+                                }),
+                                block_id: None,
+                            });
+                        }
+                    }
+                    conflicts
+                })
+                .await;
+
+            editor
+                .update(&mut cx, |editor, cx| editor.set_conflicts(conflicts, cx))
+                .ok();
+        }));
+    }
+
+    fn set_conflicts(&mut self, mut conflicts: Vec<Conflict>, cx: &mut Context<Self>) {
+        // Keep the blocks of the conflicts that are unchanged, so that they don't
+        // flicker as the file is edited.
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut old_block_ids = std::mem::take(&mut self.conflict_state.conflicts)
+            .into_iter()
+            .filter_map(|conflict| {
+                let region = conflict.region.map(|anchor| anchor.to_offset(&snapshot));
+                Some((region, conflict.block_id?))
+            })
+            .collect::<HashMap<_, _>>();
+        for conflict in &mut conflicts {
+            let region = conflict
+                .region
+                .clone()
+                .map(|anchor| anchor.to_offset(&snapshot));
+            conflict.block_id = old_block_ids.remove(&region);
+        }
+        self.remove_blocks(old_block_ids.into_values().collect(), None, cx);
+
+        self.clear_row_highlights::<ConflictOursHighlight>();
+        self.clear_row_highlights::<ConflictBaseHighlight>();
+        self.clear_row_highlights::<ConflictTheirsHighlight>();
+
+        let colors = cx.theme().colors();
+        let (ours_color, base_color, theirs_color) = (
+            colors.version_control_modified_background,
+            colors.version_control_conflict_background,
+            colors.version_control_added_background,
+        );
+        for conflict in &conflicts {
+            // Each side is highlighted along with the marker line that precedes it.
+            let region = &conflict.region;
+            let separator_start = region
+                .base
+                .as_ref()
+                .map_or(region.ours.end, |base| base.end);
+            self.highlight_rows::<ConflictOursHighlight>(
+                region.range.start..region.ours.end,
+                ours_color,
+                false,
+                cx,
+            );
+            if let Some(base) = &region.base {
+                self.highlight_rows::<ConflictBaseHighlight>(
+                    region.ours.end..base.end,
+                    base_color,
+                    false,
+                    cx,
+                );
+            }
+            self.highlight_rows::<ConflictTheirsHighlight>(
+                separator_start..region.range.end,
+                theirs_color,
+                false,
+                cx,
+            );
+        }
+
+        let editor = cx.entity().downgrade();
+        let new_conflicts = conflicts
+            .iter_mut()
+            .filter(|conflict| conflict.block_id.is_none())
+            .collect::<Vec<_>>();
+        let blocks = new_conflicts
+            .iter()
+            .map(|conflict| {
+                let editor = editor.clone();
+                let region = conflict.region.clone();
+                let ix = post_inc(&mut self.conflict_state.next_block_ix);
+                BlockProperties {
+                    placement: BlockPlacement::Above(region.range.start),
+                    height: 1,
+                    style: BlockStyle::Sticky,
+                    render: Arc::new(move |cx: &mut BlockContext| {
+                        render_conflict_actions(ix, &region, editor.clone(), cx)
+                    }),
+                    priority: 0,
+                }
+            })
+            .collect::<Vec<_>>();
+        let block_ids = self.insert_blocks(blocks, None, cx);
+        for (conflict, block_id) in new_conflicts.into_iter().zip(block_ids) {
+            conflict.block_id = Some(block_id);
+        }
+
+        let buffer_ids = conflicts
+            .iter()
+            .map(|conflict| conflict.buffer_id)
+            .collect::<HashSet<_>>();
+        let resolved_buffer_ids = self
+            .conflict_state
+            .conflicted_buffers
+            .difference(&buffer_ids)
+            .copied()
+            .collect::<Vec<_>>();
+        self.conflict_state.conflicted_buffers = buffer_ids;
+        self.conflict_state.conflicts = conflicts;
+        for buffer_id in resolved_buffer_ids {
+            self.stage_resolved_buffer(buffer_id, cx);
+        }
+    }
+
+    /// Returns the repository in which the buffer's file is conflicted, along
+    /// with the file's path in it.
+    fn conflicted_repository(
+        &self,
+        buffer_id: BufferId,
+        cx: &App,
+    ) -> Option<(Entity<Repository>, RepoPath)> {
+        let (repository, path) = self
+            .project
+            .as_ref()?
+            .read(cx)
+            .git_store()
+            .read(cx)
+            .repository_and_path_for_buffer_id(buffer_id, cx)?;
+        repository
+            .read(cx)
+            .has_conflict(&path)
+            .then_some((repository, path))
+    }
+
+    /// Stages a buffer whose conflicts were all resolved, marking it as resolved
+    /// in the repository.
+    fn stage_resolved_buffer(&self, buffer_id: BufferId, cx: &mut Context<Self>) -> Option<()> {
+        let buffer = self.buffer.read(cx).buffer(buffer_id)?;
+        // Markers left behind, such as those of a partially deleted region,
+        // mean the file isn't resolved yet.
+        if has_conflict_markers(&buffer.read(cx).text()) {
+            return None;
+        }
+
+        let (repository, path) = self.conflicted_repository(buffer_id, cx)?;
+        repository
+            .update(cx, |repository, cx| {
+                repository.stage_entries(vec![path], cx)
+            })
+            .detach_and_log_err(cx);
+        Some(())
+    }
+
+    pub fn resolve_conflict(
+        &mut self,
+        position: Anchor,
+        resolution: ConflictResolution,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let Some(conflict) = self.conflict_at(position, &snapshot) else {
+            return;
+        };
+
+        let region = &conflict.region;
+        let text_for_range =
+            |range: &Range<Anchor>| snapshot.text_for_range(range.clone()).collect::<String>();
+        let text = match resolution {
+            ConflictResolution::Ours => text_for_range(&region.ours),
+            ConflictResolution::Theirs => text_for_range(&region.theirs),
+            ConflictResolution::Both => {
+                text_for_range(&region.ours) + &text_for_range(&region.theirs)
+            }
+            ConflictResolution::Base => match &region.base {
+                Some(base) => text_for_range(base),
+                None => return,
+            },
+        };
+
+        let range = region.range.clone();
+        self.transact(window, cx, |editor, _window, cx| {
+            editor
+                .buffer
+                .update(cx, |buffer, cx| buffer.edit([(range, text)], None, cx));
+        });
+    }
+
+    fn conflict_at(&self, position: Anchor, snapshot: &MultiBufferSnapshot) -> Option<&Conflict> {
+        self.conflict_state.conflicts.iter().find(|conflict| {
+            conflict.region.range.start.cmp(&position, snapshot).is_le()
+                && conflict.region.range.end.cmp(&position, snapshot).is_ge()
+        })
+    }
+
+    fn resolve_conflict_at_cursor(
+        &mut self,
+        resolution: ConflictResolution,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let position = self.selections.newest_anchor().head();
+        self.resolve_conflict(position, resolution, window, cx);
+    }
+
+    pub fn accept_ours(&mut self, _: &AcceptOurs, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_conflict_at_cursor(ConflictResolution::Ours, window, cx);
+    }
+
+    pub fn accept_theirs(&mut self, _: &AcceptTheirs, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_conflict_at_cursor(ConflictResolution::Theirs, window, cx);
+    }
+
+    pub fn accept_both(&mut self, _: &AcceptBoth, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_conflict_at_cursor(ConflictResolution::Both, window, cx);
+    }
+
+    pub fn accept_base(&mut self, _: &AcceptBase, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_conflict_at_cursor(ConflictResolution::Base, window, cx);
+    }
+
+    pub fn go_to_next_conflict(
+        &mut self,
+        _: &GoToNextConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_conflict(Ordering::Greater, window, cx);
+    }
+
+    pub fn go_to_previous_conflict(
+        &mut self,
+        _: &GoToPreviousConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_conflict(Ordering::Less, window, cx);
+    }
+
+    /// Moves the cursor to the start of the nearest conflict after or before it,
+    /// wrapping around at the end of the buffer.
+    fn go_to_conflict(&mut self, direction: Ordering, window: &mut Window, cx: &mut Context<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let cursor = self.selections.newest_anchor().head().to_offset(&snapshot);
+        let starts = self
+            .conflict_state
+            .conflicts
+            .iter()
+            .map(|conflict| conflict.region.range.start.to_offset(&snapshot))
+            .collect::<Vec<_>>();
+        let destination = if direction == Ordering::Greater {
+            starts
+                .iter()
+                .find(|start| **start > cursor)
+                .or(starts.first())
+        } else {
+            starts
+                .iter()
+                .rev()
+                .find(|start| **start < cursor)
+                .or(starts.last())
+        };
+
+        if let Some(&destination) = destination {
+            self.change_selections(Some(Autoscroll::center()), window, cx, |selections| {
+                selections.select_ranges([destination..destination]);
+            });
+        }
+    }
+}
+
+fn render_conflict_actions(
+    ix: usize,
+    region: &ConflictRegion<Anchor>,
+    editor: gpui::WeakEntity<Editor>,
+    cx: &mut BlockContext,
+) -> AnyElement {
+    let button = |id: &'static str,
+                  label: &'static str,
+                  tooltip: Option<String>,
+                  resolution: ConflictResolution| {
+        let editor = editor.clone();
+        let position = region.range.start;
+        Button::new((id, ix), label)
+            .label_size(LabelSize::Small)
+            .style(ButtonStyle::Subtle)
+            .when_some(tooltip, |button, tooltip| {
+                button.tooltip(Tooltip::text(tooltip))
+            })
+            .on_click(move |_, window, cx| {
+                editor
+                    .update(cx, |editor, cx| {
+                        editor.resolve_conflict(position, resolution, window, cx)
+                    })
+                    .ok();
+            })
+    };
+    let label_tooltip = |label: &SharedString| {
+        (!label.is_empty()).then(|| format!("Keep the changes from {label}"))
+    };
+
+    h_flex()
+        .block_mouse_down()
+        .h(cx.line_height)
+        .pl(cx.gutter_dimensions.full_width())
+        .gap_1()
+        .child(button(
+            "accept-ours",
+            "Accept Ours",
+            label_tooltip(&region.ours_label),
+            ConflictResolution::Ours,
+        ))
+        .child(button(
+            "accept-theirs",
+            "Accept Theirs",
+            label_tooltip(&region.theirs_label),
+            ConflictResolution::Theirs,
+        ))
+        .child(button(
+            "accept-both",
+            "Accept Both",
+            None,
+            ConflictResolution::Both,
+        ))
+        .when(region.base.is_some(), |row| {
+            row.child(button(
+                "accept-base",
+                "Accept Base",
+                Some("Keep the common ancestor".to_string()),
+                ConflictResolution::Base,
+            ))
+        })
+        .into_any_element()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unindent::Unindent as _;
+
+    #[test]
+    fn test_parse_conflicts() {
+        let text = "
+            one
+            <<<<<<< HEAD
+            two
+            =======
+            three
+            >>>>>>> feature
+            four
+            <<<<<<< ours
+            five
+            ||||||| base
+            six
+            =======
+            >>>>>>> theirs
+            <<<<<<< unterminated
+            seven
+        "
+        .unindent();
+
+        let conflicts = parse_conflicts(&text);
+        assert_eq!(conflicts.len(), 2);
+
+        let region = &conflicts[0];
+        assert_eq!(&text[region.ours.clone()], "two\n");
+        assert_eq!(&text[region.theirs.clone()], "three\n");
+        assert_eq!(region.base, None);
+        assert_eq!(
+            &text[region.range.clone()],
+            "<<<<<<< HEAD\ntwo\n=======\nthree\n>>>>>>> feature\n"
+        );
+        assert_eq!(region.ours_label, "HEAD");
+        assert_eq!(region.theirs_label, "feature");
+
+        let region = &conflicts[1];
+        assert_eq!(&text[region.ours.clone()], "five\n");
+        assert_eq!(&text[region.base.clone().unwrap()], "six\n");
+        assert_eq!(&text[region.theirs.clone()], "");
+        assert_eq!(region.theirs_label, "theirs");
+    }
+
+    #[test]
+    fn test_has_conflict_markers() {
+        assert!(has_conflict_markers(
+            "<<<<<<< HEAD\none\n=======\ntwo\n>>>>>>> feature\n"
+        ));
+        // A region whose other markers were deleted is still unresolved.
+        assert!(has_conflict_markers("one\n>>>>>>> feature\n"));
+        assert!(has_conflict_markers("<<<<<<<< HEAD\n"));
+        // Separators outside of a region are ordinary text.
+        assert!(!has_conflict_markers("Title\n=======\ntext\n"));
+        assert!(!has_conflict_markers("||||||| base\n"));
+    }
+}
@@ -214,6 +214,19 @@ pub fn deploy_context_menu(
                     } else {
                         builder.disabled_action(COPY_PERMALINK_LABEL, Box::new(CopyPermalinkToLine))
                     }
+                })
+                .map(|builder| {
+                    const FILE_HISTORY_LABEL: &str = "View File History";
+                    const LINE_HISTORY_LABEL: &str = "View Line History";
+                    if has_git_repo {
+                        builder
+                            .action(FILE_HISTORY_LABEL, Box::new(git::ViewFileHistory))
+                            .action(LINE_HISTORY_LABEL, Box::new(git::ViewLineHistory))
+                    } else {
+                        builder
+                            .disabled_action(FILE_HISTORY_LABEL, Box::new(git::ViewFileHistory))
+                            .disabled_action(LINE_HISTORY_LABEL, Box::new(git::ViewLineHistory))
+                    }
                 });
             match focus {
                 Some(focus) => builder.context(focus),
@@ -6,6 +6,7 @@ use buffer_diff::DiffHunkStatusKind;
 use collections::BTreeMap;
 use futures::Future;
 
+use git::status::{UnmergedStatus, UnmergedStatusCode};
 use gpui::{
     prelude::*, AnyWindowHandle, App, Context, Entity, Focusable as _, Keystroke, Pixels, Point,
     VisualTestContext, Window, WindowHandle,
@@ -330,6 +331,27 @@ impl EditorTestContext {
         self.cx.run_until_parked();
     }
 
+    /// Marks the buffer's file as having unresolved merge conflicts in the repository.
+    pub fn set_conflicted(&mut self) {
+        self.cx.run_until_parked();
+        let fs = self.update_editor(|editor, _, cx| {
+            editor.project.as_ref().unwrap().read(cx).fs().as_fake()
+        });
+        let path = self.update_buffer(|buffer, _| buffer.file().unwrap().path().clone());
+        fs.set_status_for_repo_via_git_operation(
+            &Self::root_path().join(".git"),
+            &[(
+                path.as_ref(),
+                UnmergedStatus {
+                    first_head: UnmergedStatusCode::Updated,
+                    second_head: UnmergedStatusCode::Updated,
+                }
+                .into(),
+            )],
+        );
+        self.cx.run_until_parked();
+    }
+
     #[track_caller]
     pub fn assert_index_text(&mut self, expected: Option<&str>) {
         let fs = self.update_editor(|editor, _, cx| {
@@ -0,0 +1,412 @@
+mod fim_template;
+mod input_excerpt;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
+use gpui::{App, Context, Entity, EntityId, Task};
+use http_client::HttpClient;
+use inline_completion::{Direction, EditPredictionProvider, InlineCompletion};
+use language::language_settings::{all_language_settings, LocalModelSettings};
+use language::{Anchor, Buffer, Point, ToOffset, ToPoint};
+use project::Project;
+use util::ResultExt as _;
+
+use crate::fim_template::{render_prompt, template_for_model};
+use crate::input_excerpt::excerpt_for_cursor_position;
+
+pub const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(150);
+
+/// The number of tokens of code around the cursor that the model sees.
+const CONTEXT_TOKEN_LIMIT: usize = 1024;
+
+const DEFAULT_MAX_TOKENS: u32 = 128;
+
+/// The local server that runs the model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FimServer {
+    Ollama,
+    LmStudio,
+}
+
+/// Predicts the code at the cursor by prompting a local model to fill in the code
+/// between the text before and after it.
+pub struct FimCompletionProvider {
+    server: FimServer,
+    http_client: Arc<dyn HttpClient>,
+    pending_refresh: Option<Task<()>>,
+    current_completion: Option<CurrentCompletion>,
+}
+
+struct CurrentCompletion {
+    buffer_id: EntityId,
+    /// The position of the cursor when the completion was requested.
+    position: Anchor,
+    text: String,
+}
+
+impl CurrentCompletion {
+    /// Returns the rest of the completion, when the text typed since it was requested
+    /// matches its start.
+    fn remaining_text(
+        &self,
+        buffer: &Entity<Buffer>,
+        cursor_position: Anchor,
+        cx: &App,
+    ) -> Option<&str> {
+        if buffer.entity_id() != self.buffer_id {
+            return None;
+        }
+
+        let buffer = buffer.read(cx);
+        if !self.position.is_valid(buffer) {
+            return None;
+        }
+        let start = self.position.to_offset(buffer);
+        let end = cursor_position.to_offset(buffer);
+        if end < start {
+            return None;
+        }
+
+        let typed_text = buffer.text_for_range(start..end).collect::<String>();
+        let remaining_text = self.text.strip_prefix(&typed_text)?;
+        (!remaining_text.trim().is_empty()).then_some(remaining_text)
+    }
+}
+
+impl FimCompletionProvider {
+    pub fn new(server: FimServer, http_client: Arc<dyn HttpClient>) -> Self {
+        Self {
+            server,
+            http_client,
+            pending_refresh: None,
+            current_completion: None,
+        }
+    }
+
+    fn settings<'a>(&self, cx: &'a App) -> &'a LocalModelSettings {
+        let edit_predictions = &all_language_settings(None, cx).edit_predictions;
+        match self.server {
+            FimServer::Ollama => &edit_predictions.ollama,
+            FimServer::LmStudio => &edit_predictions.lmstudio,
+        }
+    }
+}
+
+impl EditPredictionProvider for FimCompletionProvider {
+    fn name() -> &'static str {
+        "fim"
+    }
+
+    fn display_name() -> &'static str {
+        "Local Model"
+    }
+
+    fn show_completions_in_menu() -> bool {
+        false
+    }
+
+    fn is_enabled(&self, _buffer: &Entity<Buffer>, _cursor_position: Anchor, cx: &App) -> bool {
+        let settings = self.settings(cx);
+        settings.model.as_ref().map_or(false, |model| {
+            template_for_model(model, settings.fim_template.as_ref()).is_some()
+        })
+    }
+
+    fn is_refreshing(&self) -> bool {
+        self.pending_refresh.is_some()
+    }
+
+    fn refresh(
+        &mut self,
+        _project: Option<Entity<Project>>,
+        buffer: Entity<Buffer>,
+        cursor_position: Anchor,
+        debounce: bool,
+        cx: &mut Context<Self>,
+    ) {
+        // Keep showing the current completion while the user types along with it.
+        if let Some(current_completion) = self.current_completion.as_ref() {
+            if current_completion
+                .remaining_text(&buffer, cursor_position, cx)
+                .is_some()
+            {
+                return;
+            }
+        }
+
+        let settings = self.settings(cx).clone();
+        let Some(model) = settings.model.clone() else {
+            return;
+        };
+        let Some(template) = template_for_model(&model, settings.fim_template.as_ref()) else {
+            return;
+        };
+
+        let stop = template.stop.clone();
+        let snapshot = buffer.read(cx).snapshot();
+        let position = cursor_position.bias_left(&snapshot);
+        let server = self.server;
+        let http_client = self.http_client.clone();
+
+        // Replacing the pending refresh drops it, which cancels its request.
+        self.pending_refresh = Some(cx.spawn(|this, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(DEBOUNCE_TIMEOUT).await;
+            }
+
+            let (prompt, is_mid_line) = cx
+                .background_executor()
+                .spawn(async move {
+                    let point = position.to_point(&snapshot);
+                    let excerpt =
+                        excerpt_for_cursor_position(point, &snapshot, CONTEXT_TOKEN_LIMIT);
+                    let line_end = Point::new(point.row, snapshot.line_len(point.row));
+                    let is_mid_line = snapshot
+                        .text_for_range(point..line_end)
+                        .any(|chunk| !chunk.trim().is_empty());
+                    (
+                        render_prompt(&template, &excerpt.prefix, &excerpt.suffix),
+                        is_mid_line,
+                    )
+                })
+                .await;
+
+            let completion =
+                request_completion(server, http_client.as_ref(), &settings, model, prompt, stop)
+                    .await
+                    .context("fill-in-the-middle completion failed")
+                    .log_err();
+
+            this.update(&mut cx, |this, cx| {
+                if let Some(text) = completion {
+                    // Completions in the middle of a line have to fit before the rest of it.
+                    let text = if is_mid_line {
+                        text.lines().next().unwrap_or_default()
+                    } else {
+                        text.as_str()
+                    };
+                    let text = text.trim_end();
+                    this.current_completion =
+                        (!text.trim().is_empty()).then(|| CurrentCompletion {
+                            buffer_id: buffer.entity_id(),
+                            position,
+                            text: text.to_string(),
+                        });
+                }
+                this.pending_refresh = None;
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn cycle(
+        &mut self,
+        _buffer: Entity<Buffer>,
+        _cursor_position: Anchor,
+        _direction: Direction,
+        _cx: &mut Context<Self>,
+    ) {
+    }
+
+    fn accept(&mut self, _cx: &mut Context<Self>) {
+        self.pending_refresh = None;
+        self.current_completion = None;
+    }
+
+    fn discard(&mut self, _cx: &mut Context<Self>) {
+        self.pending_refresh = None;
+        self.current_completion = None;
+    }
+
+    fn suggest(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cursor_position: Anchor,
+        cx: &mut Context<Self>,
+    ) -> Option<InlineCompletion> {
+        let text = self
+            .current_completion
+            .as_ref()?
+            .remaining_text(buffer, cursor_position, cx)?;
+        let position = cursor_position.bias_right(buffer.read(cx));
+        Some(InlineCompletion {
+            id: None,
+            edits: vec![(position..position, text.to_string())],
+            edit_preview: None,
+        })
+    }
+}
+
+async fn request_completion(
+    server: FimServer,
+    http_client: &dyn HttpClient,
+    settings: &LocalModelSettings,
+    model: String,
+    prompt: String,
+    stop: Vec<String>,
+) -> Result<String> {
+    let max_tokens = settings.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+    match server {
+        FimServer::Ollama => {
+            let api_url = settings
+                .api_url
+                .as_deref()
+                .unwrap_or(ollama::OLLAMA_API_URL);
+            let request = ollama::GenerateRequest {
+                model,
+                prompt,
+                raw: true,
+                stream: false,
+                keep_alive: ollama::KeepAlive::default(),
+                options: Some(ollama::ChatOptions {
+                    num_predict: Some(max_tokens as isize),
+                    stop: Some(stop),
+                    temperature: Some(0.),
+                    ..Default::default()
+                }),
+            };
+            let response = ollama::generate(http_client, api_url, request).await?;
+            Ok(response.response)
+        }
+        FimServer::LmStudio => {
+            let api_url = settings
+                .api_url
+                .as_deref()
+                .unwrap_or(lmstudio::LMSTUDIO_API_URL);
+            let request = lmstudio::CompletionRequest {
+                model,
+                prompt,
+                stream: false,
+                max_tokens: Some(max_tokens as i32),
+                stop: Some(stop),
+                temperature: Some(0.),
+            };
+            let response = lmstudio::complete_prompt(http_client, api_url, request).await?;
+            response
+                .choices
+                .into_iter()
+                .next()
+                .map(|choice| choice.text)
+                .context("LM Studio returned no completion")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::AsyncReadExt as _;
+    use gpui::{AppContext as _, TestAppContext};
+    use http_client::{FakeHttpClient, Response};
+    use indoc::indoc;
+    use language::language_settings::AllLanguageSettings;
+    use parking_lot::Mutex;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_fim_completion(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                    settings
+                        .edit_predictions
+                        .get_or_insert_with(Default::default)
+                        .ollama
+                        .model = Some("qwen2.5-coder:1.5b".into());
+                });
+            });
+        });
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    requests
+                        .lock()
+                        .push((uri, serde_json::from_str::<serde_json::Value>(&body)?));
+
+                    let response = json!({ "response": "a + b\n", "done": true });
+                    Ok(Response::builder()
+                        .status(200)
+                        .body(response.to_string().into())?)
+                }
+            }
+        });
+
+        let buffer = cx.new(|cx| {
+            language::Buffer::local(
+                indoc! {"
+                    fn add(a: i32, b: i32) -> i32 {
+
+                    }"},
+                cx,
+            )
+        });
+        let provider =
+            cx.new(|_| FimCompletionProvider::new(FimServer::Ollama, http_client.clone()));
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(1, 0)));
+        assert!(provider.read_with(cx, |provider, cx| provider.is_enabled(&buffer, cursor, cx)));
+
+        // Typing while the refresh is debounced cancels the earlier request.
+        provider.update(cx, |provider, cx| {
+            provider.refresh(None, buffer.clone(), cursor, true, cx)
+        });
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 0)..Point::new(1, 0), "    ")], None, cx)
+        });
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(1, 4)));
+        provider.update(cx, |provider, cx| {
+            provider.refresh(None, buffer.clone(), cursor, true, cx)
+        });
+        cx.executor().advance_clock(DEBOUNCE_TIMEOUT);
+        cx.run_until_parked();
+
+        let requests = std::mem::take(&mut *requests.lock());
+        assert_eq!(requests.len(), 1);
+        let (uri, body) = &requests[0];
+        assert_eq!(uri, "http://localhost:11434/api/generate");
+        assert_eq!(body["model"], "qwen2.5-coder:1.5b");
+        assert_eq!(body["raw"], true);
+        assert_eq!(
+            body["prompt"],
+            "<|fim_prefix|>fn add(a: i32, b: i32) -> i32 {\n    <|fim_suffix|>\n}<|fim_middle|>"
+        );
+
+        let completion = provider
+            .update(cx, |provider, cx| provider.suggest(&buffer, cursor, cx))
+            .unwrap();
+        assert_eq!(completion.edits.len(), 1);
+        assert_eq!(completion.edits[0].1, "a + b");
+
+        // Typing along with the completion shows the rest of it.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 4)..Point::new(1, 4), "a ")], None, cx)
+        });
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(1, 6)));
+        let completion = provider
+            .update(cx, |provider, cx| provider.suggest(&buffer, cursor, cx))
+            .unwrap();
+        assert_eq!(completion.edits[0].1, "+ b");
+
+        // Typing something else hides it.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 6)..Point::new(1, 6), "-")], None, cx)
+        });
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(1, 7)));
+        assert!(provider
+            .update(cx, |provider, cx| provider.suggest(&buffer, cursor, cx))
+            .is_none());
+    }
+}
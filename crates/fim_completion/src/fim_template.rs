@@ -0,0 +1,109 @@
+use language::language_settings::FimTemplate;
+
+/// The templates of model families trained for fill-in-the-middle completion, keyed by
+/// a part of the name that all models of the family share.
+const KNOWN_TEMPLATES: &[(&str, &str, &[&str])] = &[
+    (
+        "qwen2.5-coder",
+        "<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>",
+        &["<|endoftext|>", "<|fim_pad|>", "<|file_sep|>", "<|im_end|>"],
+    ),
+    (
+        "deepseek-coder",
+        "<｜fim▁begin｜>{prefix}<｜fim▁hole｜>{suffix}<｜fim▁end｜>",
+        &["<｜end▁of▁sentence｜>"],
+    ),
+    (
+        "codellama",
+        "<PRE> {prefix} <SUF>{suffix} <MID>",
+        &["<EOT>"],
+    ),
+    ("codestral", "[SUFFIX]{suffix}[PREFIX]{prefix}", &["</s>"]),
+    (
+        "codegemma",
+        "<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>",
+        &["<|file_separator|>", "<|fim_prefix|>"],
+    ),
+    (
+        "starcoder",
+        "<fim_prefix>{prefix}<fim_suffix>{suffix}<fim_middle>",
+        &["<|endoftext|>", "<file_sep>"],
+    ),
+    (
+        "granite",
+        "<fim_prefix>{prefix}<fim_suffix>{suffix}<fim_middle>",
+        &["<|endoftext|>"],
+    ),
+];
+
+/// Returns the template to prompt a model with, preferring the one from the settings
+/// over the known templates.
+pub fn template_for_model(model: &str, configured: Option<&FimTemplate>) -> Option<FimTemplate> {
+    if let Some(template) = configured {
+        return Some(template.clone());
+    }
+
+    let model = model.to_lowercase();
+    KNOWN_TEMPLATES
+        .iter()
+        .find(|(family, _, _)| model.contains(family))
+        .map(|(_, prompt, stop)| FimTemplate {
+            prompt: prompt.to_string(),
+            stop: stop.iter().map(|stop| stop.to_string()).collect(),
+        })
+}
+
+/// Fills the code before and after the cursor into the template's prompt.
+///
+/// The placeholders are replaced in a single pass, so that code containing them is
+/// left as it is.
+pub fn render_prompt(template: &FimTemplate, prefix: &str, suffix: &str) -> String {
+    let mut prompt = String::with_capacity(template.prompt.len() + prefix.len() + suffix.len());
+    let mut rest = template.prompt.as_str();
+    while let Some(ix) = rest.find('{') {
+        prompt.push_str(&rest[..ix]);
+        rest = &rest[ix..];
+        if let Some(tail) = rest.strip_prefix("{prefix}") {
+            prompt.push_str(prefix);
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix("{suffix}") {
+            prompt.push_str(suffix);
+            rest = tail;
+        } else {
+            prompt.push('{');
+            rest = &rest[1..];
+        }
+    }
+    prompt.push_str(rest);
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prompt() {
+        let template = template_for_model("Qwen2.5-Coder:1.5b", None).unwrap();
+        assert_eq!(
+            render_prompt(&template, "fn {suffix}(", ") {}"),
+            "<|fim_prefix|>fn {suffix}(<|fim_suffix|>) {}<|fim_middle|>"
+        );
+
+        let template = template_for_model("codestral:22b", None).unwrap();
+        assert_eq!(
+            render_prompt(&template, "let a", ";"),
+            "[SUFFIX];[PREFIX]let a"
+        );
+
+        let configured = FimTemplate {
+            prompt: "<fim>{prefix}<hole>{suffix}<end>".into(),
+            stop: Vec::new(),
+        };
+        assert_eq!(
+            template_for_model("qwen2.5-coder", Some(&configured)),
+            Some(configured)
+        );
+        assert_eq!(template_for_model("llama3.2", None), None);
+    }
+}
@@ -0,0 +1,56 @@
+use language::{BufferSnapshot, Point};
+
+/// Typical number of string bytes per token for the purposes of limiting the prompt. This is
+/// intentionally low to err on the side of underestimating limits.
+const BYTES_PER_TOKEN_GUESS: usize = 3;
+
+/// The code before and after the cursor.
+#[derive(Debug, PartialEq)]
+pub struct InputExcerpt {
+    pub prefix: String,
+    pub suffix: String,
+}
+
+/// Returns the lines around the cursor, expanding one line at a time in both directions
+/// until the token limit or the edges of the buffer are reached.
+pub fn excerpt_for_cursor_position(
+    position: Point,
+    snapshot: &BufferSnapshot,
+    token_limit: usize,
+) -> InputExcerpt {
+    let mut range = Point::new(position.row, 0)..Point::new(position.row, 0);
+    range.end.column = snapshot.line_len(range.end.row);
+    let mut remaining_tokens = token_limit.saturating_sub(tokens_for_line(range.end.column));
+
+    loop {
+        let mut expanded = false;
+
+        if remaining_tokens > 0 && range.start.row > 0 {
+            range.start.row -= 1;
+            let line_tokens = tokens_for_line(snapshot.line_len(range.start.row));
+            remaining_tokens = remaining_tokens.saturating_sub(line_tokens);
+            expanded = true;
+        }
+
+        if remaining_tokens > 0 && range.end.row < snapshot.max_point().row {
+            range.end.row += 1;
+            range.end.column = snapshot.line_len(range.end.row);
+            remaining_tokens = remaining_tokens.saturating_sub(tokens_for_line(range.end.column));
+            expanded = true;
+        }
+
+        if !expanded {
+            break;
+        }
+    }
+
+    InputExcerpt {
+        prefix: snapshot.text_for_range(range.start..position).collect(),
+        suffix: snapshot.text_for_range(position..range.end).collect(),
+    }
+}
+
+fn tokens_for_line(len: u32) -> usize {
+    // Count the newline, so that blank lines count towards the limit too.
+    (len as usize + 1).div_ceil(BYTES_PER_TOKEN_GUESS)
+}
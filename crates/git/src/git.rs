@@ -35,9 +35,17 @@ actions!(
         ToggleStaged,
         StageAndNext,
         UnstageAndNext,
+        // per-conflict
+        AcceptOurs,
+        AcceptTheirs,
+        AcceptBoth,
+        AcceptBase,
+        GoToNextConflict,
+        GoToPreviousConflict,
         // per-file
         StageFile,
         UnstageFile,
+        StashFile,
         // repo-wide
         StageAll,
         UnstageAll,
@@ -49,7 +57,25 @@ actions!(
         Pull,
         Fetch,
         Commit,
-        ExpandCommitEditor
+        Amend,
+        Fixup,
+        ExpandCommitEditor,
+        // stash
+        StashAll,
+        StashStaged,
+        ViewStashes,
+        ApplyStash,
+        PopStash,
+        DropStash,
+        // history
+        ViewHistory,
+        ViewFileHistory,
+        ViewLineHistory,
+        // rebase
+        InteractiveRebase,
+        RebaseContinue,
+        RebaseSkip,
+        RebaseAbort,
     ]
 );
 action_with_deprecated_aliases!(git, RestoreFile, ["editor::RevertFile"]);
@@ -10,13 +10,14 @@ use gpui::SharedString;
 use parking_lot::Mutex;
 use rope::Rope;
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
 use std::io::Write as _;
 use std::process::Stdio;
 use std::sync::LazyLock;
 use std::{
     cmp::Ordering,
+    ops::RangeInclusive,
     path::{Component, Path, PathBuf},
     sync::Arc,
 };
@@ -116,6 +117,8 @@ pub struct CommitDetails {
     pub sha: SharedString,
     pub message: SharedString,
     pub commit_timestamp: i64,
+    pub author_email: SharedString,
+    pub author_name: SharedString,
     pub committer_email: SharedString,
     pub committer_name: SharedString,
 }
@@ -125,6 +128,151 @@ pub struct Remote {
     pub name: SharedString,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The position of the entry in the stash, as in `stash@{index}`.
+    pub index: usize,
+    pub sha: SharedString,
+    pub message: SharedString,
+    /// This is a unix timestamp
+    pub commit_timestamp: i64,
+}
+
+/// A file changed by a commit, relative to the commit's first parent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitFile {
+    pub path: RepoPath,
+    /// The contents of the file in the parent commit, or `None` if the commit adds it.
+    pub old_text: Option<String>,
+    /// The contents of the file in the commit, or `None` if the commit deletes it.
+    pub new_text: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    /// Only include commits that touch this path, following renames.
+    pub path: Option<RepoPath>,
+    /// Only include commits that touch these lines of `path`. Lines are 1-based.
+    pub line_range: Option<RangeInclusive<u32>>,
+    /// Only include commits whose author contains this text, ignoring case.
+    pub author: Option<String>,
+    /// Only include commits whose message contains this text, ignoring case.
+    pub message: Option<String>,
+    /// The number of matching commits to skip, for paging.
+    pub skip: usize,
+    /// The maximum number of commits to return.
+    pub limit: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommitOptions {
+    /// Replace the current HEAD commit instead of creating a new one on top of it.
+    pub amend: bool,
+    /// Create a `fixup!` commit for this commit, to be squashed into it by
+    /// `git rebase --autosquash`.
+    pub fixup: Option<String>,
+    pub signing: CommitSigning,
+}
+
+/// How commits are signed. Any field left unset falls back to the repository's git
+/// configuration.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CommitSigning {
+    /// Whether to sign commits, overriding `commit.gpgsign`.
+    pub enabled: Option<bool>,
+    /// The kind of signature to create, overriding `gpg.format`.
+    pub format: Option<SigningFormat>,
+    /// The key to sign with, overriding `user.signingkey`. For SSH signing this can be the
+    /// path to a public key.
+    pub key: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningFormat {
+    Openpgp,
+    Ssh,
+    X509,
+}
+
+impl SigningFormat {
+    /// The name git uses for this format in `gpg.format`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigningFormat::Openpgp => "openpgp",
+            SigningFormat::Ssh => "ssh",
+            SigningFormat::X509 => "x509",
+        }
+    }
+}
+
+/// What an interactive rebase does with a commit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebaseAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RebaseAction::Pick => "pick",
+            RebaseAction::Reword => "reword",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        }
+    }
+}
+
+impl std::str::FromStr for RebaseAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pick" => Ok(RebaseAction::Pick),
+            "reword" => Ok(RebaseAction::Reword),
+            "squash" => Ok(RebaseAction::Squash),
+            "fixup" => Ok(RebaseAction::Fixup),
+            "drop" => Ok(RebaseAction::Drop),
+            _ => Err(anyhow!("unknown rebase action {s:?}")),
+        }
+    }
+}
+
+/// A line of an interactive rebase's todo list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebaseTodoEntry {
+    pub action: RebaseAction,
+    pub sha: String,
+    /// The new message of a reworded commit. Ignored for other actions.
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebaseOutcome {
+    /// Every commit was applied and the branch now points at the result.
+    Completed,
+    /// The rebase stopped, usually on a conflict, and waits to be continued, skipped or aborted.
+    Stopped,
+}
+
+/// The state of a rebase that has stopped part of the way through.
+///
+/// `done` and `total` count the steps of git's own todo list, which has an extra step for each
+/// reworded commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebaseStatus {
+    /// The commit that couldn't be applied, if the rebase stopped on one.
+    pub stopped_at: Option<String>,
+    /// The number of steps handled so far, including the one it stopped at.
+    pub done: usize,
+    pub total: usize,
+}
+
 pub enum ResetMode {
     // reset the branch pointer, leave index and worktree unchanged
     // (this will make it look like things that were committed are now
@@ -172,6 +320,12 @@ pub trait GitRepository: Send + Sync {
 
     fn show(&self, commit: &str) -> Result<CommitDetails>;
 
+    /// Returns the commits reachable from HEAD that match the given options, most recent first.
+    fn log(&self, options: &LogOptions) -> Result<Vec<CommitDetails>>;
+
+    /// Returns the files changed by the given commit.
+    fn commit_files(&self, commit: &str) -> Result<Vec<CommitFile>>;
+
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
 
     /// Returns the absolute path to the repository. For worktrees, this will be the path to the
@@ -195,7 +349,12 @@ pub trait GitRepository: Send + Sync {
     /// If any of the paths were previously staged but do not exist in HEAD, they will be removed from the index.
     fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
 
-    fn commit(&self, message: &str, name_and_email: Option<(&str, &str)>) -> Result<()>;
+    fn commit(
+        &self,
+        message: &str,
+        name_and_email: Option<(&str, &str)>,
+        options: &CommitOptions,
+    ) -> Result<()>;
 
     fn push(
         &self,
@@ -217,6 +376,40 @@ pub trait GitRepository: Send + Sync {
 
     /// returns a list of remote branches that contain HEAD
     fn check_for_pushed_commit(&self) -> Result<Vec<SharedString>>;
+
+    /// Saves local changes to a new stash entry and reverts them in the worktree.
+    ///
+    /// If `paths` is non-empty, only changes to those paths are stashed. If `staged` is true,
+    /// only the changes in the index are stashed.
+    fn stash_push(&self, message: Option<&str>, paths: &[RepoPath], staged: bool) -> Result<()>;
+
+    /// Returns the stash entries, most recent first.
+    fn stash_list(&self) -> Result<Vec<StashEntry>>;
+
+    /// Returns the files changed by the stash entry at the given index.
+    fn stash_show(&self, index: usize) -> Result<Vec<CommitFile>>;
+
+    /// Applies, pops or drops the stash entry at the given index. These fail if the entry is no
+    /// longer the stash with the given SHA, since indexes shift whenever a stash is pushed or
+    /// dropped.
+    fn stash_apply(&self, index: usize, sha: &str) -> Result<()>;
+    fn stash_pop(&self, index: usize, sha: &str) -> Result<()>;
+    fn stash_drop(&self, index: usize, sha: &str) -> Result<()>;
+
+    /// Returns the commits an interactive rebase onto `base` would replay, oldest first.
+    fn rebase_commits(&self, base: &str) -> Result<Vec<CommitDetails>>;
+
+    /// Rebases the current branch onto `base`, handling the commits as `todo` lists them, in
+    /// that order.
+    fn rebase(&self, base: &str, todo: &[RebaseTodoEntry]) -> Result<RebaseOutcome>;
+
+    /// Resumes a stopped rebase. If `skip` is true, the commit it stopped at is left out.
+    fn rebase_continue(&self, skip: bool) -> Result<RebaseOutcome>;
+
+    fn rebase_abort(&self) -> Result<()>;
+
+    /// Returns the state of the rebase in progress, if there is one.
+    fn rebase_status(&self) -> Result<Option<RebaseStatus>>;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
@@ -257,6 +450,130 @@ impl RealGitRepository {
             .context("failed to read git work directory")
             .map(Path::to_path_buf)
     }
+
+    /// Builds a git command for running a rebase without any interaction. Editors that git opens
+    /// along the way, such as for the combined message of a squash, keep the text git proposes.
+    fn rebase_command(&self) -> Result<std::process::Command> {
+        let mut cmd = new_std_command(&self.git_binary_path);
+        cmd.current_dir(self.working_directory()?)
+            .env("GIT_EDITOR", "true")
+            .arg("rebase");
+        Ok(cmd)
+    }
+
+    fn rebase_outcome(&self, output: std::process::Output) -> Result<RebaseOutcome> {
+        if self.path().join("rebase-merge").exists() {
+            return Ok(RebaseOutcome::Stopped);
+        }
+        std::fs::remove_dir_all(self.path().join(REBASE_FILES_DIR)).ok();
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to rebase:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(RebaseOutcome::Completed)
+    }
+
+    fn run_stash_command(&self, subcommand: &str, index: usize, sha: &str) -> Result<()> {
+        let working_directory = self.working_directory()?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(stash_ref(index))
+            .output()?;
+        if String::from_utf8_lossy(&output.stdout).trim() != sha {
+            return Err(anyhow!(
+                "{} is no longer the selected stash. Reopen the stash and try again.",
+                stash_ref(index)
+            ));
+        }
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["stash", subcommand, "--quiet"])
+            .arg(stash_ref(index))
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to {subcommand} stash:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn stash_ref(index: usize) -> String {
+    format!("stash@{{{index}}}")
+}
+
+/// Where we keep the todo list and reworded messages of a rebase started from Zed, relative to
+/// the git directory.
+const REBASE_FILES_DIR: &str = "zed-rebase";
+
+/// Quotes an argument for the POSIX shell that git runs editors and `exec` lines with.
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// Formats the todo list git reads at the start of an interactive rebase, writing each reworded
+/// message to its own file in `files_dir`.
+fn write_rebase_todo(todo: &[RebaseTodoEntry], files_dir: &Path) -> Result<String> {
+    let mut text = String::new();
+    let mut has_previous_commit = false;
+    for (ix, entry) in todo.iter().enumerate() {
+        // Anything else could add lines, such as `exec`, to the todo list.
+        if !is_object_id(&entry.sha) {
+            return Err(anyhow!("{:?} is not a commit hash", entry.sha));
+        }
+        match entry.action {
+            RebaseAction::Squash | RebaseAction::Fixup if !has_previous_commit => {
+                return Err(anyhow!(
+                    "Can't {} {} without a previous commit to combine it with",
+                    entry.action.as_str(),
+                    entry.sha
+                ));
+            }
+            RebaseAction::Drop => {}
+            _ => has_previous_commit = true,
+        }
+
+        match (entry.action, &entry.message) {
+            // `reword` would open an editor, so the commit is picked and then amended with the
+            // new message instead.
+            (RebaseAction::Reword, Some(message)) => {
+                let message_path = files_dir.join(format!("reword-{ix}"));
+                std::fs::write(&message_path, message)?;
+                text.push_str(&format!(
+                    "pick {}\nexec git commit --amend --quiet --file={}\n",
+                    entry.sha,
+                    shell_quote(&message_path.to_string_lossy())
+                ));
+            }
+            (RebaseAction::Reword, None) => {
+                text.push_str(&format!("pick {}\n", entry.sha));
+            }
+            (action, _) => {
+                text.push_str(&format!("{} {}\n", action.as_str(), entry.sha));
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// Returns whether `sha` is a full SHA-1 or SHA-256 object id.
+fn is_object_id(sha: &str) -> bool {
+    matches!(sha.len(), 40 | 64) && sha.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Ensures a revision given to git as a positional argument isn't parsed as an option.
+fn validate_revision(revision: &str) -> Result<()> {
+    if revision.is_empty() || revision.starts_with('-') {
+        return Err(anyhow!("{revision:?} is not a valid revision"));
+    }
+    Ok(())
 }
 
 // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
@@ -290,6 +607,12 @@ impl GitRepository for RealGitRepository {
                 .to_string()
                 .into(),
             commit_timestamp: commit.time().seconds(),
+            author_email: String::from_utf8_lossy(commit.author().email_bytes())
+                .to_string()
+                .into(),
+            author_name: String::from_utf8_lossy(commit.author().name_bytes())
+                .to_string()
+                .into(),
             committer_email: String::from_utf8_lossy(commit.committer().email_bytes())
                 .to_string()
                 .into(),
@@ -300,6 +623,85 @@ impl GitRepository for RealGitRepository {
         Ok(details)
     }
 
+    fn log(&self, options: &LogOptions) -> Result<Vec<CommitDetails>> {
+        let working_directory = self.working_directory()?;
+
+        let mut cmd = new_std_command(&self.git_binary_path);
+        cmd.current_dir(&working_directory)
+            .args(["log", LOG_FORMAT, "--regexp-ignore-case", "--fixed-strings"])
+            .arg(format!("--skip={}", options.skip))
+            .arg(format!("--max-count={}", options.limit));
+        if let Some(author) = &options.author {
+            cmd.arg(format!("--author={author}"));
+        }
+        if let Some(message) = &options.message {
+            cmd.arg(format!("--grep={message}"));
+        }
+        match (&options.path, &options.line_range) {
+            (Some(path), Some(line_range)) => {
+                cmd.arg(format!(
+                    "-L{},{}:{}",
+                    line_range.start(),
+                    line_range.end(),
+                    path.to_string_lossy()
+                ));
+            }
+            (Some(path), None) => {
+                cmd.args(["--follow", "--"]).arg(path.as_ref());
+            }
+            (None, _) => {}
+        }
+
+        let output = cmd.output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to load history:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        parse_log(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn commit_files(&self, commit: &str) -> Result<Vec<CommitFile>> {
+        let repo = self.repository.lock();
+        let commit = repo.revparse_single(commit)?.peel_to_commit()?;
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+        let load_text = |file: git2::DiffFile| -> Result<Option<String>> {
+            if file.id().is_zero() || i32::from(file.mode()) == i32::from(git2::FileMode::Link) {
+                return Ok(None);
+            }
+            let content = repo.find_blob(file.id())?.content().to_owned();
+            Ok(Some(String::from_utf8(content)?))
+        };
+
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            // Binary files can't be previewed, so they are left out.
+            let (Some(old_text), Some(new_text)) = (
+                load_text(delta.old_file()).log_err(),
+                load_text(delta.new_file()).log_err(),
+            ) else {
+                continue;
+            };
+            files.push(CommitFile {
+                path: RepoPath::from(path),
+                old_text,
+                new_text,
+            });
+        }
+        Ok(files)
+    }
+
     fn reset(&self, commit: &str, mode: ResetMode) -> Result<()> {
         let working_directory = self.working_directory()?;
 
@@ -617,14 +1019,60 @@ impl GitRepository for RealGitRepository {
         Ok(())
     }
 
-    fn commit(&self, message: &str, name_and_email: Option<(&str, &str)>) -> Result<()> {
+    fn commit(
+        &self,
+        message: &str,
+        name_and_email: Option<(&str, &str)>,
+        options: &CommitOptions,
+    ) -> Result<()> {
+        if options.amend && options.fixup.is_some() {
+            return Err(anyhow!(
+                "Can't amend and create a fixup commit at the same time"
+            ));
+        }
         let working_directory = self.working_directory()?;
 
         let mut cmd = new_std_command(&self.git_binary_path);
-        cmd.current_dir(&working_directory)
-            .args(["commit", "--quiet", "-m"])
-            .arg(message)
-            .arg("--cleanup=strip");
+        cmd.current_dir(&working_directory);
+        if let Some(format) = options.signing.format {
+            cmd.arg("-c").arg(format!("gpg.format={}", format.as_str()));
+        }
+        if let Some(key) = &options.signing.key {
+            // Git doesn't expand `~` in the signing key, but SSH keys are usually given as paths
+            // in the home directory.
+            let key = match key.strip_prefix("~/") {
+                Some(relative) => util::paths::home_dir().join(relative).display().to_string(),
+                None => key.clone(),
+            };
+            cmd.arg("-c").arg(format!("user.signingkey={key}"));
+        }
+        cmd.args(["commit", "--quiet", "--cleanup=strip"]);
+
+        match &options.fixup {
+            Some(commit) => {
+                cmd.arg(format!("--fixup={commit}"));
+                // The message of a fixup commit is generated from its target, so anything
+                // given here only adds to its body.
+                if !message.trim().is_empty() {
+                    cmd.arg("-m").arg(message);
+                }
+            }
+            None => {
+                cmd.arg("-m").arg(message);
+            }
+        }
+        if options.amend {
+            cmd.arg("--amend");
+        }
+        match options.signing.enabled {
+            Some(true) => {
+                cmd.arg("--gpg-sign");
+            }
+            Some(false) => {
+                cmd.arg("--no-gpg-sign");
+            }
+            None => {}
+        }
 
         if let Some((name, email)) = name_and_email {
             cmd.arg("--author").arg(&format!("{name} <{email}>"));
@@ -794,6 +1242,142 @@ impl GitRepository for RealGitRepository {
 
         Ok(remote_branches)
     }
+
+    fn stash_push(&self, message: Option<&str>, paths: &[RepoPath], staged: bool) -> Result<()> {
+        let working_directory = self.working_directory()?;
+
+        let mut cmd = new_std_command(&self.git_binary_path);
+        cmd.current_dir(&working_directory)
+            .args(["stash", "push", "--quiet"]);
+        if staged {
+            cmd.arg("--staged");
+        }
+        if let Some(message) = message {
+            cmd.arg("--message").arg(message);
+        }
+        if !paths.is_empty() {
+            cmd.arg("--").args(paths.iter().map(|p| p.as_ref()));
+        }
+
+        let output = cmd.output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to stash changes:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let working_directory = self.working_directory()?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["stash", "list", "--format=%H%x00%ct%x00%gs"])
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to list stashes:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        parse_stash_list(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn stash_show(&self, index: usize) -> Result<Vec<CommitFile>> {
+        self.commit_files(&stash_ref(index))
+    }
+
+    fn stash_apply(&self, index: usize, sha: &str) -> Result<()> {
+        self.run_stash_command("apply", index, sha)
+    }
+
+    fn stash_pop(&self, index: usize, sha: &str) -> Result<()> {
+        self.run_stash_command("pop", index, sha)
+    }
+
+    fn stash_drop(&self, index: usize, sha: &str) -> Result<()> {
+        self.run_stash_command("drop", index, sha)
+    }
+
+    fn rebase_commits(&self, base: &str) -> Result<Vec<CommitDetails>> {
+        validate_revision(base)?;
+        let working_directory = self.working_directory()?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["log", LOG_FORMAT, "--reverse", "--no-merges"])
+            .arg(format!("{base}..HEAD"))
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to list commits to rebase:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        parse_log(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn rebase(&self, base: &str, todo: &[RebaseTodoEntry]) -> Result<RebaseOutcome> {
+        validate_revision(base)?;
+        let git_dir = self.path();
+        if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+            return Err(anyhow!("A rebase is already in progress"));
+        }
+
+        let files_dir = git_dir.join(REBASE_FILES_DIR);
+        std::fs::remove_dir_all(&files_dir).ok();
+        std::fs::create_dir_all(&files_dir)?;
+        let todo_path = files_dir.join("todo");
+        std::fs::write(&todo_path, write_rebase_todo(todo, &files_dir)?)?;
+
+        // Git opens the sequence editor on its own todo list, which we replace with ours.
+        let output = self
+            .rebase_command()?
+            .env(
+                "GIT_SEQUENCE_EDITOR",
+                format!("cp {}", shell_quote(&todo_path.to_string_lossy())),
+            )
+            .args(["--interactive", base])
+            .output()?;
+        self.rebase_outcome(output)
+    }
+
+    fn rebase_continue(&self, skip: bool) -> Result<RebaseOutcome> {
+        let output = self
+            .rebase_command()?
+            .arg(if skip { "--skip" } else { "--continue" })
+            .output()?;
+        self.rebase_outcome(output)
+    }
+
+    fn rebase_abort(&self) -> Result<()> {
+        let output = self.rebase_command()?.arg("--abort").output()?;
+        std::fs::remove_dir_all(self.path().join(REBASE_FILES_DIR)).ok();
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to abort rebase:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+
+    fn rebase_status(&self) -> Result<Option<RebaseStatus>> {
+        let rebase_dir = self.path().join("rebase-merge");
+        if !rebase_dir.exists() {
+            return Ok(None);
+        }
+        let read = |name: &str| std::fs::read_to_string(rebase_dir.join(name));
+        Ok(Some(RebaseStatus {
+            stopped_at: read("stopped-sha").ok().map(|sha| sha.trim().to_string()),
+            done: read("msgnum")?.trim().parse()?,
+            total: read("end")?.trim().parse()?,
+        }))
+    }
 }
 
 fn run_remote_command(
@@ -846,6 +1430,18 @@ pub struct FakeGitRepositoryState {
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
     pub simulated_index_write_error_message: Option<String>,
+    /// Stash entries, most recent first.
+    pub stashes: Vec<FakeStash>,
+    /// Commits reachable from HEAD, most recent first.
+    pub commits: Vec<CommitDetails>,
+    pub commit_files: HashMap<String, Vec<CommitFile>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeStash {
+    pub sha: String,
+    pub message: String,
+    pub files: Vec<CommitFile>,
 }
 
 impl FakeGitRepository {
@@ -866,10 +1462,41 @@ impl FakeGitRepositoryState {
             current_branch_name: Default::default(),
             branches: Default::default(),
             simulated_index_write_error_message: None,
+            stashes: Default::default(),
+            commits: Default::default(),
+            commit_files: Default::default(),
         }
     }
 }
 
+impl FakeGitRepository {
+    fn check_stash(state: &FakeGitRepositoryState, index: usize, sha: &str) -> Result<FakeStash> {
+        let stash = state
+            .stashes
+            .get(index)
+            .with_context(|| format!("{} is not a valid stash", stash_ref(index)))?;
+        if stash.sha != sha {
+            return Err(anyhow!(
+                "{} is no longer the selected stash. Reopen the stash and try again.",
+                stash_ref(index)
+            ));
+        }
+        Ok(stash.clone())
+    }
+
+    fn apply_stash(state: &mut FakeGitRepositoryState, index: usize, sha: &str) -> Result<()> {
+        let stash = Self::check_stash(state, index, sha)?;
+        for file in stash.files {
+            if let Some(new_text) = file.new_text {
+                state.index_contents.insert(file.path, new_text);
+            } else {
+                state.index_contents.remove(&file.path);
+            }
+        }
+        Ok(())
+    }
+}
+
 impl GitRepository for FakeGitRepository {
     fn reload_index(&self) {}
 
@@ -912,8 +1539,52 @@ impl GitRepository for FakeGitRepository {
         vec![]
     }
 
-    fn show(&self, _: &str) -> Result<CommitDetails> {
-        unimplemented!()
+    fn show(&self, commit: &str) -> Result<CommitDetails> {
+        let state = self.state.lock();
+        state
+            .commits
+            .iter()
+            .find(|details| details.sha.starts_with(commit))
+            .cloned()
+            .with_context(|| format!("{commit} is not a commit"))
+    }
+
+    fn log(&self, options: &LogOptions) -> Result<Vec<CommitDetails>> {
+        let state = self.state.lock();
+        let contains = |text: &str, filter: &Option<String>| {
+            filter.as_ref().map_or(true, |filter| {
+                text.to_lowercase().contains(&filter.to_lowercase())
+            })
+        };
+        Ok(state
+            .commits
+            .iter()
+            .filter(|details| {
+                // Like `--author`, match against the whole "name <email>" identity.
+                let author = format!("{} <{}>", details.author_name, details.author_email);
+                contains(&author, &options.author) && contains(&details.message, &options.message)
+            })
+            .filter(|details| {
+                options.path.as_ref().map_or(true, |path| {
+                    state
+                        .commit_files
+                        .get(&*details.sha)
+                        .is_some_and(|files| files.iter().any(|file| &file.path == path))
+                })
+            })
+            .skip(options.skip)
+            .take(options.limit)
+            .cloned()
+            .collect())
+    }
+
+    fn commit_files(&self, commit: &str) -> Result<Vec<CommitFile>> {
+        let state = self.state.lock();
+        state
+            .commit_files
+            .get(commit)
+            .cloned()
+            .with_context(|| format!("{commit} is not a commit"))
     }
 
     fn reset(&self, _: &str, _: ResetMode) -> Result<()> {
@@ -1014,7 +1685,12 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
-    fn commit(&self, _message: &str, _name_and_email: Option<(&str, &str)>) -> Result<()> {
+    fn commit(
+        &self,
+        _message: &str,
+        _name_and_email: Option<(&str, &str)>,
+        _options: &CommitOptions,
+    ) -> Result<()> {
         unimplemented!()
     }
 
@@ -1048,6 +1724,138 @@ impl GitRepository for FakeGitRepository {
     fn check_for_pushed_commit(&self) -> Result<Vec<SharedString>> {
         unimplemented!()
     }
+
+    fn stash_push(&self, message: Option<&str>, paths: &[RepoPath], _staged: bool) -> Result<()> {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+        let mut files = state
+            .index_contents
+            .iter()
+            .filter(|(path, _)| paths.is_empty() || paths.contains(path))
+            .filter(|(path, text)| state.head_contents.get(*path) != Some(*text))
+            .map(|(path, text)| CommitFile {
+                path: path.clone(),
+                old_text: state.head_contents.get(path).cloned(),
+                new_text: Some(text.clone()),
+            })
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        if files.is_empty() {
+            return Err(anyhow!("No local changes to save"));
+        }
+
+        for file in &files {
+            if let Some(old_text) = &file.old_text {
+                state
+                    .index_contents
+                    .insert(file.path.clone(), old_text.clone());
+            } else {
+                state.index_contents.remove(&file.path);
+            }
+            state.statuses.remove(&file.path);
+        }
+        let message = message.map_or_else(
+            || {
+                let branch = state
+                    .current_branch_name
+                    .as_deref()
+                    .unwrap_or("(no branch)");
+                format!("WIP on {branch}")
+            },
+            |message| message.to_string(),
+        );
+        static NEXT_STASH_ID: std::sync::atomic::AtomicUsize =
+            std::sync::atomic::AtomicUsize::new(0);
+        let sha = format!(
+            "{:040x}",
+            NEXT_STASH_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        );
+        state.stashes.insert(
+            0,
+            FakeStash {
+                sha,
+                message,
+                files,
+            },
+        );
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let state = self.state.lock();
+        Ok(state
+            .stashes
+            .iter()
+            .enumerate()
+            .map(|(index, stash)| StashEntry {
+                index,
+                sha: stash.sha.clone().into(),
+                message: stash.message.clone().into(),
+                commit_timestamp: 0,
+            })
+            .collect())
+    }
+
+    fn stash_show(&self, index: usize) -> Result<Vec<CommitFile>> {
+        let state = self.state.lock();
+        state
+            .stashes
+            .get(index)
+            .map(|stash| stash.files.clone())
+            .with_context(|| format!("{} is not a valid stash", stash_ref(index)))
+    }
+
+    fn stash_apply(&self, index: usize, sha: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        Self::apply_stash(&mut state, index, sha)?;
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize, sha: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        Self::apply_stash(&mut state, index, sha)?;
+        state.stashes.remove(index);
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_drop(&self, index: usize, sha: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        Self::check_stash(&state, index, sha)?;
+        state.stashes.remove(index);
+        Ok(())
+    }
+
+    fn rebase_commits(&self, _base: &str) -> Result<Vec<CommitDetails>> {
+        unimplemented!()
+    }
+
+    fn rebase(&self, _base: &str, _todo: &[RebaseTodoEntry]) -> Result<RebaseOutcome> {
+        unimplemented!()
+    }
+
+    fn rebase_continue(&self, _skip: bool) -> Result<RebaseOutcome> {
+        unimplemented!()
+    }
+
+    fn rebase_abort(&self) -> Result<()> {
+        unimplemented!()
+    }
+
+    fn rebase_status(&self) -> Result<Option<RebaseStatus>> {
+        Ok(None)
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -1218,6 +2026,72 @@ fn parse_branch_input(input: &str) -> Result<Vec<Branch>> {
     Ok(branches)
 }
 
+/// The `git log` format that [`parse_log`] reads.
+const LOG_FORMAT: &str = "--format=%x1e%H%x00%ct%x00%ae%x00%an%x00%ce%x00%cn%x00%B%x00";
+
+fn parse_log(input: &str) -> Result<Vec<CommitDetails>> {
+    let mut commits = Vec::new();
+    for record in input.split('\x1e') {
+        if record.trim().is_empty() {
+            continue;
+        }
+        // Anything after the last field is the patch that `-L` always prints.
+        let mut fields = record.split('\x00');
+        let sha: SharedString = fields.next().context("no commit hash")?.to_string().into();
+        let commit_timestamp = fields.next().context("no commit date")?.parse::<i64>()?;
+        let author_email: SharedString =
+            fields.next().context("no author email")?.to_string().into();
+        let author_name: SharedString = fields.next().context("no author name")?.to_string().into();
+        let committer_email: SharedString = fields
+            .next()
+            .context("no committer email")?
+            .to_string()
+            .into();
+        let committer_name: SharedString = fields
+            .next()
+            .context("no committer name")?
+            .to_string()
+            .into();
+        let message: SharedString = fields
+            .next()
+            .context("no commit message")?
+            .trim_end()
+            .to_string()
+            .into();
+        commits.push(CommitDetails {
+            sha,
+            message,
+            commit_timestamp,
+            author_email,
+            author_name,
+            committer_email,
+            committer_name,
+        });
+    }
+    Ok(commits)
+}
+
+fn parse_stash_list(input: &str) -> Result<Vec<StashEntry>> {
+    let mut entries = Vec::new();
+    for (index, line) in input.lines().filter(|line| !line.is_empty()).enumerate() {
+        let mut fields = line.split('\x00');
+        let sha: SharedString = fields.next().context("no commit hash")?.to_string().into();
+        let commit_timestamp = fields.next().context("no commit date")?.parse::<i64>()?;
+        let message: SharedString = fields
+            .next()
+            .context("no reflog subject")?
+            .to_string()
+            .into();
+        entries.push(StashEntry {
+            index,
+            sha,
+            message,
+            commit_timestamp,
+        });
+    }
+    Ok(entries)
+}
+
 fn parse_upstream_track(upstream_track: &str) -> Result<UpstreamTracking> {
     if upstream_track == "" {
         return Ok(UpstreamTracking::Tracked(UpstreamTrackingStatus {
@@ -1277,3 +2151,299 @@ fn test_branches_parsing() {
         }]
     )
 }
+
+#[test]
+fn test_stash_list_parsing() {
+    let input = "\
+        8a3f1c2e9b7d6a5f4e3d2c1b0a9f8e7d6c5b4a39\x001741000000\x00On main: try new layout\n\
+        0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c\x001740000000\x00WIP on main: 060964d fix typo\n";
+    assert_eq!(
+        parse_stash_list(input).unwrap(),
+        vec![
+            StashEntry {
+                index: 0,
+                sha: "8a3f1c2e9b7d6a5f4e3d2c1b0a9f8e7d6c5b4a39".into(),
+                message: "On main: try new layout".into(),
+                commit_timestamp: 1741000000,
+            },
+            StashEntry {
+                index: 1,
+                sha: "0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c".into(),
+                message: "WIP on main: 060964d fix typo".into(),
+                commit_timestamp: 1740000000,
+            },
+        ]
+    )
+}
+
+#[test]
+fn test_log_parsing() {
+    let input = "\
+        \x1e8a3f1c2e9b7d6a5f4e3d2c1b0a9f8e7d6c5b4a39\x001741000000\x00ada@example.com\x00Ada\x00carol@example.com\x00Carol\x00Fix typo\n\nIn the readme.\n\x00\n\
+        \x1e0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c\x001740000000\x00bob@example.com\x00Bob\x00bob@example.com\x00Bob\x00Initial commit\n\x00\n\
+        diff --git a/README.md b/README.md\n";
+    assert_eq!(
+        parse_log(input).unwrap(),
+        vec![
+            CommitDetails {
+                sha: "8a3f1c2e9b7d6a5f4e3d2c1b0a9f8e7d6c5b4a39".into(),
+                message: "Fix typo\n\nIn the readme.".into(),
+                commit_timestamp: 1741000000,
+                author_email: "ada@example.com".into(),
+                author_name: "Ada".into(),
+                committer_email: "carol@example.com".into(),
+                committer_name: "Carol".into(),
+            },
+            CommitDetails {
+                sha: "0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c".into(),
+                message: "Initial commit".into(),
+                commit_timestamp: 1740000000,
+                author_email: "bob@example.com".into(),
+                author_name: "Bob".into(),
+                committer_email: "bob@example.com".into(),
+                committer_name: "Bob".into(),
+            },
+        ]
+    )
+}
+
+#[cfg(test)]
+fn rebase_test_repository(dir: &Path) -> (RealGitRepository, git2::Repository) {
+    let repo = git2::Repository::init(dir).expect("Failed to initialize git repository");
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "test").unwrap();
+    config.set_str("user.email", "test@zed.dev").unwrap();
+    config.set_bool("commit.gpgsign", false).unwrap();
+    let real_repo = RealGitRepository::new(
+        git2::Repository::open(dir).unwrap(),
+        None,
+        Arc::new(GitHostingProviderRegistry::new()),
+    );
+    (real_repo, repo)
+}
+
+#[cfg(test)]
+#[track_caller]
+fn rebase_test_commit(
+    repo: &git2::Repository,
+    path: &str,
+    contents: &str,
+    message: &str,
+) -> String {
+    std::fs::write(repo.workdir().unwrap().join(path), contents).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(path)).unwrap();
+    index.write().unwrap();
+
+    let signature = git2::Signature::now("test", "test@zed.dev").unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        parent.as_ref().into_iter().collect::<Vec<_>>().as_slice(),
+    )
+    .unwrap()
+    .to_string()
+}
+
+#[test]
+fn test_rebase() {
+    let dir = util::test::TempTree::new(serde_json::json!({}));
+    let (real_repo, repo) = rebase_test_repository(dir.path());
+    let base = rebase_test_commit(&repo, "a.txt", "a\n", "Add a");
+    let add_b = rebase_test_commit(&repo, "b.txt", "b\n", "Add b");
+    let add_c = rebase_test_commit(&repo, "c.txt", "c\n", "Add c");
+    let change_c = rebase_test_commit(&repo, "c.txt", "c2\n", "Change c");
+    let add_d = rebase_test_commit(&repo, "d.txt", "d\n", "Add d");
+
+    let commits = real_repo.rebase_commits(&base).unwrap();
+    assert_eq!(
+        commits
+            .iter()
+            .map(|commit| commit.sha.to_string())
+            .collect::<Vec<_>>(),
+        vec![
+            add_b.clone(),
+            add_c.clone(),
+            change_c.clone(),
+            add_d.clone()
+        ]
+    );
+
+    let outcome = real_repo
+        .rebase(
+            &base,
+            &[
+                RebaseTodoEntry {
+                    action: RebaseAction::Reword,
+                    sha: add_c,
+                    message: Some("Add c, reworded".into()),
+                },
+                RebaseTodoEntry {
+                    action: RebaseAction::Fixup,
+                    sha: change_c,
+                    message: None,
+                },
+                RebaseTodoEntry {
+                    action: RebaseAction::Pick,
+                    sha: add_b,
+                    message: None,
+                },
+                RebaseTodoEntry {
+                    action: RebaseAction::Drop,
+                    sha: add_d,
+                    message: None,
+                },
+            ],
+        )
+        .unwrap();
+    assert_eq!(outcome, RebaseOutcome::Completed);
+    assert_eq!(real_repo.rebase_status().unwrap(), None);
+    assert_eq!(
+        real_repo
+            .rebase_commits(&base)
+            .unwrap()
+            .iter()
+            .map(|commit| commit.message.to_string())
+            .collect::<Vec<_>>(),
+        vec!["Add c, reworded", "Add b"]
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("c.txt")).unwrap(),
+        "c2\n"
+    );
+    assert!(!dir.path().join("d.txt").exists());
+    assert!(!real_repo.path().join(REBASE_FILES_DIR).exists());
+}
+
+#[test]
+fn test_rebase_rejects_injected_arguments() {
+    let dir = util::test::TempTree::new(serde_json::json!({}));
+    let (real_repo, repo) = rebase_test_repository(dir.path());
+    let base = rebase_test_commit(&repo, "a.txt", "a\n", "Add a");
+    let add_b = rebase_test_commit(&repo, "b.txt", "b\n", "Add b");
+    let head = repo.head().unwrap().target();
+    let pwned = dir.path().join("pwned");
+
+    // A hash that smuggles an `exec` line into the todo list.
+    let result = real_repo.rebase(
+        &base,
+        &[RebaseTodoEntry {
+            action: RebaseAction::Pick,
+            sha: format!("{add_b}\nexec touch {}", pwned.display()),
+            message: None,
+        }],
+    );
+    assert!(result.is_err());
+
+    // A base that git would parse as an option.
+    let malicious_base = format!("--exec=touch {}", pwned.display());
+    assert!(real_repo.rebase_commits(&malicious_base).is_err());
+    let result = real_repo.rebase(
+        &malicious_base,
+        &[RebaseTodoEntry {
+            action: RebaseAction::Pick,
+            sha: add_b,
+            message: None,
+        }],
+    );
+    assert!(result.is_err());
+
+    assert!(!pwned.exists());
+    assert_eq!(real_repo.rebase_status().unwrap(), None);
+    assert_eq!(repo.head().unwrap().target(), head);
+}
+
+#[test]
+fn test_rebase_squash_without_previous_commit() {
+    let dir = util::test::TempTree::new(serde_json::json!({}));
+    let (real_repo, repo) = rebase_test_repository(dir.path());
+    let base = rebase_test_commit(&repo, "a.txt", "a\n", "Add a");
+    let add_b = rebase_test_commit(&repo, "b.txt", "b\n", "Add b");
+    let head = repo.head().unwrap().target();
+
+    let result = real_repo.rebase(
+        &base,
+        &[RebaseTodoEntry {
+            action: RebaseAction::Squash,
+            sha: add_b,
+            message: None,
+        }],
+    );
+    assert!(result.is_err());
+    assert_eq!(real_repo.rebase_status().unwrap(), None);
+    assert_eq!(repo.head().unwrap().target(), head);
+}
+
+#[test]
+fn test_rebase_conflicts() {
+    let dir = util::test::TempTree::new(serde_json::json!({}));
+    let (real_repo, repo) = rebase_test_repository(dir.path());
+    let base = rebase_test_commit(&repo, "a.txt", "one\n", "One");
+    let two = rebase_test_commit(&repo, "a.txt", "two\n", "Two");
+    let three = rebase_test_commit(&repo, "a.txt", "three\n", "Three");
+    let head = repo.head().unwrap().target();
+
+    // Applying "Three" before "Two" conflicts, since it changes the line "Two" introduces.
+    let swapped = [
+        RebaseTodoEntry {
+            action: RebaseAction::Pick,
+            sha: three.clone(),
+            message: None,
+        },
+        RebaseTodoEntry {
+            action: RebaseAction::Pick,
+            sha: two,
+            message: None,
+        },
+    ];
+
+    assert_eq!(
+        real_repo.rebase(&base, &swapped).unwrap(),
+        RebaseOutcome::Stopped
+    );
+    assert_eq!(
+        real_repo.rebase_status().unwrap(),
+        Some(RebaseStatus {
+            stopped_at: Some(three.clone()),
+            done: 1,
+            total: 2,
+        })
+    );
+    assert!(real_repo.rebase(&base, &swapped).is_err());
+
+    real_repo.rebase_abort().unwrap();
+    assert_eq!(real_repo.rebase_status().unwrap(), None);
+    assert_eq!(repo.head().unwrap().target(), head);
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+        "three\n"
+    );
+
+    assert_eq!(
+        real_repo.rebase(&base, &swapped).unwrap(),
+        RebaseOutcome::Stopped
+    );
+    assert_eq!(
+        real_repo.rebase_continue(true).unwrap(),
+        RebaseOutcome::Completed
+    );
+    assert_eq!(real_repo.rebase_status().unwrap(), None);
+    assert_eq!(
+        real_repo
+            .rebase_commits(&base)
+            .unwrap()
+            .iter()
+            .map(|commit| commit.message.to_string())
+            .collect::<Vec<_>>(),
+        vec!["Two"]
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+        "two\n"
+    );
+}
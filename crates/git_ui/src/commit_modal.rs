@@ -112,6 +112,17 @@ impl CommitModal {
         workspace.register_action(|workspace, _: &Commit, window, cx| {
             CommitModal::toggle(workspace, window, cx);
         });
+        workspace.register_action(|workspace, _: &git::Amend, window, cx| {
+            let Some(git_panel) = workspace.panel::<GitPanel>(cx) else {
+                return;
+            };
+            git_panel.update(cx, |git_panel, cx| {
+                git_panel.set_amend_pending(true, window, cx);
+            });
+            if workspace.active_modal::<CommitModal>(cx).is_none() {
+                CommitModal::toggle(workspace, window, cx);
+            }
+        });
     }
 
     pub fn toggle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<'_, Workspace>) {
@@ -234,7 +245,7 @@ impl CommitModal {
     pub fn render_footer(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let git_panel = self.git_panel.clone();
 
-        let (branch, can_commit, tooltip, commit_label, co_authors) =
+        let (branch, can_commit, tooltip, commit_label, amend_toggle, co_authors) =
             self.git_panel.update(cx, |git_panel, cx| {
                 let branch = git_panel
                     .active_repository
@@ -248,8 +259,9 @@ impl CommitModal {
                     .unwrap_or_else(|| "<no branch>".into());
                 let (can_commit, tooltip) = git_panel.configure_commit_button(cx);
                 let title = git_panel.commit_button_title();
+                let amend_toggle = git_panel.render_amend_toggle(cx);
                 let co_authors = git_panel.render_co_authors(cx);
-                (branch, can_commit, tooltip, title, co_authors)
+                (branch, can_commit, tooltip, title, amend_toggle, co_authors)
             });
 
         let branch_picker_button = panel_button(branch)
@@ -315,7 +327,13 @@ impl CommitModal {
             .w_full()
             .h(px(self.properties.footer_height))
             .gap_1()
-            .child(h_flex().gap_1().child(branch_picker).children(co_authors))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(branch_picker)
+                    .children(amend_toggle)
+                    .children(co_authors),
+            )
             .child(div().flex_1())
             .child(
                 h_flex()
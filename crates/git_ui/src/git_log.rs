@@ -0,0 +1,611 @@
+use std::{
+    ops::{Range, RangeInclusive},
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use buffer_diff::BufferDiffSnapshot;
+use editor::{Editor, EditorEvent};
+use git::repository::{CommitDetails, LogOptions, RepoPath};
+use gpui::{
+    uniform_list, AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ScrollStrategy,
+    Subscription, Task, UniformListScrollHandle, WeakEntity,
+};
+use language::{Anchor, Buffer, Point, TextBufferSnapshot};
+use menu::{Confirm, SelectNext, SelectPrevious};
+use project::git::Repository;
+use theme::ActiveTheme;
+use time::OffsetDateTime;
+use time_format::TimestampFormat;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use workspace::{
+    item::{Item, ItemEvent},
+    notifications::DetachAndPromptErr,
+    Workspace,
+};
+
+use crate::project_diff::ProjectDiff;
+
+/// The number of commits requested from the repository at a time.
+const PAGE_SIZE: usize = 100;
+/// How many rows from the end of the loaded commits the list may get before the next page is
+/// requested.
+const LOAD_MORE_THRESHOLD: usize = 20;
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(250);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(view_history);
+        workspace.register_action(view_file_history);
+        workspace.register_action(view_line_history);
+    })
+    .detach();
+}
+
+fn view_history(
+    workspace: &mut Workspace,
+    _: &git::ViewHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    GitLog::deploy(workspace, repository, None, None, window, cx);
+}
+
+fn view_file_history(
+    workspace: &mut Workspace,
+    _: &git::ViewFileHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some((repository, path, _, _)) = active_editor_selection(workspace, cx) else {
+        return;
+    };
+    GitLog::deploy(workspace, repository, Some(path), None, window, cx);
+}
+
+fn view_line_history(
+    workspace: &mut Workspace,
+    _: &git::ViewLineHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some((repository, path, buffer, rows)) = active_editor_selection(workspace, cx) else {
+        return;
+    };
+    // `git log -L` reads the line numbers from the committed file, which the buffer's
+    // uncommitted changes may have shifted.
+    let diff = workspace.project().update(cx, |project, cx| {
+        project.open_uncommitted_diff(buffer.clone(), cx)
+    });
+    cx.spawn_in(window, |workspace, mut cx| async move {
+        let diff = diff.await?;
+        let head_rows = cx.update(|_, cx| {
+            let buffer = buffer.read(cx).text_snapshot();
+            rows_in_head(&diff.read(cx).snapshot(cx), &buffer, rows)
+        })?;
+        let head_rows =
+            head_rows.ok_or_else(|| anyhow!("The selected lines haven't been committed yet"))?;
+        // Git expects one-based, inclusive line numbers.
+        let line_range = head_rows.start() + 1..=head_rows.end() + 1;
+        workspace.update_in(&mut cx, |workspace, window, cx| {
+            GitLog::deploy(
+                workspace,
+                repository,
+                Some(path),
+                Some(line_range),
+                window,
+                cx,
+            );
+        })
+    })
+    .detach_and_prompt_err("Failed to view line history", window, cx, |_, _, _| None);
+}
+
+/// Maps the given rows of the buffer, inclusive, to the rows of the same lines in the diff's base
+/// text. Selected lines that were changed map to the lines they replaced, and `None` is returned
+/// when all of them were added.
+fn rows_in_head(
+    diff: &BufferDiffSnapshot,
+    buffer: &TextBufferSnapshot,
+    rows: Range<u32>,
+) -> Option<RangeInclusive<u32>> {
+    // The rows a range of points spans, including a final line without a newline.
+    let row_range =
+        |range: Range<Point>| range.start.row..range.end.row + (range.end.column > 0) as u32;
+    let base_text = diff.base_text();
+    let to_head_row = |row: u32, last: bool| {
+        // How many rows the lines after the hunks seen so far moved by.
+        let mut delta = 0;
+        for hunk in diff.hunks_intersecting_range(Anchor::MIN..Anchor::MAX, buffer) {
+            let buffer_rows = row_range(hunk.range);
+            let base_rows = row_range(
+                base_text.offset_to_point(hunk.diff_base_byte_range.start)
+                    ..base_text.offset_to_point(hunk.diff_base_byte_range.end),
+            );
+            if row < buffer_rows.start {
+                break;
+            }
+            if row < buffer_rows.end {
+                return if last {
+                    base_rows.end as i64 - 1
+                } else {
+                    base_rows.start as i64
+                };
+            }
+            delta = base_rows.end as i64 - buffer_rows.end as i64;
+        }
+        row as i64 + delta
+    };
+
+    let start = to_head_row(rows.start, false);
+    let end = to_head_row(rows.end, true);
+    (start <= end).then(|| start as u32..=end as u32)
+}
+
+/// Returns the repository and path of the buffer under the active editor's newest selection,
+/// along with the buffer and the rows that selection spans.
+fn active_editor_selection(
+    workspace: &Workspace,
+    cx: &mut Context<Workspace>,
+) -> Option<(Entity<Repository>, RepoPath, Entity<Buffer>, Range<u32>)> {
+    let editor = workspace.active_item_as::<Editor>(cx)?;
+    let (buffer, rows) = editor.update(cx, |editor, cx| editor.newest_selection_buffer_rows(cx))?;
+    let buffer_id = buffer.read(cx).remote_id();
+    let (repository, path) = workspace
+        .project()
+        .read(cx)
+        .git_store()
+        .read(cx)
+        .repository_and_path_for_buffer_id(buffer_id, cx)?;
+    Some((repository, path, buffer, rows))
+}
+
+/// A paged list of a repository's commits, optionally filtered by path, line range, author and
+/// message.
+pub struct GitLog {
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    path_editor: Entity<Editor>,
+    author_editor: Entity<Editor>,
+    message_editor: Entity<Editor>,
+    /// The lines whose history is shown, which only apply while the path filter still names the
+    /// file they were selected in.
+    line_range: Option<(RepoPath, RangeInclusive<u32>)>,
+    commits: Vec<CommitDetails>,
+    selected_index: Option<usize>,
+    has_more: bool,
+    error: Option<SharedString>,
+    scroll_handle: UniformListScrollHandle,
+    load_task: Option<Task<()>>,
+    filter_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl GitLog {
+    /// Opens the history of the given repository, reusing an existing log with the same filters.
+    pub fn deploy(
+        workspace: &mut Workspace,
+        repository: Entity<Repository>,
+        path: Option<RepoPath>,
+        line_range: Option<RangeInclusive<u32>>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace.items_of_type::<Self>(cx).find(|item| {
+            item.read(cx)
+                .has_filters(&repository, path.as_ref(), line_range.as_ref(), cx)
+        });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let workspace_handle = cx.entity().downgrade();
+        let git_log =
+            cx.new(|cx| Self::new(repository, path, line_range, workspace_handle, window, cx));
+        workspace.add_item_to_active_pane(Box::new(git_log), None, true, window, cx);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        path: Option<RepoPath>,
+        line_range: Option<RangeInclusive<u32>>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let filter_editor =
+            |placeholder: &str, text: Option<String>, window: &mut Window, cx: &mut App| {
+                cx.new(|cx| {
+                    let mut editor = Editor::single_line(window, cx);
+                    editor.set_placeholder_text(placeholder, cx);
+                    if let Some(text) = text {
+                        editor.set_text(text, window, cx);
+                    }
+                    editor
+                })
+            };
+        let path_editor = filter_editor(
+            "Path",
+            path.as_ref().map(|path| path.to_string_lossy().to_string()),
+            window,
+            cx,
+        );
+        let author_editor = filter_editor("Author", None, window, cx);
+        let message_editor = filter_editor("Message", None, window, cx);
+
+        let _subscriptions = [&path_editor, &author_editor, &message_editor]
+            .into_iter()
+            .map(|editor| cx.subscribe(editor, Self::handle_filter_event))
+            .collect();
+
+        let mut this = Self {
+            repository,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            path_editor,
+            author_editor,
+            message_editor,
+            line_range: path.zip(line_range),
+            commits: Vec::new(),
+            selected_index: None,
+            has_more: true,
+            error: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            load_task: None,
+            filter_task: None,
+            _subscriptions,
+        };
+        this.reload(cx);
+        this
+    }
+
+    fn handle_filter_event(
+        &mut self,
+        _: Entity<Editor>,
+        event: &EditorEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let EditorEvent::BufferEdited = event {
+            self.filter_task = Some(cx.spawn(|this, mut cx| async move {
+                cx.background_executor().timer(FILTER_DEBOUNCE).await;
+                this.update(&mut cx, |this, cx| this.reload(cx)).ok();
+            }));
+        }
+    }
+
+    fn filter_text(editor: &Entity<Editor>, cx: &App) -> Option<String> {
+        let text = editor.read(cx).text(cx);
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    fn log_options(&self, cx: &App) -> LogOptions {
+        let path = Self::filter_text(&self.path_editor, cx).map(|path| RepoPath::from_str(&path));
+        let line_range = self
+            .line_range
+            .as_ref()
+            .filter(|(line_path, _)| Some(line_path) == path.as_ref())
+            .map(|(_, line_range)| line_range.clone());
+        LogOptions {
+            path,
+            line_range,
+            author: Self::filter_text(&self.author_editor, cx),
+            message: Self::filter_text(&self.message_editor, cx),
+            skip: self.commits.len(),
+            limit: PAGE_SIZE,
+        }
+    }
+
+    fn has_filters(
+        &self,
+        repository: &Entity<Repository>,
+        path: Option<&RepoPath>,
+        line_range: Option<&RangeInclusive<u32>>,
+        cx: &App,
+    ) -> bool {
+        let options = self.log_options(cx);
+        &self.repository == repository
+            && options.path.as_ref() == path
+            && options.line_range.as_ref() == line_range
+            && options.author.is_none()
+            && options.message.is_none()
+    }
+
+    /// Discards the loaded commits and fetches the first page again with the current filters.
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        self.commits.clear();
+        self.selected_index = None;
+        self.has_more = true;
+        self.error = None;
+        self.load_task = None;
+        self.load_more(cx);
+    }
+
+    fn load_more(&mut self, cx: &mut Context<Self>) {
+        if self.load_task.is_some() || !self.has_more {
+            return;
+        }
+
+        let request = self.repository.read(cx).log(self.log_options(cx));
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            let commits = match request.await {
+                Ok(commits) => commits,
+                Err(error) => Err(error.into()),
+            };
+            this.update(&mut cx, |this, cx| {
+                this.load_task = None;
+                match commits {
+                    Ok(commits) => {
+                        this.has_more = commits.len() == PAGE_SIZE;
+                        this.commits.extend(commits);
+                        if this.selected_index.is_none() && !this.commits.is_empty() {
+                            this.selected_index = Some(0);
+                        }
+                    }
+                    Err(error) => {
+                        this.has_more = false;
+                        this.error = Some(error.to_string().into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if self.commits.is_empty() {
+            return;
+        }
+        let ix = self
+            .selected_index
+            .map_or(0, |ix| (ix + 1).min(self.commits.len() - 1));
+        self.select_index(ix, cx);
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        if self.commits.is_empty() {
+            return;
+        }
+        let ix = self.selected_index.map_or(0, |ix| ix.saturating_sub(1));
+        self.select_index(ix, cx);
+    }
+
+    fn select_index(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected_index = Some(ix);
+        self.scroll_handle
+            .scroll_to_item(ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_index {
+            self.open_commit(ix, window, cx);
+        }
+    }
+
+    fn open_commit(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(commit) = self.commits.get(ix).cloned() else {
+            return;
+        };
+        let repository = self.repository.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ProjectDiff::deploy_commit(workspace, repository, commit, window, cx);
+            })
+            .ok();
+    }
+
+    fn render_filter(&self, editor: &Entity<Editor>, cx: &Context<Self>) -> impl IntoElement {
+        h_flex()
+            .flex_1()
+            .px_2()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(editor.clone())
+    }
+
+    fn render_commit(
+        &self,
+        ix: usize,
+        commit: &CommitDetails,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let short_sha = commit
+            .sha
+            .get(..git::SHORT_SHA_LENGTH)
+            .unwrap_or(&commit.sha)
+            .to_string();
+        let subject = commit
+            .message
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let timestamp = OffsetDateTime::from_unix_timestamp(commit.commit_timestamp)
+            .map(|timestamp| {
+                time_format::format_local_timestamp(
+                    timestamp,
+                    OffsetDateTime::now_utc(),
+                    TimestampFormat::Relative,
+                )
+            })
+            .unwrap_or_default();
+
+        ListItem::new(ix)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(self.selected_index == Some(ix))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .child(
+                        Label::new(short_sha)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .buffer_font(cx),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .child(Label::new(subject).single_line()),
+                    )
+                    .child(
+                        Label::new(commit.author_name.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(timestamp)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.select_index(ix, cx);
+                this.open_commit(ix, window, cx);
+            }))
+            .into_any_element()
+    }
+
+    fn render_commits(&self, cx: &mut Context<Self>) -> AnyElement {
+        if self.commits.is_empty() {
+            let message: SharedString = if let Some(error) = &self.error {
+                error.clone()
+            } else if self.load_task.is_some() {
+                "Loading history…".into()
+            } else {
+                "No commits".into()
+            };
+            return div()
+                .flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element();
+        }
+
+        uniform_list(
+            cx.entity().clone(),
+            "commits",
+            self.commits.len(),
+            |this, range, _window, cx| {
+                if range.end + LOAD_MORE_THRESHOLD >= this.commits.len() {
+                    this.load_more(cx);
+                }
+                range
+                    .filter_map(|ix| {
+                        let commit = this.commits.get(ix)?.clone();
+                        Some(this.render_commit(ix, &commit, cx))
+                    })
+                    .collect()
+            },
+        )
+        .size_full()
+        .track_scroll(self.scroll_handle.clone())
+        .into_any_element()
+    }
+}
+
+impl EventEmitter<ItemEvent> for GitLog {}
+
+impl Focusable for GitLog {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for GitLog {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &ItemEvent, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _window: &Window, cx: &App) -> Option<SharedString> {
+        let options = self.log_options(cx);
+        Some(match (options.path, options.line_range) {
+            (Some(path), Some(line_range)) => format!(
+                "History: {}:{}-{}",
+                path.to_string_lossy(),
+                line_range.start(),
+                line_range.end()
+            )
+            .into(),
+            (Some(path), None) => format!("History: {}", path.to_string_lossy()).into(),
+            (None, _) => "Git Log".into(),
+        })
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git Log Opened")
+    }
+}
+
+impl Render for GitLog {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("GitLog")
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .p_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.render_filter(&self.path_editor, cx))
+                    .child(self.render_filter(&self.author_editor, cx))
+                    .child(self.render_filter(&self.message_editor, cx)),
+            )
+            .child(
+                div()
+                    .key_context("CommitList")
+                    .track_focus(&self.focus_handle)
+                    .on_action(cx.listener(Self::select_next))
+                    .on_action(cx.listener(Self::select_previous))
+                    .on_action(cx.listener(Self::confirm))
+                    .flex_1()
+                    .size_full()
+                    .child(self.render_commits(cx)),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer_diff::BufferDiff;
+    use gpui::TestAppContext;
+
+    use super::*;
+
+    #[gpui::test]
+    fn test_rows_in_head(cx: &mut TestAppContext) {
+        let buffer = cx.new(|cx| Buffer::local("a\nx\nb\nc\nD\ne\n", cx));
+        let diff = cx.update(|cx| {
+            BufferDiff::new_with_base_text("a\nb\nc\nd\ne\n", &buffer, cx).snapshot(cx)
+        });
+        let buffer = buffer.read_with(cx, |buffer, _| buffer.text_snapshot());
+
+        // Lines after an added one moved down.
+        assert_eq!(rows_in_head(&diff, &buffer, 2..3), Some(1..=2));
+        // Changed lines map to the ones they replaced.
+        assert_eq!(rows_in_head(&diff, &buffer, 0..4), Some(0..=3));
+        assert_eq!(rows_in_head(&diff, &buffer, 4..5), Some(3..=4));
+        // Added lines have no history.
+        assert_eq!(rows_in_head(&diff, &buffer, 1..1), None);
+    }
+}
@@ -18,11 +18,15 @@ use editor::{
 };
 use futures::StreamExt as _;
 use git::repository::{
-    Branch, CommitDetails, CommitSummary, PushOptions, Remote, RemoteCommandOutput, ResetMode,
-    Upstream, UpstreamTracking, UpstreamTrackingStatus,
+    Branch, CommitDetails, CommitOptions, CommitSigning, CommitSummary, LogOptions, PushOptions,
+    RebaseOutcome, RebaseStatus, Remote, RemoteCommandOutput, ResetMode, Upstream,
+    UpstreamTracking, UpstreamTrackingStatus,
 };
 use git::{repository::RepoPath, status::FileStatus, Commit, ToggleStaged};
-use git::{RestoreTrackedFiles, StageAll, TrashUntrackedFiles, UnstageAll};
+use git::{
+    RestoreTrackedFiles, StageAll, StashAll, StashFile, StashStaged, TrashUntrackedFiles,
+    UnstageAll, ViewStashes,
+};
 use gpui::*;
 use itertools::Itertools;
 use language::{Buffer, File};
@@ -32,10 +36,12 @@ use language_model::{
 use menu::{Confirm, SecondaryConfirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
 use multi_buffer::ExcerptInfo;
 use panel::{
-    panel_editor_container, panel_editor_style, panel_filled_button, panel_icon_button, PanelHeader,
+    panel_button, panel_editor_container, panel_editor_style, panel_filled_button,
+    panel_icon_button, PanelHeader,
 };
 use project::{
     git::{GitEvent, Repository},
+    project_settings::ProjectSettings,
     Fs, Project, ProjectPath,
 };
 use serde::{Deserialize, Serialize};
@@ -96,6 +102,14 @@ fn git_panel_context_menu(window: &mut Window, cx: &mut App) -> Entity<ContextMe
             .separator()
             .action("Discard Tracked Changes", RestoreTrackedFiles.boxed_clone())
             .action("Trash Untracked Files", TrashUntrackedFiles.boxed_clone())
+            .separator()
+            .action("Stash All", StashAll.boxed_clone())
+            .action("Stash Staged", StashStaged.boxed_clone())
+            .action("View Stashes", ViewStashes.boxed_clone())
+            .separator()
+            .action("Amend Last Commit", git::Amend.boxed_clone())
+            .action("Fixup Commit…", git::Fixup.boxed_clone())
+            .action("Interactive Rebase…", git::InteractiveRebase.boxed_clone())
     })
 }
 
@@ -109,6 +123,30 @@ pub fn init(cx: &mut App) {
             workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
                 workspace.toggle_panel_focus::<GitPanel>(window, cx);
             });
+            workspace.register_action(|workspace, _: &git::Fixup, window, cx| {
+                let Some(panel) = workspace.panel::<GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| panel.fixup(window, cx));
+            });
+            workspace.register_action(|workspace, _: &git::RebaseContinue, window, cx| {
+                let Some(panel) = workspace.panel::<GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| panel.continue_rebase(false, window, cx));
+            });
+            workspace.register_action(|workspace, _: &git::RebaseSkip, window, cx| {
+                let Some(panel) = workspace.panel::<GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| panel.continue_rebase(true, window, cx));
+            });
+            workspace.register_action(|workspace, _: &git::RebaseAbort, window, cx| {
+                let Some(panel) = workspace.panel::<GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| panel.abort_rebase(window, cx));
+            });
         },
     )
     .detach();
@@ -226,6 +264,11 @@ pub struct GitPanel {
     workspace: WeakEntity<Workspace>,
     context_menu: Option<(Entity<ContextMenu>, Point<Pixels>, Subscription)>,
     modal_open: bool,
+    /// Whether the next commit amends HEAD. While set, the commit editor holds HEAD's message
+    /// and this holds the draft it replaced.
+    amend_pending: Option<String>,
+    /// The rebase that stopped in the active repository and waits to be continued or aborted.
+    rebase_status: Option<RebaseStatus>,
 }
 
 struct RemoteOperationGuard {
@@ -357,6 +400,8 @@ impl GitPanel {
                 context_menu: None,
                 workspace,
                 modal_open: false,
+                amend_pending: None,
+                rebase_status: None,
             };
             git_panel.schedule_update(false, window, cx);
             git_panel.show_scrollbar = git_panel.should_show_scrollbar(cx);
@@ -1057,6 +1102,42 @@ impl GitPanel {
         self.perform_stage(false, repo_paths, cx);
     }
 
+    fn stash_all(&mut self, _: &StashAll, _window: &mut Window, cx: &mut Context<Self>) {
+        self.perform_stash(Vec::new(), false, cx);
+    }
+
+    fn stash_staged(&mut self, _: &StashStaged, _window: &mut Window, cx: &mut Context<Self>) {
+        self.perform_stash(Vec::new(), true, cx);
+    }
+
+    fn stash_selected(&mut self, _: &StashFile, _window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self
+            .selected_entry
+            .and_then(|ix| self.entries.get(ix))
+            .and_then(|entry| entry.status_entry())
+        else {
+            return;
+        };
+        let repo_paths = vec![entry.repo_path.clone()];
+        self.perform_stash(repo_paths, false, cx);
+    }
+
+    fn perform_stash(&mut self, repo_paths: Vec<RepoPath>, staged: bool, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let stash = active_repository
+            .read(cx)
+            .stash_push(None, repo_paths, staged);
+        cx.spawn(|this, mut cx| async move {
+            if let Err(e) = stash.await? {
+                this.update(&mut cx, |this, cx| this.show_err_toast(e, cx))?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn toggle_staged_for_entry(
         &mut self,
         entry: &GitListEntry,
@@ -1195,6 +1276,40 @@ impl GitPanel {
     }
 
     pub(crate) fn commit_changes(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let commit_message = self.custom_or_suggested_commit_message(cx);
+
+        let Some(mut message) = commit_message else {
+            self.commit_editor.read(cx).focus_handle(cx).focus(window);
+            return;
+        };
+
+        if self.add_coauthors {
+            self.fill_co_authors(&mut message, cx);
+        }
+
+        let options = CommitOptions {
+            amend: self.amend_pending.is_some(),
+            fixup: None,
+            signing: self.commit_signing(cx),
+        };
+        self.commit_with_options(message, options, window, cx);
+    }
+
+    fn commit_signing(&self, cx: &App) -> CommitSigning {
+        ProjectSettings::get_global(cx)
+            .git
+            .commit_signing
+            .clone()
+            .unwrap_or_default()
+    }
+
+    fn commit_with_options(
+        &mut self,
+        message: String,
+        options: CommitOptions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
         let Some(active_repository) = self.active_repository.clone() else {
             return;
         };
@@ -1215,20 +1330,28 @@ impl GitPanel {
             return;
         }
 
-        let commit_message = self.custom_or_suggested_commit_message(cx);
-
-        let Some(mut message) = commit_message else {
-            self.commit_editor.read(cx).focus_handle(cx).focus(window);
-            return;
+        // The draft that amending replaced is restored once the amend succeeds, while other
+        // commits clear the message they used. Fixup commits don't use the editor at all.
+        let amend = options.amend;
+        let message_after_commit = if amend {
+            self.amend_pending.take()
+        } else if options.fixup.is_some() {
+            None
+        } else {
+            Some(String::new())
         };
 
-        if self.add_coauthors {
-            self.fill_co_authors(&mut message, cx);
-        }
-
         let task = if self.has_staged_changes() {
             // Repository serializes all git operations, so we can just send a commit immediately
-            let commit_task = active_repository.read(cx).commit(message.into(), None);
+            let commit_task = active_repository
+                .read(cx)
+                .commit(message.into(), None, options);
+            cx.background_spawn(async move { commit_task.await? })
+        } else if options.amend && !self.has_tracked_changes() {
+            // Amending without any changes only rewords HEAD.
+            let commit_task = active_repository
+                .read(cx)
+                .commit(message.into(), None, options);
             cx.background_spawn(async move { commit_task.await? })
         } else {
             let changed_files = self
@@ -1248,8 +1371,9 @@ impl GitPanel {
                 active_repository.update(cx, |repo, cx| repo.stage_entries(changed_files, cx));
             cx.spawn(|_, mut cx| async move {
                 stage_task.await?;
-                let commit_task = active_repository
-                    .update(&mut cx, |repo, _| repo.commit(message.into(), None))?;
+                let commit_task = active_repository.update(&mut cx, |repo, _| {
+                    repo.commit(message.into(), None, options)
+                })?;
                 commit_task.await?
             })
         };
@@ -1259,10 +1383,17 @@ impl GitPanel {
                 this.pending_commit.take();
                 match result {
                     Ok(()) => {
-                        this.commit_editor
-                            .update(cx, |editor, cx| editor.clear(window, cx));
+                        if let Some(message) = message_after_commit {
+                            this.commit_editor
+                                .update(cx, |editor, cx| editor.set_text(message, window, cx));
+                        }
+                    }
+                    Err(e) => {
+                        if amend {
+                            this.amend_pending = message_after_commit;
+                        }
+                        this.show_err_toast(e, cx)
                     }
-                    Err(e) => this.show_err_toast(e, cx),
                 }
             })
             .ok();
@@ -1271,6 +1402,107 @@ impl GitPanel {
         self.pending_commit = Some(task);
     }
 
+    /// Switches the next commit between amending HEAD and creating a new commit. Amending loads
+    /// HEAD's message into the commit editor, and switching back restores the previous draft.
+    pub(crate) fn set_amend_pending(
+        &mut self,
+        amend: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if amend == self.amend_pending.is_some() {
+            return;
+        }
+
+        if !amend {
+            if let Some(draft) = self.amend_pending.take() {
+                self.commit_editor
+                    .update(cx, |editor, cx| editor.set_text(draft, window, cx));
+            }
+            cx.notify();
+            return;
+        }
+
+        let draft = self.commit_editor.read(cx).text(cx);
+        self.amend_pending = Some(draft);
+        cx.notify();
+
+        let head = self.load_commit_details("HEAD", cx);
+        cx.spawn_in(window, |this, mut cx| async move {
+            let head = head.await;
+            this.update_in(&mut cx, |this, window, cx| {
+                // Amending may have been turned off again while HEAD was loading.
+                if this.amend_pending.is_none() {
+                    return;
+                }
+                match head {
+                    Ok(head) => this.commit_editor.update(cx, |editor, cx| {
+                        editor.set_text(head.message.to_string(), window, cx)
+                    }),
+                    Err(e) => {
+                        this.amend_pending = None;
+                        this.show_err_toast(e, cx);
+                    }
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn amend(&mut self, _: &git::Amend, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_amend_pending(true, window, cx);
+    }
+
+    /// Commits the staged changes as a `fixup!` commit for a recent commit the user picks.
+    pub(crate) fn fixup(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        const FIXUP_CANDIDATE_COUNT: usize = 50;
+
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        let log = repo.read(cx).log(LogOptions {
+            limit: FIXUP_CANDIDATE_COUNT,
+            ..Default::default()
+        });
+        let workspace = self.workspace.clone();
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            let commits = log.await??;
+            let options = commits
+                .iter()
+                .map(|commit| {
+                    let short_sha = commit
+                        .sha
+                        .get(..git::SHORT_SHA_LENGTH)
+                        .unwrap_or(&commit.sha);
+                    let subject = commit.message.lines().next().unwrap_or_default();
+                    SharedString::from(format!("{short_sha} {subject}"))
+                })
+                .collect();
+            let selection = cx
+                .update(|window, cx| {
+                    picker_prompt::prompt("Pick a commit to fix up", options, workspace, window, cx)
+                })?
+                .await?;
+            let Some(commit) = selection.and_then(|ix| commits.get(ix)) else {
+                return Ok(());
+            };
+
+            this.update_in(&mut cx, |this, window, cx| {
+                let options = CommitOptions {
+                    amend: false,
+                    fixup: Some(commit.sha.to_string()),
+                    signing: this.commit_signing(cx),
+                };
+                this.commit_with_options(String::new(), options, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to create fixup commit", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
     fn uncommit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let Some(repo) = self.active_repository.clone() else {
             return;
@@ -1432,6 +1664,7 @@ index 1234567..abcdef0 100644
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            response_format: None,
         };
 
         self.generate_commit_message_task = Some(cx.spawn(|this, mut cx| {
@@ -1829,6 +2062,7 @@ index 1234567..abcdef0 100644
                         }
                         git_panel.update_visible_entries(cx);
                         git_panel.update_editor_placeholder(cx);
+                        git_panel.update_rebase_status(cx);
                     })
                     .ok();
             }
@@ -1876,6 +2110,74 @@ index 1234567..abcdef0 100644
         .detach_and_log_err(cx);
     }
 
+    pub(crate) fn update_rebase_status(&mut self, cx: &mut Context<Self>) {
+        let Some(repo) = self.active_repository.clone() else {
+            self.rebase_status = None;
+            return;
+        };
+        let status = repo.read(cx).rebase_status();
+        cx.spawn(|this, mut cx| async move {
+            let status = status.await??;
+            this.update(&mut cx, |this, cx| {
+                if this.rebase_status != status {
+                    this.rebase_status = status;
+                    cx.notify();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub(crate) fn continue_rebase(
+        &mut self,
+        skip: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        if !skip && self.has_unstaged_conflicts() {
+            self.show_err_toast(
+                anyhow::anyhow!("Resolve and stage all conflicts before continuing the rebase"),
+                cx,
+            );
+            return;
+        }
+
+        let rebase = repo.read(cx).rebase_continue(skip);
+        cx.spawn_in(window, |this, mut cx| async move {
+            let outcome = rebase.await?;
+            this.update(&mut cx, |this, cx| {
+                match outcome {
+                    Ok(RebaseOutcome::Completed) => this.rebase_status = None,
+                    Ok(RebaseOutcome::Stopped) => {}
+                    Err(e) => this.show_err_toast(e, cx),
+                }
+                this.update_rebase_status(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub(crate) fn abort_rebase(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        let abort = repo.read(cx).rebase_abort();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let result = abort.await?;
+            this.update(&mut cx, |this, cx| {
+                match result {
+                    Ok(()) => this.rebase_status = None,
+                    Err(e) => this.show_err_toast(e, cx),
+                }
+                this.update_rebase_status(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn clear_pending(&mut self) {
         self.pending.retain(|v| !v.finished)
     }
@@ -2108,6 +2410,24 @@ index 1234567..abcdef0 100644
         self.has_staged_changes()
     }
 
+    pub(crate) fn render_amend_toggle(&self, cx: &Context<Self>) -> Option<AnyElement> {
+        let active_repository = self.active_repository.as_ref()?;
+        let branch = active_repository.read(cx).current_branch()?;
+        branch.most_recent_commit.as_ref()?;
+
+        Some(
+            IconButton::new("amend", IconName::Pencil)
+                .icon_color(Color::Disabled)
+                .selected_icon_color(Color::Selected)
+                .toggle_state(self.amend_pending.is_some())
+                .tooltip(Tooltip::text("Amend last commit"))
+                .on_click(cx.listener(|this, _, window, cx| {
+                    this.set_amend_pending(this.amend_pending.is_none(), window, cx);
+                }))
+                .into_any_element(),
+        )
+    }
+
     pub(crate) fn render_co_authors(&self, cx: &Context<Self>) -> Option<AnyElement> {
         let potential_co_authors = self.potential_co_authors(cx);
         if potential_co_authors.is_empty() {
@@ -2145,7 +2465,10 @@ index 1234567..abcdef0 100644
     pub fn configure_commit_button(&self, cx: &mut Context<Self>) -> (bool, &'static str) {
         if self.has_unstaged_conflicts() {
             (false, "You must resolve conflicts before committing")
-        } else if !self.has_staged_changes() && !self.has_tracked_changes() {
+        } else if !self.has_staged_changes()
+            && !self.has_tracked_changes()
+            && self.amend_pending.is_none()
+        {
             (
                 false,
                 "You must have either staged changes or tracked files to commit",
@@ -2162,7 +2485,9 @@ index 1234567..abcdef0 100644
     }
 
     pub fn commit_button_title(&self) -> &'static str {
-        if self.has_staged_changes() {
+        if self.amend_pending.is_some() {
+            "Amend"
+        } else if self.has_staged_changes() {
             "Commit"
         } else {
             "Commit Tracked"
@@ -2196,6 +2521,7 @@ index 1234567..abcdef0 100644
         let panel_editor_style = panel_editor_style(true, window, cx);
 
         let enable_coauthors = self.render_co_authors(cx);
+        let amend_toggle = self.render_amend_toggle(cx);
         // Note: This is hard-coded to `false` as it is not fully implemented.
         let show_generate_commit_message_button = false;
 
@@ -2257,6 +2583,7 @@ index 1234567..abcdef0 100644
                                     ),
                                 )
                             })
+                            .children(amend_toggle)
                             .children(enable_coauthors)
                             .child(
                                 panel_filled_button(title)
@@ -2311,6 +2638,66 @@ index 1234567..abcdef0 100644
         Some(footer)
     }
 
+    fn render_rebase_banner(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let status = self.rebase_status.as_ref()?;
+        let mut title = format!("Rebasing ({}/{})", status.done, status.total);
+        if let Some(sha) = &status.stopped_at {
+            title.push_str(&format!(
+                ", stopped at {}",
+                sha.get(..git::SHORT_SHA_LENGTH).unwrap_or(sha)
+            ));
+        }
+
+        Some(
+            h_flex()
+                .items_center()
+                .py_2()
+                .px(px(8.))
+                .gap_1()
+                .border_t_1()
+                .border_color(cx.theme().colors().border)
+                .child(
+                    Icon::new(IconName::GitBranch)
+                        .size(IconSize::Small)
+                        .color(Color::Warning),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .overflow_hidden()
+                        .child(Label::new(title).size(LabelSize::Small).truncate()),
+                )
+                .child(
+                    panel_button("Abort")
+                        .tooltip(Tooltip::for_action_title(
+                            "Abort the rebase",
+                            &git::RebaseAbort,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| this.abort_rebase(window, cx))),
+                )
+                .child(
+                    panel_button("Skip")
+                        .tooltip(Tooltip::for_action_title(
+                            "Leave out the commit the rebase stopped at",
+                            &git::RebaseSkip,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.continue_rebase(true, window, cx)
+                        })),
+                )
+                .child(
+                    panel_filled_button("Continue")
+                        .tooltip(Tooltip::for_action_title(
+                            "Continue the rebase",
+                            &git::RebaseContinue,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.continue_rebase(false, window, cx)
+                        })),
+                ),
+        )
+    }
+
     fn render_previous_commit(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
         let active_repository = self.active_repository.as_ref()?;
         let branch = active_repository.read(cx).current_branch()?;
@@ -2622,6 +3009,7 @@ index 1234567..abcdef0 100644
             context_menu
                 .action(stage_title, ToggleStaged.boxed_clone())
                 .action(restore_title, git::RestoreFile.boxed_clone())
+                .action("Stash File", StashFile.boxed_clone())
                 .separator()
                 .action("Open Diff", Confirm.boxed_clone())
                 .action("Open File", SecondaryConfirm.boxed_clone())
@@ -2917,7 +3305,11 @@ impl Render for GitPanel {
             .on_action(cx.listener(Self::unstage_all))
             .on_action(cx.listener(Self::restore_tracked_files))
             .on_action(cx.listener(Self::clean_all))
+            .on_action(cx.listener(Self::stash_all))
+            .on_action(cx.listener(Self::stash_staged))
+            .on_action(cx.listener(Self::stash_selected))
             .on_action(cx.listener(Self::expand_commit_editor))
+            .on_action(cx.listener(Self::amend))
             .when(has_write_access && has_co_authors, |git_panel| {
                 git_panel.on_action(cx.listener(Self::toggle_fill_co_authors))
             })
@@ -2944,6 +3336,7 @@ impl Render for GitPanel {
                             this.child(self.render_empty_state(cx).into_any_element())
                         }
                     })
+                    .children(self.render_rebase_banner(cx))
                     .children(self.render_footer(window, cx))
                     .children(self.render_previous_commit(cx))
                     .into_any_element(),
@@ -3066,8 +3459,8 @@ impl GitPanelMessageTooltip {
 
                 let commit_details = editor::commit_tooltip::CommitDetails {
                     sha: details.sha.clone(),
-                    committer_name: details.committer_name.clone(),
-                    committer_email: details.committer_email.clone(),
+                    committer_name: details.author_name.clone(),
+                    committer_email: details.author_email.clone(),
                     commit_time: OffsetDateTime::from_unix_timestamp(details.commit_timestamp)?,
                     message: Some(editor::commit_tooltip::ParsedCommitMessage {
                         message: details.message.clone(),
@@ -9,19 +9,25 @@ use workspace::Workspace;
 mod askpass_modal;
 pub mod branch_picker;
 mod commit_modal;
+pub mod git_log;
 pub mod git_panel;
 mod git_panel_settings;
 pub mod picker_prompt;
 pub mod project_diff;
+pub mod rebase_planner;
 mod remote_output_toast;
 pub mod repository_selector;
+pub mod stash_picker;
 
 pub fn init(cx: &mut App) {
     GitPanelSettings::register(cx);
     branch_picker::init(cx);
     cx.observe_new(ProjectDiff::register).detach();
     commit_modal::init(cx);
+    git_log::init(cx);
     git_panel::init(cx);
+    rebase_planner::init(cx);
+    stash_picker::init(cx);
 
     cx.observe_new(|workspace: &mut Workspace, _, cx| {
         let project = workspace.project().read(cx);
@@ -1,5 +1,5 @@
 use crate::git_panel::{GitPanel, GitPanelAddon, GitStatusEntry};
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use buffer_diff::{BufferDiff, DiffHunkSecondaryStatus};
 use collections::HashSet;
 use editor::{
@@ -8,26 +8,34 @@ use editor::{
     Editor, EditorEvent,
 };
 use feature_flags::FeatureFlagViewExt;
-use futures::StreamExt;
+use futures::{channel::oneshot, StreamExt};
 use git::{
-    status::FileStatus, Commit, StageAll, StageAndNext, ToggleStaged, UnstageAll, UnstageAndNext,
+    repository::{CommitDetails, CommitFile, StashEntry},
+    status::{FileStatus, StatusCode},
+    ApplyStash, Commit, DropStash, PopStash, StageAll, StageAndNext, ToggleStaged, UnstageAll,
+    UnstageAndNext,
 };
 use gpui::{
-    actions, Action, AnyElement, AnyView, App, AppContext as _, AsyncWindowContext, Entity,
-    EventEmitter, FocusHandle, Focusable, Render, Subscription, Task, WeakEntity,
+    actions, Action, AnyElement, AnyView, App, AppContext as _, AsyncApp, AsyncWindowContext,
+    Entity, EventEmitter, FocusHandle, Focusable, PromptLevel, Render, Subscription, Task,
+    WeakEntity,
 };
-use language::{Anchor, Buffer, Capability, OffsetRangeExt};
+use language::{Anchor, Buffer, Capability, DiskState, OffsetRangeExt};
 use multi_buffer::{MultiBuffer, PathKey};
 use project::{
-    git::{GitEvent, GitStore},
-    Project, ProjectPath,
+    git::{GitEvent, GitStore, Repository},
+    File, Project, ProjectPath,
+};
+use std::{
+    any::{Any, TypeId},
+    sync::Arc,
 };
-use std::any::{Any, TypeId};
 use theme::ActiveTheme;
 use ui::{prelude::*, vertical_divider, Tooltip};
 use util::ResultExt as _;
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle, TabContentParams},
+    notifications::DetachAndPromptErr,
     searchable::SearchableItemHandle,
     ItemNavHistory, SerializableItem, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView,
     Workspace,
@@ -36,6 +44,7 @@ use workspace::{
 actions!(git, [Diff]);
 
 pub struct ProjectDiff {
+    source: DiffSource,
     multibuffer: Entity<MultiBuffer>,
     editor: Entity<Editor>,
     project: Entity<Project>,
@@ -49,6 +58,32 @@ pub struct ProjectDiff {
     _subscription: Subscription,
 }
 
+#[derive(Clone)]
+enum DiffSource {
+    /// The uncommitted changes in the active repository, kept up to date as they change.
+    Uncommitted,
+    /// The changes recorded in a stash entry, shown read-only.
+    Stash {
+        repository: Entity<Repository>,
+        entry: StashEntry,
+    },
+    /// The changes introduced by a commit relative to its first parent, shown read-only.
+    Commit {
+        repository: Entity<Repository>,
+        commit: CommitDetails,
+    },
+}
+
+impl DiffSource {
+    fn sha(&self) -> Option<&SharedString> {
+        match self {
+            DiffSource::Uncommitted => None,
+            DiffSource::Stash { entry, .. } => Some(&entry.sha),
+            DiffSource::Commit { commit, .. } => Some(&commit.sha),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct DiffBuffer {
     path_key: PathKey,
@@ -90,7 +125,11 @@ impl ProjectDiff {
         window: &mut Window,
         cx: &mut Context<Workspace>,
     ) {
-        let project_diff = if let Some(existing) = workspace.item_of_type::<Self>(cx) {
+        let existing = workspace
+            .items_of_type::<Self>(cx)
+            .filter(|item| matches!(item.read(cx).source, DiffSource::Uncommitted))
+            .max_by_key(|item| item.item_id());
+        let project_diff = if let Some(existing) = existing {
             workspace.activate_item(&existing, true, true, window, cx);
             existing
         } else {
@@ -113,27 +152,109 @@ impl ProjectDiff {
         }
     }
 
+    /// Opens a read-only diff of the changes recorded in the given stash entry.
+    pub fn deploy_stash(
+        workspace: &mut Workspace,
+        repository: Entity<Repository>,
+        entry: StashEntry,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        Self::deploy_source(
+            workspace,
+            DiffSource::Stash { repository, entry },
+            window,
+            cx,
+        )
+    }
+
+    /// Opens a read-only diff of the changes introduced by the given commit.
+    pub fn deploy_commit(
+        workspace: &mut Workspace,
+        repository: Entity<Repository>,
+        commit: CommitDetails,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        Self::deploy_source(
+            workspace,
+            DiffSource::Commit { repository, commit },
+            window,
+            cx,
+        )
+    }
+
+    fn deploy_source(
+        workspace: &mut Workspace,
+        source: DiffSource,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = source.sha().and_then(|sha| {
+            workspace
+                .items_of_type::<Self>(cx)
+                .find(|item| item.read(cx).source.sha() == Some(sha))
+        });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let workspace_handle = cx.entity();
+        let project_diff = cx.new(|cx| {
+            Self::new_with_source(
+                source,
+                workspace.project().clone(),
+                workspace_handle,
+                window,
+                cx,
+            )
+        });
+        workspace.add_item_to_active_pane(Box::new(project_diff), None, true, window, cx);
+    }
+
     fn new(
         project: Entity<Project>,
         workspace: Entity<Workspace>,
         window: &mut Window,
         cx: &mut Context<Self>,
+    ) -> Self {
+        Self::new_with_source(DiffSource::Uncommitted, project, workspace, window, cx)
+    }
+
+    fn new_with_source(
+        source: DiffSource,
+        project: Entity<Project>,
+        workspace: Entity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Self {
         let focus_handle = cx.focus_handle();
-        let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+        let is_uncommitted = matches!(source, DiffSource::Uncommitted);
+        let multibuffer = cx.new(|_| {
+            MultiBuffer::new(if is_uncommitted {
+                Capability::ReadWrite
+            } else {
+                Capability::ReadOnly
+            })
+        });
 
         let editor = cx.new(|cx| {
+            // Stashed and committed buffers aren't project buffers, so the editor must not try to load
+            // uncommitted diffs for them.
             let mut diff_display_editor = Editor::for_multibuffer(
                 multibuffer.clone(),
-                Some(project.clone()),
+                is_uncommitted.then(|| project.clone()),
                 true,
                 window,
                 cx,
             );
             diff_display_editor.set_expand_all_diff_hunks(cx);
-            diff_display_editor.register_addon(GitPanelAddon {
-                workspace: workspace.downgrade(),
-            });
+            if is_uncommitted {
+                diff_display_editor.register_addon(GitPanelAddon {
+                    workspace: workspace.downgrade(),
+                });
+            }
             diff_display_editor
         });
         cx.subscribe_in(&editor, window, Self::handle_editor_event)
@@ -146,7 +267,9 @@ impl ProjectDiff {
             move |this, _git_store, event, _window, _cx| match event {
                 GitEvent::ActiveRepositoryChanged
                 | GitEvent::FileSystemUpdated
-                | GitEvent::GitStateUpdated => {
+                | GitEvent::GitStateUpdated
+                    if matches!(this.source, DiffSource::Uncommitted) =>
+                {
                     *this.update_needed.borrow_mut() = ();
                 }
                 _ => {}
@@ -156,12 +279,29 @@ impl ProjectDiff {
         let (mut send, recv) = postage::watch::channel::<()>();
         let worker = window.spawn(cx, {
             let this = cx.weak_entity();
-            |cx| Self::handle_status_updates(this, recv, cx)
+            let source = source.clone();
+            |mut cx| async move {
+                match source {
+                    DiffSource::Uncommitted => Self::handle_status_updates(this, recv, cx).await,
+                    DiffSource::Stash { repository, entry } => {
+                        let files = repository
+                            .update(&mut cx, |repository, _| repository.stash_show(entry.index))?;
+                        Self::load_commit_files(this, repository, files, cx).await
+                    }
+                    DiffSource::Commit { repository, commit } => {
+                        let files = repository.update(&mut cx, |repository, _| {
+                            repository.commit_files(&commit.sha)
+                        })?;
+                        Self::load_commit_files(this, repository, files, cx).await
+                    }
+                }
+            }
         });
         // Kick off a refresh immediately
         *send.borrow_mut() = ();
 
         Self {
+            source,
             project,
             git_store: git_store.clone(),
             workspace: workspace.downgrade(),
@@ -294,7 +434,9 @@ impl ProjectDiff {
         cx: &mut Context<Self>,
     ) {
         match event {
-            EditorEvent::SelectionsChanged { local: true } => {
+            EditorEvent::SelectionsChanged { local: true }
+                if matches!(self.source, DiffSource::Uncommitted) =>
+            {
                 let Some(project_path) = self.active_path(cx) else {
                     return;
                 };
@@ -450,6 +592,167 @@ impl ProjectDiff {
         Ok(())
     }
 
+    async fn load_commit_files(
+        this: WeakEntity<Self>,
+        repository: Entity<Repository>,
+        files: oneshot::Receiver<Result<Vec<CommitFile>>>,
+        mut cx: AsyncWindowContext,
+    ) -> Result<()> {
+        let files = files.await??;
+        for file in files {
+            let buffer_to_load = this.update(&mut cx, |this, cx| {
+                this.load_commit_file(&repository, file, cx)
+            })?;
+            if let Some(buffer) = buffer_to_load.await.log_err() {
+                cx.update(|window, cx| {
+                    this.update(cx, |this, cx| {
+                        this.multibuffer.update(cx, |multibuffer, cx| {
+                            multibuffer.add_diff(buffer.diff.clone(), cx)
+                        });
+                        this.register_buffer(buffer, window, cx)
+                    })
+                    .ok();
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn load_commit_file(
+        &mut self,
+        repository: &Entity<Repository>,
+        file: CommitFile,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<DiffBuffer>> {
+        let namespace = if file.old_text.is_none() {
+            NEW_NAMESPACE
+        } else {
+            TRACKED_NAMESPACE
+        };
+        let path_key = PathKey::namespaced(namespace, file.path.0.clone());
+        let file_status = FileStatus::index(match (&file.old_text, &file.new_text) {
+            (None, _) => StatusCode::Added,
+            (_, None) => StatusCode::Deleted,
+            _ => StatusCode::Modified,
+        });
+        // Give the buffer a file so that its excerpt header shows the file's path.
+        let project_file = repository
+            .read(cx)
+            .repo_path_to_project_path(&file.path)
+            .and_then(|project_path| {
+                let worktree = self
+                    .project
+                    .read(cx)
+                    .worktree_for_id(project_path.worktree_id, cx)?;
+                Some(Arc::new(File {
+                    worktree,
+                    path: project_path.path,
+                    disk_state: DiskState::New,
+                    entry_id: None,
+                    is_local: false,
+                    is_private: false,
+                }))
+            });
+        let language_registry = self.project.read(cx).languages().clone();
+
+        cx.spawn(|_, mut cx| async move {
+            let language = language_registry
+                .language_for_file_path(&file.path)
+                .await
+                .ok();
+            let new_buffer = |text: Option<String>, cx: &mut AsyncApp| {
+                cx.new(|cx| {
+                    let mut buffer = Buffer::local(text.unwrap_or_default(), cx);
+                    buffer.set_language_registry(language_registry.clone());
+                    buffer.set_language(language.clone(), cx);
+                    buffer
+                })
+            };
+            let buffer = new_buffer(file.new_text, &mut cx)?;
+            let base_buffer = new_buffer(file.old_text, &mut cx)?;
+            if let Some(project_file) = project_file {
+                buffer.update(&mut cx, |buffer, cx| buffer.file_updated(project_file, cx))?;
+            }
+
+            let snapshot = buffer.update(&mut cx, |buffer, _| buffer.text_snapshot())?;
+            let diff = cx.new(|cx| BufferDiff::new(&snapshot, cx))?;
+            diff.update(&mut cx, |diff, cx| {
+                diff.set_base_text(base_buffer, snapshot, cx)
+            })?
+            .await?;
+
+            Ok(DiffBuffer {
+                path_key,
+                buffer,
+                diff,
+                file_status,
+            })
+        })
+    }
+
+    fn apply_stash(&mut self, _: &ApplyStash, window: &mut Window, cx: &mut Context<Self>) {
+        self.perform_stash_action(StashAction::Apply, window, cx);
+    }
+
+    fn pop_stash(&mut self, _: &PopStash, window: &mut Window, cx: &mut Context<Self>) {
+        self.perform_stash_action(StashAction::Pop, window, cx);
+    }
+
+    fn drop_stash(&mut self, _: &DropStash, window: &mut Window, cx: &mut Context<Self>) {
+        self.perform_stash_action(StashAction::Drop, window, cx);
+    }
+
+    fn perform_stash_action(
+        &mut self,
+        action: StashAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let DiffSource::Stash { repository, entry } = self.source.clone() else {
+            return;
+        };
+        let confirmation = (action == StashAction::Drop).then(|| {
+            window.prompt(
+                PromptLevel::Warning,
+                &format!("Drop the stash \"{}\"?", entry.message),
+                Some("The stashed changes will be lost."),
+                &["Drop", "Cancel"],
+                cx,
+            )
+        });
+        cx.spawn_in(window, |this, mut cx| async move {
+            if let Some(confirmation) = confirmation {
+                if confirmation.await != Ok(0) {
+                    return Ok(());
+                }
+            }
+            // Other stashes may have been pushed or dropped since this one was opened, so look
+            // up its current position by SHA. The repository checks the SHA again before acting
+            // on it, in case the stash changes in between.
+            let index = repository
+                .update(&mut cx, |repository, _| repository.stash_list())?
+                .await??
+                .into_iter()
+                .find(|stash| stash.sha == entry.sha)
+                .map(|stash| stash.index)
+                .context("stash entry no longer exists")?;
+            repository
+                .update(&mut cx, |repository, _| match action {
+                    StashAction::Apply => repository.stash_apply(index, &entry.sha),
+                    StashAction::Pop => repository.stash_pop(index, &entry.sha),
+                    StashAction::Drop => repository.stash_drop(index, &entry.sha),
+                })?
+                .await??;
+            if action != StashAction::Apply {
+                this.update(&mut cx, |_, cx| cx.emit(EditorEvent::Closed))?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err(action.error_title(), window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn excerpt_paths(&self, cx: &App) -> Vec<String> {
         self.multibuffer
@@ -460,6 +763,23 @@ impl ProjectDiff {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StashAction {
+    Apply,
+    Pop,
+    Drop,
+}
+
+impl StashAction {
+    fn error_title(self) -> &'static str {
+        match self {
+            StashAction::Apply => "Failed to apply stash",
+            StashAction::Pop => "Failed to pop stash",
+            StashAction::Drop => "Failed to drop stash",
+        }
+    }
+}
+
 impl EventEmitter<EditorEvent> for ProjectDiff {}
 
 impl Focusable for ProjectDiff {
@@ -499,11 +819,27 @@ impl Item for ProjectDiff {
     }
 
     fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
-        Some("Project Diff".into())
+        match &self.source {
+            DiffSource::Uncommitted => Some("Project Diff".into()),
+            DiffSource::Stash { entry, .. } => Some(entry.message.clone()),
+            DiffSource::Commit { commit, .. } => Some(commit.message.clone()),
+        }
     }
 
     fn tab_content(&self, params: TabContentParams, _window: &Window, _: &App) -> AnyElement {
-        Label::new("Uncommitted Changes")
+        let label: SharedString = match &self.source {
+            DiffSource::Uncommitted => "Uncommitted Changes".into(),
+            DiffSource::Stash { entry, .. } => format!("stash@{{{}}}", entry.index).into(),
+            DiffSource::Commit { commit, .. } => {
+                let short_sha = commit
+                    .sha
+                    .get(..git::SHORT_SHA_LENGTH)
+                    .unwrap_or(&commit.sha);
+                let subject = commit.message.lines().next().unwrap_or_default();
+                format!("{short_sha} {subject}").into()
+            }
+        };
+        Label::new(label)
             .color(if params.selected {
                 Color::Default
             } else {
@@ -553,7 +889,15 @@ impl Item for ProjectDiff {
         Self: Sized,
     {
         let workspace = self.workspace.upgrade()?;
-        Some(cx.new(|cx| ProjectDiff::new(self.project.clone(), workspace, window, cx)))
+        Some(cx.new(|cx| {
+            ProjectDiff::new_with_source(
+                self.source.clone(),
+                self.project.clone(),
+                workspace,
+                window,
+                cx,
+            )
+        }))
     }
 
     fn is_dirty(&self, cx: &App) -> bool {
@@ -565,7 +909,7 @@ impl Item for ProjectDiff {
     }
 
     fn can_save(&self, _: &App) -> bool {
-        true
+        matches!(self.source, DiffSource::Uncommitted)
     }
 
     fn save(
@@ -635,17 +979,27 @@ impl Item for ProjectDiff {
 impl Render for ProjectDiff {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let is_empty = self.multibuffer.read(cx).is_empty();
+        let is_stash = matches!(self.source, DiffSource::Stash { .. });
 
         div()
             .track_focus(&self.focus_handle)
             .key_context(if is_empty { "EmptyPane" } else { "GitDiff" })
+            .when(is_stash, |el| {
+                el.on_action(cx.listener(Self::apply_stash))
+                    .on_action(cx.listener(Self::pop_stash))
+                    .on_action(cx.listener(Self::drop_stash))
+            })
             .bg(cx.theme().colors().editor_background)
             .flex()
             .items_center()
             .justify_center()
             .size_full()
             .when(is_empty, |el| {
-                el.child(Label::new("No uncommitted changes"))
+                el.child(Label::new(match self.source {
+                    DiffSource::Uncommitted => "No uncommitted changes",
+                    DiffSource::Stash { .. } => "No changes in stash",
+                    DiffSource::Commit { .. } => "No changes in commit",
+                }))
             })
             .when(!is_empty, |el| el.child(self.editor.clone()))
     }
@@ -785,6 +1139,50 @@ impl Render for ProjectDiffToolbar {
             return div();
         };
         let focus_handle = project_diff.focus_handle(cx);
+        if matches!(project_diff.read(cx).source, DiffSource::Commit { .. }) {
+            return div();
+        }
+        if matches!(project_diff.read(cx).source, DiffSource::Stash { .. }) {
+            return h_group_sm()
+                .my_neg_1()
+                .items_center()
+                .py_1()
+                .pl_2()
+                .pr_1()
+                .child(
+                    Button::new("apply-stash", "Apply")
+                        .tooltip(Tooltip::for_action_title_in(
+                            "Apply stash",
+                            &ApplyStash,
+                            &focus_handle,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.dispatch_action(&ApplyStash, window, cx)
+                        })),
+                )
+                .child(
+                    Button::new("pop-stash", "Pop")
+                        .tooltip(Tooltip::for_action_title_in(
+                            "Apply and drop stash",
+                            &PopStash,
+                            &focus_handle,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.dispatch_action(&PopStash, window, cx)
+                        })),
+                )
+                .child(
+                    Button::new("drop-stash", "Drop")
+                        .tooltip(Tooltip::for_action_title_in(
+                            "Drop stash",
+                            &DropStash,
+                            &focus_handle,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.dispatch_action(&DropStash, window, cx)
+                        })),
+                );
+        }
         let button_states = project_diff.read(cx).button_states(cx);
 
         h_group_xl()
@@ -0,0 +1,611 @@
+use editor::Editor;
+use git::repository::{CommitDetails, LogOptions, RebaseAction, RebaseOutcome, RebaseTodoEntry};
+use gpui::{
+    actions, AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ScrollHandle, Task,
+    WeakEntity,
+};
+use menu::{SelectNext, SelectPrevious};
+use project::git::Repository;
+use theme::ActiveTheme;
+use ui::{prelude::*, Tooltip};
+use workspace::{
+    item::{Item, ItemEvent},
+    notifications::DetachAndPromptErr,
+    Workspace,
+};
+
+use crate::{git_panel::GitPanel, picker_prompt};
+
+actions!(
+    rebase_planner,
+    [
+        MoveUp,
+        MoveDown,
+        Pick,
+        Reword,
+        Squash,
+        Fixup,
+        Drop,
+        StartRebase
+    ]
+);
+
+/// The number of recent commits offered as the base of an interactive rebase.
+const BASE_CANDIDATE_COUNT: usize = 100;
+
+const ACTIONS: [RebaseAction; 5] = [
+    RebaseAction::Pick,
+    RebaseAction::Reword,
+    RebaseAction::Squash,
+    RebaseAction::Fixup,
+    RebaseAction::Drop,
+];
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(interactive_rebase);
+    })
+    .detach();
+}
+
+fn interactive_rebase(
+    workspace: &mut Workspace,
+    _: &git::InteractiveRebase,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let log = repository.read(cx).log(LogOptions {
+        limit: BASE_CANDIDATE_COUNT,
+        ..Default::default()
+    });
+
+    cx.spawn_in(window, |workspace, mut cx| async move {
+        let commits = log.await??;
+        let options = commits.iter().map(commit_title).collect();
+        let selection = cx
+            .update(|window, cx| {
+                picker_prompt::prompt(
+                    "Pick the commit to rebase onto",
+                    options,
+                    workspace.clone(),
+                    window,
+                    cx,
+                )
+            })?
+            .await?;
+        let Some(base) = selection.and_then(|ix| commits.get(ix)).cloned() else {
+            return Ok(());
+        };
+
+        workspace.update_in(&mut cx, |workspace, window, cx| {
+            RebasePlanner::deploy(workspace, repository, base, window, cx);
+        })
+    })
+    .detach_and_prompt_err("Failed to start rebase", window, cx, |e, _, _| {
+        Some(e.to_string())
+    });
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..git::SHORT_SHA_LENGTH).unwrap_or(sha)
+}
+
+fn commit_subject(commit: &CommitDetails) -> &str {
+    commit.message.lines().next().unwrap_or_default()
+}
+
+fn commit_title(commit: &CommitDetails) -> SharedString {
+    format!("{} {}", short_sha(&commit.sha), commit_subject(commit)).into()
+}
+
+fn action_label(action: RebaseAction) -> &'static str {
+    match action {
+        RebaseAction::Pick => "Pick",
+        RebaseAction::Reword => "Reword",
+        RebaseAction::Squash => "Squash",
+        RebaseAction::Fixup => "Fixup",
+        RebaseAction::Drop => "Drop",
+    }
+}
+
+fn action_description(action: RebaseAction) -> &'static str {
+    match action {
+        RebaseAction::Pick => "Keep the commit as it is",
+        RebaseAction::Reword => "Keep the commit with a new message",
+        RebaseAction::Squash => "Meld into the previous commit, keeping both messages",
+        RebaseAction::Fixup => "Meld into the previous commit, keeping its message",
+        RebaseAction::Drop => "Leave the commit out",
+    }
+}
+
+struct TodoEntry {
+    commit: CommitDetails,
+    action: RebaseAction,
+    /// The new message of the commit, created the first time it's marked for rewording so the
+    /// edits survive switching to another action and back.
+    message_editor: Option<Entity<Editor>>,
+}
+
+#[derive(Clone)]
+struct DraggedTodoEntry {
+    ix: usize,
+    title: SharedString,
+}
+
+impl Render for DraggedTodoEntry {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .py_1()
+            .rounded_md()
+            .bg(cx.theme().colors().elevated_surface_background)
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(self.title.clone()).size(LabelSize::Small))
+    }
+}
+
+/// An editor for the todo list of an interactive rebase of the current branch onto a base
+/// commit.
+pub struct RebasePlanner {
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    base: CommitDetails,
+    entries: Vec<TodoEntry>,
+    selected_index: usize,
+    error: Option<SharedString>,
+    scroll_handle: ScrollHandle,
+    load_task: Option<Task<()>>,
+    rebase_task: Option<Task<()>>,
+}
+
+impl RebasePlanner {
+    /// Opens a planner for rebasing onto `base`, reusing an existing one for the same base.
+    pub fn deploy(
+        workspace: &mut Workspace,
+        repository: Entity<Repository>,
+        base: CommitDetails,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace.items_of_type::<Self>(cx).find(|item| {
+            let planner = item.read(cx);
+            planner.repository == repository && planner.base.sha == base.sha
+        });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let workspace_handle = cx.entity().downgrade();
+        let planner = cx.new(|cx| Self::new(repository, base, workspace_handle, cx));
+        workspace.add_item_to_active_pane(Box::new(planner), None, true, window, cx);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        base: CommitDetails,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self {
+            repository,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            base,
+            entries: Vec::new(),
+            selected_index: 0,
+            error: None,
+            scroll_handle: ScrollHandle::new(),
+            load_task: None,
+            rebase_task: None,
+        };
+        this.load_commits(cx);
+        this
+    }
+
+    fn load_commits(&mut self, cx: &mut Context<Self>) {
+        let request = self.repository.read(cx).rebase_commits(&self.base.sha);
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            let commits = match request.await {
+                Ok(commits) => commits,
+                Err(error) => Err(error.into()),
+            };
+            this.update(&mut cx, |this, cx| {
+                this.load_task = None;
+                match commits {
+                    Ok(commits) => {
+                        this.entries = commits
+                            .into_iter()
+                            .map(|commit| TodoEntry {
+                                commit,
+                                action: RebaseAction::Pick,
+                                message_editor: None,
+                            })
+                            .collect();
+                    }
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_index + 1 < self.entries.len() {
+            self.select_index(self.selected_index + 1, cx);
+        }
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_index > 0 {
+            self.select_index(self.selected_index - 1, cx);
+        }
+    }
+
+    fn select_index(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected_index = ix;
+        self.scroll_handle.scroll_to_item(ix);
+        cx.notify();
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_index > 0 {
+            self.move_entry(self.selected_index, self.selected_index - 1, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_index + 1 < self.entries.len() {
+            self.move_entry(self.selected_index, self.selected_index + 1, cx);
+        }
+    }
+
+    /// Moves the entry at `from` so that it ends up at index `to`, keeping it selected.
+    fn move_entry(&mut self, from: usize, to: usize, cx: &mut Context<Self>) {
+        if from == to || from >= self.entries.len() || to >= self.entries.len() {
+            return;
+        }
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        self.error = None;
+        self.select_index(to, cx);
+    }
+
+    fn set_action(
+        &mut self,
+        ix: usize,
+        action: RebaseAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(entry) = self.entries.get_mut(ix) else {
+            return;
+        };
+        entry.action = action;
+        if action == RebaseAction::Reword && entry.message_editor.is_none() {
+            let message = entry.commit.message.to_string();
+            entry.message_editor = Some(cx.new(|cx| {
+                let mut editor = Editor::auto_height(8, window, cx);
+                editor.set_text(message, window, cx);
+                editor
+            }));
+        }
+        self.error = None;
+        cx.notify();
+    }
+
+    fn set_selected_action(
+        &mut self,
+        action: RebaseAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.set_action(self.selected_index, action, window, cx);
+    }
+
+    fn todo(&self, cx: &App) -> Vec<RebaseTodoEntry> {
+        self.entries
+            .iter()
+            .map(|entry| RebaseTodoEntry {
+                action: entry.action,
+                sha: entry.commit.sha.to_string(),
+                message: entry
+                    .message_editor
+                    .as_ref()
+                    .filter(|_| entry.action == RebaseAction::Reword)
+                    .map(|editor| editor.read(cx).text(cx)),
+            })
+            .collect()
+    }
+
+    fn start_rebase(&mut self, _: &StartRebase, window: &mut Window, cx: &mut Context<Self>) {
+        if self.rebase_task.is_some() || self.entries.is_empty() {
+            return;
+        }
+
+        let rebase = self
+            .repository
+            .read(cx)
+            .rebase(&self.base.sha, self.todo(cx));
+        self.error = None;
+        self.rebase_task = Some(cx.spawn_in(window, |this, mut cx| async move {
+            let outcome = match rebase.await {
+                Ok(outcome) => outcome,
+                Err(error) => Err(error.into()),
+            };
+            this.update_in(&mut cx, |this, window, cx| {
+                this.rebase_task = None;
+                match outcome {
+                    Ok(RebaseOutcome::Completed) => cx.emit(ItemEvent::CloseItem),
+                    Ok(RebaseOutcome::Stopped) => {
+                        this.workspace
+                            .update(cx, |workspace, cx| {
+                                if let Some(panel) = workspace.focus_panel::<GitPanel>(window, cx) {
+                                    panel.update(cx, |panel, cx| panel.update_rebase_status(cx));
+                                }
+                            })
+                            .ok();
+                        cx.emit(ItemEvent::CloseItem);
+                    }
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn render_entry(&self, ix: usize, entry: &TodoEntry, cx: &mut Context<Self>) -> AnyElement {
+        let title = commit_title(&entry.commit);
+        let subject = commit_subject(&entry.commit).to_string();
+        let is_dropped = entry.action == RebaseAction::Drop;
+        let is_melded = matches!(entry.action, RebaseAction::Squash | RebaseAction::Fixup);
+
+        let actions = h_flex()
+            .gap_0p5()
+            .children(ACTIONS.into_iter().map(|action| {
+                Button::new(
+                    SharedString::from(format!("{}-{ix}", action.as_str())),
+                    action_label(action),
+                )
+                .label_size(LabelSize::Small)
+                .toggle_state(entry.action == action)
+                .tooltip(Tooltip::text(action_description(action)))
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    this.set_action(ix, action, window, cx);
+                    this.select_index(ix, cx);
+                }))
+            }));
+
+        v_flex()
+            .id(("todo-entry", ix))
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .when(ix == self.selected_index, |this| {
+                this.bg(cx.theme().colors().element_selected)
+            })
+            .on_click(cx.listener(move |this, _, _, cx| this.select_index(ix, cx)))
+            .on_drag(
+                DraggedTodoEntry {
+                    ix,
+                    title: title.clone(),
+                },
+                |entry, _, _, cx| cx.new(|_| entry.clone()),
+            )
+            .drag_over::<DraggedTodoEntry>(|style, _, _, cx| {
+                style.bg(cx.theme().colors().drop_target_background)
+            })
+            .on_drop(cx.listener(move |this, dragged: &DraggedTodoEntry, _, cx| {
+                this.move_entry(dragged.ix, ix, cx);
+            }))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .child(
+                        Icon::new(IconName::Menu)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(actions)
+                    .child(
+                        Label::new(short_sha(&entry.commit.sha).to_string())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .buffer_font(cx),
+                    )
+                    .child(
+                        div().flex_1().overflow_hidden().child(
+                            Label::new(subject)
+                                .single_line()
+                                .when(is_dropped, |label| label.strikethrough())
+                                .color(if is_dropped || is_melded {
+                                    Color::Muted
+                                } else {
+                                    Color::Default
+                                }),
+                        ),
+                    ),
+            )
+            .when_some(
+                entry
+                    .message_editor
+                    .clone()
+                    .filter(|_| entry.action == RebaseAction::Reword),
+                |this, editor| {
+                    this.child(
+                        div()
+                            .ml_6()
+                            .px_2()
+                            .py_1()
+                            .rounded_md()
+                            .border_1()
+                            .border_color(cx.theme().colors().border_variant)
+                            .child(editor),
+                    )
+                },
+            )
+            .into_any_element()
+    }
+
+    fn render_entries(&self, cx: &mut Context<Self>) -> AnyElement {
+        if self.entries.is_empty() {
+            let message: SharedString = if let Some(error) = &self.error {
+                error.clone()
+            } else if self.load_task.is_some() {
+                "Loading commits…".into()
+            } else {
+                "No commits to rebase".into()
+            };
+            return div()
+                .flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element();
+        }
+
+        v_flex()
+            .id("todo-list")
+            .size_full()
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
+            .children(
+                self.entries
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, entry)| self.render_entry(ix, entry, cx)),
+            )
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<ItemEvent> for RebasePlanner {}
+
+impl Focusable for RebasePlanner {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for RebasePlanner {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &ItemEvent, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some(format!("Rebase onto {}", short_sha(&self.base.sha)).into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Rebase Planner Opened")
+    }
+}
+
+impl Render for RebasePlanner {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_rebasing = self.rebase_task.is_some();
+        let focus_handle = self.focus_handle.clone();
+
+        v_flex()
+            .key_context("RebasePlanner")
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .p_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        div().flex_1().overflow_hidden().child(
+                            Label::new(format!("Rebase onto {}", commit_title(&self.base)))
+                                .single_line(),
+                        ),
+                    )
+                    .child(
+                        Button::new(
+                            "start-rebase",
+                            if is_rebasing {
+                                "Rebasing…"
+                            } else {
+                                "Start Rebase"
+                            },
+                        )
+                        .style(ButtonStyle::Filled)
+                        .disabled(is_rebasing || self.entries.is_empty())
+                        .tooltip(move |window, cx| {
+                            Tooltip::for_action_in(
+                                "Start Rebase",
+                                &StartRebase,
+                                &focus_handle,
+                                window,
+                                cx,
+                            )
+                        })
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.start_rebase(&StartRebase, window, cx)
+                        })),
+                    ),
+            )
+            .when_some(
+                self.error.clone().filter(|_| !self.entries.is_empty()),
+                |this, error| {
+                    this.child(
+                        div()
+                            .px_2()
+                            .py_1()
+                            .border_b_1()
+                            .border_color(cx.theme().colors().border)
+                            .child(Label::new(error).size(LabelSize::Small).color(Color::Error)),
+                    )
+                },
+            )
+            .child(
+                div()
+                    .key_context("TodoList")
+                    .track_focus(&self.focus_handle)
+                    .on_action(cx.listener(Self::select_next))
+                    .on_action(cx.listener(Self::select_previous))
+                    .on_action(cx.listener(Self::move_up))
+                    .on_action(cx.listener(Self::move_down))
+                    .on_action(cx.listener(|this, _: &Pick, window, cx| {
+                        this.set_selected_action(RebaseAction::Pick, window, cx)
+                    }))
+                    .on_action(cx.listener(|this, _: &Reword, window, cx| {
+                        this.set_selected_action(RebaseAction::Reword, window, cx)
+                    }))
+                    .on_action(cx.listener(|this, _: &Squash, window, cx| {
+                        this.set_selected_action(RebaseAction::Squash, window, cx)
+                    }))
+                    .on_action(cx.listener(|this, _: &Fixup, window, cx| {
+                        this.set_selected_action(RebaseAction::Fixup, window, cx)
+                    }))
+                    .on_action(cx.listener(|this, _: &Drop, window, cx| {
+                        this.set_selected_action(RebaseAction::Drop, window, cx)
+                    }))
+                    .on_action(cx.listener(Self::start_rebase))
+                    .flex_1()
+                    .size_full()
+                    .child(self.render_entries(cx)),
+            )
+    }
+}
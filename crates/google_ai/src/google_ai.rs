@@ -73,6 +73,37 @@ pub async fn stream_generate_content(
     }
 }
 
+/// Caches the contents of a request, so that later requests can refer to them instead
+/// of sending them again.
+pub async fn create_cached_content(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: &str,
+    request: CreateCachedContentRequest,
+) -> Result<CachedContent> {
+    let uri = format!("{api_url}/v1beta/cachedContents?key={api_key}");
+    let request = serde_json::to_string(&request)?;
+
+    let request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(&uri)
+        .header("Content-Type", "application/json");
+
+    let http_request = request_builder.body(AsyncBody::from(request))?;
+    let mut response = client.send(http_request).await?;
+    let mut text = String::new();
+    response.body_mut().read_to_string(&mut text).await?;
+    if response.status().is_success() {
+        Ok(serde_json::from_str::<CachedContent>(&text)?)
+    } else {
+        Err(anyhow!(
+            "error during cachedContents.create, status code: {:?}, body: {}",
+            response.status(),
+            text
+        ))
+    }
+}
+
 pub async fn count_tokens(
     client: &dyn HttpClient,
     api_url: &str,
@@ -127,6 +158,28 @@ pub struct GenerateContentRequest {
     pub contents: Vec<Content>,
     pub generation_config: Option<GenerationConfig>,
     pub safety_settings: Option<Vec<SafetySetting>>,
+    /// The name of cached content to continue, which precedes the contents of the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_content: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCachedContentRequest {
+    /// The versioned model the contents are cached for, such as `models/gemini-1.5-flash-002`.
+    pub model: String,
+    pub contents: Vec<Content>,
+    /// How long the contents are kept, such as `300s`.
+    pub ttl: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedContent {
+    /// The name to refer to the cached contents by, such as `cachedContents/abc123`.
+    pub name: String,
+    pub model: Option<String>,
+    pub expire_time: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -134,6 +187,22 @@ pub struct GenerateContentRequest {
 pub struct GenerateContentResponse {
     pub candidates: Option<Vec<GenerateContentCandidate>>,
     pub prompt_feedback: Option<PromptFeedback>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageMetadata {
+    /// The tokens in the prompt, including those read from the cache.
+    #[serde(default)]
+    pub prompt_token_count: u32,
+    #[serde(default)]
+    pub cached_content_token_count: u32,
+    #[serde(default)]
+    pub candidates_token_count: u32,
+    #[serde(default)]
+    pub total_token_count: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -147,40 +216,40 @@ pub struct GenerateContentCandidate {
     pub citation_metadata: Option<CitationMetadata>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Content {
     pub parts: Vec<Part>,
     pub role: Role,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Role {
     User,
     Model,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Part {
     TextPart(TextPart),
     InlineDataPart(InlineDataPart),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TextPart {
     pub text: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InlineDataPart {
     pub inline_data: GenerativeContentBlob,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GenerativeContentBlob {
     pub mime_type: String,
@@ -219,6 +288,11 @@ pub struct GenerationConfig {
     pub temperature: Option<f64>,
     pub top_p: Option<f64>,
     pub top_k: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_mime_type: Option<String>,
+    /// The schema of the JSON response, in the subset of OpenAPI schemas Gemini supports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -298,6 +372,14 @@ pub struct CountTokensResponse {
     pub total_tokens: usize,
 }
 
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GoogleModelCacheConfiguration {
+    pub min_total_token: usize,
+    pub should_speculate: bool,
+    pub max_cache_anchors: usize,
+}
+
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Clone, Default, Debug, Deserialize, Serialize, PartialEq, Eq, strum::EnumIter)]
 pub enum Model {
@@ -320,6 +402,9 @@ pub enum Model {
         /// The name displayed in the UI, such as in the assistant panel model dropdown menu.
         display_name: Option<String>,
         max_tokens: usize,
+        /// Indicates whether this custom model supports context caching.
+        #[serde(default)]
+        cache_configuration: Option<GoogleModelCacheConfiguration>,
     },
 }
 
@@ -361,6 +446,34 @@ impl Model {
             Model::Custom { max_tokens, .. } => *max_tokens,
         }
     }
+
+    pub fn cache_configuration(&self) -> Option<GoogleModelCacheConfiguration> {
+        match self {
+            Model::Gemini15Pro | Model::Gemini15Flash | Model::Gemini20Flash => {
+                Some(GoogleModelCacheConfiguration {
+                    min_total_token: 32_768,
+                    should_speculate: false,
+                    max_cache_anchors: 2,
+                })
+            }
+            Model::Custom {
+                cache_configuration,
+                ..
+            } => cache_configuration.clone(),
+            _ => None,
+        }
+    }
+
+    /// Returns the ID of a stable version of the model, as contents can only be cached
+    /// for a fixed version of a model.
+    pub fn cache_model_id(&self) -> &str {
+        match self {
+            Model::Gemini15Pro => "gemini-1.5-pro-002",
+            Model::Gemini15Flash => "gemini-1.5-flash-002",
+            Model::Gemini20Flash => "gemini-2.0-flash-001",
+            _ => self.id(),
+        }
+    }
 }
 
 impl std::fmt::Display for Model {
@@ -231,6 +231,67 @@ impl Render for InlineCompletionButton {
                 );
             }
 
+            provider @ (EditPredictionProvider::Ollama | EditPredictionProvider::LmStudio) => {
+                let edit_predictions = &all_language_settings.edit_predictions;
+                let (icon, title, model) = if provider == EditPredictionProvider::Ollama {
+                    (
+                        IconName::AiOllama,
+                        "Ollama Edit Predictions",
+                        edit_predictions.ollama.model.clone(),
+                    )
+                } else {
+                    (
+                        IconName::AiLmStudio,
+                        "LM Studio Edit Predictions",
+                        edit_predictions.lmstudio.model.clone(),
+                    )
+                };
+
+                let icon_button = IconButton::new("fim-icon", icon)
+                    .shape(IconButtonShape::Square)
+                    .when(model.is_none(), |this| {
+                        this.indicator(Indicator::dot().color(Color::Error))
+                            .indicator_border_color(Some(cx.theme().colors().status_bar_background))
+                    })
+                    .when(!self.popover_menu_handle.is_deployed(), |element| {
+                        element.tooltip(move |window, cx| {
+                            let meta = model
+                                .clone()
+                                .unwrap_or_else(|| "No model configured".to_string());
+                            Tooltip::with_meta(title, Some(&ToggleMenu), meta, window, cx)
+                        })
+                    });
+
+                let this = cx.entity().clone();
+                let mut popover_menu = PopoverMenu::new("fim")
+                    .menu(move |window, cx| {
+                        Some(this.update(cx, |this, cx| this.build_fim_context_menu(window, cx)))
+                    })
+                    .anchor(Corner::BottomRight)
+                    .with_handle(self.popover_menu_handle.clone());
+
+                let is_refreshing = self
+                    .edit_prediction_provider
+                    .as_ref()
+                    .map_or(false, |provider| provider.is_refreshing(cx));
+
+                if is_refreshing {
+                    popover_menu = popover_menu.trigger(
+                        icon_button.with_animation(
+                            "pulsating-label",
+                            Animation::new(Duration::from_secs(2))
+                                .repeat()
+                                .with_easing(pulsating_between(0.2, 1.0)),
+                            |icon_button, delta| icon_button.alpha(delta),
+                        ),
+                    );
+                } else {
+                    popover_menu = popover_menu.trigger(icon_button);
+                }
+
+                div().child(popover_menu.into_any_element())
+            }
+
             EditPredictionProvider::Zed => {
                 if !cx.has_flag::<PredictEditsFeatureFlag>() {
                     return div();
@@ -678,6 +739,16 @@ impl InlineCompletionButton {
         })
     }
 
+    fn build_fim_context_menu(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<ContextMenu> {
+        ContextMenu::build(window, cx, |menu, window, cx| {
+            self.build_language_settings_menu(menu, window, cx)
+        })
+    }
+
     fn build_zeta_context_menu(
         &self,
         window: &mut Window,
@@ -23,7 +23,12 @@ use serde_json::Value;
 use settings::{
     add_references_to_properties, Settings, SettingsLocation, SettingsSources, SettingsStore,
 };
-use std::{borrow::Cow, num::NonZeroU32, path::Path, sync::Arc};
+use std::{
+    borrow::Cow,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use util::serde::default_true;
 
 /// Initializes the language settings.
@@ -209,6 +214,11 @@ pub enum EditPredictionProvider {
     Copilot,
     Supermaven,
     Zed,
+    /// Fill-in-the-middle completions from a model served by Ollama.
+    Ollama,
+    /// Fill-in-the-middle completions from a model served by LM Studio.
+    #[serde(rename = "lmstudio")]
+    LmStudio,
 }
 
 impl EditPredictionProvider {
@@ -217,7 +227,9 @@ impl EditPredictionProvider {
             EditPredictionProvider::Zed => true,
             EditPredictionProvider::None
             | EditPredictionProvider::Copilot
-            | EditPredictionProvider::Supermaven => false,
+            | EditPredictionProvider::Supermaven
+            | EditPredictionProvider::Ollama
+            | EditPredictionProvider::LmStudio => false,
         }
     }
 }
@@ -236,6 +248,12 @@ pub struct EditPredictionSettings {
     pub mode: EditPredictionsMode,
     /// Settings specific to GitHub Copilot.
     pub copilot: CopilotSettings,
+    /// Settings for edit predictions from a model served by Ollama.
+    pub ollama: LocalModelSettings,
+    /// Settings for edit predictions from a model served by LM Studio.
+    pub lmstudio: LocalModelSettings,
+    /// Settings for edit predictions from Zeta.
+    pub zeta: ZetaSettings,
     /// Whether edit predictions are enabled in the assistant panel.
     /// This setting has no effect if globally disabled.
     pub enabled_in_assistant: bool,
@@ -283,6 +301,92 @@ pub struct CopilotSettings {
     pub proxy_no_verify: Option<bool>,
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct LocalModelSettings {
+    /// The URL of the server's API.
+    pub api_url: Option<String>,
+    /// The model that completes the code at the cursor.
+    pub model: Option<String>,
+    /// The template of the model's fill-in-the-middle prompts, for models
+    /// that aren't known to Zed.
+    pub fim_template: Option<FimTemplate>,
+    /// The maximum number of tokens to generate for a single prediction.
+    pub max_tokens: Option<u32>,
+}
+
+impl LocalModelSettings {
+    fn merge(&mut self, content: &LocalModelSettingsContent) {
+        if let Some(api_url) = content.api_url.clone() {
+            self.api_url = Some(api_url);
+        }
+        if let Some(model) = content.model.clone() {
+            self.model = Some(model);
+        }
+        if let Some(fim_template) = content.fim_template.clone() {
+            self.fim_template = Some(fim_template);
+        }
+        if let Some(max_tokens) = content.max_tokens {
+            self.max_tokens = Some(max_tokens);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ZetaSettings {
+    /// The base URL of a self-hosted server that predicts edits in place of Zed's.
+    pub api_url: Option<String>,
+    /// The value of the `Authorization` header sent to the self-hosted server.
+    pub auth_header: Option<String>,
+    /// Where requests and their predictions are recorded to, or replayed from.
+    pub recording: Option<ZetaRecording>,
+}
+
+impl ZetaSettings {
+    fn merge(&mut self, content: &ZetaSettingsContent) {
+        if let Some(api_url) = content.api_url.clone() {
+            self.api_url = Some(api_url);
+        }
+        if let Some(auth_header) = content.auth_header.clone() {
+            self.auth_header = Some(auth_header);
+        }
+        if let Some(recording) = content.recording.clone() {
+            self.recording = Some(recording);
+        }
+    }
+}
+
+/// A directory of recorded edit prediction requests and their responses.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ZetaRecording {
+    /// Whether to record predictions, or to replay the recorded ones.
+    pub mode: ZetaRecordingMode,
+    /// The directory the recordings are stored in.
+    pub path: PathBuf,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ZetaRecordingMode {
+    /// Send requests as usual, and save each one along with its prediction.
+    Record,
+    /// Answer requests with the recorded prediction of an identical request,
+    /// without sending them.
+    Replay,
+}
+
+/// How to prompt a model to fill in the code between a prefix and a suffix.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct FimTemplate {
+    /// The prompt, in which `{prefix}` and `{suffix}` are replaced with the code
+    /// before and after the cursor.
+    ///
+    /// For example: "<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>"
+    pub prompt: String,
+    /// The sequences that end the completion, such as the model's end-of-text token.
+    #[serde(default)]
+    pub stop: Vec<String>,
+}
+
 /// The settings for all languages.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AllLanguageSettingsContent {
@@ -503,6 +607,15 @@ pub struct EditPredictionSettingsContent {
     /// Settings specific to GitHub Copilot.
     #[serde(default)]
     pub copilot: CopilotSettingsContent,
+    /// Settings for edit predictions from a model served by Ollama.
+    #[serde(default)]
+    pub ollama: LocalModelSettingsContent,
+    /// Settings for edit predictions from a model served by LM Studio.
+    #[serde(default)]
+    pub lmstudio: LocalModelSettingsContent,
+    /// Settings for edit predictions from Zeta.
+    #[serde(default)]
+    pub zeta: ZetaSettingsContent,
     /// Whether edit predictions are enabled in the assistant prompt editor.
     /// This has no effect if globally disabled.
     #[serde(default = "default_true")]
@@ -523,6 +636,52 @@ pub struct CopilotSettingsContent {
     pub proxy_no_verify: Option<bool>,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct LocalModelSettingsContent {
+    /// The URL of the server's API.
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// The model that completes the code at the cursor. Models trained for
+    /// fill-in-the-middle completion, such as Qwen2.5-Coder, work best.
+    ///
+    /// Default: none
+    #[serde(default)]
+    pub model: Option<String>,
+    /// The template of the model's fill-in-the-middle prompts. Only needed for
+    /// models that aren't known to Zed.
+    ///
+    /// Default: none
+    #[serde(default)]
+    pub fim_template: Option<FimTemplate>,
+    /// The maximum number of tokens to generate for a single prediction.
+    ///
+    /// Default: 128
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ZetaSettingsContent {
+    /// The base URL of a self-hosted server to request predictions from, in place
+    /// of Zed's. The server needs to implement the `/predict_edits/v2` endpoint.
+    ///
+    /// Default: none
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// The value of the `Authorization` header sent to the self-hosted server,
+    /// such as "Bearer <token>".
+    ///
+    /// Default: none
+    #[serde(default)]
+    pub auth_header: Option<String>,
+    /// Records each request and its prediction to a directory, or answers
+    /// requests with the recorded predictions instead of sending them.
+    ///
+    /// Default: none
+    #[serde(default)]
+    pub recording: Option<ZetaRecording>,
+}
+
 /// The settings for enabling/disabling features.
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -1133,6 +1292,15 @@ impl settings::Settings for AllLanguageSettings {
             })
             .unwrap_or_default();
 
+        let mut ollama_settings = LocalModelSettings::default();
+        let mut lmstudio_settings = LocalModelSettings::default();
+        let mut zeta_settings = ZetaSettings::default();
+        if let Some(edit_predictions) = default_value.edit_predictions.as_ref() {
+            ollama_settings.merge(&edit_predictions.ollama);
+            lmstudio_settings.merge(&edit_predictions.lmstudio);
+            zeta_settings.merge(&edit_predictions.zeta);
+        }
+
         let mut edit_predictions_enabled_in_assistant = default_value
             .edit_predictions
             .as_ref()
@@ -1166,6 +1334,9 @@ impl settings::Settings for AllLanguageSettings {
             if let Some(edit_predictions) = user_settings.edit_predictions.as_ref() {
                 edit_predictions_mode = edit_predictions.mode;
                 edit_predictions_enabled_in_assistant = edit_predictions.enabled_in_assistant;
+                ollama_settings.merge(&edit_predictions.ollama);
+                lmstudio_settings.merge(&edit_predictions.lmstudio);
+                zeta_settings.merge(&edit_predictions.zeta);
 
                 if let Some(disabled_globs) = edit_predictions.disabled_globs.as_ref() {
                     completion_globs.extend(disabled_globs.iter());
@@ -1245,6 +1416,9 @@ impl settings::Settings for AllLanguageSettings {
                     .collect(),
                 mode: edit_predictions_mode,
                 copilot: copilot_settings,
+                ollama: ollama_settings,
+                lmstudio: lmstudio_settings,
+                zeta: zeta_settings,
                 enabled_in_assistant: edit_predictions_enabled_in_assistant,
             },
             defaults,
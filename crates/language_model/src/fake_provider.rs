@@ -161,8 +161,18 @@ impl LanguageModel for FakeLanguageModel {
         1000000
     }
 
-    fn count_tokens(&self, _: LanguageModelRequest, _: &App) -> BoxFuture<'static, Result<usize>> {
-        futures::future::ready(Ok(0)).boxed()
+    /// Counts every byte of the request's text as a token.
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        _: &App,
+    ) -> BoxFuture<'static, Result<usize>> {
+        let token_count = request
+            .messages
+            .iter()
+            .map(|message| message.string_contents().len())
+            .sum();
+        futures::future::ready(Ok(token_count)).boxed()
     }
 
     fn stream_completion(
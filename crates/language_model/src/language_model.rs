@@ -57,8 +57,45 @@ pub struct LanguageModelCacheConfiguration {
 pub enum LanguageModelCompletionEvent {
     Stop(StopReason),
     Text(String),
+    /// A chunk of the model's reasoning, streamed before its answer.
+    ///
+    /// The signature, which some providers send once the reasoning is complete,
+    /// has to be sent back along with the reasoning in later requests.
+    Thinking {
+        text: String,
+        signature: Option<String>,
+    },
+    /// A block of reasoning the provider encrypted, which can't be shown but has to
+    /// be sent back in later requests.
+    RedactedThinking {
+        data: String,
+    },
     ToolUse(LanguageModelToolUse),
-    StartMessage { message_id: String },
+    StartMessage {
+        message_id: String,
+    },
+    /// The tokens used by the request so far, which replace those of earlier updates.
+    UsageUpdate(TokenUsage),
+}
+
+/// The tokens used by a request, as reported by the provider.
+///
+/// The input tokens don't include those written to or read from the prompt cache.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cache_creation_input_tokens: u32,
+    pub cache_read_input_tokens: u32,
+}
+
+impl TokenUsage {
+    pub fn total_tokens(&self) -> u32 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
@@ -174,8 +211,11 @@ pub trait LanguageModel: Send + Sync {
                     match result {
                         Ok(LanguageModelCompletionEvent::StartMessage { .. }) => None,
                         Ok(LanguageModelCompletionEvent::Text(text)) => Some(Ok(text)),
+                        Ok(LanguageModelCompletionEvent::Thinking { .. }) => None,
+                        Ok(LanguageModelCompletionEvent::RedactedThinking { .. }) => None,
                         Ok(LanguageModelCompletionEvent::Stop(_)) => None,
                         Ok(LanguageModelCompletionEvent::ToolUse(_)) => None,
+                        Ok(LanguageModelCompletionEvent::UsageUpdate(_)) => None,
                         Err(err) => Some(Err(err)),
                     }
                 }))
@@ -199,6 +239,17 @@ pub trait LanguageModel: Send + Sync {
         None
     }
 
+    /// Whether the model honors [`LanguageModelRequest::response_format`].
+    fn supports_structured_output(&self) -> bool {
+        false
+    }
+
+    /// Whether the model can see the [`MessageContent::Image`]s of a request. The images
+    /// are left out of requests to models that can't.
+    fn supports_images(&self) -> bool {
+        false
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     fn as_fake(&self) -> &fake_provider::FakeLanguageModel {
         unimplemented!()
@@ -230,6 +281,59 @@ impl dyn LanguageModel {
         let schema_json = serde_json::to_value(&schema).unwrap();
         self.use_any_tool(request, T::name(), T::description(), schema_json, cx)
     }
+
+    /// Asks the model for an answer that matches the JSON schema of `T`.
+    ///
+    /// Models that support structured output are constrained to the schema directly, while
+    /// all others are forced to answer by using a tool whose input is `T`.
+    pub fn complete_structured<T: LanguageModelTool>(
+        &self,
+        mut request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> impl 'static + Future<Output = Result<T>> {
+        let schema = schemars::schema_for!(T);
+        let schema_json = serde_json::to_value(&schema).unwrap();
+        let response = if self.supports_structured_output() {
+            request.response_format = Some(LanguageModelResponseFormat {
+                name: T::name(),
+                schema: schema_json,
+            });
+            let stream = self.stream_completion_text(request, cx);
+            async move { stream.await?.stream.try_collect::<String>().await }.boxed()
+        } else {
+            let stream = self.use_any_tool(request, T::name(), T::description(), schema_json, cx);
+            async move { stream.await?.try_collect::<String>().await }.boxed()
+        };
+        async move { Ok(parse_structured_output(&response.await?)?) }
+    }
+}
+
+/// Deserializes a structured answer, tolerating the code fences some models wrap their
+/// JSON in.
+pub fn parse_structured_output<T: DeserializeOwned>(
+    response: &str,
+) -> Result<T, StructuredOutputError> {
+    let mut json = response.trim();
+    if let Some(fenced) = json.strip_prefix("```") {
+        json = fenced
+            .trim_start_matches("json")
+            .trim_end()
+            .trim_end_matches("```")
+            .trim();
+    }
+    serde_json::from_str(json).map_err(|error| StructuredOutputError {
+        response: response.to_string(),
+        error,
+    })
+}
+
+/// The model's answer didn't match the requested schema.
+#[derive(Debug, Error)]
+#[error("model response did not match the schema: {error}")]
+pub struct StructuredOutputError {
+    pub response: String,
+    #[source]
+    pub error: serde_json::Error,
 }
 
 pub trait LanguageModelTool: 'static + DeserializeOwned + JsonSchema {
@@ -336,3 +440,28 @@ impl From<String> for LanguageModelProviderName {
         Self(SharedString::from(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Answer {
+        score: u32,
+    }
+
+    #[test]
+    fn test_parse_structured_output() {
+        assert_eq!(
+            parse_structured_output::<Answer>(r#" {"score": 3} "#).unwrap(),
+            Answer { score: 3 }
+        );
+        assert_eq!(
+            parse_structured_output::<Answer>("```json\n{\"score\": 4}\n```").unwrap(),
+            Answer { score: 4 }
+        );
+
+        let error = parse_structured_output::<Answer>(r#"{"score": "high"}"#).unwrap_err();
+        assert_eq!(error.response, r#"{"score": "high"}"#);
+    }
+}
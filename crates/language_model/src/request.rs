@@ -174,6 +174,16 @@ pub struct LanguageModelToolResult {
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub enum MessageContent {
     Text(String),
+    /// The reasoning that preceded an assistant's answer. It is never part of the
+    /// message's string contents.
+    Thinking {
+        text: String,
+        signature: Option<String>,
+    },
+    /// Reasoning the provider encrypted, which has to be sent back unchanged.
+    RedactedThinking {
+        data: String,
+    },
     Image(LanguageModelImage),
     ToolUse(LanguageModelToolUse),
     ToolResult(LanguageModelToolResult),
@@ -204,7 +214,10 @@ impl LanguageModelRequestMessage {
         for string in self.content.iter().filter_map(|content| match content {
             MessageContent::Text(text) => Some(text.as_str()),
             MessageContent::ToolResult(tool_result) => Some(tool_result.content.as_ref()),
-            MessageContent::ToolUse(_) | MessageContent::Image(_) => None,
+            MessageContent::Thinking { .. }
+            | MessageContent::RedactedThinking { .. }
+            | MessageContent::ToolUse(_)
+            | MessageContent::Image(_) => None,
         }) {
             buffer.push_str(string);
         }
@@ -219,10 +232,15 @@ impl LanguageModelRequestMessage {
                 .first()
                 .map(|content| match content {
                     MessageContent::Text(text) => text.chars().all(|c| c.is_whitespace()),
+                    MessageContent::Thinking { text, .. } => {
+                        text.chars().all(|c| c.is_whitespace())
+                    }
                     MessageContent::ToolResult(tool_result) => {
                         tool_result.content.chars().all(|c| c.is_whitespace())
                     }
-                    MessageContent::ToolUse(_) | MessageContent::Image(_) => true,
+                    MessageContent::RedactedThinking { .. }
+                    | MessageContent::ToolUse(_)
+                    | MessageContent::Image(_) => true,
                 })
                 .unwrap_or(false)
     }
@@ -235,12 +253,23 @@ pub struct LanguageModelRequestTool {
     pub input_schema: serde_json::Value,
 }
 
+/// Constrains the model's answer to JSON that matches a schema.
+#[derive(Debug, PartialEq, Hash, Clone, Serialize, Deserialize)]
+pub struct LanguageModelResponseFormat {
+    pub name: String,
+    pub schema: serde_json::Value,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
 pub struct LanguageModelRequest {
     pub messages: Vec<LanguageModelRequestMessage>,
     pub tools: Vec<LanguageModelRequestTool>,
     pub stop: Vec<String>,
     pub temperature: Option<f32>,
+    /// Only honored by models that support structured output natively. Use
+    /// `complete_structured` to get structured output from any model.
+    #[serde(default)]
+    pub response_format: Option<LanguageModelResponseFormat>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
@@ -0,0 +1,53 @@
+use language_model::TokenUsage;
+use ui::{prelude::*, Tooltip};
+
+/// Shows whether a request read its prompt from the provider's cache, or only wrote it.
+#[derive(IntoElement)]
+pub struct CacheStatusIndicator {
+    id: ElementId,
+    usage: TokenUsage,
+    hit: bool,
+}
+
+impl CacheStatusIndicator {
+    /// Returns `None` for requests that didn't touch the prompt cache at all.
+    pub fn new(id: impl Into<ElementId>, usage: TokenUsage) -> Option<Self> {
+        let hit = if usage.cache_read_input_tokens > 0 {
+            true
+        } else if usage.cache_creation_input_tokens > 0 {
+            false
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            id: id.into(),
+            usage,
+            hit,
+        })
+    }
+}
+
+impl RenderOnce for CacheStatusIndicator {
+    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let (title, color) = if self.hit {
+            ("Cache Hit", Color::Success)
+        } else {
+            ("Cache Miss", Color::Hint)
+        };
+        let usage = self.usage;
+        let meta = format!(
+            "{} tokens read from the cache, {} written to it, {} uncached",
+            usage.cache_read_input_tokens, usage.cache_creation_input_tokens, usage.input_tokens
+        );
+
+        div()
+            .id(self.id)
+            .child(
+                Icon::new(IconName::DatabaseZap)
+                    .size(IconSize::XSmall)
+                    .color(color),
+            )
+            .tooltip(move |window, cx| Tooltip::with_meta(title, None, meta.clone(), window, cx))
+    }
+}
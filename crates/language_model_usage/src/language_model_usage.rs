@@ -0,0 +1,103 @@
+mod cache_indicator;
+mod persistence;
+mod pricing;
+mod usage_panel;
+
+use chrono::{Local, Utc};
+use gpui::{App, AppContext as _, Context, Entity, Global};
+use language_model::{LanguageModel, LanguageModelProviderId, TokenUsage};
+use project::Project;
+use util::ResultExt as _;
+
+pub use crate::cache_indicator::CacheStatusIndicator;
+use crate::persistence::USAGE_DB;
+pub use crate::pricing::{pricing_for_model, ModelPricing};
+pub use crate::usage_panel::{ToggleFocus, UsagePanel};
+
+pub fn init(cx: &mut App) {
+    let ledger = cx.new(|_| UsageLedger);
+    cx.set_global(GlobalUsageLedger(ledger));
+    usage_panel::init(cx);
+}
+
+struct GlobalUsageLedger(Entity<UsageLedger>);
+
+impl Global for GlobalUsageLedger {}
+
+/// Records the tokens used by language model requests, along with their cost, in a local
+/// database.
+///
+/// Observers are notified after each recorded request.
+pub struct UsageLedger;
+
+impl UsageLedger {
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalUsageLedger>()
+            .map(|ledger| ledger.0.clone())
+    }
+
+    pub fn record(
+        &mut self,
+        provider_id: &LanguageModelProviderId,
+        model_id: &str,
+        project: Option<String>,
+        usage: TokenUsage,
+        cx: &mut Context<Self>,
+    ) {
+        let cost = pricing_for_model(provider_id, model_id).map(|pricing| pricing.cost(&usage));
+        let recorded_at = Utc::now().timestamp();
+        let day = Local::now().format("%Y-%m-%d").to_string();
+        let provider = provider_id.0.to_string();
+        let model = model_id.to_string();
+
+        cx.spawn(|this, mut cx| async move {
+            USAGE_DB
+                .insert_usage(
+                    recorded_at,
+                    day,
+                    provider,
+                    model,
+                    project,
+                    usage.input_tokens,
+                    usage.output_tokens,
+                    usage.cache_creation_input_tokens,
+                    usage.cache_read_input_tokens,
+                    cost,
+                )
+                .await
+                .log_err();
+            this.update(&mut cx, |_, cx| cx.notify()).ok();
+        })
+        .detach();
+    }
+}
+
+/// Records the tokens used by a completion of the given model, attributing them to the
+/// project the request was made from.
+pub fn record_usage(
+    model: &dyn LanguageModel,
+    usage: TokenUsage,
+    project: Option<&Entity<Project>>,
+    cx: &mut App,
+) {
+    if usage == TokenUsage::default() {
+        return;
+    }
+    let Some(ledger) = UsageLedger::global(cx) else {
+        return;
+    };
+
+    let project = project.and_then(|project| {
+        let root_names = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).root_name().to_string())
+            .collect::<Vec<_>>();
+        (!root_names.is_empty()).then(|| root_names.join(", "))
+    });
+    let provider_id = model.provider_id();
+    let model_id = model.id();
+    ledger.update(cx, |ledger, cx| {
+        ledger.record(&provider_id, &model_id.0, project, usage, cx)
+    });
+}
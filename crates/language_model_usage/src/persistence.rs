@@ -0,0 +1,193 @@
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+
+define_connection!(pub static ref USAGE_DB: UsageDb<()> =
+    &[sql!(
+        CREATE TABLE language_model_usage(
+            recorded_at INTEGER NOT NULL,
+            day TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            project TEXT,
+            input_tokens INTEGER NOT NULL,
+            output_tokens INTEGER NOT NULL,
+            cache_creation_input_tokens INTEGER NOT NULL,
+            cache_read_input_tokens INTEGER NOT NULL,
+            cost REAL
+        ) STRICT;
+
+        CREATE INDEX language_model_usage_recorded_at ON language_model_usage(recorded_at);
+    )];
+    global
+);
+
+impl UsageDb {
+    query! {
+        pub async fn insert_usage(
+            recorded_at: i64,
+            day: String,
+            provider: String,
+            model: String,
+            project: Option<String>,
+            input_tokens: u32,
+            output_tokens: u32,
+            cache_creation_input_tokens: u32,
+            cache_read_input_tokens: u32,
+            cost: Option<f64>
+        ) -> Result<()> {
+            INSERT INTO language_model_usage(
+                recorded_at,
+                day,
+                provider,
+                model,
+                project,
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens,
+                cache_read_input_tokens,
+                cost
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        }
+    }
+
+    query! {
+        pub fn usage_by_day(since: i64) -> Result<Vec<(String, i64, i64, i64, i64, Option<f64>)>> {
+            SELECT
+                day,
+                SUM(input_tokens),
+                SUM(output_tokens),
+                SUM(cache_creation_input_tokens),
+                SUM(cache_read_input_tokens),
+                SUM(cost)
+            FROM language_model_usage
+            WHERE recorded_at >= ?
+            GROUP BY day
+            ORDER BY day DESC
+        }
+    }
+
+    query! {
+        pub fn usage_by_model(since: i64) -> Result<Vec<(String, String, i64, i64, i64, i64, Option<f64>)>> {
+            SELECT
+                provider,
+                model,
+                SUM(input_tokens),
+                SUM(output_tokens),
+                SUM(cache_creation_input_tokens),
+                SUM(cache_read_input_tokens),
+                SUM(cost)
+            FROM language_model_usage
+            WHERE recorded_at >= ?
+            GROUP BY provider, model
+            ORDER BY SUM(cost) DESC, provider, model
+        }
+    }
+
+    query! {
+        pub fn usage_by_project(since: i64) -> Result<Vec<(Option<String>, i64, i64, i64, i64, Option<f64>)>> {
+            SELECT
+                project,
+                SUM(input_tokens),
+                SUM(output_tokens),
+                SUM(cache_creation_input_tokens),
+                SUM(cache_read_input_tokens),
+                SUM(cost)
+            FROM language_model_usage
+            WHERE recorded_at >= ?
+            GROUP BY project
+            ORDER BY SUM(cost) DESC, project
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_usage_grouping() {
+        let db = UsageDb(db::open_test_db("test_usage_grouping").await);
+
+        db.insert_usage(
+            100,
+            "2025-03-01".into(),
+            "anthropic".into(),
+            "claude-3-5-sonnet-latest".into(),
+            Some("zed".into()),
+            10,
+            20,
+            0,
+            5,
+            Some(0.5),
+        )
+        .await
+        .unwrap();
+        db.insert_usage(
+            200,
+            "2025-03-02".into(),
+            "ollama".into(),
+            "llama3.2".into(),
+            None,
+            1,
+            2,
+            0,
+            0,
+            None,
+        )
+        .await
+        .unwrap();
+        db.insert_usage(
+            300,
+            "2025-03-02".into(),
+            "anthropic".into(),
+            "claude-3-5-sonnet-latest".into(),
+            Some("zed".into()),
+            30,
+            40,
+            7,
+            0,
+            Some(0.25),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            db.usage_by_day(0).unwrap(),
+            vec![
+                ("2025-03-02".to_string(), 31, 42, 7, 0, Some(0.25)),
+                ("2025-03-01".to_string(), 10, 20, 0, 5, Some(0.5)),
+            ]
+        );
+        assert_eq!(
+            db.usage_by_model(150).unwrap(),
+            vec![
+                (
+                    "anthropic".to_string(),
+                    "claude-3-5-sonnet-latest".to_string(),
+                    30,
+                    40,
+                    7,
+                    0,
+                    Some(0.25)
+                ),
+                (
+                    "ollama".to_string(),
+                    "llama3.2".to_string(),
+                    1,
+                    2,
+                    0,
+                    0,
+                    None
+                ),
+            ]
+        );
+        assert_eq!(
+            db.usage_by_project(0).unwrap(),
+            vec![
+                (Some("zed".to_string()), 40, 60, 7, 5, Some(0.75)),
+                (None, 1, 2, 0, 0, None),
+            ]
+        );
+    }
+}
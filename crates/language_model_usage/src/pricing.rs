@@ -0,0 +1,123 @@
+use language_model::{LanguageModelProviderId, TokenUsage};
+
+/// The price of a model in US dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    /// Returns the cost of the given usage in US dollars.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+// Models are matched by the first entry their ID contains, so more specific names
+// need to come before the names they contain.
+const ANTHROPIC_PRICING: &[(&str, ModelPricing)] = &[
+    ("claude-3-7-sonnet", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-5-sonnet", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-5-haiku", ModelPricing::new(0.8, 4.0, 1.0, 0.08)),
+    ("claude-3-opus", ModelPricing::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-3-sonnet", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-haiku", ModelPricing::new(0.25, 1.25, 0.3, 0.03)),
+];
+
+const OPEN_AI_PRICING: &[(&str, ModelPricing)] = &[
+    ("gpt-4o-mini", ModelPricing::new(0.15, 0.6, 0.0, 0.075)),
+    ("gpt-4o", ModelPricing::new(2.5, 10.0, 0.0, 1.25)),
+    ("o1-mini", ModelPricing::new(1.1, 4.4, 0.0, 0.55)),
+    ("o3-mini", ModelPricing::new(1.1, 4.4, 0.0, 0.55)),
+    ("o1", ModelPricing::new(15.0, 60.0, 0.0, 7.5)),
+    ("gpt-4-turbo", ModelPricing::new(10.0, 30.0, 0.0, 0.0)),
+    ("gpt-4", ModelPricing::new(30.0, 60.0, 0.0, 0.0)),
+    ("gpt-3.5-turbo", ModelPricing::new(0.5, 1.5, 0.0, 0.0)),
+];
+
+const GOOGLE_PRICING: &[(&str, ModelPricing)] = &[
+    (
+        "gemini-2.0-flash-lite",
+        ModelPricing::new(0.075, 0.3, 0.0, 0.0),
+    ),
+    ("gemini-2.0-flash", ModelPricing::new(0.1, 0.4, 0.0, 0.025)),
+    ("gemini-1.5-pro", ModelPricing::new(1.25, 5.0, 0.0, 0.0)),
+    ("gemini-1.5-flash", ModelPricing::new(0.075, 0.3, 0.0, 0.0)),
+];
+
+const DEEPSEEK_PRICING: &[(&str, ModelPricing)] = &[
+    ("deepseek-chat", ModelPricing::new(0.27, 1.1, 0.0, 0.07)),
+    (
+        "deepseek-reasoner",
+        ModelPricing::new(0.55, 2.19, 0.0, 0.14),
+    ),
+];
+
+/// Returns the pricing of a model, or `None` if it is unknown or the provider doesn't
+/// bill per token (such as local models or the models of Zed's own provider).
+pub fn pricing_for_model(
+    provider_id: &LanguageModelProviderId,
+    model_id: &str,
+) -> Option<ModelPricing> {
+    let table = match provider_id.0.as_ref() {
+        "anthropic" | "amazon-bedrock" => ANTHROPIC_PRICING,
+        "openai" => OPEN_AI_PRICING,
+        "google" => GOOGLE_PRICING,
+        "deepseek" => DEEPSEEK_PRICING,
+        _ => return None,
+    };
+
+    let model_id = model_id.to_lowercase();
+    table
+        .iter()
+        .find(|(name, _)| model_id.contains(name))
+        .map(|(_, pricing)| *pricing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_for_model() {
+        let anthropic = LanguageModelProviderId::from("anthropic".to_string());
+        let bedrock = LanguageModelProviderId::from("amazon-bedrock".to_string());
+        let open_ai = LanguageModelProviderId::from("openai".to_string());
+        let ollama = LanguageModelProviderId::from("ollama".to_string());
+
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 2_000_000,
+        };
+        let pricing = pricing_for_model(&anthropic, "claude-3-5-sonnet-latest").unwrap();
+        assert_eq!(pricing.cost(&usage), 3.0 + 1.5 + 0.6);
+
+        assert_eq!(
+            pricing_for_model(&bedrock, "us.anthropic.claude-3-5-sonnet-20241022-v2:0"),
+            Some(pricing)
+        );
+        assert_eq!(
+            pricing_for_model(&open_ai, "gpt-4o-mini"),
+            Some(ModelPricing::new(0.15, 0.6, 0.0, 0.075))
+        );
+        assert_eq!(pricing_for_model(&ollama, "llama3.2"), None);
+    }
+}
@@ -0,0 +1,421 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{
+    actions, px, App, AsyncWindowContext, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    Subscription, Task, WeakEntity, Window,
+};
+use serde::{Deserialize, Serialize};
+use ui::{prelude::*, Tab};
+use util::{ResultExt as _, TryFutureExt as _};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+use crate::persistence::USAGE_DB;
+use crate::UsageLedger;
+
+const USAGE_PANEL_KEY: &str = "UsagePanel";
+const USAGE_PERIOD_IN_DAYS: i64 = 30;
+
+actions!(usage_panel, [ToggleFocus]);
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<UsagePanel>(window, cx);
+        });
+    })
+    .detach();
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum UsageGrouping {
+    #[default]
+    Day,
+    Model,
+    Project,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedUsagePanel {
+    width: Option<Pixels>,
+    position: Option<DockPosition>,
+    grouping: Option<UsageGrouping>,
+}
+
+/// The summed usage of the requests that share a day, model or project.
+#[derive(Debug, Clone)]
+struct UsageRow {
+    label: SharedString,
+    input_tokens: i64,
+    output_tokens: i64,
+    cache_creation_input_tokens: i64,
+    cache_read_input_tokens: i64,
+    cost: Option<f64>,
+}
+
+/// Shows how many tokens were spent on language model requests over the last 30 days,
+/// and what they cost.
+pub struct UsagePanel {
+    focus_handle: FocusHandle,
+    width: Option<Pixels>,
+    position: DockPosition,
+    grouping: UsageGrouping,
+    rows: Vec<UsageRow>,
+    load_rows: Task<()>,
+    pending_serialization: Task<Option<()>>,
+    _ledger_subscription: Option<Subscription>,
+}
+
+impl UsagePanel {
+    fn new(cx: &mut Context<Self>) -> Self {
+        let ledger_subscription = UsageLedger::global(cx)
+            .map(|ledger| cx.observe(&ledger, |this, _, cx| this.reload(cx)));
+
+        let mut this = Self {
+            focus_handle: cx.focus_handle(),
+            width: None,
+            position: DockPosition::Right,
+            grouping: UsageGrouping::default(),
+            rows: Vec::new(),
+            load_rows: Task::ready(()),
+            pending_serialization: Task::ready(None),
+            _ledger_subscription: ledger_subscription,
+        };
+        this.reload(cx);
+        this
+    }
+
+    pub fn load(
+        workspace: WeakEntity<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<Entity<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let serialized_panel = if let Some(panel) = cx
+                .background_spawn(async move { KEY_VALUE_STORE.read_kvp(USAGE_PANEL_KEY) })
+                .await
+                .log_err()
+                .flatten()
+            {
+                Some(serde_json::from_str::<SerializedUsagePanel>(&panel)?)
+            } else {
+                None
+            };
+
+            workspace.update_in(&mut cx, |_, _, cx| {
+                cx.new(|cx| {
+                    let mut panel = Self::new(cx);
+                    if let Some(serialized_panel) = serialized_panel {
+                        panel.width = serialized_panel.width.map(|width| width.round());
+                        if let Some(position) = serialized_panel.position {
+                            panel.position = position;
+                        }
+                        if let Some(grouping) = serialized_panel.grouping {
+                            panel.grouping = grouping;
+                            panel.reload(cx);
+                        }
+                    }
+                    panel
+                })
+            })
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut Context<Self>) {
+        let serialized_panel = SerializedUsagePanel {
+            width: self.width,
+            position: Some(self.position),
+            grouping: Some(self.grouping),
+        };
+        self.pending_serialization = cx.background_spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        USAGE_PANEL_KEY.into(),
+                        serde_json::to_string(&serialized_panel)?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn set_grouping(&mut self, grouping: UsageGrouping, cx: &mut Context<Self>) {
+        if self.grouping != grouping {
+            self.grouping = grouping;
+            self.reload(cx);
+            self.serialize(cx);
+        }
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let grouping = self.grouping;
+        let since = (Utc::now() - Duration::days(USAGE_PERIOD_IN_DAYS)).timestamp();
+        let rows = cx.background_spawn(async move { query_rows(grouping, since) });
+        self.load_rows = cx.spawn(|this, mut cx| async move {
+            if let Some(rows) = rows.await.log_err() {
+                this.update(&mut cx, |this, cx| {
+                    this.rows = rows;
+                    cx.notify();
+                })
+                .ok();
+            }
+        });
+    }
+
+    fn render_grouping_button(
+        &self,
+        grouping: UsageGrouping,
+        label: &'static str,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        Button::new(label, label)
+            .style(ButtonStyle::Subtle)
+            .label_size(LabelSize::Small)
+            .toggle_state(self.grouping == grouping)
+            .on_click(cx.listener(move |this, _, _, cx| this.set_grouping(grouping, cx)))
+    }
+
+    fn render_row(&self, row: &UsageRow) -> impl IntoElement {
+        let mut tokens = format!(
+            "{} in, {} out",
+            format_tokens(row.input_tokens),
+            format_tokens(row.output_tokens)
+        );
+        if row.cache_creation_input_tokens > 0 {
+            tokens.push_str(&format!(
+                ", {} cache writes",
+                format_tokens(row.cache_creation_input_tokens)
+            ));
+        }
+        if row.cache_read_input_tokens > 0 {
+            tokens.push_str(&format!(
+                ", {} cache reads",
+                format_tokens(row.cache_read_input_tokens)
+            ));
+        }
+
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .justify_between()
+            .child(
+                v_flex()
+                    .min_w_0()
+                    .child(Label::new(row.label.clone()).truncate())
+                    .child(
+                        Label::new(tokens)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(Label::new(format_cost(row.cost)))
+    }
+}
+
+fn query_rows(grouping: UsageGrouping, since: i64) -> Result<Vec<UsageRow>> {
+    let row = |label: String, input, output, cache_creation, cache_read, cost| UsageRow {
+        label: label.into(),
+        input_tokens: input,
+        output_tokens: output,
+        cache_creation_input_tokens: cache_creation,
+        cache_read_input_tokens: cache_read,
+        cost,
+    };
+
+    Ok(match grouping {
+        UsageGrouping::Day => USAGE_DB
+            .usage_by_day(since)?
+            .into_iter()
+            .map(|(day, input, output, cache_creation, cache_read, cost)| {
+                row(day, input, output, cache_creation, cache_read, cost)
+            })
+            .collect(),
+        UsageGrouping::Model => USAGE_DB
+            .usage_by_model(since)?
+            .into_iter()
+            .map(
+                |(provider, model, input, output, cache_creation, cache_read, cost)| {
+                    row(
+                        format!("{model} ({provider})"),
+                        input,
+                        output,
+                        cache_creation,
+                        cache_read,
+                        cost,
+                    )
+                },
+            )
+            .collect(),
+        UsageGrouping::Project => USAGE_DB
+            .usage_by_project(since)?
+            .into_iter()
+            .map(
+                |(project, input, output, cache_creation, cache_read, cost)| {
+                    row(
+                        project.unwrap_or_else(|| "No Project".to_string()),
+                        input,
+                        output,
+                        cache_creation,
+                        cache_read,
+                        cost,
+                    )
+                },
+            )
+            .collect(),
+    })
+}
+
+fn format_tokens(tokens: i64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{:.1}k", tokens as f64 / 1_000.0)
+    } else {
+        tokens.to_string()
+    }
+}
+
+fn format_cost(cost: Option<f64>) -> String {
+    match cost {
+        Some(cost) if cost < 0.01 && cost > 0.0 => "<$0.01".to_string(),
+        Some(cost) => format!("${cost:.2}"),
+        None => "–".to_string(),
+    }
+}
+
+impl Render for UsagePanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let total_cost = self
+            .rows
+            .iter()
+            .filter_map(|row| row.cost)
+            .reduce(|total, cost| total + cost);
+        let total_tokens: i64 = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.input_tokens
+                    + row.output_tokens
+                    + row.cache_creation_input_tokens
+                    + row.cache_read_input_tokens
+            })
+            .sum();
+
+        v_flex()
+            .key_context("UsagePanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .px_2()
+                    .py_1()
+                    // Match the height of the tab bar so they line up.
+                    .h(Tab::container_height(cx))
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Label::new(format!(
+                        "Usage in the Last {USAGE_PERIOD_IN_DAYS} Days"
+                    )))
+                    .child(Icon::new(IconName::DatabaseZap)),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .justify_between()
+                    .child(
+                        Label::new(format!("{} tokens", format_tokens(total_tokens)))
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(format_cost(total_cost))),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .pb_1()
+                    .gap_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.render_grouping_button(UsageGrouping::Day, "By Day", cx))
+                    .child(self.render_grouping_button(UsageGrouping::Model, "By Model", cx))
+                    .child(self.render_grouping_button(UsageGrouping::Project, "By Project", cx)),
+            )
+            .map(|this| {
+                if self.rows.is_empty() {
+                    this.child(v_flex().p_4().child(
+                        Label::new("No language model requests were made yet.").color(Color::Muted),
+                    ))
+                } else {
+                    this.child(
+                        v_flex()
+                            .id("usage-rows")
+                            .flex_1()
+                            .overflow_y_scroll()
+                            .children(self.rows.iter().map(|row| self.render_row(row))),
+                    )
+                }
+            })
+    }
+}
+
+impl Focusable for UsagePanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for UsagePanel {}
+
+impl Panel for UsagePanel {
+    fn persistent_name() -> &'static str {
+        "UsagePanel"
+    }
+
+    fn position(&self, _: &Window, _: &App) -> DockPosition {
+        self.position
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        self.position = position;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn size(&self, _: &Window, _: &App) -> Pixels {
+        self.width.unwrap_or(px(320.))
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, _: &Window, _: &App) -> Option<IconName> {
+        Some(IconName::DatabaseZap)
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Usage Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn gpui::Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        9
+    }
+}
@@ -8,6 +8,7 @@ use provider::deepseek::DeepSeekLanguageModelProvider;
 
 pub mod provider;
 mod settings;
+mod think_tags;
 pub mod ui;
 
 use crate::provider::anthropic::AnthropicLanguageModelProvider;
@@ -70,6 +71,7 @@ fn register_language_model_providers(
         cx,
     );
     registry.register_provider(CopilotChatLanguageModelProvider::new(cx), cx);
+    provider::open_ai_compatible::register_endpoints(registry, client.http_client(), cx);
 
     cx.observe_flag::<feature_flags::LanguageModels, _>(move |enabled, cx| {
         let user_store = user_store.clone();
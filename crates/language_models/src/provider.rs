@@ -8,3 +8,4 @@ pub mod lmstudio;
 pub mod mistral;
 pub mod ollama;
 pub mod open_ai;
+pub mod open_ai_compatible;
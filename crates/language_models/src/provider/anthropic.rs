@@ -1,6 +1,6 @@
 use crate::ui::InstructionListItem;
 use crate::AllLanguageModelSettings;
-use anthropic::{AnthropicError, ContentDelta, Event, ResponseContent};
+use anthropic::{AnthropicError, AnthropicModelMode, ContentDelta, Event, ResponseContent};
 use anyhow::{anyhow, Context as _, Result};
 use collections::{BTreeMap, HashMap};
 use credentials_provider::CredentialsProvider;
@@ -16,7 +16,7 @@ use language_model::{
     LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, MessageContent, RateLimiter, Role,
 };
-use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason};
+use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason, TokenUsage};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
@@ -55,6 +55,9 @@ pub struct AvailableModel {
     pub default_temperature: Option<f32>,
     #[serde(default)]
     pub extra_beta_headers: Vec<String>,
+    /// Whether the model thinks before answering, and how many tokens it may spend thinking.
+    #[serde(default)]
+    pub mode: AnthropicModelMode,
 }
 
 pub struct AnthropicLanguageModelProvider {
@@ -228,6 +231,7 @@ impl LanguageModelProvider for AnthropicLanguageModelProvider {
                     max_output_tokens: model.max_output_tokens,
                     default_temperature: model.default_temperature,
                     extra_beta_headers: model.extra_beta_headers.clone(),
+                    mode: model.mode.clone(),
                 },
             );
         }
@@ -288,7 +292,7 @@ pub fn count_anthropic_tokens(
 
             for content in message.content {
                 match content {
-                    MessageContent::Text(text) => {
+                    MessageContent::Text(text) | MessageContent::Thinking { text, .. } => {
                         string_contents.push_str(&text);
                     }
                     MessageContent::Image(image) => {
@@ -297,6 +301,7 @@ pub fn count_anthropic_tokens(
                     MessageContent::ToolUse(_tool_use) => {
                         // TODO: Estimate token usage from tool uses.
                     }
+                    MessageContent::RedactedThinking { .. } => {}
                     MessageContent::ToolResult(tool_result) => {
                         string_contents.push_str(&tool_result.content);
                     }
@@ -384,6 +389,10 @@ impl LanguageModel for AnthropicModel {
         Some(self.model.max_output_tokens())
     }
 
+    fn supports_images(&self) -> bool {
+        true
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -402,6 +411,7 @@ impl LanguageModel for AnthropicModel {
             self.model.id().into(),
             self.model.default_temperature(),
             self.model.max_output_tokens(),
+            self.model.mode(),
         );
         let request = self.stream_completion(request, cx);
         let future = self.request_limiter.stream(async move {
@@ -434,6 +444,8 @@ impl LanguageModel for AnthropicModel {
             self.model.tool_model_id().into(),
             self.model.default_temperature(),
             self.model.max_output_tokens(),
+            // Thinking can't be combined with forcing the use of a tool.
+            AnthropicModelMode::Default,
         );
         request.tool_choice = Some(anthropic::ToolChoice::Tool {
             name: tool_name.clone(),
@@ -464,7 +476,9 @@ pub fn into_anthropic(
     model: String,
     default_temperature: f32,
     max_output_tokens: u32,
+    mode: AnthropicModelMode,
 ) -> anthropic::Request {
+    let thinking_budget_tokens = mode.thinking_budget_tokens();
     let mut new_messages: Vec<anthropic::Message> = Vec::new();
     let mut system_message = String::new();
 
@@ -496,6 +510,24 @@ pub fn into_anthropic(
                                 None
                             }
                         }
+                        // Thinking is only sent back when it's enabled, and only with the
+                        // signature that proves it came from the model.
+                        MessageContent::Thinking {
+                            text,
+                            signature: Some(signature),
+                        } if thinking_budget_tokens.is_some() => {
+                            Some(anthropic::RequestContent::Thinking {
+                                thinking: text,
+                                signature,
+                            })
+                        }
+                        MessageContent::RedactedThinking { data }
+                            if thinking_budget_tokens.is_some() =>
+                        {
+                            Some(anthropic::RequestContent::RedactedThinking { data })
+                        }
+                        MessageContent::Thinking { .. }
+                        | MessageContent::RedactedThinking { .. } => None,
                         MessageContent::Image(image) => Some(anthropic::RequestContent::Image {
                             source: anthropic::ImageSource {
                                 source_type: "base64".to_string(),
@@ -550,7 +582,9 @@ pub fn into_anthropic(
     anthropic::Request {
         model,
         messages: new_messages,
-        max_tokens: max_output_tokens,
+        max_tokens: max_output_tokens + thinking_budget_tokens.unwrap_or(0),
+        thinking: thinking_budget_tokens
+            .map(|budget_tokens| anthropic::Thinking::Enabled { budget_tokens }),
         system: Some(system_message),
         tools: request
             .tools
@@ -564,7 +598,12 @@ pub fn into_anthropic(
         tool_choice: None,
         metadata: None,
         stop_sequences: Vec::new(),
-        temperature: request.temperature.or(Some(default_temperature)),
+        // The temperature can't be changed while thinking.
+        temperature: if thinking_budget_tokens.is_some() {
+            None
+        } else {
+            request.temperature.or(Some(default_temperature))
+        },
         top_k: None,
         top_p: None,
     }
@@ -582,14 +621,22 @@ pub fn map_to_language_model_completion_events(
     struct State {
         events: Pin<Box<dyn Send + Stream<Item = Result<Event, AnthropicError>>>>,
         tool_uses_by_index: HashMap<usize, RawToolUse>,
+        usage: TokenUsage,
+        pending_event: Option<LanguageModelCompletionEvent>,
     }
 
     futures::stream::unfold(
         State {
             events,
             tool_uses_by_index: HashMap::default(),
+            usage: TokenUsage::default(),
+            pending_event: None,
         },
         |mut state| async move {
+            if let Some(event) = state.pending_event.take() {
+                return Some((Some(Ok(event)), state));
+            }
+
             while let Some(event) = state.events.next().await {
                 match event {
                     Ok(event) => match event {
@@ -603,6 +650,28 @@ pub fn map_to_language_model_completion_events(
                                     state,
                                 ));
                             }
+                            ResponseContent::Thinking {
+                                thinking,
+                                signature,
+                            } => {
+                                return Some((
+                                    Some(Ok(LanguageModelCompletionEvent::Thinking {
+                                        text: thinking,
+                                        signature,
+                                    })),
+                                    state,
+                                ));
+                            }
+                            // Redacted thinking can't be shown, but it has to be sent
+                            // back as it is.
+                            ResponseContent::RedactedThinking { data } => {
+                                return Some((
+                                    Some(Ok(LanguageModelCompletionEvent::RedactedThinking {
+                                        data,
+                                    })),
+                                    state,
+                                ));
+                            }
                             ResponseContent::ToolUse { id, name, .. } => {
                                 state.tool_uses_by_index.insert(
                                     index,
@@ -623,6 +692,24 @@ pub fn map_to_language_model_completion_events(
                                     state,
                                 ));
                             }
+                            ContentDelta::ThinkingDelta { thinking } => {
+                                return Some((
+                                    Some(Ok(LanguageModelCompletionEvent::Thinking {
+                                        text: thinking,
+                                        signature: None,
+                                    })),
+                                    state,
+                                ));
+                            }
+                            ContentDelta::SignatureDelta { signature } => {
+                                return Some((
+                                    Some(Ok(LanguageModelCompletionEvent::Thinking {
+                                        text: String::new(),
+                                        signature: Some(signature),
+                                    })),
+                                    state,
+                                ));
+                            }
                             ContentDelta::InputJsonDelta { partial_json } => {
                                 if let Some(tool_use) = state.tool_uses_by_index.get_mut(&index) {
                                     tool_use.input_json.push_str(&partial_json);
@@ -656,14 +743,18 @@ pub fn map_to_language_model_completion_events(
                             }
                         }
                         Event::MessageStart { message } => {
+                            update_usage(&mut state.usage, &message.usage);
+                            state.pending_event =
+                                Some(LanguageModelCompletionEvent::UsageUpdate(state.usage));
                             return Some((
                                 Some(Ok(LanguageModelCompletionEvent::StartMessage {
                                     message_id: message.id,
                                 })),
                                 state,
-                            ))
+                            ));
                         }
-                        Event::MessageDelta { delta, .. } => {
+                        Event::MessageDelta { delta, usage } => {
+                            update_usage(&mut state.usage, &usage);
                             if let Some(stop_reason) = delta.stop_reason.as_deref() {
                                 let stop_reason = match stop_reason {
                                     "end_turn" => StopReason::EndTurn,
@@ -671,12 +762,14 @@ pub fn map_to_language_model_completion_events(
                                     "tool_use" => StopReason::ToolUse,
                                     _ => StopReason::EndTurn,
                                 };
-
-                                return Some((
-                                    Some(Ok(LanguageModelCompletionEvent::Stop(stop_reason))),
-                                    state,
-                                ));
+                                state.pending_event =
+                                    Some(LanguageModelCompletionEvent::Stop(stop_reason));
                             }
+
+                            return Some((
+                                Some(Ok(LanguageModelCompletionEvent::UsageUpdate(state.usage))),
+                                state,
+                            ));
                         }
                         Event::Error { error } => {
                             return Some((
@@ -698,6 +791,22 @@ pub fn map_to_language_model_completion_events(
     .filter_map(|event| async move { event })
 }
 
+/// Updates the usage with the counts in an event, which only has some of them.
+fn update_usage(usage: &mut TokenUsage, new: &anthropic::Usage) {
+    if let Some(input_tokens) = new.input_tokens {
+        usage.input_tokens = input_tokens;
+    }
+    if let Some(output_tokens) = new.output_tokens {
+        usage.output_tokens = output_tokens;
+    }
+    if let Some(cache_creation_input_tokens) = new.cache_creation_input_tokens {
+        usage.cache_creation_input_tokens = cache_creation_input_tokens;
+    }
+    if let Some(cache_read_input_tokens) = new.cache_read_input_tokens {
+        usage.cache_read_input_tokens = cache_read_input_tokens;
+    }
+}
+
 struct ConfigurationView {
     api_key_editor: Entity<Editor>,
     state: gpui::Entity<State>,
@@ -13,8 +13,9 @@ use bedrock::bedrock_client::types::{
 };
 use bedrock::bedrock_client::{self, Config};
 use bedrock::{
-    value_to_aws_document, BedrockError, BedrockInnerContent, BedrockMessage, BedrockSpecificTool,
-    BedrockStreamingResponse, BedrockTool, BedrockToolChoice, BedrockToolInputSchema, Model,
+    cache_point, value_to_aws_document, BedrockError, BedrockInnerContent, BedrockMessage,
+    BedrockModelCacheConfiguration, BedrockSpecificTool, BedrockStreamingResponse,
+    BedrockSystemContent, BedrockTool, BedrockToolChoice, BedrockToolInputSchema, Model,
 };
 use collections::{BTreeMap, HashMap};
 use credentials_provider::CredentialsProvider;
@@ -29,7 +30,7 @@ use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCacheConfiguration,
     LanguageModelCompletionEvent, LanguageModelId, LanguageModelName, LanguageModelProvider,
     LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
-    LanguageModelRequest, LanguageModelToolUse, MessageContent, RateLimiter, Role,
+    LanguageModelRequest, LanguageModelToolUse, MessageContent, RateLimiter, Role, TokenUsage,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -241,6 +242,13 @@ impl LanguageModelProvider for BedrockLanguageModelProvider {
                     max_tokens: model.max_tokens,
                     max_output_tokens: model.max_output_tokens,
                     default_temperature: model.default_temperature,
+                    cache_configuration: model.cache_configuration.as_ref().map(|config| {
+                        BedrockModelCacheConfiguration {
+                            max_cache_anchors: config.max_cache_anchors,
+                            should_speculate: config.should_speculate,
+                            min_total_token: config.min_total_token,
+                        }
+                    }),
                 },
             );
         }
@@ -452,7 +460,13 @@ impl LanguageModel for BedrockModel {
     }
 
     fn cache_configuration(&self) -> Option<LanguageModelCacheConfiguration> {
-        None
+        self.model
+            .cache_configuration()
+            .map(|config| LanguageModelCacheConfiguration {
+                max_cache_anchors: config.max_cache_anchors,
+                should_speculate: config.should_speculate,
+                min_total_token: config.min_total_token,
+            })
     }
 }
 
@@ -464,6 +478,7 @@ pub fn into_bedrock(
 ) -> bedrock::Request {
     let mut new_messages: Vec<BedrockMessage> = Vec::new();
     let mut system_message = String::new();
+    let mut cache_system_message = false;
 
     for message in request.messages {
         if message.contents_empty() {
@@ -472,7 +487,7 @@ pub fn into_bedrock(
 
         match message.role {
             Role::User | Role::Assistant => {
-                let bedrock_message_content: Vec<BedrockInnerContent> = message
+                let mut bedrock_message_content: Vec<BedrockInnerContent> = message
                     .content
                     .into_iter()
                     .filter_map(|content| match content {
@@ -486,6 +501,9 @@ pub fn into_bedrock(
                         _ => None,
                     })
                     .collect();
+                if message.cache && !bedrock_message_content.is_empty() {
+                    bedrock_message_content.push(BedrockInnerContent::CachePoint(cache_point()));
+                }
                 let bedrock_role = match message.role {
                     Role::User => bedrock::BedrockRole::User,
                     Role::Assistant => bedrock::BedrockRole::Assistant,
@@ -510,15 +528,24 @@ pub fn into_bedrock(
                     system_message.push_str("\n\n");
                 }
                 system_message.push_str(&message.string_contents());
+                cache_system_message |= message.cache;
             }
         }
     }
 
+    let mut system = Vec::new();
+    if !system_message.is_empty() {
+        system.push(BedrockSystemContent::Text(system_message));
+        if cache_system_message {
+            system.push(BedrockSystemContent::CachePoint(cache_point()));
+        }
+    }
+
     bedrock::Request {
         model,
         messages: new_messages,
         max_tokens: max_output_tokens,
-        system: Some(system_message),
+        system,
         tools: vec![],
         tool_choice: None,
         metadata: None,
@@ -548,7 +575,7 @@ pub fn get_bedrock_tokens(
 
                 for content in message.content {
                     match content {
-                        MessageContent::Text(text) => {
+                        MessageContent::Text(text) | MessageContent::Thinking { text, .. } => {
                             string_contents.push_str(&text);
                         }
                         MessageContent::Image(image) => {
@@ -557,6 +584,7 @@ pub fn get_bedrock_tokens(
                         MessageContent::ToolUse(_tool_use) => {
                             // TODO: Estimate token usage from tool uses.
                         }
+                        MessageContent::RedactedThinking { .. } => {}
                         MessageContent::ToolResult(tool_result) => {
                             string_contents.push_str(&tool_result.content);
                         }
@@ -749,6 +777,31 @@ pub fn map_to_language_model_completion_events(
                                             ));
                                         }
                                     }
+                                    ConverseStreamOutput::Metadata(metadata) => {
+                                        if let Some(usage) = metadata.usage() {
+                                            return Some((
+                                                Some(Ok(
+                                                    LanguageModelCompletionEvent::UsageUpdate(
+                                                        TokenUsage {
+                                                            input_tokens: usage.input_tokens()
+                                                                as u32,
+                                                            output_tokens: usage.output_tokens()
+                                                                as u32,
+                                                            cache_creation_input_tokens: usage
+                                                                .cache_write_input_tokens()
+                                                                .unwrap_or(0)
+                                                                as u32,
+                                                            cache_read_input_tokens: usage
+                                                                .cache_read_input_tokens()
+                                                                .unwrap_or(0)
+                                                                as u32,
+                                                        },
+                                                    ),
+                                                )),
+                                                state,
+                                            ));
+                                        }
+                                    }
                                     _ => {}
                                 },
                                 Err(err) => return Some((Some(Err(anyhow!(err))), state)),
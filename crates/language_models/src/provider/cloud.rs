@@ -37,8 +37,8 @@ use ui::{prelude::*, TintColor};
 use crate::provider::anthropic::{
     count_anthropic_tokens, into_anthropic, map_to_language_model_completion_events,
 };
-use crate::provider::google::into_google;
-use crate::provider::open_ai::{count_open_ai_tokens, into_open_ai};
+use crate::provider::google::{self, into_google};
+use crate::provider::open_ai::{count_open_ai_tokens, into_open_ai, map_to_completion_events};
 use crate::AllLanguageModelSettings;
 
 pub const PROVIDER_NAME: &str = "Zed";
@@ -91,6 +91,9 @@ pub struct AvailableModel {
     /// Any extra beta headers to provide when using the model.
     #[serde(default)]
     pub extra_beta_headers: Vec<String>,
+    /// Whether the model thinks before answering (Anthropic only).
+    #[serde(default)]
+    pub mode: anthropic::AnthropicModelMode,
 }
 
 pub struct CloudLanguageModelProvider {
@@ -299,6 +302,7 @@ impl LanguageModelProvider for CloudLanguageModelProvider {
                     default_temperature: model.default_temperature,
                     max_output_tokens: model.max_output_tokens,
                     extra_beta_headers: model.extra_beta_headers.clone(),
+                    mode: model.mode.clone(),
                 }),
                 AvailableProvider::OpenAi => CloudModel::OpenAi(open_ai::Model::Custom {
                     name: model.name.clone(),
@@ -306,11 +310,13 @@ impl LanguageModelProvider for CloudLanguageModelProvider {
                     max_tokens: model.max_tokens,
                     max_output_tokens: model.max_output_tokens,
                     max_completion_tokens: model.max_completion_tokens,
+                    reasoning_effort: None,
                 }),
                 AvailableProvider::Google => CloudModel::Google(google_ai::Model::Custom {
                     name: model.name.clone(),
                     display_name: model.display_name.clone(),
                     max_tokens: model.max_tokens,
+                    cache_configuration: None,
                 }),
             };
             models.insert(model.id().to_string(), model.clone());
@@ -529,6 +535,21 @@ impl LanguageModel for CloudLanguageModel {
         }
     }
 
+    fn supports_structured_output(&self) -> bool {
+        match &self.model {
+            CloudModel::Anthropic(_) => false,
+            CloudModel::OpenAi(model) => model.supports_structured_output(),
+            CloudModel::Google(_) => true,
+        }
+    }
+
+    fn supports_images(&self) -> bool {
+        match &self.model {
+            CloudModel::Anthropic(_) | CloudModel::Google(_) => true,
+            CloudModel::OpenAi(model) => model.supports_images(),
+        }
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -570,6 +591,7 @@ impl LanguageModel for CloudLanguageModel {
                     model.id().into(),
                     model.default_temperature(),
                     model.max_output_tokens(),
+                    model.mode(),
                 );
                 let client = self.client.clone();
                 let llm_api_token = self.llm_api_token.clone();
@@ -594,7 +616,14 @@ impl LanguageModel for CloudLanguageModel {
             }
             CloudModel::OpenAi(model) => {
                 let client = self.client.clone();
-                let request = into_open_ai(request, model.id().into(), model.max_output_tokens());
+                let request = into_open_ai(
+                    request,
+                    model.id().into(),
+                    model.max_output_tokens(),
+                    model.reasoning_effort(),
+                    model.supports_images(),
+                    false,
+                );
                 let llm_api_token = self.llm_api_token.clone();
                 let future = self.request_limiter.stream(async move {
                     let response = Self::perform_llm_completion(
@@ -609,15 +638,9 @@ impl LanguageModel for CloudLanguageModel {
                         },
                     )
                     .await?;
-                    Ok(open_ai::extract_text_from_events(response_lines(response)))
+                    Ok(map_to_completion_events(response_lines(response)))
                 });
-                async move {
-                    Ok(future
-                        .await?
-                        .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                        .boxed())
-                }
-                .boxed()
+                async move { Ok(future.await?.boxed()) }.boxed()
             }
             CloudModel::Google(model) => {
                 let client = self.client.clone();
@@ -636,17 +659,11 @@ impl LanguageModel for CloudLanguageModel {
                         },
                     )
                     .await?;
-                    Ok(google_ai::extract_text_from_events(response_lines(
-                        response,
-                    )))
+                    Ok(google::map_to_language_model_completion_events(
+                        response_lines(response),
+                    ))
                 });
-                async move {
-                    Ok(future
-                        .await?
-                        .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                        .boxed())
-                }
-                .boxed()
+                async move { Ok(future.await?.boxed()) }.boxed()
             }
         }
     }
@@ -669,6 +686,7 @@ impl LanguageModel for CloudLanguageModel {
                     model.tool_model_id().into(),
                     model.default_temperature(),
                     model.max_output_tokens(),
+                    anthropic::AnthropicModelMode::Default,
                 );
                 request.tool_choice = Some(anthropic::ToolChoice::Tool {
                     name: tool_name.clone(),
@@ -704,8 +722,14 @@ impl LanguageModel for CloudLanguageModel {
                     .boxed()
             }
             CloudModel::OpenAi(model) => {
-                let mut request =
-                    into_open_ai(request, model.id().into(), model.max_output_tokens());
+                let mut request = into_open_ai(
+                    request,
+                    model.id().into(),
+                    model.max_output_tokens(),
+                    model.reasoning_effort(),
+                    model.supports_images(),
+                    false,
+                );
                 request.tool_choice = Some(open_ai::ToolChoice::Other(
                     open_ai::ToolDefinition::Function {
                         function: open_ai::FunctionDefinition {
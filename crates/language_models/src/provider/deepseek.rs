@@ -11,7 +11,7 @@ use http_client::HttpClient;
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCompletionEvent, LanguageModelId,
     LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
-    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
+    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role, TokenUsage,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -332,21 +332,12 @@ impl LanguageModel for DeepSeekLanguageModel {
         async move {
             let stream = stream.await?;
             Ok(stream
-                .map(|result| {
-                    result.and_then(|response| {
-                        response
-                            .choices
-                            .first()
-                            .ok_or_else(|| anyhow!("Empty response"))
-                            .map(|choice| {
-                                choice
-                                    .delta
-                                    .content
-                                    .clone()
-                                    .unwrap_or_default()
-                                    .map(LanguageModelCompletionEvent::Text)
-                            })
-                    })
+                .flat_map(|result| {
+                    let events = match result {
+                        Ok(response) => map_to_completion_events(response),
+                        Err(error) => vec![Err(error)],
+                    };
+                    futures::stream::iter(events)
                 })
                 .boxed())
         }
@@ -496,6 +487,37 @@ pub fn into_deepseek(
     }
 }
 
+fn map_to_completion_events(
+    response: deepseek::StreamResponse,
+) -> Vec<Result<LanguageModelCompletionEvent>> {
+    let mut events = Vec::new();
+    if let Some(choice) = response.choices.first() {
+        events.push(Ok(match &choice.delta.reasoning_content {
+            // The reasoner streams its reasoning before the answer.
+            Some(reasoning) if !reasoning.is_empty() => LanguageModelCompletionEvent::Thinking {
+                text: reasoning.clone(),
+                signature: None,
+            },
+            _ => {
+                LanguageModelCompletionEvent::Text(choice.delta.content.clone().unwrap_or_default())
+            }
+        }));
+    }
+    if let Some(usage) = response.usage {
+        let cache_read_input_tokens = usage.prompt_cache_hit_tokens;
+        events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+            input_tokens: usage.prompt_tokens.saturating_sub(cache_read_input_tokens),
+            output_tokens: usage.completion_tokens,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens,
+        })));
+    }
+    if events.is_empty() {
+        events.push(Err(anyhow!("Empty response")));
+    }
+    events
+}
+
 struct ConfigurationView {
     api_key_editor: Entity<Editor>,
     state: Entity<State>,
@@ -1,23 +1,29 @@
 use anyhow::{anyhow, Context as _, Result};
-use collections::BTreeMap;
+use collections::{BTreeMap, HashMap};
 use credentials_provider::CredentialsProvider;
 use editor::{Editor, EditorElement, EditorStyle};
-use futures::{future::BoxFuture, FutureExt, StreamExt};
-use google_ai::stream_generate_content;
+use futures::{future::BoxFuture, stream, FutureExt, Stream, StreamExt};
+use google_ai::{
+    stream_generate_content, GenerateContentResponse, GoogleModelCacheConfiguration, Part, TextPart,
+};
 use gpui::{
     AnyView, App, AsyncApp, Context, Entity, FontStyle, Subscription, Task, TextStyle, WhiteSpace,
 };
 use http_client::HttpClient;
 use language_model::{AuthenticateError, LanguageModelCompletionEvent};
 use language_model::{
-    LanguageModel, LanguageModelId, LanguageModelName, LanguageModelProvider,
-    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
-    LanguageModelRequest, RateLimiter, Role,
+    LanguageModel, LanguageModelCacheConfiguration, LanguageModelId, LanguageModelName,
+    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelProviderState, LanguageModelRequest, MessageContent, RateLimiter, Role,
+    TokenUsage,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
-use std::{future, sync::Arc};
+use std::future;
+use std::hash::{DefaultHasher, Hash as _, Hasher as _};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use strum::IntoEnumIterator;
 use theme::ThemeSettings;
 use ui::{prelude::*, Icon, IconName, List, Tooltip};
@@ -29,6 +35,15 @@ use crate::AllLanguageModelSettings;
 const PROVIDER_ID: &str = "google";
 const PROVIDER_NAME: &str = "Google AI";
 
+/// How long the cached prefix of a request is kept for later requests.
+const CACHED_CONTENT_TTL: Duration = Duration::from_secs(5 * 60);
+/// Cached contents that expire sooner than this aren't referred to, as they may expire
+/// before the request is handled.
+const CACHED_CONTENT_EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+/// Typical number of string bytes per token, used to skip caching prefixes which are
+/// too short to be cached.
+const BYTES_PER_TOKEN_GUESS: usize = 4;
+
 #[derive(Default, Clone, Debug, PartialEq)]
 pub struct GoogleSettings {
     pub api_url: String,
@@ -40,6 +55,7 @@ pub struct AvailableModel {
     name: String,
     display_name: Option<String>,
     max_tokens: usize,
+    cache_configuration: Option<LanguageModelCacheConfiguration>,
 }
 
 pub struct GoogleLanguageModelProvider {
@@ -50,9 +66,16 @@ pub struct GoogleLanguageModelProvider {
 pub struct State {
     api_key: Option<String>,
     api_key_from_env: bool,
+    cached_contents: Arc<Mutex<HashMap<u64, CachedContent>>>,
     _subscription: Subscription,
 }
 
+/// The prefix of an earlier request that was cached with Gemini's context caching.
+struct CachedContent {
+    name: String,
+    expires_at: Instant,
+}
+
 const GOOGLE_AI_API_KEY_VAR: &str = "GOOGLE_AI_API_KEY";
 
 impl State {
@@ -137,6 +160,7 @@ impl GoogleLanguageModelProvider {
         let state = cx.new(|cx| State {
             api_key: None,
             api_key_from_env: false,
+            cached_contents: Arc::default(),
             _subscription: cx.observe_global::<SettingsStore>(|_, cx| {
                 cx.notify();
             }),
@@ -199,6 +223,13 @@ impl LanguageModelProvider for GoogleLanguageModelProvider {
                     name: model.name.clone(),
                     display_name: model.display_name.clone(),
                     max_tokens: model.max_tokens,
+                    cache_configuration: model.cache_configuration.as_ref().map(|config| {
+                        GoogleModelCacheConfiguration {
+                            max_cache_anchors: config.max_cache_anchors,
+                            should_speculate: config.should_speculate,
+                            min_total_token: config.min_total_token,
+                        }
+                    }),
                 },
             );
         }
@@ -304,30 +335,73 @@ impl LanguageModel for GoogleLanguageModel {
         'static,
         Result<futures::stream::BoxStream<'static, Result<LanguageModelCompletionEvent>>>,
     > {
-        let request = into_google(request, self.model.id().to_string());
+        // A request can only continue one cached content, so only the last anchor is used.
+        // The final message is left out, as it's new with every request and caching it
+        // would create content that's never continued.
+        let cached_prefix_len = self.model.cache_configuration().and_then(|config| {
+            let earlier_messages = &request.messages[..request.messages.len().saturating_sub(1)];
+            let prefix_len = earlier_messages.iter().rposition(|message| message.cache)? + 1;
+            let prefix_bytes = request.messages[..prefix_len]
+                .iter()
+                .map(|message| message.string_contents().len())
+                .sum::<usize>();
+            (prefix_bytes / BYTES_PER_TOKEN_GUESS >= config.min_total_token).then_some(prefix_len)
+        });
+        let cache_model_id = self.model.cache_model_id().to_string();
+        let mut request = into_google(request, self.model.id().to_string());
 
         let http_client = self.http_client.clone();
-        let Ok((api_key, api_url)) = cx.read_entity(&self.state, |state, cx| {
+        let Ok((api_key, api_url, cached_contents)) = cx.read_entity(&self.state, |state, cx| {
             let settings = &AllLanguageModelSettings::get_global(cx).google;
-            (state.api_key.clone(), settings.api_url.clone())
+            (
+                state.api_key.clone(),
+                settings.api_url.clone(),
+                state.cached_contents.clone(),
+            )
         }) else {
             return futures::future::ready(Err(anyhow!("App state dropped"))).boxed();
         };
 
         let future = self.rate_limiter.stream(async move {
             let api_key = api_key.ok_or_else(|| anyhow!("Missing Google API Key"))?;
+            if let Some(prefix_len) = cached_prefix_len {
+                // Requests whose prefix can't be cached are sent in full.
+                continue_cached_content(
+                    &mut request,
+                    prefix_len,
+                    cache_model_id,
+                    &cached_contents,
+                    http_client.as_ref(),
+                    &api_url,
+                    &api_key,
+                )
+                .await
+                .log_err();
+            }
             let response =
                 stream_generate_content(http_client.as_ref(), &api_url, &api_key, request);
             let events = response.await?;
-            Ok(google_ai::extract_text_from_events(events).boxed())
+            Ok(map_to_language_model_completion_events(events).boxed())
         });
-        async move {
-            Ok(future
-                .await?
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
+
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
+    fn supports_images(&self) -> bool {
+        true
+    }
+
+    fn cache_configuration(&self) -> Option<LanguageModelCacheConfiguration> {
+        self.model
+            .cache_configuration()
+            .map(|config| LanguageModelCacheConfiguration {
+                max_cache_anchors: config.max_cache_anchors,
+                should_speculate: config.should_speculate,
+                min_total_token: config.min_total_token,
+            })
     }
 
     fn use_any_tool(
@@ -346,15 +420,16 @@ pub fn into_google(
     request: LanguageModelRequest,
     model: String,
 ) -> google_ai::GenerateContentRequest {
+    let response_schema = request
+        .response_format
+        .map(|format| into_gemini_schema(&format.schema));
     google_ai::GenerateContentRequest {
         model,
         contents: request
             .messages
             .into_iter()
             .map(|msg| google_ai::Content {
-                parts: vec![google_ai::Part::TextPart(google_ai::TextPart {
-                    text: msg.string_contents(),
-                })],
+                parts: into_google_parts(&msg.content),
                 role: match msg.role {
                     Role::User => google_ai::Role::User,
                     Role::Assistant => google_ai::Role::Model,
@@ -369,9 +444,249 @@ pub fn into_google(
             temperature: request.temperature.map(|t| t as f64).or(Some(1.0)),
             top_p: None,
             top_k: None,
+            response_mime_type: response_schema
+                .is_some()
+                .then(|| "application/json".to_string()),
+            response_schema,
         }),
         safety_settings: None,
+        cached_content: None,
+    }
+}
+
+/// Converts the content of a message, sending its images inline after its text.
+fn into_google_parts(contents: &[MessageContent]) -> Vec<google_ai::Part> {
+    let mut text = String::new();
+    let mut images = Vec::new();
+    for content in contents {
+        match content {
+            MessageContent::Text(chunk) => text.push_str(chunk),
+            MessageContent::ToolResult(tool_result) => text.push_str(&tool_result.content),
+            MessageContent::Image(image) => {
+                images.push(google_ai::Part::InlineDataPart(google_ai::InlineDataPart {
+                    inline_data: google_ai::GenerativeContentBlob {
+                        mime_type: "image/png".to_string(),
+                        data: image.source.to_string(),
+                    },
+                }))
+            }
+            MessageContent::Thinking { .. }
+            | MessageContent::RedactedThinking { .. }
+            | MessageContent::ToolUse(_) => {}
+        }
     }
+
+    let mut parts = vec![google_ai::Part::TextPart(google_ai::TextPart { text })];
+    parts.extend(images);
+    parts
+}
+
+/// Converts a JSON schema to the subset of OpenAPI schemas Gemini accepts, which has no
+/// references and marks optional values as nullable instead of using type arrays.
+fn into_gemini_schema(schema: &serde_json::Value) -> serde_json::Value {
+    let definitions = schema
+        .get("definitions")
+        .or_else(|| schema.get("$defs"))
+        .and_then(|definitions| definitions.as_object())
+        .cloned()
+        .unwrap_or_default();
+    convert_gemini_schema(schema, &definitions, 0)
+}
+
+fn convert_gemini_schema(
+    schema: &serde_json::Value,
+    definitions: &serde_json::Map<String, serde_json::Value>,
+    depth: usize,
+) -> serde_json::Value {
+    use serde_json::{json, Value};
+
+    // Recursive types can't be expressed without references.
+    const MAX_DEPTH: usize = 16;
+
+    let Some(object) = schema.as_object() else {
+        return json!({});
+    };
+    if depth > MAX_DEPTH {
+        return json!({});
+    }
+
+    if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+        let name = reference.rsplit('/').next().unwrap_or_default();
+        return definitions.get(name).map_or(json!({}), |definition| {
+            convert_gemini_schema(definition, definitions, depth + 1)
+        });
+    }
+
+    for key in ["allOf", "anyOf", "oneOf"] {
+        let Some(variants) = object.get(key).and_then(Value::as_array) else {
+            continue;
+        };
+        let non_null_variants = variants
+            .iter()
+            .filter(|variant| variant.get("type").and_then(Value::as_str) != Some("null"))
+            .collect::<Vec<_>>();
+        let mut converted = match non_null_variants.as_slice() {
+            [variant] => convert_gemini_schema(variant, definitions, depth + 1),
+            _ => json!({
+                "anyOf": non_null_variants
+                    .iter()
+                    .map(|variant| convert_gemini_schema(variant, definitions, depth + 1))
+                    .collect::<Vec<_>>()
+            }),
+        };
+        if non_null_variants.len() < variants.len() {
+            converted["nullable"] = json!(true);
+        }
+        if let Some(description) = object.get("description") {
+            converted["description"] = description.clone();
+        }
+        return converted;
+    }
+
+    let mut converted = serde_json::Map::new();
+    match object.get("type") {
+        Some(Value::Array(types)) => {
+            let mut types = types.iter().filter(|ty| ty.as_str() != Some("null"));
+            if let Some(ty) = types.next() {
+                converted.insert("type".into(), ty.clone());
+            }
+            converted.insert("nullable".into(), json!(true));
+        }
+        Some(ty) => {
+            converted.insert("type".into(), ty.clone());
+        }
+        None => {}
+    }
+    for key in [
+        "description",
+        "enum",
+        "required",
+        "minItems",
+        "maxItems",
+        "nullable",
+    ] {
+        if let Some(value) = object.get(key) {
+            converted.insert(key.into(), value.clone());
+        }
+    }
+    if let Some(properties) = object.get("properties").and_then(Value::as_object) {
+        let properties = properties
+            .iter()
+            .map(|(name, property)| {
+                (
+                    name.clone(),
+                    convert_gemini_schema(property, definitions, depth + 1),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+        converted.insert("properties".into(), Value::Object(properties));
+    }
+    if let Some(items) = object.get("items") {
+        converted.insert(
+            "items".into(),
+            convert_gemini_schema(items, definitions, depth + 1),
+        );
+    }
+    Value::Object(converted)
+}
+
+/// Replaces the first contents of the request with cached content, reusing the cached
+/// content of an earlier request with the same prefix if it hasn't expired yet.
+async fn continue_cached_content(
+    request: &mut google_ai::GenerateContentRequest,
+    prefix_len: usize,
+    cache_model_id: String,
+    cached_contents: &Mutex<HashMap<u64, CachedContent>>,
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: &str,
+) -> Result<()> {
+    // The contents after the cached prefix can't be empty.
+    if prefix_len >= request.contents.len() {
+        return Ok(());
+    }
+
+    let prefix = &request.contents[..prefix_len];
+    let key = {
+        let mut hasher = DefaultHasher::new();
+        cache_model_id.hash(&mut hasher);
+        serde_json::to_string(prefix)?.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    let now = Instant::now();
+    let cached_name = {
+        let mut cached_contents = cached_contents.lock().unwrap();
+        cached_contents.retain(|_, cached| cached.expires_at > now + CACHED_CONTENT_EXPIRY_MARGIN);
+        cached_contents.get(&key).map(|cached| cached.name.clone())
+    };
+
+    let name = if let Some(name) = cached_name {
+        name
+    } else {
+        let cached = google_ai::create_cached_content(
+            client,
+            api_url,
+            api_key,
+            google_ai::CreateCachedContentRequest {
+                model: format!("models/{cache_model_id}"),
+                contents: prefix.to_vec(),
+                ttl: format!("{}s", CACHED_CONTENT_TTL.as_secs()),
+            },
+        )
+        .await?;
+        cached_contents.lock().unwrap().insert(
+            key,
+            CachedContent {
+                name: cached.name.clone(),
+                expires_at: now + CACHED_CONTENT_TTL,
+            },
+        );
+        cached.name
+    };
+
+    request.contents.drain(..prefix_len);
+    request.cached_content = Some(name);
+    request.model = cache_model_id;
+    Ok(())
+}
+
+pub fn map_to_language_model_completion_events(
+    events: impl Stream<Item = Result<GenerateContentResponse>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    events.flat_map(|event| {
+        let mut completion_events = Vec::new();
+        match event {
+            Ok(event) => {
+                let text = event
+                    .candidates
+                    .and_then(|candidates| candidates.into_iter().next())
+                    .and_then(|candidate| candidate.content.parts.into_iter().next())
+                    .and_then(|part| match part {
+                        Part::TextPart(TextPart { text }) => Some(text),
+                        _ => None,
+                    });
+                if let Some(text) = text {
+                    completion_events.push(Ok(LanguageModelCompletionEvent::Text(text)));
+                }
+                // Every chunk has the usage of the request so far.
+                if let Some(usage) = event.usage_metadata {
+                    completion_events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(
+                        TokenUsage {
+                            input_tokens: usage
+                                .prompt_token_count
+                                .saturating_sub(usage.cached_content_token_count),
+                            output_tokens: usage.candidates_token_count,
+                            cache_creation_input_tokens: 0,
+                            cache_read_input_tokens: usage.cached_content_token_count,
+                        },
+                    )));
+                }
+            }
+            Err(error) => completion_events.push(Err(error)),
+        }
+        stream::iter(completion_events)
+    })
 }
 
 pub fn count_google_tokens(
@@ -577,3 +892,63 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_into_gemini_schema() {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "Review",
+            "type": "object",
+            "required": ["comments"],
+            "properties": {
+                "comments": {
+                    "type": "array",
+                    "items": { "$ref": "#/definitions/Comment" }
+                },
+                "summary": { "type": ["string", "null"] }
+            },
+            "definitions": {
+                "Comment": {
+                    "type": "object",
+                    "properties": {
+                        "line": { "type": "integer", "format": "uint32", "minimum": 0.0 },
+                        "severity": {
+                            "anyOf": [{ "$ref": "#/definitions/Severity" }, { "type": "null" }]
+                        }
+                    }
+                },
+                "Severity": { "type": "string", "enum": ["low", "high"] }
+            }
+        });
+
+        assert_eq!(
+            into_gemini_schema(&schema),
+            json!({
+                "type": "object",
+                "required": ["comments"],
+                "properties": {
+                    "comments": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "line": { "type": "integer" },
+                                "severity": {
+                                    "type": "string",
+                                    "enum": ["low", "high"],
+                                    "nullable": true
+                                }
+                            }
+                        }
+                    },
+                    "summary": { "type": "string", "nullable": true }
+                }
+            })
+        );
+    }
+}
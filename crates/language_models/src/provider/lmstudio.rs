@@ -6,7 +6,7 @@ use language_model::{AuthenticateError, LanguageModelCompletionEvent};
 use language_model::{
     LanguageModel, LanguageModelId, LanguageModelName, LanguageModelProvider,
     LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
-    LanguageModelRequest, RateLimiter, Role,
+    LanguageModelRequest, MessageContent, RateLimiter, Role,
 };
 use lmstudio::{
     get_models, preload_model, stream_chat_completion, ChatCompletionRequest, ChatMessage,
@@ -19,6 +19,7 @@ use std::{collections::BTreeMap, sync::Arc};
 use ui::{prelude::*, ButtonLike, Indicator};
 use util::ResultExt;
 
+use crate::think_tags::map_think_tags;
 use crate::AllLanguageModelSettings;
 
 const LMSTUDIO_DOWNLOAD_URL: &str = "https://lmstudio.ai/download";
@@ -42,6 +43,8 @@ pub struct AvailableModel {
     pub display_name: Option<String>,
     /// The model's context window size.
     pub max_tokens: usize,
+    /// Whether the model accepts images. Defaults to whether LM Studio lists it as a vision model.
+    pub supports_images: Option<bool>,
 }
 
 pub struct LmStudioLanguageModelProvider {
@@ -73,7 +76,9 @@ impl State {
             let mut models: Vec<lmstudio::Model> = models
                 .into_iter()
                 .filter(|model| model.r#type != ModelType::Embeddings)
-                .map(|model| lmstudio::Model::new(&model.id, None, None))
+                .map(|model| {
+                    lmstudio::Model::new(&model.id, None, None, model.r#type == ModelType::Vlm)
+                })
                 .collect();
 
             models.sort_by(|a, b| a.name.cmp(&b.name));
@@ -170,12 +175,18 @@ impl LanguageModelProvider for LmStudioLanguageModelProvider {
             .available_models
             .iter()
         {
+            let supports_images = model.supports_images.unwrap_or_else(|| {
+                models
+                    .get(&model.name)
+                    .map_or(false, |model| model.supports_images)
+            });
             models.insert(
                 model.name.clone(),
                 lmstudio::Model {
                     name: model.name.clone(),
                     display_name: model.display_name.clone(),
                     max_tokens: model.max_tokens,
+                    supports_images,
                 },
             );
         }
@@ -228,6 +239,49 @@ pub struct LmStudioLanguageModel {
 }
 
 impl LmStudioLanguageModel {
+    /// Converts the content of a user message, sending its images only to vision models.
+    fn to_lmstudio_content(&self, contents: Vec<MessageContent>) -> lmstudio::MessageContent {
+        let mut parts = Vec::new();
+        for content in contents {
+            match content {
+                MessageContent::Text(text) => parts.push(lmstudio::MessagePart::Text { text }),
+                MessageContent::ToolResult(tool_result) => {
+                    parts.push(lmstudio::MessagePart::Text {
+                        text: tool_result.content.to_string(),
+                    })
+                }
+                MessageContent::Image(image) if self.model.supports_images => {
+                    parts.push(lmstudio::MessagePart::ImageUrl {
+                        image_url: lmstudio::ImageUrl {
+                            url: format!("data:image/png;base64,{}", image.source),
+                        },
+                    })
+                }
+                MessageContent::Image(_)
+                | MessageContent::Thinking { .. }
+                | MessageContent::RedactedThinking { .. }
+                | MessageContent::ToolUse(_) => {}
+            }
+        }
+
+        let has_images = parts
+            .iter()
+            .any(|part| matches!(part, lmstudio::MessagePart::ImageUrl { .. }));
+        if has_images {
+            lmstudio::MessageContent::Multipart(parts)
+        } else {
+            lmstudio::MessageContent::Plain(
+                parts
+                    .into_iter()
+                    .filter_map(|part| match part {
+                        lmstudio::MessagePart::Text { text } => Some(text),
+                        lmstudio::MessagePart::ImageUrl { .. } => None,
+                    })
+                    .collect(),
+            )
+        }
+    }
+
     fn to_lmstudio_request(&self, request: LanguageModelRequest) -> ChatCompletionRequest {
         ChatCompletionRequest {
             model: self.model.name.clone(),
@@ -236,7 +290,7 @@ impl LmStudioLanguageModel {
                 .into_iter()
                 .map(|msg| match msg.role {
                     Role::User => ChatMessage::User {
-                        content: msg.string_contents(),
+                        content: self.to_lmstudio_content(msg.content),
                     },
                     Role::Assistant => ChatMessage::Assistant {
                         content: Some(msg.string_contents()),
@@ -281,6 +335,10 @@ impl LanguageModel for LmStudioLanguageModel {
         self.model.max_token_count()
     }
 
+    fn supports_images(&self) -> bool {
+        self.model.supports_images
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -330,7 +388,10 @@ impl LanguageModel for LmStudioLanguageModel {
                                 fragment.choices[0].delta.clone(),
                             ) {
                                 let content = match chat_message {
-                                    ChatMessage::User { content } => content,
+                                    ChatMessage::User {
+                                        content: lmstudio::MessageContent::Plain(content),
+                                    } => content,
+                                    ChatMessage::User { .. } => String::new(),
                                     ChatMessage::Assistant { content, .. } => {
                                         content.unwrap_or_default()
                                     }
@@ -352,13 +413,7 @@ impl LanguageModel for LmStudioLanguageModel {
             Ok(stream)
         });
 
-        async move {
-            Ok(future
-                .await?
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(map_think_tags(future.await?).boxed()) }.boxed()
     }
 
     fn use_any_tool(
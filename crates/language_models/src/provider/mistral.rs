@@ -10,7 +10,7 @@ use http_client::HttpClient;
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCompletionEvent, LanguageModelId,
     LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
-    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
+    LanguageModelProviderState, LanguageModelRequest, MessageContent, RateLimiter, Role,
 };
 
 use futures::stream::BoxStream;
@@ -42,6 +42,9 @@ pub struct AvailableModel {
     pub max_tokens: usize,
     pub max_output_tokens: Option<u32>,
     pub max_completion_tokens: Option<u32>,
+    /// Whether the model accepts images, like Pixtral does.
+    #[serde(default)]
+    pub supports_images: bool,
 }
 
 pub struct MistralLanguageModelProvider {
@@ -201,6 +204,7 @@ impl LanguageModelProvider for MistralLanguageModelProvider {
                     max_tokens: model.max_tokens,
                     max_output_tokens: model.max_output_tokens,
                     max_completion_tokens: model.max_completion_tokens,
+                    supports_images: model.supports_images,
                 },
             );
         }
@@ -303,6 +307,10 @@ impl LanguageModel for MistralLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn supports_images(&self) -> bool {
+        self.model.supports_images()
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -338,6 +346,7 @@ impl LanguageModel for MistralLanguageModel {
             request,
             self.model.id().to_string(),
             self.max_output_tokens(),
+            self.model.supports_images(),
         );
         let stream = self.stream_completion(request, cx);
 
@@ -373,7 +382,12 @@ impl LanguageModel for MistralLanguageModel {
         schema: serde_json::Value,
         cx: &AsyncApp,
     ) -> BoxFuture<'static, Result<futures::stream::BoxStream<'static, Result<String>>>> {
-        let mut request = into_mistral(request, self.model.id().into(), self.max_output_tokens());
+        let mut request = into_mistral(
+            request,
+            self.model.id().into(),
+            self.max_output_tokens(),
+            self.model.supports_images(),
+        );
         request.tools = vec![mistral::ToolDefinition::Function {
             function: mistral::FunctionDefinition {
                 name: tool_name.clone(),
@@ -419,6 +433,7 @@ pub fn into_mistral(
     request: LanguageModelRequest,
     model: String,
     max_output_tokens: Option<u32>,
+    supports_images: bool,
 ) -> mistral::Request {
     let len = request.messages.len();
     let merged_messages =
@@ -427,15 +442,18 @@ pub fn into_mistral(
             .into_iter()
             .fold(Vec::with_capacity(len), |mut acc, msg| {
                 let role = msg.role;
-                let content = msg.string_contents();
 
                 acc.push(match role {
-                    Role::User => mistral::RequestMessage::User { content },
+                    Role::User => mistral::RequestMessage::User {
+                        content: into_mistral_content(msg.content, supports_images),
+                    },
                     Role::Assistant => mistral::RequestMessage::Assistant {
-                        content: Some(content),
+                        content: Some(msg.string_contents()),
                         tool_calls: Vec::new(),
                     },
-                    Role::System => mistral::RequestMessage::System { content },
+                    Role::System => mistral::RequestMessage::System {
+                        content: msg.string_contents(),
+                    },
                 });
                 acc
             });
@@ -461,6 +479,49 @@ pub fn into_mistral(
     }
 }
 
+/// Converts the content of a user message, leaving out its images unless the model
+/// supports them.
+fn into_mistral_content(
+    contents: Vec<MessageContent>,
+    supports_images: bool,
+) -> mistral::MessageContent {
+    let mut parts = Vec::new();
+    for content in contents {
+        match content {
+            MessageContent::Text(text) => parts.push(mistral::MessagePart::Text { text }),
+            MessageContent::ToolResult(tool_result) => parts.push(mistral::MessagePart::Text {
+                text: tool_result.content.to_string(),
+            }),
+            MessageContent::Image(image) if supports_images => {
+                parts.push(mistral::MessagePart::ImageUrl {
+                    image_url: format!("data:image/png;base64,{}", image.source),
+                })
+            }
+            MessageContent::Image(_)
+            | MessageContent::Thinking { .. }
+            | MessageContent::RedactedThinking { .. }
+            | MessageContent::ToolUse(_) => {}
+        }
+    }
+
+    let has_images = parts
+        .iter()
+        .any(|part| matches!(part, mistral::MessagePart::ImageUrl { .. }));
+    if has_images {
+        mistral::MessageContent::Multipart(parts)
+    } else {
+        mistral::MessageContent::Plain(
+            parts
+                .into_iter()
+                .filter_map(|part| match part {
+                    mistral::MessagePart::Text { text } => Some(text),
+                    mistral::MessagePart::ImageUrl { .. } => None,
+                })
+                .collect(),
+        )
+    }
+}
+
 struct ConfigurationView {
     api_key_editor: Entity<Editor>,
     state: gpui::Entity<State>,
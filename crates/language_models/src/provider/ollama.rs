@@ -6,11 +6,11 @@ use language_model::{AuthenticateError, LanguageModelCompletionEvent};
 use language_model::{
     LanguageModel, LanguageModelId, LanguageModelName, LanguageModelProvider,
     LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
-    LanguageModelRequest, RateLimiter, Role,
+    LanguageModelRequest, MessageContent, RateLimiter, Role,
 };
 use ollama::{
-    get_models, preload_model, stream_chat_completion, ChatMessage, ChatOptions, ChatRequest,
-    ChatResponseDelta, KeepAlive, OllamaToolCall,
+    get_models, preload_model, show_model, stream_chat_completion, ChatMessage, ChatOptions,
+    ChatRequest, ChatResponseDelta, KeepAlive, OllamaToolCall,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -19,6 +19,7 @@ use std::{collections::BTreeMap, sync::Arc};
 use ui::{prelude::*, ButtonLike, Indicator};
 use util::ResultExt;
 
+use crate::think_tags::map_think_tags;
 use crate::AllLanguageModelSettings;
 
 const OLLAMA_DOWNLOAD_URL: &str = "https://ollama.com/download";
@@ -44,6 +45,8 @@ pub struct AvailableModel {
     pub max_tokens: usize,
     /// The number of seconds to keep the connection open after the last request
     pub keep_alive: Option<KeepAlive>,
+    /// Whether the model accepts images. Defaults to the capabilities Ollama reports for it.
+    pub supports_images: Option<bool>,
 }
 
 pub struct OllamaLanguageModelProvider {
@@ -72,14 +75,26 @@ impl State {
         cx.spawn(|this, mut cx| async move {
             let models = get_models(http_client.as_ref(), &api_url, None).await?;
 
-            let mut models: Vec<ollama::Model> = models
-                .into_iter()
-                // Since there is no metadata from the Ollama API
-                // indicating which models are embedding models,
-                // simply filter out models with "-embed" in their name
-                .filter(|model| !model.name.contains("-embed"))
-                .map(|model| ollama::Model::new(&model.name, None, None))
-                .collect();
+            let mut models: Vec<ollama::Model> = futures::future::join_all(
+                models
+                    .into_iter()
+                    // Since there is no metadata from the Ollama API
+                    // indicating which models are embedding models,
+                    // simply filter out models with "-embed" in their name
+                    .filter(|model| !model.name.contains("-embed"))
+                    .map(|model| {
+                        let http_client = http_client.as_ref();
+                        let api_url = api_url.as_str();
+                        async move {
+                            let supports_images = show_model(http_client, api_url, &model.name)
+                                .await
+                                .log_err()
+                                .map_or(false, |details| details.supports_vision());
+                            ollama::Model::new(&model.name, None, None, supports_images)
+                        }
+                    }),
+            )
+            .await;
 
             models.sort_by(|a, b| a.name.cmp(&b.name));
 
@@ -175,6 +190,11 @@ impl LanguageModelProvider for OllamaLanguageModelProvider {
             .available_models
             .iter()
         {
+            let supports_images = model.supports_images.unwrap_or_else(|| {
+                models
+                    .get(&model.name)
+                    .map_or(false, |model| model.supports_images)
+            });
             models.insert(
                 model.name.clone(),
                 ollama::Model {
@@ -182,6 +202,7 @@ impl LanguageModelProvider for OllamaLanguageModelProvider {
                     display_name: model.display_name.clone(),
                     max_tokens: model.max_tokens,
                     keep_alive: model.keep_alive.clone(),
+                    supports_images,
                 },
             );
         }
@@ -242,9 +263,22 @@ impl OllamaLanguageModel {
                 .messages
                 .into_iter()
                 .map(|msg| match msg.role {
-                    Role::User => ChatMessage::User {
-                        content: msg.string_contents(),
-                    },
+                    Role::User => {
+                        let images = msg
+                            .content
+                            .iter()
+                            .filter_map(|content| match content {
+                                MessageContent::Image(image) if self.model.supports_images => {
+                                    Some(image.source.to_string())
+                                }
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>();
+                        ChatMessage::User {
+                            content: msg.string_contents(),
+                            images: (!images.is_empty()).then_some(images),
+                        }
+                    }
                     Role::Assistant => ChatMessage::Assistant {
                         content: msg.string_contents(),
                         tool_calls: None,
@@ -263,6 +297,7 @@ impl OllamaLanguageModel {
                 ..Default::default()
             }),
             tools: vec![],
+            format: request.response_format.map(|format| format.schema),
         }
     }
     fn request_completion(
@@ -308,6 +343,14 @@ impl LanguageModel for OllamaLanguageModel {
         self.model.max_token_count()
     }
 
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
+    fn supports_images(&self) -> bool {
+        self.model.supports_images
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -347,7 +390,7 @@ impl LanguageModel for OllamaLanguageModel {
                     match response {
                         Ok(delta) => {
                             let content = match delta.message {
-                                ChatMessage::User { content } => content,
+                                ChatMessage::User { content, .. } => content,
                                 ChatMessage::Assistant { content, .. } => content,
                                 ChatMessage::System { content } => content,
                             };
@@ -360,13 +403,7 @@ impl LanguageModel for OllamaLanguageModel {
             Ok(stream)
         });
 
-        async move {
-            Ok(future
-                .await?
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(map_think_tags(future.await?).boxed()) }.boxed()
     }
 
     fn use_any_tool(
@@ -2,7 +2,7 @@ use anyhow::{anyhow, Context as _, Result};
 use collections::BTreeMap;
 use credentials_provider::CredentialsProvider;
 use editor::{Editor, EditorElement, EditorStyle};
-use futures::{future::BoxFuture, FutureExt, StreamExt};
+use futures::{future::BoxFuture, FutureExt, Stream, StreamExt};
 use gpui::{
     AnyView, App, AsyncApp, Context, Entity, FontStyle, Subscription, Task, TextStyle, WhiteSpace,
 };
@@ -10,10 +10,12 @@ use http_client::HttpClient;
 use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCompletionEvent, LanguageModelId,
     LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
-    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
+    LanguageModelProviderState, LanguageModelRequest, LanguageModelToolUse, MessageContent,
+    RateLimiter, Role, StopReason, TokenUsage,
 };
 use open_ai::{
-    stream_completion, FunctionDefinition, ResponseStreamEvent, ToolChoice, ToolDefinition,
+    stream_completion, FunctionDefinition, JsonSchemaFormat, ReasoningEffort, ResponseFormat,
+    ResponseStreamEvent, StreamOptions, ToolChoice, ToolDefinition,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -43,6 +45,8 @@ pub struct AvailableModel {
     pub max_tokens: usize,
     pub max_output_tokens: Option<u32>,
     pub max_completion_tokens: Option<u32>,
+    /// How much effort o-series models spend reasoning: "low", "medium" or "high".
+    pub reasoning_effort: Option<ReasoningEffort>,
 }
 
 pub struct OpenAiLanguageModelProvider {
@@ -203,6 +207,7 @@ impl LanguageModelProvider for OpenAiLanguageModelProvider {
                     max_tokens: model.max_tokens,
                     max_output_tokens: model.max_output_tokens,
                     max_completion_tokens: model.max_completion_tokens,
+                    reasoning_effort: model.reasoning_effort,
                 },
             );
         }
@@ -302,6 +307,14 @@ impl LanguageModel for OpenAiLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn supports_structured_output(&self) -> bool {
+        self.model.supports_structured_output()
+    }
+
+    fn supports_images(&self) -> bool {
+        self.model.supports_images()
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -318,14 +331,16 @@ impl LanguageModel for OpenAiLanguageModel {
         'static,
         Result<futures::stream::BoxStream<'static, Result<LanguageModelCompletionEvent>>>,
     > {
-        let request = into_open_ai(request, self.model.id().into(), self.max_output_tokens());
+        let request = into_open_ai(
+            request,
+            self.model.id().into(),
+            self.max_output_tokens(),
+            self.model.reasoning_effort(),
+            self.model.supports_images(),
+            false,
+        );
         let completions = self.stream_completion(request, cx);
-        async move {
-            Ok(open_ai::extract_text_from_events(completions.await?)
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(map_to_completion_events(completions.await?).boxed()) }.boxed()
     }
 
     fn use_any_tool(
@@ -336,7 +351,14 @@ impl LanguageModel for OpenAiLanguageModel {
         schema: serde_json::Value,
         cx: &AsyncApp,
     ) -> BoxFuture<'static, Result<futures::stream::BoxStream<'static, Result<String>>>> {
-        let mut request = into_open_ai(request, self.model.id().into(), self.max_output_tokens());
+        let mut request = into_open_ai(
+            request,
+            self.model.id().into(),
+            self.max_output_tokens(),
+            self.model.reasoning_effort(),
+            self.model.supports_images(),
+            false,
+        );
         request.tool_choice = Some(ToolChoice::Other(ToolDefinition::Function {
             function: FunctionDefinition {
                 name: tool_name.clone(),
@@ -366,36 +388,270 @@ impl LanguageModel for OpenAiLanguageModel {
     }
 }
 
+/// Converts the request, leaving out the tool calls and images that the model
+/// doesn't support.
 pub fn into_open_ai(
     request: LanguageModelRequest,
     model: String,
     max_output_tokens: Option<u32>,
+    reasoning_effort: Option<ReasoningEffort>,
+    supports_images: bool,
+    supports_tools: bool,
 ) -> open_ai::Request {
     let stream = !model.starts_with("o1-");
+    let mut messages = Vec::new();
+    for message in request.messages {
+        match message.role {
+            Role::System => messages.push(open_ai::RequestMessage::System {
+                content: message.string_contents(),
+            }),
+            Role::Assistant => {
+                let mut text = String::new();
+                let mut tool_calls = Vec::new();
+                for content in message.content {
+                    match content {
+                        MessageContent::Text(chunk) => text.push_str(&chunk),
+                        MessageContent::ToolUse(tool_use) if supports_tools => {
+                            tool_calls.push(open_ai::ToolCall {
+                                id: tool_use.id.to_string(),
+                                content: open_ai::ToolCallContent::Function {
+                                    function: open_ai::FunctionContent {
+                                        name: tool_use.name.to_string(),
+                                        arguments: tool_use.input.to_string(),
+                                    },
+                                },
+                            });
+                        }
+                        MessageContent::Thinking { .. }
+                        | MessageContent::RedactedThinking { .. }
+                        | MessageContent::Image(_)
+                        | MessageContent::ToolUse(_)
+                        | MessageContent::ToolResult(_) => {}
+                    }
+                }
+                if !text.is_empty() || !tool_calls.is_empty() {
+                    messages.push(open_ai::RequestMessage::Assistant {
+                        content: (!text.is_empty()).then_some(text),
+                        tool_calls,
+                    });
+                }
+            }
+            Role::User => {
+                let mut parts = Vec::new();
+                for content in message.content {
+                    match content {
+                        MessageContent::Text(text) => {
+                            parts.push(open_ai::MessagePart::Text { text })
+                        }
+                        MessageContent::Image(image) if supports_images => {
+                            parts.push(open_ai::MessagePart::ImageUrl {
+                                image_url: open_ai::ImageUrl {
+                                    url: format!("data:image/png;base64,{}", image.source),
+                                },
+                            })
+                        }
+                        // Tool results have to follow the assistant message that
+                        // called the tools.
+                        MessageContent::ToolResult(tool_result) if supports_tools => {
+                            messages.push(open_ai::RequestMessage::Tool {
+                                content: tool_result.content.to_string(),
+                                tool_call_id: tool_result.tool_use_id.to_string(),
+                            })
+                        }
+                        MessageContent::ToolResult(tool_result) => {
+                            parts.push(open_ai::MessagePart::Text {
+                                text: tool_result.content.to_string(),
+                            })
+                        }
+                        MessageContent::Thinking { .. }
+                        | MessageContent::RedactedThinking { .. }
+                        | MessageContent::Image(_)
+                        | MessageContent::ToolUse(_) => {}
+                    }
+                }
+                let has_images = parts
+                    .iter()
+                    .any(|part| matches!(part, open_ai::MessagePart::ImageUrl { .. }));
+                let content = if has_images {
+                    open_ai::MessageContent::Multipart(parts)
+                } else {
+                    let text = parts
+                        .into_iter()
+                        .filter_map(|part| match part {
+                            open_ai::MessagePart::Text { text } => Some(text),
+                            open_ai::MessagePart::ImageUrl { .. } => None,
+                        })
+                        .collect::<String>();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    open_ai::MessageContent::Plain(text)
+                };
+                messages.push(open_ai::RequestMessage::User { content });
+            }
+        }
+    }
+
     open_ai::Request {
         model,
-        messages: request
-            .messages
-            .into_iter()
-            .map(|msg| match msg.role {
-                Role::User => open_ai::RequestMessage::User {
-                    content: msg.string_contents(),
-                },
-                Role::Assistant => open_ai::RequestMessage::Assistant {
-                    content: Some(msg.string_contents()),
-                    tool_calls: Vec::new(),
-                },
-                Role::System => open_ai::RequestMessage::System {
-                    content: msg.string_contents(),
-                },
-            })
-            .collect(),
+        messages,
         stream,
+        stream_options: stream.then_some(StreamOptions {
+            include_usage: true,
+        }),
         stop: request.stop,
         temperature: request.temperature.unwrap_or(1.0),
         max_tokens: max_output_tokens,
-        tools: Vec::new(),
+        tools: if supports_tools {
+            request
+                .tools
+                .into_iter()
+                .map(|tool| ToolDefinition::Function {
+                    function: FunctionDefinition {
+                        name: tool.name,
+                        description: Some(tool.description),
+                        parameters: Some(tool.input_schema),
+                    },
+                })
+                .collect()
+        } else {
+            Vec::new()
+        },
         tool_choice: None,
+        reasoning_effort,
+        response_format: request
+            .response_format
+            .map(|format| ResponseFormat::JsonSchema {
+                json_schema: JsonSchemaFormat {
+                    name: format.name,
+                    schema: format.schema,
+                    strict: None,
+                },
+            }),
+    }
+}
+
+/// Maps the streamed chunks to completion events, keeping the reasoning some
+/// OpenAI-compatible servers stream apart from the answer, and assembling the
+/// tool calls that are streamed in pieces.
+pub fn map_to_completion_events(
+    events: impl Stream<Item = Result<ResponseStreamEvent>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    #[derive(Default)]
+    struct RawToolCall {
+        id: String,
+        name: String,
+        arguments: String,
+    }
+
+    let mut tool_calls_by_index = BTreeMap::<usize, RawToolCall>::default();
+
+    fn finish_tool_calls(
+        tool_calls_by_index: &mut BTreeMap<usize, RawToolCall>,
+    ) -> Vec<Result<LanguageModelCompletionEvent>> {
+        std::mem::take(tool_calls_by_index)
+            .into_values()
+            .map(|tool_call| {
+                let input = if tool_call.arguments.trim().is_empty() {
+                    serde_json::Value::Object(serde_json::Map::default())
+                } else {
+                    serde_json::from_str(&tool_call.arguments)
+                        .with_context(|| format!("invalid arguments for {}", tool_call.name))?
+                };
+                Ok(LanguageModelCompletionEvent::ToolUse(
+                    LanguageModelToolUse {
+                        id: tool_call.id.into(),
+                        name: tool_call.name.into(),
+                        input,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    events
+        .map(Some)
+        .chain(futures::stream::once(async { None }))
+        .flat_map(move |event| {
+            let mut completion_events = Vec::new();
+            match event {
+                Some(Ok(event)) => {
+                    // The usage is streamed in the last chunk, which has no choices.
+                    if let Some(usage) = event.usage {
+                        completion_events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(
+                            token_usage(&usage),
+                        )));
+                    }
+                    for choice in event.choices {
+                        let delta = choice.delta;
+                        if let Some(reasoning) = delta.reasoning_content {
+                            if !reasoning.is_empty() {
+                                completion_events.push(Ok(
+                                    LanguageModelCompletionEvent::Thinking {
+                                        text: reasoning,
+                                        signature: None,
+                                    },
+                                ));
+                            }
+                        }
+                        if let Some(content) = delta.content {
+                            if !content.is_empty() {
+                                completion_events
+                                    .push(Ok(LanguageModelCompletionEvent::Text(content)));
+                            }
+                        }
+                        for chunk in delta.tool_calls.unwrap_or_default() {
+                            let tool_call = tool_calls_by_index.entry(chunk.index).or_default();
+                            if let Some(id) = chunk.id {
+                                tool_call.id = id;
+                            }
+                            if let Some(function) = chunk.function {
+                                if let Some(name) = function.name {
+                                    tool_call.name = name;
+                                }
+                                if let Some(arguments) = function.arguments {
+                                    tool_call.arguments.push_str(&arguments);
+                                }
+                            }
+                        }
+                        if let Some(finish_reason) = choice.finish_reason {
+                            let called_tools = !tool_calls_by_index.is_empty();
+                            completion_events.extend(finish_tool_calls(&mut tool_calls_by_index));
+                            let stop_reason = match finish_reason.as_str() {
+                                "length" => StopReason::MaxTokens,
+                                _ if called_tools => StopReason::ToolUse,
+                                _ => StopReason::EndTurn,
+                            };
+                            completion_events
+                                .push(Ok(LanguageModelCompletionEvent::Stop(stop_reason)));
+                        }
+                    }
+                }
+                Some(Err(error)) => completion_events.push(Err(error)),
+                // Some servers end the stream without a finish reason.
+                None if !tool_calls_by_index.is_empty() => {
+                    completion_events.extend(finish_tool_calls(&mut tool_calls_by_index));
+                    completion_events
+                        .push(Ok(LanguageModelCompletionEvent::Stop(StopReason::ToolUse)));
+                }
+                None => {}
+            }
+            futures::stream::iter(completion_events)
+        })
+}
+
+/// Converts the usage reported by OpenAI, whose prompt tokens include those read
+/// from the cache.
+pub fn token_usage(usage: &open_ai::Usage) -> TokenUsage {
+    let cached_tokens = usage
+        .prompt_tokens_details
+        .as_ref()
+        .map_or(0, |details| details.cached_tokens);
+    TokenUsage {
+        input_tokens: usage.prompt_tokens.saturating_sub(cached_tokens),
+        output_tokens: usage.completion_tokens,
+        cache_creation_input_tokens: 0,
+        cache_read_input_tokens: cached_tokens,
     }
 }
 
@@ -617,3 +873,144 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::{LanguageModelRequestMessage, LanguageModelToolResult};
+    use open_ai::{ChoiceDelta, FunctionChunk, ResponseMessageDelta, ToolCallChunk};
+
+    #[test]
+    fn test_into_open_ai() {
+        let tool_use = LanguageModelToolUse {
+            id: "call_1".into(),
+            name: "search".into(),
+            input: serde_json::json!({ "query": "zed" }),
+        };
+        let request = LanguageModelRequest {
+            messages: vec![
+                LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: vec!["Search for zed".into()],
+                    cache: false,
+                },
+                LanguageModelRequestMessage {
+                    role: Role::Assistant,
+                    content: vec![MessageContent::ToolUse(tool_use)],
+                    cache: false,
+                },
+                LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: vec![MessageContent::ToolResult(LanguageModelToolResult {
+                        tool_use_id: "call_1".into(),
+                        is_error: false,
+                        content: "Found it".into(),
+                    })],
+                    cache: false,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let with_tools = into_open_ai(request.clone(), "model".into(), None, None, false, true);
+        assert_eq!(
+            with_tools.messages,
+            vec![
+                open_ai::RequestMessage::User {
+                    content: "Search for zed".to_string().into(),
+                },
+                open_ai::RequestMessage::Assistant {
+                    content: None,
+                    tool_calls: vec![open_ai::ToolCall {
+                        id: "call_1".into(),
+                        content: open_ai::ToolCallContent::Function {
+                            function: open_ai::FunctionContent {
+                                name: "search".into(),
+                                arguments: r#"{"query":"zed"}"#.into(),
+                            },
+                        },
+                    }],
+                },
+                open_ai::RequestMessage::Tool {
+                    content: "Found it".into(),
+                    tool_call_id: "call_1".into(),
+                },
+            ]
+        );
+
+        let without_tools = into_open_ai(request, "model".into(), None, None, false, false);
+        assert_eq!(
+            without_tools.messages,
+            vec![
+                open_ai::RequestMessage::User {
+                    content: "Search for zed".to_string().into(),
+                },
+                open_ai::RequestMessage::User {
+                    content: "Found it".to_string().into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_tool_calls() {
+        let chunk = |tool_calls: Vec<ToolCallChunk>, finish_reason: Option<&str>| {
+            Ok(ResponseStreamEvent {
+                created: 0,
+                model: "model".into(),
+                choices: vec![ChoiceDelta {
+                    index: 0,
+                    delta: ResponseMessageDelta {
+                        role: None,
+                        content: None,
+                        reasoning_content: None,
+                        tool_calls: Some(tool_calls),
+                    },
+                    finish_reason: finish_reason.map(|reason| reason.to_string()),
+                }],
+                usage: None,
+            })
+        };
+        let events = futures::stream::iter(vec![
+            chunk(
+                vec![ToolCallChunk {
+                    index: 0,
+                    id: Some("call_1".into()),
+                    function: Some(FunctionChunk {
+                        name: Some("search".into()),
+                        arguments: Some(r#"{"query":"#.into()),
+                    }),
+                }],
+                None,
+            ),
+            chunk(
+                vec![ToolCallChunk {
+                    index: 0,
+                    id: None,
+                    function: Some(FunctionChunk {
+                        name: None,
+                        arguments: Some(r#""zed"}"#.into()),
+                    }),
+                }],
+                Some("tool_calls"),
+            ),
+        ]);
+
+        let events = smol::block_on(
+            map_to_completion_events(events)
+                .map(|event| event.unwrap())
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            events,
+            vec![
+                LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                    id: "call_1".into(),
+                    name: "search".into(),
+                    input: serde_json::json!({ "query": "zed" }),
+                }),
+                LanguageModelCompletionEvent::Stop(StopReason::ToolUse),
+            ]
+        );
+    }
+}
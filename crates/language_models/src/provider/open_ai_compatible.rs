@@ -0,0 +1,754 @@
+use anyhow::{anyhow, Context as _, Result};
+use collections::{BTreeMap, HashSet};
+use credentials_provider::CredentialsProvider;
+use editor::{Editor, EditorElement, EditorStyle};
+use futures::{future::BoxFuture, FutureExt, StreamExt};
+use gpui::{
+    AnyView, App, AsyncApp, Context, Entity, FontStyle, Subscription, Task, TextStyle, WhiteSpace,
+};
+use http_client::HttpClient;
+use language_model::{
+    AuthenticateError, LanguageModel, LanguageModelCompletionEvent, LanguageModelId,
+    LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelProviderState, LanguageModelRegistry, LanguageModelRequest, RateLimiter,
+};
+use open_ai::{FunctionDefinition, ResponseStreamEvent, ToolChoice, ToolDefinition};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use std::sync::Arc;
+use theme::ThemeSettings;
+use ui::{prelude::*, Icon, IconName, Tooltip};
+use util::ResultExt;
+
+use crate::provider::open_ai::{into_open_ai, map_to_completion_events};
+use crate::AllLanguageModelSettings;
+
+/// The context window assumed for discovered models, as the `/models` endpoint
+/// doesn't report it.
+const DEFAULT_MAX_TOKENS: usize = 32_768;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpenAiCompatibleSettings {
+    pub api_url: String,
+    pub available_models: Vec<AvailableModel>,
+    pub discover_models: bool,
+    pub requires_api_key: bool,
+    pub capabilities: ModelCapabilities,
+}
+
+impl Default for OpenAiCompatibleSettings {
+    fn default() -> Self {
+        Self {
+            api_url: String::new(),
+            available_models: Vec::new(),
+            discover_models: false,
+            requires_api_key: true,
+            capabilities: ModelCapabilities::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AvailableModel {
+    /// The model's name in the endpoint's API.
+    pub name: String,
+    /// The model's name in Zed's UI, such as in the model selector dropdown menu in the assistant panel.
+    pub display_name: Option<String>,
+    /// The model's context window size.
+    pub max_tokens: usize,
+    pub max_output_tokens: Option<u32>,
+}
+
+/// What the models served by an endpoint support.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ModelCapabilities {
+    /// Whether the models can call tools.
+    ///
+    /// Default: false
+    pub tools: bool,
+    /// Whether the models accept images.
+    ///
+    /// Default: false
+    pub images: bool,
+    /// Whether the endpoint streams responses. When it doesn't, responses are
+    /// shown once they're complete.
+    ///
+    /// Default: true
+    pub streaming: bool,
+}
+
+impl Default for ModelCapabilities {
+    fn default() -> Self {
+        Self {
+            tools: false,
+            images: false,
+            streaming: true,
+        }
+    }
+}
+
+fn endpoint_settings<'a>(
+    id: &LanguageModelProviderId,
+    cx: &'a App,
+) -> Option<&'a OpenAiCompatibleSettings> {
+    AllLanguageModelSettings::get_global(cx)
+        .openai_compatible
+        .get(id.0.as_ref())
+}
+
+fn endpoint_api_url(id: &LanguageModelProviderId, cx: &App) -> Result<String> {
+    endpoint_settings(id, cx)
+        .map(|settings| settings.api_url.clone())
+        .ok_or_else(|| anyhow!("the {id} endpoint is no longer configured"))
+}
+
+fn endpoint_requires_api_key(id: &LanguageModelProviderId, cx: &App) -> bool {
+    endpoint_settings(id, cx).map_or(true, |settings| settings.requires_api_key)
+}
+
+/// The key the endpoint's API key is stored under, which follows the endpoint
+/// when its URL changes.
+fn credentials_key(id: &LanguageModelProviderId) -> String {
+    format!("open_ai_compatible:{id}")
+}
+
+/// A language model provider for one of the named OpenAI-compatible endpoints in
+/// the settings, such as vLLM, LiteLLM or an internal gateway.
+pub struct OpenAiCompatibleLanguageModelProvider {
+    id: LanguageModelProviderId,
+    http_client: Arc<dyn HttpClient>,
+    state: gpui::Entity<State>,
+}
+
+pub struct State {
+    id: LanguageModelProviderId,
+    http_client: Arc<dyn HttpClient>,
+    api_key: Option<String>,
+    discovered_models: Vec<String>,
+    fetch_models_task: Option<Task<Result<()>>>,
+    _subscription: Subscription,
+}
+
+impl State {
+    fn is_authenticated(&self, cx: &App) -> bool {
+        self.api_key.is_some() || !endpoint_requires_api_key(&self.id, cx)
+    }
+
+    fn reset_api_key(&self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let credentials_provider = <dyn CredentialsProvider>::global(cx);
+        let credentials_key = credentials_key(&self.id);
+        cx.spawn(|this, mut cx| async move {
+            credentials_provider
+                .delete_credentials(&credentials_key, &cx)
+                .await
+                .log_err();
+            this.update(&mut cx, |this, cx| {
+                this.api_key = None;
+                this.discovered_models.clear();
+                cx.notify();
+            })
+        })
+    }
+
+    fn set_api_key(&mut self, api_key: String, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let credentials_provider = <dyn CredentialsProvider>::global(cx);
+        let credentials_key = credentials_key(&self.id);
+        cx.spawn(|this, mut cx| async move {
+            credentials_provider
+                .write_credentials(&credentials_key, "Bearer", api_key.as_bytes(), &cx)
+                .await
+                .log_err();
+            this.update(&mut cx, |this, cx| {
+                this.api_key = Some(api_key);
+                this.restart_fetch_models_task(cx);
+                cx.notify();
+            })
+        })
+    }
+
+    fn authenticate(&mut self, cx: &mut Context<Self>) -> Task<Result<(), AuthenticateError>> {
+        if self.api_key.is_some() {
+            return Task::ready(Ok(()));
+        }
+        if !endpoint_requires_api_key(&self.id, cx) {
+            self.restart_fetch_models_task(cx);
+            return Task::ready(Ok(()));
+        }
+
+        let credentials_provider = <dyn CredentialsProvider>::global(cx);
+        let credentials_key = credentials_key(&self.id);
+        cx.spawn(|this, mut cx| async move {
+            let (_, api_key) = credentials_provider
+                .read_credentials(&credentials_key, &cx)
+                .await?
+                .ok_or(AuthenticateError::CredentialsNotFound)?;
+            let api_key = String::from_utf8(api_key).context("invalid API key")?;
+            this.update(&mut cx, |this, cx| {
+                this.api_key = Some(api_key);
+                this.restart_fetch_models_task(cx);
+                cx.notify();
+            })?;
+
+            Ok(())
+        })
+    }
+
+    fn fetch_models(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(settings) = endpoint_settings(&self.id, cx) else {
+            return Task::ready(Ok(()));
+        };
+        let api_key = match self.api_key.clone() {
+            Some(api_key) => Some(api_key),
+            None if !settings.requires_api_key => Some(String::new()),
+            None => None,
+        };
+        let (Some(api_key), true) = (api_key, settings.discover_models) else {
+            self.discovered_models.clear();
+            return Task::ready(Ok(()));
+        };
+        let http_client = self.http_client.clone();
+        let api_url = settings.api_url.clone();
+
+        cx.spawn(|this, mut cx| async move {
+            let models = open_ai::list_models(http_client.as_ref(), &api_url, &api_key).await?;
+            let mut models = models.into_iter().map(|model| model.id).collect::<Vec<_>>();
+            models.sort();
+
+            this.update(&mut cx, |this, cx| {
+                this.discovered_models = models;
+                cx.notify();
+            })
+        })
+    }
+
+    fn restart_fetch_models_task(&mut self, cx: &mut Context<Self>) {
+        let task = self.fetch_models(cx);
+        self.fetch_models_task.replace(task);
+    }
+}
+
+impl OpenAiCompatibleLanguageModelProvider {
+    pub fn new(
+        id: LanguageModelProviderId,
+        http_client: Arc<dyn HttpClient>,
+        cx: &mut App,
+    ) -> Self {
+        let state = cx.new(|cx| {
+            let subscription = cx.observe_global::<SettingsStore>({
+                let mut settings = endpoint_settings(&id, cx).cloned();
+                move |this: &mut State, cx| {
+                    let new_settings = endpoint_settings(&this.id, cx);
+                    if settings.as_ref() != new_settings {
+                        settings = new_settings.cloned();
+                        this.restart_fetch_models_task(cx);
+                        cx.notify();
+                    }
+                }
+            });
+
+            State {
+                id: id.clone(),
+                http_client: http_client.clone(),
+                api_key: None,
+                discovered_models: Vec::new(),
+                fetch_models_task: None,
+                _subscription: subscription,
+            }
+        });
+
+        Self {
+            id,
+            http_client,
+            state,
+        }
+    }
+
+    fn create_language_model(
+        &self,
+        model: AvailableModel,
+        capabilities: ModelCapabilities,
+    ) -> Arc<dyn LanguageModel> {
+        Arc::new(OpenAiCompatibleLanguageModel {
+            id: LanguageModelId::from(model.name.clone()),
+            provider_id: self.id.clone(),
+            provider_name: self.name(),
+            model,
+            capabilities,
+            state: self.state.clone(),
+            http_client: self.http_client.clone(),
+            request_limiter: RateLimiter::new(4),
+        })
+    }
+}
+
+impl LanguageModelProviderState for OpenAiCompatibleLanguageModelProvider {
+    type ObservableEntity = State;
+
+    fn observable_entity(&self) -> Option<gpui::Entity<Self::ObservableEntity>> {
+        Some(self.state.clone())
+    }
+}
+
+impl LanguageModelProvider for OpenAiCompatibleLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName(self.id.0.clone())
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Ai
+    }
+
+    fn default_model(&self, cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.provided_models(cx).into_iter().next()
+    }
+
+    fn provided_models(&self, cx: &App) -> Vec<Arc<dyn LanguageModel>> {
+        let Some(settings) = endpoint_settings(&self.id, cx) else {
+            return Vec::new();
+        };
+
+        let mut models = BTreeMap::default();
+
+        // Add the models discovered through the endpoint's API
+        for name in &self.state.read(cx).discovered_models {
+            models.insert(
+                name.clone(),
+                AvailableModel {
+                    name: name.clone(),
+                    display_name: None,
+                    max_tokens: DEFAULT_MAX_TOKENS,
+                    max_output_tokens: None,
+                },
+            );
+        }
+
+        // Override with available models from settings
+        for model in &settings.available_models {
+            models.insert(model.name.clone(), model.clone());
+        }
+
+        models
+            .into_values()
+            .map(|model| self.create_language_model(model, settings.capabilities))
+            .collect()
+    }
+
+    fn is_authenticated(&self, cx: &App) -> bool {
+        self.state.read(cx).is_authenticated(cx)
+    }
+
+    fn authenticate(&self, cx: &mut App) -> Task<Result<(), AuthenticateError>> {
+        self.state.update(cx, |state, cx| state.authenticate(cx))
+    }
+
+    fn configuration_view(&self, window: &mut Window, cx: &mut App) -> AnyView {
+        cx.new(|cx| ConfigurationView::new(self.state.clone(), window, cx))
+            .into()
+    }
+
+    fn reset_credentials(&self, cx: &mut App) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.reset_api_key(cx))
+    }
+}
+
+pub struct OpenAiCompatibleLanguageModel {
+    id: LanguageModelId,
+    provider_id: LanguageModelProviderId,
+    provider_name: LanguageModelProviderName,
+    model: AvailableModel,
+    capabilities: ModelCapabilities,
+    state: gpui::Entity<State>,
+    http_client: Arc<dyn HttpClient>,
+    request_limiter: RateLimiter,
+}
+
+impl OpenAiCompatibleLanguageModel {
+    fn stream_completion(
+        &self,
+        request: open_ai::Request,
+        cx: &AsyncApp,
+    ) -> BoxFuture<'static, Result<futures::stream::BoxStream<'static, Result<ResponseStreamEvent>>>>
+    {
+        let http_client = self.http_client.clone();
+        let Ok((api_key, api_url)) = cx.read_entity(&self.state, |state, cx| {
+            let api_key = match state.api_key.clone() {
+                Some(api_key) => Some(api_key),
+                // Servers that don't check keys are sent none.
+                None if !endpoint_requires_api_key(&state.id, cx) => Some(String::new()),
+                None => None,
+            };
+            (api_key, endpoint_api_url(&state.id, cx))
+        }) else {
+            return futures::future::ready(Err(anyhow!("App state dropped"))).boxed();
+        };
+        let provider_name = self.provider_name.clone();
+
+        let future = self.request_limiter.stream(async move {
+            let api_url = api_url?;
+            let api_key = api_key.ok_or_else(|| anyhow!("Missing {} API Key", provider_name.0))?;
+            let request =
+                open_ai::stream_completion(http_client.as_ref(), &api_url, &api_key, request);
+            let response = request.await?;
+            Ok(response)
+        });
+
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
+
+    /// Converts the request, leaving out the tools and images that the endpoint
+    /// doesn't support.
+    fn to_open_ai_request(&self, request: LanguageModelRequest) -> open_ai::Request {
+        let mut request = into_open_ai(
+            request,
+            self.model.name.clone(),
+            self.model.max_output_tokens,
+            None,
+            self.capabilities.images,
+            self.capabilities.tools,
+        );
+        request.stream = self.capabilities.streaming;
+        request.stream_options = self
+            .capabilities
+            .streaming
+            .then_some(open_ai::StreamOptions {
+                include_usage: true,
+            });
+        request
+    }
+}
+
+impl LanguageModel for OpenAiCompatibleLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(
+            self.model
+                .display_name
+                .clone()
+                .unwrap_or_else(|| self.model.name.clone()),
+        )
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.provider_id.clone()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.provider_name.clone()
+    }
+
+    fn telemetry_id(&self) -> String {
+        format!("openai_compatible/{}", self.model.name)
+    }
+
+    fn max_token_count(&self) -> usize {
+        self.model.max_tokens
+    }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        self.model.max_output_tokens
+    }
+
+    fn supports_images(&self) -> bool {
+        self.capabilities.images
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &App,
+    ) -> BoxFuture<'static, Result<usize>> {
+        super::open_ai::count_open_ai_tokens(
+            request,
+            open_ai::Model::Custom {
+                name: self.model.name.clone(),
+                display_name: None,
+                max_tokens: self.model.max_tokens,
+                max_output_tokens: self.model.max_output_tokens,
+                max_completion_tokens: None,
+                reasoning_effort: None,
+            },
+            cx,
+        )
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<futures::stream::BoxStream<'static, Result<LanguageModelCompletionEvent>>>,
+    > {
+        let request = self.to_open_ai_request(request);
+        let completions = self.stream_completion(request, cx);
+        async move { Ok(map_to_completion_events(completions.await?).boxed()) }.boxed()
+    }
+
+    fn use_any_tool(
+        &self,
+        request: LanguageModelRequest,
+        tool_name: String,
+        tool_description: String,
+        schema: serde_json::Value,
+        cx: &AsyncApp,
+    ) -> BoxFuture<'static, Result<futures::stream::BoxStream<'static, Result<String>>>> {
+        if !self.capabilities.tools {
+            return futures::future::ready(Err(anyhow!(
+                "{} is not configured to support tools",
+                self.provider_name.0
+            )))
+            .boxed();
+        }
+
+        let mut request = self.to_open_ai_request(request);
+        request.tool_choice = Some(ToolChoice::Other(ToolDefinition::Function {
+            function: FunctionDefinition {
+                name: tool_name.clone(),
+                description: None,
+                parameters: None,
+            },
+        }));
+        request.tools = vec![ToolDefinition::Function {
+            function: FunctionDefinition {
+                name: tool_name.clone(),
+                description: Some(tool_description),
+                parameters: Some(schema),
+            },
+        }];
+
+        let response = self.stream_completion(request, cx);
+        self.request_limiter
+            .run(async move {
+                let response = response.await?;
+                Ok(
+                    open_ai::extract_tool_args_from_events(tool_name, Box::pin(response))
+                        .await?
+                        .boxed(),
+                )
+            })
+            .boxed()
+    }
+}
+
+/// Registers a provider for each of the configured endpoints, and keeps them
+/// registered as endpoints are added to and removed from the settings.
+pub(crate) fn register_endpoints(
+    registry: &mut LanguageModelRegistry,
+    http_client: Arc<dyn HttpClient>,
+    cx: &mut Context<LanguageModelRegistry>,
+) {
+    let mut registered = HashSet::<String>::default();
+    let mut update_endpoints =
+        move |registry: &mut LanguageModelRegistry, cx: &mut Context<LanguageModelRegistry>| {
+            let endpoints = AllLanguageModelSettings::get_global(cx)
+                .openai_compatible
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>();
+
+            registered.retain(|name| {
+                let keep = endpoints.contains(name);
+                if !keep {
+                    registry.unregister_provider(LanguageModelProviderId::from(name.clone()), cx);
+                }
+                keep
+            });
+
+            for name in endpoints {
+                let id = LanguageModelProviderId::from(name.clone());
+                // Endpoints can't replace the built-in providers.
+                if registered.contains(&name) || registry.provider(&id).is_some() {
+                    continue;
+                }
+                registry.register_provider(
+                    OpenAiCompatibleLanguageModelProvider::new(id, http_client.clone(), cx),
+                    cx,
+                );
+                registered.insert(name);
+            }
+        };
+
+    update_endpoints(registry, cx);
+    cx.observe_global::<SettingsStore>(move |registry, cx| update_endpoints(registry, cx))
+        .detach();
+}
+
+struct ConfigurationView {
+    api_key_editor: Entity<Editor>,
+    state: gpui::Entity<State>,
+    load_credentials_task: Option<Task<()>>,
+}
+
+impl ConfigurationView {
+    fn new(state: gpui::Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let api_key_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("API key", cx);
+            editor
+        });
+
+        cx.observe(&state, |_, _, cx| {
+            cx.notify();
+        })
+        .detach();
+
+        let load_credentials_task = Some(cx.spawn_in(window, {
+            let state = state.clone();
+            |this, mut cx| async move {
+                if let Some(task) = state
+                    .update(&mut cx, |state, cx| state.authenticate(cx))
+                    .log_err()
+                {
+                    // We don't log an error, because "not signed in" is also an error.
+                    let _ = task.await;
+                }
+
+                this.update(&mut cx, |this, cx| {
+                    this.load_credentials_task = None;
+                    cx.notify();
+                })
+                .log_err();
+            }
+        }));
+
+        Self {
+            api_key_editor,
+            state,
+            load_credentials_task,
+        }
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let api_key = self.api_key_editor.read(cx).text(cx);
+        if api_key.is_empty() {
+            return;
+        }
+
+        let state = self.state.clone();
+        cx.spawn_in(window, |_, mut cx| async move {
+            state
+                .update(&mut cx, |state, cx| state.set_api_key(api_key, cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+
+    fn reset_api_key(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.api_key_editor
+            .update(cx, |editor, cx| editor.set_text("", window, cx));
+
+        let state = self.state.clone();
+        cx.spawn_in(window, |_, mut cx| async move {
+            state
+                .update(&mut cx, |state, cx| state.reset_api_key(cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+
+    fn render_api_key_editor(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.ui_font.family.clone(),
+            font_features: settings.ui_font.features.clone(),
+            font_fallbacks: settings.ui_font.fallbacks.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: settings.ui_font.weight,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            white_space: WhiteSpace::Normal,
+            ..Default::default()
+        };
+        EditorElement::new(
+            &self.api_key_editor,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl Render for ConfigurationView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let is_authenticated = state.is_authenticated(cx);
+        let has_api_key = state.api_key.is_some();
+        let name = state.id.to_string();
+        let api_url = endpoint_api_url(&state.id, cx).unwrap_or_default();
+
+        if self.load_credentials_task.is_some() {
+            div().child(Label::new("Loading credentials...")).into_any()
+        } else if !is_authenticated {
+            v_flex()
+                .size_full()
+                .on_action(cx.listener(Self::save_api_key))
+                .child(Label::new(format!(
+                    "To use Zed's assistant with {name}, paste the API key for {api_url} below and hit enter."
+                )))
+                .child(
+                    h_flex()
+                        .w_full()
+                        .my_2()
+                        .px_2()
+                        .py_1()
+                        .bg(cx.theme().colors().editor_background)
+                        .border_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .rounded_md()
+                        .child(self.render_api_key_editor(cx)),
+                )
+                .child(
+                    Label::new(
+                        "If the endpoint doesn't check API keys, set `requires_api_key` to false in its settings instead.",
+                    )
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+                )
+                .into_any()
+        } else if !has_api_key {
+            h_flex()
+                .gap_1()
+                .child(Icon::new(IconName::Check).color(Color::Success))
+                .child(Label::new(format!("{api_url} doesn't require an API key.")))
+                .into_any()
+        } else {
+            h_flex()
+                .size_full()
+                .justify_between()
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(Icon::new(IconName::Check).color(Color::Success))
+                        .child(Label::new("API key configured.")),
+                )
+                .child(
+                    Button::new("reset-key", "Reset key")
+                        .icon(Some(IconName::Trash))
+                        .icon_size(IconSize::Small)
+                        .icon_position(IconPosition::Start)
+                        .tooltip(Tooltip::text(format!("Remove the API key for {api_url}")))
+                        .on_click(
+                            cx.listener(|this, _, window, cx| this.reset_api_key(window, cx)),
+                        ),
+                )
+                .into_any()
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use collections::BTreeMap;
 use gpui::App;
 use language_model::LanguageModelCacheConfiguration;
 use project::Fs;
@@ -20,6 +21,7 @@ use crate::provider::{
     mistral::MistralSettings,
     ollama::OllamaSettings,
     open_ai::OpenAiSettings,
+    open_ai_compatible::{ModelCapabilities, OpenAiCompatibleSettings},
 };
 
 /// Initializes the language model settings.
@@ -67,6 +69,7 @@ pub struct AllLanguageModelSettings {
     pub lmstudio: LmStudioSettings,
     pub deepseek: DeepSeekSettings,
     pub mistral: MistralSettings,
+    pub openai_compatible: BTreeMap<String, OpenAiCompatibleSettings>,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -81,6 +84,8 @@ pub struct AllLanguageModelSettingsContent {
     pub deepseek: Option<DeepseekSettingsContent>,
     pub copilot_chat: Option<CopilotChatSettingsContent>,
     pub mistral: Option<MistralSettingsContent>,
+    /// OpenAI-compatible endpoints, each shown as its own provider with the given name.
+    pub openai_compatible: Option<BTreeMap<String, OpenAiCompatibleSettingsContent>>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -109,6 +114,7 @@ impl AnthropicSettingsContent {
                                     max_output_tokens,
                                     default_temperature,
                                     extra_beta_headers,
+                                    mode,
                                 } => Some(provider::anthropic::AvailableModel {
                                     name,
                                     display_name,
@@ -124,6 +130,7 @@ impl AnthropicSettingsContent {
                                     max_output_tokens,
                                     default_temperature,
                                     extra_beta_headers,
+                                    mode,
                                 }),
                                 _ => None,
                             })
@@ -182,6 +189,20 @@ pub struct MistralSettingsContent {
     pub available_models: Option<Vec<provider::mistral::AvailableModel>>,
 }
 
+#[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct OpenAiCompatibleSettingsContent {
+    /// The URL of the endpoint's API, such as `http://localhost:8000/v1`.
+    pub api_url: Option<String>,
+    pub available_models: Option<Vec<provider::open_ai_compatible::AvailableModel>>,
+    /// Whether to add the models listed by the endpoint's `/models` API to the
+    /// available models.
+    pub discover_models: Option<bool>,
+    /// Whether the endpoint checks API keys. Local servers that don't are
+    /// used without one.
+    pub requires_api_key: Option<bool>,
+    pub capabilities: Option<ModelCapabilities>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(untagged)]
 pub enum OpenAiSettingsContent {
@@ -205,12 +226,14 @@ impl OpenAiSettingsContent {
                                     max_tokens,
                                     max_output_tokens,
                                     max_completion_tokens,
+                                    reasoning_effort,
                                 } => Some(provider::open_ai::AvailableModel {
                                     name,
                                     max_tokens,
                                     max_output_tokens,
                                     display_name,
                                     max_completion_tokens,
+                                    reasoning_effort,
                                 }),
                                 _ => None,
                             })
@@ -378,8 +401,26 @@ impl settings::Settings for AllLanguageModelSettings {
                 &mut settings.mistral.available_models,
                 mistral.as_ref().and_then(|s| s.available_models.clone()),
             );
+
+            // OpenAI-compatible endpoints
+            for (name, endpoint) in value.openai_compatible.iter().flatten() {
+                let settings = settings.openai_compatible.entry(name.clone()).or_default();
+                merge(&mut settings.api_url, endpoint.api_url.clone());
+                merge(
+                    &mut settings.available_models,
+                    endpoint.available_models.clone(),
+                );
+                merge(&mut settings.discover_models, endpoint.discover_models);
+                merge(&mut settings.requires_api_key, endpoint.requires_api_key);
+                merge(&mut settings.capabilities, endpoint.capabilities);
+            }
         }
 
+        // Endpoints can't be used without knowing where they are.
+        settings
+            .openai_compatible
+            .retain(|_, endpoint| !endpoint.api_url.is_empty());
+
         Ok(settings)
     }
 }
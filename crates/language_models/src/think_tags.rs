@@ -0,0 +1,128 @@
+//! Separates the reasoning of models that wrap it in `<think>` tags, such as DeepSeek-R1
+//! and QwQ when they're served locally, from their answer.
+
+use anyhow::Result;
+use futures::{stream, Stream, StreamExt};
+use language_model::LanguageModelCompletionEvent;
+
+const OPENING_TAG: &str = "<think>";
+const CLOSING_TAG: &str = "</think>";
+
+/// Maps streamed text to completion events, turning the contents of `<think>` tags
+/// into thinking events.
+pub(crate) fn map_think_tags(
+    chunks: impl Stream<Item = Result<String>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    let mut parser = ThinkTagParser::default();
+    chunks
+        .map(Some)
+        .chain(stream::once(async { None }))
+        .flat_map(move |chunk| {
+            let events = match chunk {
+                Some(Ok(chunk)) => parser.push(&chunk).into_iter().map(Ok).collect(),
+                Some(Err(error)) => vec![Err(error)],
+                None => parser.finish().into_iter().map(Ok).collect(),
+            };
+            stream::iter(events)
+        })
+}
+
+#[derive(Default)]
+struct ThinkTagParser {
+    in_thinking: bool,
+    after_thinking: bool,
+    pending: String,
+}
+
+impl ThinkTagParser {
+    fn push(&mut self, chunk: &str) -> Vec<LanguageModelCompletionEvent> {
+        self.pending.push_str(chunk);
+        let mut events = Vec::new();
+        loop {
+            let tag = if self.in_thinking {
+                CLOSING_TAG
+            } else {
+                OPENING_TAG
+            };
+            if let Some(ix) = self.pending.find(tag) {
+                let text = self.pending.drain(..ix).collect::<String>();
+                self.pending.drain(..tag.len());
+                events.extend(self.event(text));
+                self.after_thinking = self.in_thinking;
+                self.in_thinking = !self.in_thinking;
+            } else {
+                // Hold back what could be the start of a tag that is split across chunks.
+                let held_back = (1..tag.len())
+                    .rev()
+                    .find(|len| self.pending.ends_with(&tag[..*len]))
+                    .unwrap_or(0);
+                let text = self
+                    .pending
+                    .drain(..self.pending.len() - held_back)
+                    .collect::<String>();
+                events.extend(self.event(text));
+                return events;
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Option<LanguageModelCompletionEvent> {
+        let text = std::mem::take(&mut self.pending);
+        self.event(text)
+    }
+
+    fn event(&mut self, mut text: String) -> Option<LanguageModelCompletionEvent> {
+        if self.in_thinking {
+            return (!text.is_empty()).then_some(LanguageModelCompletionEvent::Thinking {
+                text,
+                signature: None,
+            });
+        }
+
+        // Models separate their answer from the reasoning with blank lines.
+        if self.after_thinking {
+            text = text.trim_start().to_string();
+            self.after_thinking = text.is_empty();
+        }
+        (!text.is_empty()).then_some(LanguageModelCompletionEvent::Text(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_think_tag_parser() {
+        let mut parser = ThinkTagParser::default();
+        let mut events = Vec::new();
+        for chunk in [
+            "<thi",
+            "nk>Let me",
+            " think.</th",
+            "ink>\n\n",
+            "The answer",
+            " is <",
+            "b>",
+        ] {
+            events.extend(parser.push(chunk));
+        }
+        events.extend(parser.finish());
+
+        let thinking = |text: &str| LanguageModelCompletionEvent::Thinking {
+            text: text.to_string(),
+            signature: None,
+        };
+        let text = |text: &str| LanguageModelCompletionEvent::Text(text.to_string());
+        assert_eq!(
+            events,
+            vec![
+                thinking("Let me"),
+                thinking(" think."),
+                text("The answer"),
+                text(" is "),
+                text("<b>"),
+            ]
+        );
+    }
+}
@@ -47,14 +47,22 @@ pub struct Model {
     pub name: String,
     pub display_name: Option<String>,
     pub max_tokens: usize,
+    #[serde(default)]
+    pub supports_images: bool,
 }
 
 impl Model {
-    pub fn new(name: &str, display_name: Option<&str>, max_tokens: Option<usize>) -> Self {
+    pub fn new(
+        name: &str,
+        display_name: Option<&str>,
+        max_tokens: Option<usize>,
+        supports_images: bool,
+    ) -> Self {
         Self {
             name: name.to_owned(),
             display_name: display_name.map(|s| s.to_owned()),
             max_tokens: max_tokens.unwrap_or(2048),
+            supports_images,
         }
     }
 
@@ -80,13 +88,38 @@ pub enum ChatMessage {
         tool_calls: Option<Vec<LmStudioToolCall>>,
     },
     User {
-        content: String,
+        content: MessageContent,
     },
     System {
         content: String,
     },
 }
 
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Plain(String),
+    Multipart(Vec<MessagePart>),
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        Self::Plain(text)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagePart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct ImageUrl {
+    pub url: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum LmStudioToolCall {
@@ -123,6 +156,31 @@ pub struct ChatCompletionRequest {
     pub tools: Vec<LmStudioTool>,
 }
 
+/// A request for a completion of a raw prompt, such as a fill-in-the-middle prompt.
+#[derive(Serialize, Debug)]
+pub struct CompletionRequest {
+    pub model: String,
+    pub prompt: String,
+    pub stream: bool,
+    pub max_tokens: Option<i32>,
+    pub stop: Option<Vec<String>>,
+    pub temperature: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CompletionResponse {
+    pub id: String,
+    pub model: String,
+    pub choices: Vec<CompletionChoice>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CompletionChoice {
+    pub index: u32,
+    pub text: String,
+    pub finish_reason: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ChatResponse {
     pub id: String,
@@ -253,6 +311,34 @@ pub async fn complete(
     }
 }
 
+pub async fn complete_prompt(
+    client: &dyn HttpClient,
+    api_url: &str,
+    request: CompletionRequest,
+) -> Result<CompletionResponse> {
+    let uri = format!("{api_url}/completions");
+    let request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+
+    let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request)?))?;
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        serde_json::from_str(&body).context("Unable to parse LM Studio completions response")
+    } else {
+        Err(anyhow!(
+            "Failed to connect to LM Studio API: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 pub async fn stream_chat_completion(
     client: &dyn HttpClient,
     api_url: &str,
@@ -56,6 +56,8 @@ pub enum Model {
     #[serde(rename = "open-codestral-mamba", alias = "open-codestral-mamba")]
     #[default]
     OpenCodestralMamba,
+    #[serde(rename = "pixtral-large-latest", alias = "pixtral-large-latest")]
+    PixtralLargeLatest,
 
     #[serde(rename = "custom")]
     Custom {
@@ -65,6 +67,8 @@ pub enum Model {
         max_tokens: usize,
         max_output_tokens: Option<u32>,
         max_completion_tokens: Option<u32>,
+        #[serde(default)]
+        supports_images: bool,
     },
 }
 
@@ -76,6 +80,7 @@ impl Model {
             "mistral-small-latest" => Ok(Self::MistralSmallLatest),
             "open-mistral-nemo" => Ok(Self::OpenMistralNemo),
             "open-codestral-mamba" => Ok(Self::OpenCodestralMamba),
+            "pixtral-large-latest" => Ok(Self::PixtralLargeLatest),
             _ => Err(anyhow!("invalid model id")),
         }
     }
@@ -87,6 +92,7 @@ impl Model {
             Self::MistralSmallLatest => "mistral-small-latest",
             Self::OpenMistralNemo => "open-mistral-nemo",
             Self::OpenCodestralMamba => "open-codestral-mamba",
+            Self::PixtralLargeLatest => "pixtral-large-latest",
             Self::Custom { name, .. } => name,
         }
     }
@@ -98,6 +104,7 @@ impl Model {
             Self::MistralSmallLatest => "mistral-small-latest",
             Self::OpenMistralNemo => "open-mistral-nemo",
             Self::OpenCodestralMamba => "open-codestral-mamba",
+            Self::PixtralLargeLatest => "pixtral-large-latest",
             Self::Custom {
                 name, display_name, ..
             } => display_name.as_ref().unwrap_or(name),
@@ -111,10 +118,24 @@ impl Model {
             Self::MistralSmallLatest => 32000,
             Self::OpenMistralNemo => 131000,
             Self::OpenCodestralMamba => 256000,
+            Self::PixtralLargeLatest => 128000,
             Self::Custom { max_tokens, .. } => *max_tokens,
         }
     }
 
+    pub fn supports_images(&self) -> bool {
+        match self {
+            Self::MistralSmallLatest | Self::PixtralLargeLatest => true,
+            Self::CodestralLatest
+            | Self::MistralLargeLatest
+            | Self::OpenMistralNemo
+            | Self::OpenCodestralMamba => false,
+            Self::Custom {
+                supports_images, ..
+            } => *supports_images,
+        }
+    }
+
     pub fn max_output_tokens(&self) -> Option<u32> {
         match self {
             Self::Custom {
@@ -197,7 +218,7 @@ pub enum RequestMessage {
         tool_calls: Vec<ToolCall>,
     },
     User {
-        content: String,
+        content: MessageContent,
     },
     System {
         content: String,
@@ -208,6 +229,31 @@ pub enum RequestMessage {
     },
 }
 
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Plain(String),
+    Multipart(Vec<MessagePart>),
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        Self::Plain(text)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagePart {
+    Text {
+        text: String,
+    },
+    /// An image, given as a URL or as a base64-encoded data URL.
+    ImageUrl {
+        image_url: String,
+    },
+}
+
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 pub struct ToolCall {
     pub id: String,
@@ -68,6 +68,8 @@ pub struct Model {
     pub display_name: Option<String>,
     pub max_tokens: usize,
     pub keep_alive: Option<KeepAlive>,
+    #[serde(default)]
+    pub supports_images: bool,
 }
 
 fn get_max_tokens(name: &str) -> usize {
@@ -91,7 +93,12 @@ fn get_max_tokens(name: &str) -> usize {
 }
 
 impl Model {
-    pub fn new(name: &str, display_name: Option<&str>, max_tokens: Option<usize>) -> Self {
+    pub fn new(
+        name: &str,
+        display_name: Option<&str>,
+        max_tokens: Option<usize>,
+        supports_images: bool,
+    ) -> Self {
         Self {
             name: name.to_owned(),
             display_name: display_name
@@ -99,6 +106,7 @@ impl Model {
                 .or_else(|| name.strip_suffix(":latest").map(ToString::to_string)),
             max_tokens: max_tokens.unwrap_or_else(|| get_max_tokens(name)),
             keep_alive: Some(KeepAlive::indefinite()),
+            supports_images,
         }
     }
 
@@ -124,6 +132,9 @@ pub enum ChatMessage {
     },
     User {
         content: String,
+        /// The base64-encoded images shown to vision models.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        images: Option<Vec<String>>,
     },
     System {
         content: String,
@@ -163,6 +174,9 @@ pub struct ChatRequest {
     pub keep_alive: KeepAlive,
     pub options: Option<ChatOptions>,
     pub tools: Vec<OllamaTool>,
+    /// A JSON schema the response is constrained to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<serde_json::Value>,
 }
 
 impl ChatRequest {
@@ -196,6 +210,25 @@ pub struct ChatResponseDelta {
     pub done: bool,
 }
 
+/// A request for a raw completion of a prompt, such as a fill-in-the-middle prompt.
+#[derive(Serialize, Debug)]
+pub struct GenerateRequest {
+    pub model: String,
+    pub prompt: String,
+    /// Whether to send the prompt as-is, without applying the model's template.
+    pub raw: bool,
+    pub stream: bool,
+    pub keep_alive: KeepAlive,
+    pub options: Option<ChatOptions>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GenerateResponse {
+    pub response: String,
+    #[allow(unused)]
+    pub done: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct LocalModelsResponse {
     pub models: Vec<LocalModelListing>,
@@ -218,6 +251,23 @@ pub struct LocalModel {
     pub details: ModelDetails,
 }
 
+/// The details of a model, as returned by `/api/show`.
+#[derive(Deserialize, Debug)]
+pub struct ModelShow {
+    /// What the model can do, such as "completion", "tools" or "vision". Ollama
+    /// versions before 0.6 leave this out.
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+impl ModelShow {
+    pub fn supports_vision(&self) -> bool {
+        self.capabilities
+            .iter()
+            .any(|capability| capability == "vision")
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ModelDetails {
     pub format: String,
@@ -259,6 +309,34 @@ pub async fn complete(
     }
 }
 
+pub async fn generate(
+    client: &dyn HttpClient,
+    api_url: &str,
+    request: GenerateRequest,
+) -> Result<GenerateResponse> {
+    let uri = format!("{api_url}/api/generate");
+    let request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+
+    let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request)?))?;
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        serde_json::from_str(&body).context("Unable to parse Ollama generate response")
+    } else {
+        Err(anyhow!(
+            "Failed to connect to Ollama API: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 pub async fn stream_chat_completion(
     client: &dyn HttpClient,
     api_url: &str,
@@ -330,6 +408,32 @@ pub async fn get_models(
     }
 }
 
+pub async fn show_model(client: &dyn HttpClient, api_url: &str, model: &str) -> Result<ModelShow> {
+    let uri = format!("{api_url}/api/show");
+    let request = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json")
+        .body(AsyncBody::from(serde_json::to_string(
+            &serde_json::json!({ "model": model }),
+        )?))?;
+
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        serde_json::from_str(&body).context("Unable to parse Ollama model details")
+    } else {
+        Err(anyhow!(
+            "Failed to connect to Ollama API: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 /// Sends an empty request to Ollama to trigger loading the model
 pub async fn preload_model(client: Arc<dyn HttpClient>, api_url: &str, model: &str) -> Result<()> {
     let uri = format!("{api_url}/api/generate");
@@ -6,7 +6,7 @@ use futures::{
     stream::{self, BoxStream},
     AsyncBufReadExt, AsyncReadExt, Stream, StreamExt,
 };
-use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use http_client::{http, AsyncBody, HttpClient, Method, Request as HttpRequest};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
@@ -58,6 +58,16 @@ impl From<Role> for String {
     }
 }
 
+/// How much effort a reasoning model spends thinking before it answers.
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, EnumIter)]
 pub enum Model {
@@ -89,6 +99,9 @@ pub enum Model {
         max_tokens: usize,
         max_output_tokens: Option<u32>,
         max_completion_tokens: Option<u32>,
+        /// The reasoning effort of o-series models, which use the API's default when unset.
+        #[serde(default)]
+        reasoning_effort: Option<ReasoningEffort>,
     },
 }
 
@@ -163,6 +176,42 @@ impl Model {
             _ => None,
         }
     }
+
+    pub fn reasoning_effort(&self) -> Option<ReasoningEffort> {
+        match self {
+            Self::Custom {
+                reasoning_effort, ..
+            } => *reasoning_effort,
+            _ => None,
+        }
+    }
+
+    /// Whether the model can be constrained to a JSON schema through `response_format`.
+    pub fn supports_structured_output(&self) -> bool {
+        match self {
+            Self::FourOmni | Self::FourOmniMini | Self::O1 | Self::O3Mini => true,
+            Self::ThreePointFiveTurbo
+            | Self::Four
+            | Self::FourTurbo
+            | Self::O1Preview
+            | Self::O1Mini => false,
+            // Custom models may be served by other APIs that don't support it.
+            Self::Custom { .. } => false,
+        }
+    }
+
+    /// Whether the model accepts images in user messages.
+    pub fn supports_images(&self) -> bool {
+        match self {
+            Self::FourOmni | Self::FourOmniMini | Self::FourTurbo | Self::O1 => true,
+            Self::ThreePointFiveTurbo
+            | Self::Four
+            | Self::O1Preview
+            | Self::O1Mini
+            | Self::O3Mini
+            | Self::Custom { .. } => false,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -171,6 +220,8 @@ pub struct Request {
     pub messages: Vec<RequestMessage>,
     pub stream: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub stop: Vec<String>,
@@ -179,6 +230,16 @@ pub struct Request {
     pub tool_choice: Option<ToolChoice>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<ToolDefinition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StreamOptions {
+    /// Whether to stream a last chunk with the usage of the whole request.
+    pub include_usage: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -199,6 +260,22 @@ pub enum Prediction {
     Content { content: String },
 }
 
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub schema: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ToolChoice {
@@ -231,7 +308,7 @@ pub enum RequestMessage {
         tool_calls: Vec<ToolCall>,
     },
     User {
-        content: String,
+        content: MessageContent,
     },
     System {
         content: String,
@@ -242,6 +319,32 @@ pub enum RequestMessage {
     },
 }
 
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Plain(String),
+    Multipart(Vec<MessagePart>),
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Plain(text)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagePart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct ImageUrl {
+    /// The URL of the image, or its contents as a `data:` URL.
+    pub url: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 pub struct ToolCall {
     pub id: String,
@@ -265,6 +368,9 @@ pub struct FunctionContent {
 pub struct ResponseMessageDelta {
     pub role: Option<Role>,
     pub content: Option<String>,
+    /// The reasoning streamed by OpenAI-compatible servers that expose it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
     #[serde(default, skip_serializing_if = "is_none_or_empty")]
     pub tool_calls: Option<Vec<ToolCallChunk>>,
 }
@@ -291,6 +397,16 @@ pub struct Usage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PromptTokensDetails {
+    /// The prompt tokens that were read from the cache, which are included in the
+    /// prompt tokens.
+    #[serde(default)]
+    pub cached_tokens: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -347,6 +463,16 @@ pub struct Choice {
     pub finish_reason: Option<String>,
 }
 
+/// Adds the API key to the request, unless it's empty because the server
+/// doesn't check keys.
+fn authorize(request_builder: http::request::Builder, api_key: &str) -> http::request::Builder {
+    if api_key.is_empty() {
+        request_builder
+    } else {
+        request_builder.header("Authorization", format!("Bearer {}", api_key))
+    }
+}
+
 pub async fn complete(
     client: &dyn HttpClient,
     api_url: &str,
@@ -354,14 +480,17 @@ pub async fn complete(
     request: Request,
 ) -> Result<Response> {
     let uri = format!("{api_url}/chat/completions");
-    let request_builder = HttpRequest::builder()
-        .method(Method::POST)
-        .uri(uri)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key));
+    let request_builder = authorize(
+        HttpRequest::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("Content-Type", "application/json"),
+        api_key,
+    );
 
     let mut request_body = request;
     request_body.stream = false;
+    request_body.stream_options = None;
 
     let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request_body)?))?;
     let mut response = client.send(request).await?;
@@ -466,13 +595,38 @@ fn adapt_response_to_stream(response: Response) -> ResponseStreamEvent {
                         RequestMessage::System { .. } => Role::System,
                         RequestMessage::Tool { .. } => Role::Tool,
                     }),
-                    content: match choice.message {
-                        RequestMessage::Assistant { content, .. } => content,
-                        RequestMessage::User { content } => Some(content),
-                        RequestMessage::System { content } => Some(content),
-                        RequestMessage::Tool { content, .. } => Some(content),
+                    content: match &choice.message {
+                        RequestMessage::Assistant { content, .. } => content.clone(),
+                        RequestMessage::User {
+                            content: MessageContent::Plain(content),
+                        } => Some(content.clone()),
+                        RequestMessage::User {
+                            content: MessageContent::Multipart(_),
+                        } => None,
+                        RequestMessage::System { content } => Some(content.clone()),
+                        RequestMessage::Tool { content, .. } => Some(content.clone()),
+                    },
+                    reasoning_content: None,
+                    tool_calls: match choice.message {
+                        RequestMessage::Assistant { tool_calls, .. } => Some(
+                            tool_calls
+                                .into_iter()
+                                .enumerate()
+                                .map(|(index, tool_call)| {
+                                    let ToolCallContent::Function { function } = tool_call.content;
+                                    ToolCallChunk {
+                                        index,
+                                        id: Some(tool_call.id),
+                                        function: Some(FunctionChunk {
+                                            name: Some(function.name),
+                                            arguments: Some(function.arguments),
+                                        }),
+                                    }
+                                })
+                                .collect(),
+                        ),
+                        _ => None,
                     },
-                    tool_calls: None,
                 },
                 finish_reason: choice.finish_reason,
             })
@@ -487,18 +641,20 @@ pub async fn stream_completion(
     api_key: &str,
     request: Request,
 ) -> Result<BoxStream<'static, Result<ResponseStreamEvent>>> {
-    if request.model.starts_with("o1") {
+    if !request.stream || request.model.starts_with("o1") {
         let response = complete(client, api_url, api_key, request).await;
         let response_stream_event = response.map(adapt_response_to_stream);
         return Ok(stream::once(future::ready(response_stream_event)).boxed());
     }
 
     let uri = format!("{api_url}/chat/completions");
-    let request_builder = HttpRequest::builder()
-        .method(Method::POST)
-        .uri(uri)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key));
+    let request_builder = authorize(
+        HttpRequest::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("Content-Type", "application/json"),
+        api_key,
+    );
 
     let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request)?))?;
     let mut response = client.send(request).await?;
@@ -555,6 +711,52 @@ pub async fn stream_completion(
     }
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListModelsResponse {
+    pub data: Vec<ModelEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ModelEntry {
+    pub id: String,
+    #[serde(default)]
+    pub owned_by: Option<String>,
+}
+
+/// Lists the models served at the given URL, for OpenAI-compatible servers that
+/// implement the `/models` endpoint.
+pub async fn list_models(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: &str,
+) -> Result<Vec<ModelEntry>> {
+    let uri = format!("{api_url}/models");
+    let request = authorize(
+        HttpRequest::builder()
+            .method(Method::GET)
+            .uri(uri)
+            .header("Accept", "application/json"),
+        api_key,
+    )
+    .body(AsyncBody::default())?;
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        let response: ListModelsResponse =
+            serde_json::from_str(&body).context("failed to parse the list of models")?;
+        Ok(response.data)
+    } else {
+        Err(anyhow!(
+            "Failed to list models: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 #[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum OpenAiEmbeddingModel {
     #[serde(rename = "text-embedding-3-small")]
@@ -647,19 +849,14 @@ pub async fn extract_tool_args_from_events(
         return Err(anyhow!("tool not used"));
     };
 
-    Ok(events.filter_map(move |event| {
+    let rest = events.filter_map(move |event| {
         let result = match event {
             Err(error) => Some(Err(error)),
             Ok(ResponseStreamEvent { choices, .. }) => choices.into_iter().find_map(|choice| {
                 choice.delta.tool_calls?.into_iter().find_map(|call| {
                     if call.index == tool_use_index {
                         let func = call.function?;
-                        let mut arguments = func.arguments?;
-                        if let Some(mut first_chunk) = first_chunk.take() {
-                            first_chunk.push_str(&arguments);
-                            arguments = first_chunk
-                        }
-                        Some(Ok(arguments))
+                        Some(Ok(func.arguments?))
                     } else {
                         None
                     }
@@ -668,7 +865,9 @@ pub async fn extract_tool_args_from_events(
         };
 
         async move { result }
-    }))
+    });
+    // Responses that weren't streamed contain all of the arguments in the first chunk.
+    Ok(stream::iter(first_chunk.map(Ok)).chain(rest))
 }
 
 pub fn extract_text_from_events(
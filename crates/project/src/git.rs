@@ -1,5 +1,6 @@
 use crate::{
     buffer_store::{BufferStore, BufferStoreEvent},
+    project_settings::ProjectSettings,
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     Project, ProjectItem, ProjectPath,
 };
@@ -14,8 +15,9 @@ use futures::{
 };
 use git::{
     repository::{
-        Branch, CommitDetails, GitRepository, PushOptions, Remote, RemoteCommandOutput, RepoPath,
-        ResetMode,
+        Branch, CommitDetails, CommitFile, CommitOptions, GitRepository, LogOptions, PushOptions,
+        RebaseOutcome, RebaseStatus, RebaseTodoEntry, Remote, RemoteCommandOutput, RepoPath,
+        ResetMode, StashEntry,
     },
     status::FileStatus,
 };
@@ -29,7 +31,7 @@ use rpc::{
     proto::{self, git_reset, ToProto},
     AnyProtoClient, TypedEnvelope,
 };
-use settings::WorktreeId;
+use settings::{Settings as _, WorktreeId};
 use std::{
     collections::VecDeque,
     future::Future,
@@ -136,6 +138,18 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_set_index_text);
         client.add_entity_request_handler(Self::handle_askpass);
         client.add_entity_request_handler(Self::handle_check_for_pushed_commits);
+        client.add_entity_request_handler(Self::handle_stash_push);
+        client.add_entity_request_handler(Self::handle_stash_list);
+        client.add_entity_request_handler(Self::handle_stash_show);
+        client.add_entity_request_handler(Self::handle_stash_apply);
+        client.add_entity_request_handler(Self::handle_stash_drop);
+        client.add_entity_request_handler(Self::handle_log);
+        client.add_entity_request_handler(Self::handle_commit_files);
+        client.add_entity_request_handler(Self::handle_rebase_commits);
+        client.add_entity_request_handler(Self::handle_rebase);
+        client.add_entity_request_handler(Self::handle_rebase_continue);
+        client.add_entity_request_handler(Self::handle_rebase_abort);
+        client.add_entity_request_handler(Self::handle_get_rebase_status);
     }
 
     pub fn active_repository(&self) -> Option<Entity<Repository>> {
@@ -306,7 +320,7 @@ impl GitStore {
         Some(status.status)
     }
 
-    fn repository_and_path_for_buffer_id(
+    pub fn repository_and_path_for_buffer_id(
         &self,
         buffer_id: BufferId,
         cx: &App,
@@ -554,13 +568,24 @@ impl GitStore {
         let repository_handle =
             Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
 
-        let message = SharedString::from(envelope.payload.message);
-        let name = envelope.payload.name.map(SharedString::from);
-        let email = envelope.payload.email.map(SharedString::from);
-
+        let payload = envelope.payload;
+        let message = SharedString::from(payload.message);
+        let name = payload.name.map(SharedString::from);
+        let email = payload.email.map(SharedString::from);
         repository_handle
-            .update(&mut cx, |repository_handle, _| {
-                repository_handle.commit(message, name.zip(email))
+            .update(&mut cx, |repository_handle, cx| {
+                // Commits are signed with the host's keys, so they follow the host's
+                // settings rather than the guest's.
+                let options = CommitOptions {
+                    amend: payload.amend,
+                    fixup: payload.fixup,
+                    signing: ProjectSettings::get_global(cx)
+                        .git
+                        .commit_signing
+                        .clone()
+                        .unwrap_or_default(),
+                };
+                repository_handle.commit(message, name.zip(email), options)
             })?
             .await??;
         Ok(proto::Ack {})
@@ -670,13 +695,7 @@ impl GitStore {
                 repository_handle.show(&envelope.payload.commit)
             })?
             .await??;
-        Ok(proto::GitCommitDetails {
-            sha: commit.sha.into(),
-            message: commit.message.into(),
-            commit_timestamp: commit.commit_timestamp,
-            committer_email: commit.committer_email.into(),
-            committer_name: commit.committer_name.into(),
-        })
+        Ok(commit_details_to_proto(commit))
     }
 
     async fn handle_reset(
@@ -807,6 +826,283 @@ impl GitStore {
         })
     }
 
+    async fn handle_stash_push(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashPush>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+        let paths = envelope
+            .payload
+            .paths
+            .iter()
+            .map(|s| RepoPath::from_str(s))
+            .collect();
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_push(
+                    envelope.payload.message,
+                    paths,
+                    envelope.payload.staged,
+                )
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_list(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashList>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitStashListResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_list()
+            })?
+            .await??;
+        Ok(proto::GitStashListResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::git_stash_list_response::StashEntry {
+                    index: entry.index as u64,
+                    sha: entry.sha.into(),
+                    message: entry.message.into(),
+                    commit_timestamp: entry.commit_timestamp,
+                })
+                .collect(),
+        })
+    }
+
+    async fn handle_stash_show(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashShow>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitStashShowResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let files = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_show(envelope.payload.index as usize)
+            })?
+            .await??;
+        Ok(proto::GitStashShowResponse {
+            files: files.into_iter().map(commit_file_to_proto).collect(),
+        })
+    }
+
+    async fn handle_stash_apply(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashApply>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+        let index = envelope.payload.index as usize;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                if envelope.payload.pop {
+                    repository_handle.stash_pop(index, &envelope.payload.sha)
+                } else {
+                    repository_handle.stash_apply(index, &envelope.payload.sha)
+                }
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_drop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashDrop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_drop(envelope.payload.index as usize, &envelope.payload.sha)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_log(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLog>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitLogResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+        let payload = envelope.payload;
+        let options = LogOptions {
+            path: payload.path.as_deref().map(RepoPath::from_str),
+            line_range: payload
+                .start_line
+                .zip(payload.end_line)
+                .map(|(start, end)| start..=end),
+            author: payload.author,
+            message: payload.message,
+            skip: payload.skip as usize,
+            limit: payload.limit as usize,
+        };
+
+        let commits = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.log(options)
+            })?
+            .await??;
+        Ok(proto::GitLogResponse {
+            commits: commits.into_iter().map(commit_details_to_proto).collect(),
+        })
+    }
+
+    async fn handle_commit_files(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCommitFiles>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitCommitFilesResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let files = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.commit_files(&envelope.payload.commit)
+            })?
+            .await??;
+        Ok(proto::GitCommitFilesResponse {
+            files: files.into_iter().map(commit_file_to_proto).collect(),
+        })
+    }
+
+    async fn handle_rebase_commits(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebaseCommits>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitRebaseCommitsResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let commits = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_commits(&envelope.payload.base)
+            })?
+            .await??;
+        Ok(proto::GitRebaseCommitsResponse {
+            commits: commits.into_iter().map(commit_details_to_proto).collect(),
+        })
+    }
+
+    async fn handle_rebase(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebase>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitRebaseResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+        let todo = envelope
+            .payload
+            .todo
+            .into_iter()
+            .map(proto_to_rebase_todo_entry)
+            .collect::<Result<Vec<_>>>()?;
+
+        let outcome = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase(&envelope.payload.base, todo)
+            })?
+            .await??;
+        Ok(proto::GitRebaseResponse {
+            stopped: outcome == RebaseOutcome::Stopped,
+        })
+    }
+
+    async fn handle_rebase_continue(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebaseContinue>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitRebaseResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let outcome = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_continue(envelope.payload.skip)
+            })?
+            .await??;
+        Ok(proto::GitRebaseResponse {
+            stopped: outcome == RebaseOutcome::Stopped,
+        })
+    }
+
+    async fn handle_rebase_abort(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebaseAbort>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_abort()
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_get_rebase_status(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitGetRebaseStatus>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitGetRebaseStatusResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let status = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_status()
+            })?
+            .await??;
+        Ok(proto::GitGetRebaseStatusResponse {
+            status: status.map(|status| proto::GitRebaseStatus {
+                stopped_at: status.stopped_at,
+                done: status.done as u64,
+                total: status.total as u64,
+            }),
+        })
+    }
+
     fn repository_for_request(
         this: &Entity<Self>,
         worktree_id: WorktreeId,
@@ -857,6 +1153,70 @@ fn make_remote_delegate(
     })
 }
 
+fn commit_details_to_proto(details: CommitDetails) -> proto::GitCommitDetails {
+    proto::GitCommitDetails {
+        sha: details.sha.into(),
+        message: details.message.into(),
+        commit_timestamp: details.commit_timestamp,
+        author_email: details.author_email.into(),
+        author_name: details.author_name.into(),
+        committer_email: details.committer_email.into(),
+        committer_name: details.committer_name.into(),
+    }
+}
+
+fn proto_to_commit_details(details: proto::GitCommitDetails) -> CommitDetails {
+    CommitDetails {
+        sha: details.sha.into(),
+        message: details.message.into(),
+        commit_timestamp: details.commit_timestamp,
+        author_email: details.author_email.into(),
+        author_name: details.author_name.into(),
+        committer_email: details.committer_email.into(),
+        committer_name: details.committer_name.into(),
+    }
+}
+
+fn proto_to_rebase_outcome(response: proto::GitRebaseResponse) -> RebaseOutcome {
+    if response.stopped {
+        RebaseOutcome::Stopped
+    } else {
+        RebaseOutcome::Completed
+    }
+}
+
+fn commit_file_to_proto(file: CommitFile) -> proto::GitCommitFile {
+    proto::GitCommitFile {
+        path: file.path.to_string_lossy().to_string(),
+        old_text: file.old_text,
+        new_text: file.new_text,
+    }
+}
+
+fn rebase_todo_entry_to_proto(entry: RebaseTodoEntry) -> proto::GitRebaseTodoEntry {
+    proto::GitRebaseTodoEntry {
+        action: entry.action.as_str().to_string(),
+        sha: entry.sha,
+        message: entry.message,
+    }
+}
+
+fn proto_to_rebase_todo_entry(entry: proto::GitRebaseTodoEntry) -> Result<RebaseTodoEntry> {
+    Ok(RebaseTodoEntry {
+        action: entry.action.parse()?,
+        sha: entry.sha,
+        message: entry.message,
+    })
+}
+
+fn proto_to_commit_file(file: proto::GitCommitFile) -> CommitFile {
+    CommitFile {
+        path: RepoPath::from_str(&file.path),
+        old_text: file.old_text,
+        new_text: file.new_text,
+    }
+}
+
 impl GitRepo {}
 
 impl Repository {
@@ -1160,13 +1520,210 @@ impl Repository {
                         })
                         .await?;
 
-                    Ok(CommitDetails {
-                        sha: resp.sha.into(),
-                        message: resp.message.into(),
-                        commit_timestamp: resp.commit_timestamp,
-                        committer_email: resp.committer_email.into(),
-                        committer_name: resp.committer_name.into(),
-                    })
+                    Ok(proto_to_commit_details(resp))
+                }
+            }
+        })
+    }
+
+    pub fn log(&self, options: LogOptions) -> oneshot::Receiver<Result<Vec<CommitDetails>>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(git_repository) => git_repository.log(&options),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitLog {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            path: options.path.map(|path| path.to_string_lossy().to_string()),
+                            start_line: options.line_range.as_ref().map(|range| *range.start()),
+                            end_line: options.line_range.as_ref().map(|range| *range.end()),
+                            author: options.author,
+                            message: options.message,
+                            skip: options.skip as u64,
+                            limit: options.limit as u64,
+                        })
+                        .await?;
+
+                    Ok(response
+                        .commits
+                        .into_iter()
+                        .map(proto_to_commit_details)
+                        .collect())
+                }
+            }
+        })
+    }
+
+    pub fn commit_files(&self, commit: &str) -> oneshot::Receiver<Result<Vec<CommitFile>>> {
+        let commit = commit.to_string();
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(git_repository) => git_repository.commit_files(&commit),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitCommitFiles {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            commit,
+                        })
+                        .await?;
+
+                    Ok(response
+                        .files
+                        .into_iter()
+                        .map(proto_to_commit_file)
+                        .collect())
+                }
+            }
+        })
+    }
+
+    pub fn rebase_commits(&self, base: &str) -> oneshot::Receiver<Result<Vec<CommitDetails>>> {
+        let base = base.to_string();
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(git_repository) => git_repository.rebase_commits(&base),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitRebaseCommits {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            base,
+                        })
+                        .await?;
+
+                    Ok(response
+                        .commits
+                        .into_iter()
+                        .map(proto_to_commit_details)
+                        .collect())
+                }
+            }
+        })
+    }
+
+    pub fn rebase(
+        &self,
+        base: &str,
+        todo: Vec<RebaseTodoEntry>,
+    ) -> oneshot::Receiver<Result<RebaseOutcome>> {
+        let base = base.to_string();
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(git_repository) => git_repository.rebase(&base, &todo),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitRebase {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            base,
+                            todo: todo.into_iter().map(rebase_todo_entry_to_proto).collect(),
+                        })
+                        .await?;
+
+                    Ok(proto_to_rebase_outcome(response))
+                }
+            }
+        })
+    }
+
+    pub fn rebase_continue(&self, skip: bool) -> oneshot::Receiver<Result<RebaseOutcome>> {
+        self.send_job(move |git_repo| async move {
+            match git_repo {
+                GitRepo::Local(git_repository) => git_repository.rebase_continue(skip),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitRebaseContinue {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            skip,
+                        })
+                        .await?;
+
+                    Ok(proto_to_rebase_outcome(response))
+                }
+            }
+        })
+    }
+
+    pub fn rebase_abort(&self) -> oneshot::Receiver<Result<()>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(git_repository) => git_repository.rebase_abort(),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitRebaseAbort {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    pub fn rebase_status(&self) -> oneshot::Receiver<Result<Option<RebaseStatus>>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(git_repository) => git_repository.rebase_status(),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitGetRebaseStatus {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                        })
+                        .await?;
+
+                    Ok(response.status.map(|status| RebaseStatus {
+                        stopped_at: status.stopped_at,
+                        done: status.done as usize,
+                        total: status.total as usize,
+                    }))
                 }
             }
         })
@@ -1344,6 +1901,7 @@ impl Repository {
         &self,
         message: SharedString,
         name_and_email: Option<(SharedString, SharedString)>,
+        options: CommitOptions,
     ) -> oneshot::Receiver<Result<()>> {
         self.send_job(|git_repo| async move {
             match git_repo {
@@ -1352,6 +1910,7 @@ impl Repository {
                     name_and_email
                         .as_ref()
                         .map(|(name, email)| (name.as_ref(), email.as_ref())),
+                    &options,
                 ),
                 GitRepo::Remote {
                     project_id,
@@ -1368,6 +1927,8 @@ impl Repository {
                             message: String::from(message),
                             name: name.map(String::from),
                             email: email.map(String::from),
+                            amend: options.amend,
+                            fixup: options.fixup,
                         })
                         .await
                         .context("sending commit request")?;
@@ -1702,4 +2263,179 @@ impl Repository {
             }
         })
     }
+
+    pub fn stash_push(
+        &self,
+        message: Option<String>,
+        paths: Vec<RepoPath>,
+        staged: bool,
+    ) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |repo| async move {
+            match repo {
+                GitRepo::Local(git_repository) => {
+                    git_repository.stash_push(message.as_deref(), &paths, staged)
+                }
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitStashPush {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            message,
+                            paths: paths
+                                .into_iter()
+                                .map(|p| p.to_string_lossy().to_string())
+                                .collect(),
+                            staged,
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    pub fn stash_list(&self) -> oneshot::Receiver<Result<Vec<StashEntry>>> {
+        self.send_job(|repo| async move {
+            match repo {
+                GitRepo::Local(git_repository) => git_repository.stash_list(),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitStashList {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                        })
+                        .await?;
+
+                    let entries = response
+                        .entries
+                        .into_iter()
+                        .map(|entry| StashEntry {
+                            index: entry.index as usize,
+                            sha: entry.sha.into(),
+                            message: entry.message.into(),
+                            commit_timestamp: entry.commit_timestamp,
+                        })
+                        .collect();
+
+                    Ok(entries)
+                }
+            }
+        })
+    }
+
+    pub fn stash_show(&self, index: usize) -> oneshot::Receiver<Result<Vec<CommitFile>>> {
+        self.send_job(move |repo| async move {
+            match repo {
+                GitRepo::Local(git_repository) => git_repository.stash_show(index),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitStashShow {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            index: index as u64,
+                        })
+                        .await?;
+
+                    let files = response
+                        .files
+                        .into_iter()
+                        .map(proto_to_commit_file)
+                        .collect();
+
+                    Ok(files)
+                }
+            }
+        })
+    }
+
+    /// Applies the stash entry at `index`, failing if it's no longer the stash with the given SHA.
+    pub fn stash_apply(&self, index: usize, sha: &str) -> oneshot::Receiver<Result<()>> {
+        self.apply_stash(index, sha, false)
+    }
+
+    /// Pops the stash entry at `index`, failing if it's no longer the stash with the given SHA.
+    pub fn stash_pop(&self, index: usize, sha: &str) -> oneshot::Receiver<Result<()>> {
+        self.apply_stash(index, sha, true)
+    }
+
+    fn apply_stash(&self, index: usize, sha: &str, pop: bool) -> oneshot::Receiver<Result<()>> {
+        let sha = sha.to_string();
+        self.send_job(move |repo| async move {
+            match repo {
+                GitRepo::Local(git_repository) => {
+                    if pop {
+                        git_repository.stash_pop(index, &sha)
+                    } else {
+                        git_repository.stash_apply(index, &sha)
+                    }
+                }
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitStashApply {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            index: index as u64,
+                            pop,
+                            sha,
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    /// Drops the stash entry at `index`, failing if it's no longer the stash with the given SHA.
+    pub fn stash_drop(&self, index: usize, sha: &str) -> oneshot::Receiver<Result<()>> {
+        let sha = sha.to_string();
+        self.send_job(move |repo| async move {
+            match repo {
+                GitRepo::Local(git_repository) => git_repository.stash_drop(index, &sha),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitStashDrop {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            index: index as u64,
+                            sha,
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
 }
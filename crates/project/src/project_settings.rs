@@ -1,6 +1,7 @@
 use anyhow::Context as _;
 use collections::HashMap;
 use fs::Fs;
+use git::repository::CommitSigning;
 use gpui::{App, AsyncApp, BorrowAppContext, Context, Entity, EventEmitter};
 use lsp::LanguageServerName;
 use paths::{
@@ -153,7 +154,7 @@ fn default_inline_diagnostics_padding() -> u32 {
     4
 }
 
-#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct GitSettings {
     /// Whether or not to show the git gutter.
     ///
@@ -172,6 +173,11 @@ pub struct GitSettings {
     ///
     /// Default: transparent
     pub hunk_style: Option<GitHunkStyleSetting>,
+    /// How commits made from Zed are signed. Unset fields fall back to the
+    /// repository's git configuration.
+    ///
+    /// Default: null
+    pub commit_signing: Option<CommitSigning>,
 }
 
 impl GitSettings {
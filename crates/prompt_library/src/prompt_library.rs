@@ -902,6 +902,7 @@ impl PromptLibrary {
                                     tools: Vec::new(),
                                     stop: Vec::new(),
                                     temperature: None,
+                                    response_format: None,
                                 },
                                 cx,
                             )
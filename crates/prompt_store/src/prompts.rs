@@ -45,6 +45,15 @@ pub struct ProjectSlashCommandPromptContext {
     pub context_buffer: String,
 }
 
+/// Asks the model to summarize the conversation before it, so that the summary can
+/// stand in for the older messages once the conversation nears the context window.
+pub const COMPACTION_PROMPT: &str = "The conversation above is getting too long to continue. Summarize it so that you can pick up where you left off with only the summary. Keep the user's goals and instructions, the decisions made, the files and symbols involved, the results of tool uses that still matter, and any open questions. Write the summary directly, without any preamble.";
+
+/// Returns the message that stands in for the summarized part of a conversation.
+pub fn compaction_message(summary: &str) -> String {
+    format!("The earlier part of this conversation was summarized as follows:\n\n{summary}")
+}
+
 pub struct PromptLoadingParams<'a> {
     pub fs: Arc<dyn Fs>,
     pub repo_path: Option<PathBuf>,
@@ -458,6 +458,25 @@ messages!(
     (GitChangeBranch, Background),
     (CheckForPushedCommits, Background),
     (CheckForPushedCommitsResponse, Background),
+    (GitStashPush, Background),
+    (GitStashList, Background),
+    (GitStashListResponse, Background),
+    (GitStashShow, Background),
+    (GitStashShowResponse, Background),
+    (GitStashApply, Background),
+    (GitStashDrop, Background),
+    (GitLog, Background),
+    (GitLogResponse, Background),
+    (GitCommitFiles, Background),
+    (GitCommitFilesResponse, Background),
+    (GitRebaseCommits, Background),
+    (GitRebaseCommitsResponse, Background),
+    (GitRebase, Background),
+    (GitRebaseResponse, Background),
+    (GitRebaseContinue, Background),
+    (GitRebaseAbort, Background),
+    (GitGetRebaseStatus, Background),
+    (GitGetRebaseStatusResponse, Background),
 );
 
 request_messages!(
@@ -604,6 +623,18 @@ request_messages!(
     (GitCreateBranch, Ack),
     (GitChangeBranch, Ack),
     (CheckForPushedCommits, CheckForPushedCommitsResponse),
+    (GitStashPush, Ack),
+    (GitStashList, GitStashListResponse),
+    (GitStashShow, GitStashShowResponse),
+    (GitStashApply, Ack),
+    (GitStashDrop, Ack),
+    (GitLog, GitLogResponse),
+    (GitCommitFiles, GitCommitFilesResponse),
+    (GitRebaseCommits, GitRebaseCommitsResponse),
+    (GitRebase, GitRebaseResponse),
+    (GitRebaseContinue, GitRebaseResponse),
+    (GitRebaseAbort, Ack),
+    (GitGetRebaseStatus, GitGetRebaseStatusResponse),
 );
 
 entity_messages!(
@@ -709,6 +740,18 @@ entity_messages!(
     GitChangeBranch,
     GitCreateBranch,
     CheckForPushedCommits,
+    GitStashPush,
+    GitStashList,
+    GitStashShow,
+    GitStashApply,
+    GitStashDrop,
+    GitLog,
+    GitCommitFiles,
+    GitRebaseCommits,
+    GitRebase,
+    GitRebaseContinue,
+    GitRebaseAbort,
+    GitGetRebaseStatus,
 );
 
 entity_messages!(
@@ -561,6 +561,7 @@ impl SummaryIndex {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            response_format: None,
         };
 
         let code_len = code.len();
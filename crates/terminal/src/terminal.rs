@@ -655,7 +655,7 @@ pub enum TaskStatus {
     /// The task is started and running currently.
     Running,
     /// After the start, the task stopped running and reported its error code back.
-    Completed { success: bool },
+    Completed { success: bool, exit_code: i32 },
 }
 
 impl TaskStatus {
@@ -668,6 +668,7 @@ impl TaskStatus {
     fn register_task_exit(&mut self, error_code: i32) {
         *self = TaskStatus::Completed {
             success: error_code == 0,
+            exit_code: error_code,
         };
     }
 }
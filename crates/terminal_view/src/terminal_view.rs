@@ -1292,7 +1292,7 @@ impl Item for TerminalView {
                     Color::Warning,
                     TerminalView::rerun_button(&terminal_task),
                 ),
-                TaskStatus::Completed { success } => {
+                TaskStatus::Completed { success, .. } => {
                     let rerun_button = TerminalView::rerun_button(&terminal_task);
 
                     if *success {
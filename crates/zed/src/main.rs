@@ -455,6 +455,7 @@ fn main() {
             app_state.fs.clone(),
             cx,
         );
+        language_model_usage::init(cx);
         snippet_provider::init(cx);
         inline_completion_registry::init(
             app_state.client.clone(),
@@ -404,6 +404,8 @@ fn initialize_panels(
             workspace_handle.clone(),
             cx.clone(),
         );
+        let usage_panel =
+            language_model_usage::UsagePanel::load(workspace_handle.clone(), cx.clone());
 
         let (
             project_panel,
@@ -412,6 +414,7 @@ fn initialize_panels(
             channels_panel,
             chat_panel,
             notification_panel,
+            usage_panel,
         ) = futures::try_join!(
             project_panel,
             outline_panel,
@@ -419,6 +422,7 @@ fn initialize_panels(
             channels_panel,
             chat_panel,
             notification_panel,
+            usage_panel,
         )?;
 
         workspace_handle.update_in(&mut cx, |workspace, window, cx| {
@@ -428,6 +432,7 @@ fn initialize_panels(
             workspace.add_panel(channels_panel, window, cx);
             workspace.add_panel(chat_panel, window, cx);
             workspace.add_panel(notification_panel, window, cx);
+            workspace.add_panel(usage_panel, window, cx);
             cx.when_flag_enabled::<GitUiFeatureFlag>(window, |workspace, window, cx| {
                 let git_panel = git_ui::git_panel::GitPanel::new(workspace, window, cx);
                 workspace.add_panel(git_panel, window, cx);
@@ -4252,6 +4257,7 @@ mod tests {
                 app_state.fs.clone(),
                 cx,
             );
+            language_model_usage::init(cx);
             let prompt_builder = PromptBuilder::load(app_state.fs.clone(), false, cx);
             assistant::init(
                 app_state.fs.clone(),
@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context as _, Result};
+use serde::Deserialize;
+use zed_llm_client::{PredictEditsBody, PredictEditsResponse};
+
+/// A request to predict edits, saved along with the prediction it received.
+#[derive(Deserialize)]
+struct RecordedPrediction {
+    request: serde_json::Value,
+    response: PredictEditsResponse,
+}
+
+/// A serialized prediction that is yet to be written to the recording directory.
+pub(crate) struct PredictionRecording {
+    dir: PathBuf,
+    path: PathBuf,
+    contents: String,
+}
+
+impl PredictionRecording {
+    pub fn new(
+        dir: &Path,
+        request: &PredictEditsBody,
+        response: &PredictEditsResponse,
+    ) -> Result<Self> {
+        let contents = serde_json::to_string_pretty(&serde_json::json!({
+            "request": request,
+            "response": response,
+        }))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            path: dir.join(format!("{}.json", response.request_id)),
+            contents,
+        })
+    }
+
+    pub fn save(self) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create recording directory {:?}", self.dir))?;
+        fs::write(&self.path, self.contents)
+            .with_context(|| format!("failed to write recording {:?}", self.path))
+    }
+}
+
+/// Returns the recorded prediction of a request identical to the given one.
+pub(crate) fn replay_prediction(
+    dir: &Path,
+    request: &PredictEditsBody,
+) -> Result<PredictEditsResponse> {
+    let request = serde_json::to_value(request)?;
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("failed to read recordings from {dir:?}"))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "json")
+        {
+            continue;
+        }
+
+        let recording = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read recording {path:?}"))?;
+        let recording: RecordedPrediction = serde_json::from_str(&recording)
+            .with_context(|| format!("failed to parse recording {path:?}"))?;
+        if recording.request == request {
+            return Ok(recording.response);
+        }
+    }
+
+    Err(anyhow!(
+        "no prediction was recorded for this request in {dir:?}"
+    ))
+}
@@ -6,6 +6,7 @@ mod onboarding_banner;
 mod onboarding_modal;
 mod onboarding_telemetry;
 mod rate_completion_modal;
+mod recording;
 
 pub(crate) use completion_diff_element::*;
 use db::kvp::KEY_VALUE_STORE;
@@ -22,17 +23,19 @@ use client::{Client, UserStore};
 use collections::{HashMap, HashSet, VecDeque};
 use futures::AsyncReadExt;
 use gpui::{
-    actions, App, AppContext as _, AsyncApp, Context, Entity, EntityId, Global, SemanticVersion,
-    Subscription, Task, WeakEntity,
+    actions, App, AppContext as _, AsyncApp, BackgroundExecutor, Context, Entity, EntityId, Global,
+    SemanticVersion, Subscription, Task, WeakEntity,
 };
 use http_client::{HttpClient, Method};
 use input_excerpt::excerpt_for_cursor_position;
 use language::{
+    language_settings::{all_language_settings, ZetaRecordingMode, ZetaSettings},
     text_diff, Anchor, Buffer, BufferSnapshot, EditPreview, OffsetRangeExt, ToOffset, ToPoint,
 };
 use language_model::{LlmApiToken, RefreshLlmTokenListener};
 use postage::watch;
 use project::Project;
+use recording::PredictionRecording;
 use release_channel::AppVersion;
 use settings::WorktreeId;
 use std::str::FromStr;
@@ -708,6 +711,13 @@ and then another
             .workspace
             .as_ref()
             .and_then(|workspace| workspace.upgrade());
+        let settings = all_language_settings(None, cx)
+            .edit_predictions
+            .zeta
+            .clone();
+        // Data is only ever collected by Zed's own server.
+        let can_collect_data = can_collect_data && settings.api_url.is_none();
+        let executor = cx.background_executor().clone();
         self.request_completion_impl(
             workspace,
             project,
@@ -715,12 +725,23 @@ and then another
             position,
             can_collect_data,
             cx,
-            Self::perform_predict_edits,
+            move |params| Self::perform_predict_edits(params, settings, executor),
         )
     }
 
+    /// Whether predictions are requested from a self-hosted server instead of Zed's.
+    pub fn uses_self_hosted_server(cx: &App) -> bool {
+        all_language_settings(None, cx)
+            .edit_predictions
+            .zeta
+            .api_url
+            .is_some()
+    }
+
     fn perform_predict_edits(
         params: PerformPredictEditsParams,
+        settings: ZetaSettings,
+        executor: BackgroundExecutor,
     ) -> impl Future<Output = Result<PredictEditsResponse>> {
         async move {
             let PerformPredictEditsParams {
@@ -728,66 +749,143 @@ and then another
                 llm_token,
                 app_version,
                 body,
-                ..
             } = params;
 
-            let http_client = client.http_client();
-            let mut token = llm_token.acquire(&client).await?;
-            let mut did_retry = false;
+            if let Some(recording) = settings
+                .recording
+                .clone()
+                .filter(|recording| recording.mode == ZetaRecordingMode::Replay)
+            {
+                return executor
+                    .spawn(async move { recording::replay_prediction(&recording.path, &body) })
+                    .await;
+            }
 
-            loop {
-                let request_builder = http_client::Request::builder().method(Method::POST);
-                let request_builder =
-                    if let Ok(predict_edits_url) = std::env::var("ZED_PREDICT_EDITS_URL") {
-                        request_builder.uri(predict_edits_url)
-                    } else {
-                        request_builder.uri(
-                            http_client
-                                .build_zed_llm_url("/predict_edits/v2", &[])?
-                                .as_ref(),
-                        )
-                    };
-                let request = request_builder
-                    .header("Content-Type", "application/json")
-                    .header("Authorization", format!("Bearer {}", token))
-                    .body(serde_json::to_string(&body)?.into())?;
+            let response = if let Some(api_url) = settings.api_url.as_deref() {
+                Self::predict_edits_with_self_hosted_server(
+                    &client,
+                    api_url,
+                    settings.auth_header.as_deref(),
+                    &body,
+                )
+                .await?
+            } else {
+                Self::predict_edits_with_zed(&client, &llm_token, app_version, &body).await?
+            };
 
-                let mut response = http_client.send(request).await?;
+            if let Some(recording) = settings
+                .recording
+                .as_ref()
+                .filter(|recording| recording.mode == ZetaRecordingMode::Record)
+            {
+                let recording = PredictionRecording::new(&recording.path, &body, &response)?;
+                executor
+                    .spawn(async move { recording.save().log_err() })
+                    .detach();
+            }
 
-                if let Some(minimum_required_version) = response
-                    .headers()
-                    .get(MINIMUM_REQUIRED_VERSION_HEADER_NAME)
-                    .and_then(|version| SemanticVersion::from_str(version.to_str().ok()?).ok())
-                {
-                    if app_version < minimum_required_version {
-                        return Err(anyhow!(ZedUpdateRequiredError {
-                            minimum_version: minimum_required_version
-                        }));
-                    }
-                }
+            Ok(response)
+        }
+    }
 
-                if response.status().is_success() {
-                    let mut body = String::new();
-                    response.body_mut().read_to_string(&mut body).await?;
-                    return Ok(serde_json::from_str(&body)?);
-                } else if !did_retry
-                    && response
-                        .headers()
-                        .get(EXPIRED_LLM_TOKEN_HEADER_NAME)
-                        .is_some()
-                {
-                    did_retry = true;
-                    token = llm_token.refresh(&client).await?;
+    async fn predict_edits_with_self_hosted_server(
+        client: &Arc<Client>,
+        api_url: &str,
+        auth_header: Option<&str>,
+        body: &PredictEditsBody,
+    ) -> Result<PredictEditsResponse> {
+        let http_client = client.http_client();
+        let mut request_builder = http_client::Request::builder()
+            .method(Method::POST)
+            .uri(format!(
+                "{}/predict_edits/v2",
+                api_url.trim_end_matches('/')
+            ))
+            .header("Content-Type", "application/json");
+        if let Some(auth_header) = auth_header {
+            request_builder = request_builder.header("Authorization", auth_header);
+        }
+        let request = request_builder.body(serde_json::to_string(body)?.into())?;
+
+        let mut response = http_client.send(request).await?;
+        let mut response_body = String::new();
+        response
+            .body_mut()
+            .read_to_string(&mut response_body)
+            .await?;
+        if response.status().is_success() {
+            Ok(serde_json::from_str(&response_body)?)
+        } else {
+            Err(anyhow!(
+                "error predicting edits.\nStatus: {:?}\nBody: {}",
+                response.status(),
+                response_body
+            ))
+        }
+    }
+
+    async fn predict_edits_with_zed(
+        client: &Arc<Client>,
+        llm_token: &LlmApiToken,
+        app_version: SemanticVersion,
+        body: &PredictEditsBody,
+    ) -> Result<PredictEditsResponse> {
+        let http_client = client.http_client();
+        let mut token = llm_token.acquire(client).await?;
+        let mut did_retry = false;
+
+        loop {
+            let request_builder = http_client::Request::builder().method(Method::POST);
+            let request_builder =
+                if let Ok(predict_edits_url) = std::env::var("ZED_PREDICT_EDITS_URL") {
+                    request_builder.uri(predict_edits_url)
                 } else {
-                    let mut body = String::new();
-                    response.body_mut().read_to_string(&mut body).await?;
-                    return Err(anyhow!(
-                        "error predicting edits.\nStatus: {:?}\nBody: {}",
-                        response.status(),
-                        body
-                    ));
+                    request_builder.uri(
+                        http_client
+                            .build_zed_llm_url("/predict_edits/v2", &[])?
+                            .as_ref(),
+                    )
+                };
+            let request = request_builder
+                .header("Content-Type", "application/json")
+                .header("Authorization", format!("Bearer {}", token))
+                .body(serde_json::to_string(body)?.into())?;
+
+            let mut response = http_client.send(request).await?;
+
+            if let Some(minimum_required_version) = response
+                .headers()
+                .get(MINIMUM_REQUIRED_VERSION_HEADER_NAME)
+                .and_then(|version| SemanticVersion::from_str(version.to_str().ok()?).ok())
+            {
+                if app_version < minimum_required_version {
+                    return Err(anyhow!(ZedUpdateRequiredError {
+                        minimum_version: minimum_required_version
+                    }));
                 }
             }
+
+            if response.status().is_success() {
+                let mut body = String::new();
+                response.body_mut().read_to_string(&mut body).await?;
+                return Ok(serde_json::from_str(&body)?);
+            } else if !did_retry
+                && response
+                    .headers()
+                    .get(EXPIRED_LLM_TOKEN_HEADER_NAME)
+                    .is_some()
+            {
+                did_retry = true;
+                token = llm_token.refresh(client).await?;
+            } else {
+                let mut body = String::new();
+                response.body_mut().read_to_string(&mut body).await?;
+                return Err(anyhow!(
+                    "error predicting edits.\nStatus: {:?}\nBody: {}",
+                    response.status(),
+                    body
+                ));
+            }
         }
     }
 
@@ -1420,7 +1518,7 @@ impl inline_completion::EditPredictionProvider for ZetaInlineCompletionProvider
     }
 
     fn needs_terms_acceptance(&self, cx: &App) -> bool {
-        !self.zeta.read(cx).tos_accepted
+        !self.zeta.read(cx).tos_accepted && !Zeta::uses_self_hosted_server(cx)
     }
 
     fn is_refreshing(&self) -> bool {
@@ -1435,7 +1533,7 @@ impl inline_completion::EditPredictionProvider for ZetaInlineCompletionProvider
         _debounce: bool,
         cx: &mut Context<Self>,
     ) {
-        if !self.zeta.read(cx).tos_accepted {
+        if self.needs_terms_acceptance(cx) {
             return;
         }
 
@@ -1647,9 +1745,13 @@ mod tests {
     use gpui::TestAppContext;
     use http_client::FakeHttpClient;
     use indoc::indoc;
-    use language::Point;
+    use language::{
+        language_settings::{AllLanguageSettings, ZetaRecording},
+        Point,
+    };
     use rpc::proto;
     use settings::SettingsStore;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
 
     use super::*;
 
@@ -1776,6 +1878,7 @@ mod tests {
             let settings_store = SettingsStore::test(cx);
             cx.set_global(settings_store);
             client::init_settings(cx);
+            language::init(cx);
         });
 
         let edits = edits_for_prediction(
@@ -1840,6 +1943,7 @@ mod tests {
             let settings_store = SettingsStore::test(cx);
             cx.set_global(settings_store);
             client::init_settings(cx);
+            language::init(cx);
         });
 
         let buffer_content = "lorem\n";
@@ -1897,6 +2001,88 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_record_and_replay_predictions(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            client::init_settings(cx);
+            language::init(cx);
+        });
+
+        let recordings_dir = tempfile::tempdir().unwrap();
+        let set_recording_mode = |mode: ZetaRecordingMode, cx: &mut TestAppContext| {
+            let path = recordings_dir.path().to_path_buf();
+            cx.update(|cx| {
+                SettingsStore::update_global(cx, |store, cx| {
+                    store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                        let zeta = &mut settings.edit_predictions.get_or_insert_default().zeta;
+                        zeta.api_url = Some("http://localhost:8000".into());
+                        zeta.recording = Some(ZetaRecording { mode, path });
+                    });
+                });
+            });
+        };
+
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let http_client = FakeHttpClient::create({
+            let request_count = request_count.clone();
+            move |request| {
+                assert_eq!(request.uri().path(), "/predict_edits/v2");
+                request_count.fetch_add(1, SeqCst);
+                async move {
+                    Ok(http_client::Response::builder()
+                        .status(200)
+                        .body(
+                            serde_json::to_string(&PredictEditsResponse {
+                                request_id: Uuid::new_v4(),
+                                output_excerpt: indoc! {"
+                                    <|editable_region_start|>
+                                    lorem
+                                    ipsum
+                                    <|editable_region_end|>"}
+                                .to_string(),
+                            })
+                            .unwrap()
+                            .into(),
+                        )
+                        .unwrap())
+                }
+            }
+        });
+        let client = cx.update(|cx| Client::new(Arc::new(FakeSystemClock::new()), http_client, cx));
+        cx.update(|cx| {
+            RefreshLlmTokenListener::register(client.clone(), cx);
+        });
+        let user_store = cx.new(|cx| UserStore::new(client.clone(), cx));
+        let zeta = cx.new(|cx| Zeta::new(None, client, user_store, cx));
+        let buffer = cx.new(|cx| Buffer::local("lorem\n", cx));
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(1, 0)));
+
+        set_recording_mode(ZetaRecordingMode::Record, cx);
+        let recorded = zeta
+            .update(cx, |zeta, cx| {
+                zeta.request_completion(None, &buffer, cursor, false, cx)
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(request_count.load(SeqCst), 1);
+
+        set_recording_mode(ZetaRecordingMode::Replay, cx);
+        let replayed = zeta
+            .update(cx, |zeta, cx| {
+                zeta.request_completion(None, &buffer, cursor, false, cx)
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(request_count.load(SeqCst), 1);
+        assert_eq!(replayed.id, recorded.id);
+        assert_eq!(replayed.output_excerpt, recorded.output_excerpt);
+    }
+
     async fn edits_for_prediction(
         buffer_content: &str,
         completion_response: &str,